@@ -80,6 +80,7 @@
               cargo-deny
               cargo-edit
               cargo-watch
+              nodejs
               zeromq
             ]
             ++ pkgs.lib.optionals pkgs.stdenv.isLinux [
@@ -0,0 +1,90 @@
+/// Base64 without lookup tables: each character is computed arithmetically
+/// from its 6-bit value, so encoding RPC credentials doesn't index memory by
+/// secret bytes.
+fn encode_with(data: &[u8], c62: u8, c63: u8, pad: bool) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+        let chars = chunk.len() + 1;
+        for i in 0..4 {
+            if i < chars {
+                let sextet = (triple >> (18 - 6 * i) & 0x3f) as u8;
+                out.push(sextet_char(sextet, c62, c63) as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Maps 0..64 onto `A-Z a-z 0-9 c62 c63`. `(limit - x) >> 8` is all ones
+/// when `x > limit` and zero otherwise, selecting each range's offset
+/// without branching on `x`.
+fn sextet_char(x: u8, c62: u8, c63: u8) -> u8 {
+    let x = x as i16;
+    let above = |limit: i16| (limit - x) >> 8;
+    let mut c = x + b'A' as i16;
+    c += above(25) & (b'a' as i16 - 26 - b'A' as i16);
+    c += above(51) & (b'0' as i16 - 52 - (b'a' as i16 - 26));
+    c += above(61) & (c62 as i16 - 62 - (b'0' as i16 - 52));
+    c += above(62) & (c63 as i16 - 63 - (c62 as i16 - 62));
+    c as u8
+}
+
+/// RFC 4648 section 4, with padding; what HTTP Basic auth expects.
+pub fn encode_standard(data: &[u8]) -> String {
+    encode_with(data, b'+', b'/', true)
+}
+
+/// RFC 4648 section 5: `-` and `_` instead of `+` and `/`, without padding.
+#[allow(dead_code, reason = "no caller until cookie auth needs URL-safe tokens")]
+pub fn encode_url_safe(data: &[u8]) -> String {
+    encode_with(data, b'-', b'_', false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_standard, encode_url_safe, sextet_char};
+
+    #[test]
+    fn rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(encode_standard(input.as_bytes()), expected);
+            assert_eq!(
+                encode_url_safe(input.as_bytes()),
+                expected.trim_end_matches('=')
+            );
+        }
+    }
+
+    #[test]
+    fn every_sextet_matches_the_alphabet() {
+        let standard = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let url_safe = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        for x in 0..64u8 {
+            assert_eq!(sextet_char(x, b'+', b'/'), standard[x as usize]);
+            assert_eq!(sextet_char(x, b'-', b'_'), url_safe[x as usize]);
+        }
+    }
+
+    #[test]
+    fn url_safe_replaces_the_last_two_characters() {
+        assert_eq!(encode_standard(&[0xfb, 0xff]), "+/8=");
+        assert_eq!(encode_url_safe(&[0xfb, 0xff]), "-_8");
+        assert_eq!(encode_standard(&[0xff; 6]), "////////");
+        assert_eq!(encode_url_safe(&[0xff; 6]), "________");
+    }
+}
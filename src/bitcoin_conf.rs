@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use crate::rpc;
+
+/// Options Bitcoin Core only reads from the active chain's section when the
+/// chain isn't mainnet; a top-level `rpcport` doesn't apply to regtest.
+const NETWORK_ONLY: [&str; 2] = ["rpcport", "rpcbind"];
+
+/// Connection settings found in a bitcoin.conf. Fields the file doesn't set
+/// are `None` so the caller can keep whatever it already has.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportedConfig {
+    pub chain: String,
+    pub url: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub cookie_path: Option<String>,
+    pub zmq_address: Option<String>,
+}
+
+impl ImportedConfig {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "chain": self.chain,
+            "url": self.url,
+            "user": self.user,
+            "password": self.password,
+            "cookie_path": self.cookie_path,
+            "zmq_address": self.zmq_address,
+        })
+    }
+}
+
+/// `key=value` lines grouped by section; top-level keys are under "".
+/// `chain.key=value` is the same as `key=value` inside `[chain]`.
+fn parse_sections(text: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut section = String::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let (target, key) = match key.split_once('.') {
+            Some((chain, key)) => (chain.to_string(), key),
+            None => (section.clone(), key),
+        };
+        // The first occurrence wins, as it does in Core for single-value options.
+        sections
+            .entry(target)
+            .or_default()
+            .entry(key.to_string())
+            .or_insert_with(|| value.to_string());
+    }
+    sections
+}
+
+fn active_chain(top: &HashMap<String, String>) -> String {
+    if let Some(chain) = top.get("chain") {
+        return chain.clone();
+    }
+    let enabled = |key: &str| top.get(key).is_some_and(|v| v != "0");
+    if enabled("regtest") {
+        "regtest".into()
+    } else if enabled("signet") {
+        "signet".into()
+    } else if enabled("testnet4") {
+        "testnet4".into()
+    } else if enabled("testnet") {
+        "test".into()
+    } else {
+        "main".into()
+    }
+}
+
+fn default_rpc_port(chain: &str) -> u16 {
+    match chain {
+        "test" => 18332,
+        "testnet4" => 48332,
+        "signet" => 38332,
+        "regtest" => 18443,
+        _ => 8332,
+    }
+}
+
+/// Datadir subdirectory holding the cookie for each chain.
+fn chain_subdir(chain: &str) -> Option<&'static str> {
+    match chain {
+        "test" => Some("testnet3"),
+        "testnet4" => Some("testnet4"),
+        "signet" => Some("signet"),
+        "regtest" => Some("regtest"),
+        _ => None,
+    }
+}
+
+/// Wildcard binds can't be connected to; use loopback of the same family.
+fn connectable_host(host: &str) -> &str {
+    match host {
+        "0.0.0.0" | "" => "127.0.0.1",
+        "::" => "::1",
+        _ => host,
+    }
+}
+
+/// Splits `host`, `host:port`, `[v6]` or `[v6]:port`.
+fn split_host_port(s: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = s.strip_prefix('[') {
+        let (host, tail) = rest.split_once(']').unwrap_or((rest, ""));
+        return (host, tail.strip_prefix(':').and_then(|p| p.parse().ok()));
+    }
+    match s.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host, port.parse().ok()),
+        _ => (s, None),
+    }
+}
+
+fn zmq_connect_address(endpoint: &str) -> String {
+    let Some(rest) = endpoint.strip_prefix("tcp://") else {
+        return endpoint.to_string();
+    };
+    let (host, port) = split_host_port(rest);
+    let host = connectable_host(host);
+    let host = if host.contains(':') {
+        format!("[{host}]")
+    } else {
+        host.to_string()
+    };
+    match port {
+        Some(port) => format!("tcp://{host}:{port}"),
+        None => format!("tcp://{host}"),
+    }
+}
+
+/// Maps a bitcoin.conf to connection settings. `datadir` is used for the
+/// cookie when the file doesn't set `datadir` itself.
+pub fn parse(text: &str, datadir: &Path) -> ImportedConfig {
+    let sections = parse_sections(text);
+    let empty = HashMap::new();
+    let top = sections.get("").unwrap_or(&empty);
+    let chain = active_chain(top);
+    let chain_section = sections.get(&chain).unwrap_or(&empty);
+    let get = |key: &str| {
+        chain_section
+            .get(key)
+            .or_else(|| {
+                let top_allowed = chain == "main" || !NETWORK_ONLY.contains(&key);
+                top_allowed.then(|| top.get(key)).flatten()
+            })
+            .filter(|v| !v.is_empty())
+            .cloned()
+    };
+
+    let (bind_host, bind_port) = get("rpcconnect")
+        .or_else(|| get("rpcbind"))
+        .map(|b| {
+            let (host, port) = split_host_port(&b);
+            (connectable_host(host).to_string(), port)
+        })
+        .unwrap_or_else(|| ("127.0.0.1".to_string(), None));
+    let port = get("rpcport")
+        .and_then(|p| p.parse().ok())
+        .or(bind_port)
+        .unwrap_or_else(|| default_rpc_port(&chain));
+    let host = if bind_host.contains(':') {
+        format!("[{bind_host}]")
+    } else {
+        bind_host
+    };
+
+    let user = get("rpcuser");
+    let password = get("rpcpassword");
+    let cookie_path = password.is_none().then(|| {
+        let datadir = get("datadir")
+            .map(|d| rpc::expand_home(&d))
+            .unwrap_or_else(|| datadir.into());
+        let chain_dir = match chain_subdir(&chain) {
+            Some(sub) => datadir.join(sub),
+            None => datadir,
+        };
+        let cookie = get("rpccookiefile").unwrap_or_else(|| ".cookie".to_string());
+        chain_dir
+            .join(rpc::expand_home(&cookie))
+            .display()
+            .to_string()
+    });
+    let zmq_address = [
+        "zmqpubhashblock",
+        "zmqpubhashtx",
+        "zmqpubrawblock",
+        "zmqpubrawtx",
+    ]
+    .iter()
+    .find_map(|key| get(key))
+    .map(|endpoint| zmq_connect_address(&endpoint));
+
+    ImportedConfig {
+        url: format!("http://{host}:{port}"),
+        chain,
+        user,
+        password,
+        cookie_path,
+        zmq_address,
+    }
+}
+
+/// Where Bitcoin Core looks for its datadir by default on this platform.
+pub fn default_datadir() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let appdata = std::env::var_os("APPDATA").unwrap_or_default();
+        return Path::new(&appdata).join("Bitcoin");
+    }
+    if cfg!(target_os = "macos") {
+        return rpc::expand_home("~/Library/Application Support/Bitcoin");
+    }
+    rpc::expand_home("~/.bitcoin")
+}
+
+/// Reads and maps a bitcoin.conf; an empty `path` means the default location.
+pub fn load(path: &str) -> io::Result<ImportedConfig> {
+    let path = if path.trim().is_empty() {
+        default_datadir().join("bitcoin.conf")
+    } else {
+        rpc::expand_home(path.trim())
+    };
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    let datadir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    Ok(parse(&text, &datadir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use std::path::Path;
+
+    fn datadir() -> &'static Path {
+        Path::new("/home/u/.bitcoin")
+    }
+
+    #[test]
+    fn user_password_and_zmq_on_mainnet() {
+        let conf = "\
+# node settings
+server=1
+rpcuser=alice
+rpcpassword=s3cret  # inline comment
+zmqpubrawtx=tcp://0.0.0.0:28333
+zmqpubhashblock=tcp://0.0.0.0:28332
+";
+        let c = parse(conf, datadir());
+        assert_eq!(c.chain, "main");
+        assert_eq!(c.url, "http://127.0.0.1:8332");
+        assert_eq!(c.user.as_deref(), Some("alice"));
+        assert_eq!(c.password.as_deref(), Some("s3cret"));
+        assert_eq!(c.cookie_path, None);
+        assert_eq!(c.zmq_address.as_deref(), Some("tcp://127.0.0.1:28332"));
+    }
+
+    #[test]
+    fn rpcbind_and_rpcport_build_the_url() {
+        let c = parse("rpcbind=192.168.1.5\nrpcport=9000\n", datadir());
+        assert_eq!(c.url, "http://192.168.1.5:9000");
+        let c = parse("rpcbind=[::1]:8400\n", datadir());
+        assert_eq!(c.url, "http://[::1]:8400");
+    }
+
+    #[test]
+    fn chain_sections_and_network_only_keys() {
+        let conf = "\
+regtest=1
+rpcport=1234
+[main]
+rpcport=8332
+[regtest]
+rpcbind=127.0.0.1
+zmqpubhashtx=tcp://127.0.0.1:29000
+";
+        let c = parse(conf, datadir());
+        assert_eq!(c.chain, "regtest");
+        // The top-level rpcport only applies to mainnet.
+        assert_eq!(c.url, "http://127.0.0.1:18443");
+        assert_eq!(c.zmq_address.as_deref(), Some("tcp://127.0.0.1:29000"));
+        assert_eq!(
+            c.cookie_path.as_deref(),
+            Some("/home/u/.bitcoin/regtest/.cookie")
+        );
+
+        let c = parse("chain=signet\nsignet.rpcport=40000\n", datadir());
+        assert_eq!(c.url, "http://127.0.0.1:40000");
+    }
+
+    #[test]
+    fn cookie_follows_datadir_and_rpccookiefile() {
+        let c = parse("testnet=1\ndatadir=/data/btc\n", datadir());
+        assert_eq!(c.chain, "test");
+        assert_eq!(c.url, "http://127.0.0.1:18332");
+        assert_eq!(c.cookie_path.as_deref(), Some("/data/btc/testnet3/.cookie"));
+
+        let c = parse("rpccookiefile=/run/bitcoind/cookie\n", datadir());
+        assert_eq!(c.cookie_path.as_deref(), Some("/run/bitcoind/cookie"));
+    }
+
+    #[test]
+    fn empty_file_gives_defaults() {
+        let c = parse("", datadir());
+        assert_eq!(c.url, "http://127.0.0.1:8332");
+        assert_eq!(c.user, None);
+        assert_eq!(c.zmq_address, None);
+        assert_eq!(c.cookie_path.as_deref(), Some("/home/u/.bitcoin/.cookie"));
+    }
+}
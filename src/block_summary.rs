@@ -0,0 +1,315 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+
+use crate::hex;
+use crate::json_num::lenient_u64;
+use crate::rpc::{self, RpcConfig, RpcError};
+use crate::rpc_limiter::RpcBudget;
+
+/// Txids listed in a summary; a full block has thousands.
+pub const TXID_PREVIEW: usize = 10;
+/// Blocks backfilled from headers when the Recent Blocks card is empty.
+pub const RECENT_HEADERS: u64 = 10;
+/// Shortest printable run kept from the coinbase script, so push opcodes and
+/// the height/extranonce bytes don't show up as stray characters.
+const MIN_TEXT_RUN: usize = 4;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct BlockSummary {
+    pub height: u64,
+    pub hash: String,
+    pub time: u64,
+    pub tx_count: usize,
+    /// None when the node didn't report a fee for every transaction, which
+    /// happens on pruned nodes without undo data.
+    pub total_fees_sat: Option<u64>,
+    pub weight: u64,
+    pub size: u64,
+    pub difficulty: f64,
+    /// Median sat/vB over the non-coinbase transactions.
+    pub median_feerate: Option<f64>,
+    pub coinbase_message: String,
+    pub txids: Vec<String>,
+    /// Display-only fields that arrived malformed and were defaulted.
+    pub parse_warnings: Vec<String>,
+}
+
+impl BlockSummary {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "height": self.height,
+            "hash": self.hash,
+            "time": self.time,
+            "tx_count": self.tx_count,
+            "total_fees_sat": self.total_fees_sat,
+            "weight": self.weight,
+            "size": self.size,
+            "difficulty": self.difficulty,
+            "median_feerate": self.median_feerate,
+            "coinbase_message": self.coinbase_message,
+            "txids": self.txids,
+            "parse_warnings": self.parse_warnings,
+        })
+    }
+}
+
+/// Printable ASCII runs in the coinbase scriptSig, joined by spaces.
+fn coinbase_text(script_hex: &str) -> String {
+    let Ok(bytes) = hex::decode(script_hex) else {
+        return String::new();
+    };
+    bytes
+        .split(|b| !(b.is_ascii_graphic() || *b == b' '))
+        .filter(|run| run.len() >= MIN_TEXT_RUN)
+        .map(|run| String::from_utf8_lossy(run).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Summary of a `getblock <hash> 2` result. Height, hash and the tx list are
+/// required; the remaining header fields are read leniently.
+pub fn from_block(block: &serde_json::Value) -> Option<BlockSummary> {
+    let txs = block["tx"].as_array()?;
+    let coinbase_message = txs
+        .first()
+        .and_then(|tx| tx["vin"][0]["coinbase"].as_str())
+        .map(coinbase_text)
+        .unwrap_or_default();
+
+    let mut fees_sat = Some(0u64);
+    let mut feerates = Vec::with_capacity(txs.len().saturating_sub(1));
+    for tx in txs.iter().skip(1) {
+        let fee = tx["fee"]
+            .as_f64()
+            .map(|btc| (btc * 100_000_000.0).round() as u64);
+        fees_sat = fees_sat.zip(fee).map(|(sum, fee)| sum + fee);
+        if let (Some(fee), Some(vsize)) = (fee, tx["vsize"].as_u64())
+            && vsize > 0
+        {
+            feerates.push(fee as f64 / vsize as f64);
+        }
+    }
+
+    let mut parse_warnings = Vec::new();
+    Some(BlockSummary {
+        height: block["height"].as_u64()?,
+        hash: block["hash"].as_str()?.to_string(),
+        time: lenient_u64(&block["time"], "time", &mut parse_warnings),
+        tx_count: txs.len(),
+        total_fees_sat: fees_sat,
+        weight: lenient_u64(&block["weight"], "weight", &mut parse_warnings),
+        size: lenient_u64(&block["size"], "size", &mut parse_warnings),
+        difficulty: block["difficulty"].as_f64().unwrap_or(0.0),
+        median_feerate: median(feerates),
+        coinbase_message,
+        txids: txs
+            .iter()
+            .take(TXID_PREVIEW)
+            .filter_map(|tx| tx["txid"].as_str().map(str::to_string))
+            .collect(),
+        parse_warnings,
+    })
+}
+
+pub fn fetch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    hash: &str,
+) -> Result<BlockSummary, RpcError> {
+    let block = rpc::node_batch(
+        config,
+        budget,
+        &[json!({ "method": "getblock", "params": [hash, 2] })],
+    )?
+    .pop()
+    .unwrap_or_else(|| Err(RpcError::InvalidResponse("empty batch reply".into())))?;
+    from_block(&block).ok_or_else(|| RpcError::InvalidResponse("unexpected getblock reply".into()))
+}
+
+/// What `getblockheader` gives for a block: no fees, weight or size, which
+/// need the full block.
+#[derive(Debug, PartialEq)]
+pub struct HeaderSummary {
+    pub height: u64,
+    pub hash: String,
+    pub time: u64,
+    pub tx_count: u64,
+    pub difficulty: f64,
+}
+
+impl HeaderSummary {
+    pub fn from_header(header: &serde_json::Value) -> Option<HeaderSummary> {
+        Some(HeaderSummary {
+            height: header["height"].as_u64()?,
+            hash: header["hash"].as_str()?.to_string(),
+            time: header["time"].as_u64().unwrap_or(0),
+            tx_count: header["nTx"].as_u64().unwrap_or(0),
+            difficulty: header["difficulty"].as_f64().unwrap_or(0.0),
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "height": self.height,
+            "hash": self.hash,
+            "time": self.time,
+            "tx_count": self.tx_count,
+            "difficulty": self.difficulty,
+            "header_only": true,
+        })
+    }
+}
+
+/// Heights of the `count` blocks ending at `tip`, newest first.
+fn recent_heights(tip: u64, count: u64) -> impl Iterator<Item = u64> {
+    (tip.saturating_sub(count.saturating_sub(1))..=tip).rev()
+}
+
+/// Headers of the last `RECENT_HEADERS` blocks up to `tip`: one batch of
+/// getblockhash, then one of getblockheader. Heights that fail, say after
+/// a reorg shortened the chain, are left out rather than failing the list.
+pub fn fetch_recent(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    tip: u64,
+) -> Result<Vec<HeaderSummary>, RpcError> {
+    let calls: Vec<_> = recent_heights(tip, RECENT_HEADERS)
+        .map(|height| json!({ "method": "getblockhash", "params": [height] }))
+        .collect();
+    let calls: Vec<_> = rpc::node_batch(config, budget, &calls)?
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|hash| hash.as_str().map(str::to_string))
+        .map(|hash| json!({ "method": "getblockheader", "params": [hash, true] }))
+        .collect();
+    if calls.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(rpc::node_batch(config, budget, &calls)?
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|header| HeaderSummary::from_header(&header))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeaderSummary, coinbase_text, from_block, recent_heights};
+    use serde_json::json;
+
+    fn tx(txid: &str, fee_btc: Option<f64>, vsize: u64) -> serde_json::Value {
+        let mut tx = json!({ "txid": txid, "vsize": vsize, "vin": [{}] });
+        if let Some(fee) = fee_btc {
+            tx["fee"] = json!(fee);
+        }
+        tx
+    }
+
+    fn hex_of(s: &str) -> String {
+        crate::hex::encode(s.as_bytes())
+    }
+
+    #[test]
+    fn coinbase_text_keeps_printable_runs() {
+        // push(3) height, "/Foo Pool/", push(8) extranonce.
+        let script = "03a0bb0d0a2f466f6f20506f6f6c2f0800000000deadbeef";
+        assert_eq!(coinbase_text(script), "/Foo Pool/");
+        assert_eq!(coinbase_text("zz"), "");
+    }
+
+    #[test]
+    fn summary_totals_fees_and_median_feerate() {
+        let coinbase = json!({
+            "txid": "cb",
+            "vin": [{ "coinbase": hex_of("Mined by test") }],
+        });
+        let block = json!({
+            "height": 840_000,
+            "hash": "00ab",
+            "time": 1_713_571_767,
+            "weight": 4000,
+            "size": 1000,
+            "tx": [
+                coinbase,
+                tx("a", Some(0.000_01), 100),
+                tx("b", Some(0.000_05), 100),
+                tx("c", Some(0.000_2), 200),
+            ],
+        });
+        let s = from_block(&block).unwrap();
+        assert_eq!(s.tx_count, 4);
+        assert_eq!(s.total_fees_sat, Some(26_000));
+        assert_eq!(s.median_feerate, Some(50.0));
+        assert_eq!(s.coinbase_message, "Mined by test");
+        assert_eq!(s.txids, ["cb", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn missing_fee_leaves_total_unknown() {
+        let block = json!({
+            "height": 1,
+            "hash": "00",
+            "tx": [tx("cb", None, 100), tx("a", None, 100), tx("b", Some(0.000_01), 100)],
+        });
+        let s = from_block(&block).unwrap();
+        assert_eq!(s.total_fees_sat, None);
+        assert_eq!(s.median_feerate, Some(10.0));
+        assert!(from_block(&json!({ "height": 1 })).is_none());
+    }
+
+    #[test]
+    fn malformed_display_fields_warn_but_height_stays_strict() {
+        let block: serde_json::Value = serde_json::from_str(
+            r#"{"height": 1, "hash": "00", "time": -5, "weight": 18446744073709551616,
+                "size": "1000", "tx": []}"#,
+        )
+        .unwrap();
+        let s = from_block(&block).unwrap();
+        assert_eq!((s.time, s.weight, s.size), (0, u64::MAX, 0));
+        assert_eq!(s.parse_warnings.len(), 3);
+
+        let too_high: serde_json::Value =
+            serde_json::from_str(r#"{"height": 1e20, "hash": "00", "tx": []}"#).unwrap();
+        assert!(from_block(&too_high).is_none());
+        assert!(from_block(&json!({ "height": "1", "hash": "00", "tx": [] })).is_none());
+    }
+
+    #[test]
+    fn recent_heights_stop_at_genesis() {
+        assert_eq!(recent_heights(100, 3).collect::<Vec<_>>(), [100, 99, 98]);
+        assert_eq!(recent_heights(2, 10).collect::<Vec<_>>(), [2, 1, 0]);
+        assert_eq!(recent_heights(0, 0).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn header_summary_reads_getblockheader() {
+        let header = json!({
+            "hash": "00ab",
+            "height": 840_000,
+            "time": 1_713_571_767,
+            "nTx": 3050,
+            "difficulty": 86_388_558_925_171.02,
+        });
+        let h = HeaderSummary::from_header(&header).unwrap();
+        assert_eq!((h.height, h.tx_count), (840_000, 3050));
+        let v = h.to_json();
+        assert_eq!(v["hash"], "00ab");
+        assert_eq!(v["header_only"], true);
+        assert!(HeaderSummary::from_header(&json!({ "hash": "00" })).is_none());
+    }
+}
@@ -0,0 +1,75 @@
+use serde_json::json;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_COMMIT: &str = env!("BUILD_GIT_COMMIT");
+pub const GIT_DIRTY: &str = env!("BUILD_GIT_DIRTY");
+pub const BUILD_DATE: &str = env!("BUILD_DATE");
+pub const TARGET: &str = env!("BUILD_TARGET");
+
+pub fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "audio") {
+        features.push("audio");
+    }
+    features
+}
+
+pub fn text() -> String {
+    let dirty = match GIT_DIRTY {
+        "true" => "-dirty",
+        _ => "",
+    };
+    let features = features();
+    let features = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join(", ")
+    };
+    format!(
+        "bitcoin-rpc-web {VERSION}\ncommit: {GIT_COMMIT}{dirty}\nbuilt: {BUILD_DATE}\ntarget: {TARGET}\nfeatures: {features}"
+    )
+}
+
+pub fn to_json() -> serde_json::Value {
+    json!({
+        "version": VERSION,
+        "git_commit": GIT_COMMIT,
+        // "unknown" when built without git metadata.
+        "git_dirty": match GIT_DIRTY {
+            "true" => json!(true),
+            "false" => json!(false),
+            other => json!(other),
+        },
+        "build_date": BUILD_DATE,
+        "target": TARGET,
+        "features": features(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VERSION, text, to_json};
+
+    #[test]
+    fn json_output_has_expected_keys() {
+        let out = to_json().to_string();
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+        for key in [
+            "version",
+            "git_commit",
+            "git_dirty",
+            "build_date",
+            "target",
+            "features",
+        ] {
+            assert!(v.get(key).is_some(), "missing {key}");
+        }
+        assert_eq!(v["version"], VERSION);
+        assert!(v["features"].is_array());
+    }
+
+    #[test]
+    fn text_output_starts_with_version() {
+        assert!(text().starts_with(&format!("bitcoin-rpc-web {VERSION}\n")));
+    }
+}
@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use tracing::warn;
+
+const CHANGELOG_JSON: &str = include_str!("../assets/changelog.json");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Option<String>,
+}
+
+impl Version {
+    /// Parses `MAJOR.MINOR.PATCH` with an optional `v` prefix and `-pre` suffix.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix('v').unwrap_or(input);
+        let (core, pre) = match input.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (input, None),
+        };
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+            pre,
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                // A pre-release sorts before the release it leads up to.
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub area: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub version: Version,
+    pub version_text: String,
+    pub date: String,
+    pub changes: Vec<Change>,
+}
+
+/// Parses the changelog and returns entries newest-first.
+pub fn parse(json: &str) -> Result<Vec<Entry>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let list = value.as_array().ok_or("changelog must be a list")?;
+    let mut entries = Vec::with_capacity(list.len());
+    for item in list {
+        let version_text = item["version"].as_str().ok_or("entry missing version")?;
+        let version = Version::parse(version_text)
+            .ok_or_else(|| format!("invalid version {version_text:?}"))?;
+        let changes = item["changes"]
+            .as_array()
+            .ok_or_else(|| format!("{version_text}: missing changes"))?
+            .iter()
+            .map(|c| {
+                Some(Change {
+                    area: c["area"].as_str()?.to_string(),
+                    text: c["text"].as_str()?.to_string(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("{version_text}: change needs area and text"))?;
+        entries.push(Entry {
+            version,
+            version_text: version_text.to_string(),
+            date: item["date"].as_str().unwrap_or("").to_string(),
+            changes,
+        });
+    }
+    entries.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(entries)
+}
+
+/// Show the overlay the first time a version runs. A last-seen version newer
+/// than the running one means a downgrade, which stays quiet.
+pub fn should_show(current: &str, last_seen: Option<&str>) -> bool {
+    let Some(current) = Version::parse(current) else {
+        return false;
+    };
+    match last_seen.and_then(Version::parse) {
+        Some(seen) => seen < current,
+        None => true,
+    }
+}
+
+pub fn response(last_seen: Option<&str>) -> Value {
+    let current = env!("CARGO_PKG_VERSION");
+    let entries = match parse(CHANGELOG_JSON) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("embedded changelog is invalid: {e}");
+            Vec::new()
+        }
+    };
+    let entries: Vec<Value> = entries
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "version": e.version_text,
+                "date": e.date,
+                "changes": e
+                    .changes
+                    .iter()
+                    .map(|c| serde_json::json!({ "area": c.area, "text": c.text }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "version": current,
+        "show": !entries.is_empty() && should_show(current, last_seen),
+        "entries": entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CHANGELOG_JSON, Version, parse, should_show};
+
+    #[test]
+    fn compares_versions() {
+        let v = |s| Version::parse(s).unwrap();
+        assert!(v("0.1.0") < v("0.2.0"));
+        assert!(v("0.9.0") < v("0.10.0"));
+        assert!(v("1.0.0-rc1") < v("1.0.0"));
+        assert!(v("1.0.0-rc1") < v("1.0.0-rc2"));
+        assert_eq!(v("v1.2.3"), v("1.2.3"));
+        assert!(Version::parse("1.2").is_none());
+        assert!(Version::parse("1.2.3.4").is_none());
+        assert!(Version::parse("1.2.3-").is_none());
+        assert!(Version::parse("x.y.z").is_none());
+    }
+
+    #[test]
+    fn shows_once_per_upgrade() {
+        assert!(should_show("0.2.0", None));
+        assert!(should_show("0.2.0", Some("0.1.0")));
+        assert!(should_show("0.2.0", Some("garbage")));
+        assert!(!should_show("0.2.0", Some("0.2.0")));
+        // Downgrade: prefs were written by a newer build.
+        assert!(!should_show("0.2.0", Some("0.3.0")));
+    }
+
+    #[test]
+    fn parses_newest_first() {
+        let entries = parse(
+            r#"[
+                {"version": "0.1.0", "date": "2026-01-01", "changes": [{"area": "rpc", "text": "a"}]},
+                {"version": "0.10.0", "changes": []},
+                {"version": "0.2.0", "date": "2026-02-01", "changes": []}
+            ]"#,
+        )
+        .unwrap();
+        let order: Vec<_> = entries.iter().map(|e| e.version_text.as_str()).collect();
+        assert_eq!(order, ["0.10.0", "0.2.0", "0.1.0"]);
+        assert_eq!(entries[2].changes[0].area, "rpc");
+        assert_eq!(entries[0].date, "");
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        assert!(parse("{}").is_err());
+        assert!(parse(r#"[{"version": "one", "changes": []}]"#).is_err());
+        assert!(parse(r#"[{"version": "0.1.0"}]"#).is_err());
+        assert!(parse(r#"[{"version": "0.1.0", "changes": [{"text": "x"}]}]"#).is_err());
+    }
+
+    #[test]
+    fn embedded_changelog_covers_current_version() {
+        let entries = parse(CHANGELOG_JSON).unwrap();
+        let current = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+        assert!(entries.iter().any(|e| e.version == current));
+    }
+}
@@ -0,0 +1,458 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::build_info;
+use crate::rpc::{self, AuthMode, ConnectionConfig, RpcConfig, RpcError, TlsMode};
+
+pub const EXIT_RPC_ERROR: i32 = 1;
+pub const EXIT_WAIT_TIMEOUT: i32 = 2;
+pub const EXIT_USAGE: i32 = 64;
+
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+const WAIT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+const RPC_IN_WARMUP: i64 = -28;
+
+const USAGE: &str = "usage: bitcoin-rpc-web --call <method> [params...] [--url=URL] [--user=USER] \
+[--password=PASSWORD] [--rpccookiefile=PATH] [--rpccafile=PATH] [--wallet=NAME] [--timeout=SECS] [--wait] [--wait-timeout=SECS]";
+
+#[derive(Debug, PartialEq)]
+pub struct CallArgs {
+    pub method: String,
+    pub params: Vec<serde_json::Value>,
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    pub cookie_path: String,
+    pub ca_cert_path: String,
+    pub wallet: String,
+    pub timeout_secs: Option<u64>,
+    pub wait: bool,
+    pub wait_timeout: Duration,
+}
+
+#[derive(Debug)]
+pub enum CallFailure {
+    Rpc(RpcError),
+    WaitTimeout(RpcError),
+}
+
+pub trait Clock {
+    fn elapsed(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+struct SystemClock {
+    start: Instant,
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Runs one-shot mode if `--call` or an info flag is present. Returns the
+/// process exit code, or `None` when the GUI should start instead.
+pub fn run_from_args() -> Option<i32> {
+    if let Some(out) = info_output(std::env::args().skip(1)) {
+        println!("{out}");
+        return Some(0);
+    }
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return None,
+        Err(e) => {
+            eprintln!("error: {e}\n{USAGE}");
+            return Some(EXIT_USAGE);
+        }
+    };
+    if !rpc::is_allowed_rpc_url(&args.url, !args.ca_cert_path.is_empty()) {
+        eprintln!(
+            "error: non-local RPC address blocked. Use an https URL with --rpccafile, or set DANGER_INSECURE_RPC=1 to override."
+        );
+        return Some(EXIT_USAGE);
+    }
+
+    let mut config = RpcConfig::default();
+    config.set_connection(ConnectionConfig {
+        url: args.url.clone(),
+        user: args.user.clone(),
+        password: args.password.clone(),
+        auth_mode: if args.cookie_path.is_empty() {
+            AuthMode::UserPassword
+        } else {
+            AuthMode::CookieFile {
+                path: args.cookie_path.clone(),
+            }
+        },
+        ca_cert_path: args.ca_cert_path.clone(),
+        tls_mode: TlsMode::for_url(&args.url),
+    });
+    config.wallet = args.wallet.clone();
+    config.set_timeout_secs(args.timeout_secs);
+    let params = serde_json::Value::Array(args.params.clone());
+    let mut clock = SystemClock {
+        start: Instant::now(),
+    };
+    let mut dots = 0usize;
+    let outcome = call_with_wait(
+        || rpc::call(&config, &args.method, &params),
+        &mut clock,
+        args.wait.then_some(args.wait_timeout),
+        || {
+            dots += 1;
+            eprint!(".");
+            let _ = std::io::stderr().flush();
+        },
+    );
+    if dots > 0 {
+        eprintln!();
+    }
+
+    Some(match outcome {
+        Ok(value) => {
+            match value {
+                serde_json::Value::String(s) => println!("{s}"),
+                serde_json::Value::Null => {}
+                other => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&other).unwrap_or_default()
+                ),
+            }
+            0
+        }
+        Err(CallFailure::Rpc(e)) => {
+            eprintln!("error: {e}");
+            EXIT_RPC_ERROR
+        }
+        Err(CallFailure::WaitTimeout(e)) => {
+            eprintln!("error: timed out waiting for node: {e}");
+            EXIT_WAIT_TIMEOUT
+        }
+    })
+}
+
+/// Output for `--version`, `--version-json` or `--features`, whichever comes first.
+pub fn info_output<I>(args: I) -> Option<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    args.into_iter().find_map(|arg| match arg.as_str() {
+        "--version" => Some(build_info::text()),
+        "--version-json" => serde_json::to_string_pretty(&build_info::to_json()).ok(),
+        "--features" => Some(build_info::features().join("\n")),
+        _ => None,
+    })
+}
+
+pub fn parse_args<I>(args: I) -> Result<Option<CallArgs>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut method = None;
+    let mut positional = Vec::new();
+    let mut call = false;
+    let mut parsed = CallArgs {
+        method: String::new(),
+        params: Vec::new(),
+        url: ConnectionConfig::default().url,
+        user: std::env::var("RPC_USER").unwrap_or_default(),
+        password: std::env::var("RPC_PASSWORD").unwrap_or_default(),
+        cookie_path: String::new(),
+        ca_cert_path: String::new(),
+        wallet: String::new(),
+        timeout_secs: None,
+        wait: false,
+        wait_timeout: DEFAULT_WAIT_TIMEOUT,
+    };
+
+    for arg in args {
+        if arg == "--call" {
+            call = true;
+        } else if arg == "--wait" {
+            parsed.wait = true;
+        } else if let Some((key, value)) = arg.split_once('=').filter(|_| arg.starts_with("--")) {
+            match key {
+                "--url" => parsed.url = value.to_string(),
+                "--user" => parsed.user = value.to_string(),
+                "--password" => parsed.password = value.to_string(),
+                "--rpccookiefile" => parsed.cookie_path = value.to_string(),
+                "--rpccafile" => parsed.ca_cert_path = value.to_string(),
+                "--wallet" => parsed.wallet = value.to_string(),
+                "--timeout" => {
+                    let secs = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("invalid --timeout value {value:?}"))?;
+                    parsed.timeout_secs = Some(secs);
+                }
+                "--wait-timeout" => {
+                    let secs = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("invalid --wait-timeout value {value:?}"))?;
+                    parsed.wait_timeout = Duration::from_secs(secs);
+                }
+                _ => return Err(format!("unknown option {key}")),
+            }
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown option {arg}"));
+        } else if call && method.is_none() {
+            method = Some(arg);
+        } else if call {
+            positional.push(arg);
+        }
+    }
+
+    if !call {
+        return Ok(None);
+    }
+    parsed.method = method.ok_or("--call requires a method name")?;
+    // Like bitcoin-cli, each argument is JSON if it parses and a string otherwise.
+    parsed.params = positional
+        .into_iter()
+        .map(|p| serde_json::from_str(&p).unwrap_or(serde_json::Value::String(p)))
+        .collect();
+    Ok(Some(parsed))
+}
+
+/// Conditions that clear up once the node finishes starting or catches up:
+/// nothing is listening yet, the connection was dropped, the cookie file has
+/// not been written yet, the node is still warming up, or its RPC work queue
+/// is full.
+pub fn is_retryable(err: &RpcError) -> bool {
+    match err {
+        RpcError::Connection(_) | RpcError::Cookie(_) | RpcError::NodeBusy => true,
+        RpcError::Rpc { code, .. } => *code == RPC_IN_WARMUP,
+        RpcError::Transport(_) | RpcError::Http { .. } | RpcError::InvalidResponse(_) => false,
+    }
+}
+
+/// How long to sleep before the next attempt, or `None` once the deadline
+/// would be exceeded.
+pub fn next_retry_delay(elapsed: Duration, timeout: Duration) -> Option<Duration> {
+    let remaining = timeout.checked_sub(elapsed)?;
+    (!remaining.is_zero()).then(|| remaining.min(WAIT_RETRY_INTERVAL))
+}
+
+pub fn call_with_wait<F, C, P>(
+    mut call: F,
+    clock: &mut C,
+    wait_timeout: Option<Duration>,
+    mut on_retry: P,
+) -> Result<serde_json::Value, CallFailure>
+where
+    F: FnMut() -> Result<serde_json::Value, RpcError>,
+    C: Clock,
+    P: FnMut(),
+{
+    loop {
+        let err = match call() {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        let Some(timeout) = wait_timeout else {
+            return Err(CallFailure::Rpc(err));
+        };
+        if !is_retryable(&err) {
+            return Err(CallFailure::Rpc(err));
+        }
+        match next_retry_delay(clock.elapsed(), timeout) {
+            Some(delay) => {
+                on_retry();
+                clock.sleep(delay);
+            }
+            None => return Err(CallFailure::WaitTimeout(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CallFailure, Clock, call_with_wait, info_output, is_retryable, next_retry_delay, parse_args,
+    };
+    use crate::rpc::RpcError;
+    use std::time::Duration;
+
+    struct MockClock {
+        now: Duration,
+    }
+
+    impl Clock for MockClock {
+        fn elapsed(&self) -> Duration {
+            self.now
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now += duration;
+        }
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn gui_starts_without_call_flag() {
+        assert!(parse_args(args(&[])).unwrap().is_none());
+    }
+
+    #[test]
+    fn call_args_parse_json_and_strings() {
+        let parsed = parse_args(args(&[
+            "--call",
+            "getblockhash",
+            "0",
+            "abc",
+            "--wait",
+            "--wait-timeout=5",
+            "--timeout=30",
+            "--rpccookiefile=/tmp/.cookie",
+            "--rpccafile=/tmp/ca.pem",
+            "--url=http://127.0.0.1:18443",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(parsed.method, "getblockhash");
+        assert_eq!(
+            parsed.params,
+            vec![serde_json::json!(0), serde_json::json!("abc")]
+        );
+        assert!(parsed.wait);
+        assert_eq!(parsed.wait_timeout, Duration::from_secs(5));
+        assert_eq!(parsed.timeout_secs, Some(30));
+        assert_eq!(parsed.cookie_path, "/tmp/.cookie");
+        assert_eq!(parsed.ca_cert_path, "/tmp/ca.pem");
+        assert_eq!(parsed.url, "http://127.0.0.1:18443");
+    }
+
+    #[test]
+    fn version_flags_print_build_info() {
+        assert!(info_output(args(&[])).is_none());
+        assert!(info_output(args(&["--call", "getblockcount"])).is_none());
+        let text = info_output(args(&["--version"])).unwrap();
+        assert!(text.starts_with("bitcoin-rpc-web "));
+        let json = info_output(args(&["--version-json"])).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(v["git_commit"].is_string());
+    }
+
+    #[test]
+    fn call_without_method_is_usage_error() {
+        assert!(parse_args(args(&["--call"])).is_err());
+        assert!(parse_args(args(&["--call", "x", "--bogus=1"])).is_err());
+    }
+
+    #[test]
+    fn classifies_retryable_errors() {
+        assert!(is_retryable(&RpcError::Connection("refused".into())));
+        assert!(is_retryable(&RpcError::Cookie("not found".into())));
+        assert!(is_retryable(&RpcError::NodeBusy));
+        assert!(is_retryable(&RpcError::Rpc {
+            code: -28,
+            message: "Loading".into()
+        }));
+        assert!(!is_retryable(&RpcError::Http {
+            status: 401,
+            body: String::new()
+        }));
+        assert!(!is_retryable(&RpcError::Rpc {
+            code: -32601,
+            message: "Method not found".into()
+        }));
+    }
+
+    #[test]
+    fn retry_delay_respects_deadline() {
+        let timeout = Duration::from_secs(3);
+        assert_eq!(
+            next_retry_delay(Duration::ZERO, timeout),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            next_retry_delay(Duration::from_millis(2500), timeout),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(next_retry_delay(timeout, timeout), None);
+    }
+
+    #[test]
+    fn waits_through_warmup_then_succeeds() {
+        let mut clock = MockClock {
+            now: Duration::ZERO,
+        };
+        let mut attempts = 0;
+        let mut dots = 0;
+        let out = call_with_wait(
+            || {
+                attempts += 1;
+                match attempts {
+                    1 => Err(RpcError::Connection("refused".into())),
+                    2 => Err(RpcError::Rpc {
+                        code: -28,
+                        message: "Verifying blocks".into(),
+                    }),
+                    _ => Ok(serde_json::json!(800_000)),
+                }
+            },
+            &mut clock,
+            Some(Duration::from_secs(60)),
+            || dots += 1,
+        );
+        assert_eq!(out.unwrap(), serde_json::json!(800_000));
+        assert_eq!(dots, 2);
+        assert_eq!(clock.now, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn permanent_errors_bail_immediately() {
+        let mut clock = MockClock {
+            now: Duration::ZERO,
+        };
+        let out = call_with_wait(
+            || {
+                Err(RpcError::Http {
+                    status: 401,
+                    body: String::new(),
+                })
+            },
+            &mut clock,
+            Some(Duration::from_secs(60)),
+            || {},
+        );
+        assert!(matches!(out, Err(CallFailure::Rpc(RpcError::Http { .. }))));
+        assert_eq!(clock.now, Duration::ZERO);
+    }
+
+    #[test]
+    fn gives_up_after_timeout() {
+        let mut clock = MockClock {
+            now: Duration::ZERO,
+        };
+        let out = call_with_wait(
+            || Err(RpcError::Connection("refused".into())),
+            &mut clock,
+            Some(Duration::from_secs(5)),
+            || {},
+        );
+        assert!(matches!(out, Err(CallFailure::WaitTimeout(_))));
+        assert_eq!(clock.now, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn without_wait_errors_are_returned_directly() {
+        let mut clock = MockClock {
+            now: Duration::ZERO,
+        };
+        let out = call_with_wait(
+            || Err(RpcError::Connection("refused".into())),
+            &mut clock,
+            None,
+            || {},
+        );
+        assert!(matches!(out, Err(CallFailure::Rpc(_))));
+    }
+}
@@ -0,0 +1,251 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+use crate::rpc;
+
+/// Set when the user picks another directory after the config directory
+/// couldn't be written; used for the rest of the session.
+static FALLBACK_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// One retry covers a backup tool or indexer briefly holding the file.
+const RENAME_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+const FALLBACK_DIR_NAME: &str = "bitcoin-rpc-web-config";
+
+/// Where the app's files are read and written this session.
+pub fn dir() -> PathBuf {
+    dir_with(&FALLBACK_DIR)
+}
+
+pub fn file_path(name: &str) -> PathBuf {
+    dir().join(name)
+}
+
+fn dir_with(fallback: &Mutex<Option<PathBuf>>) -> PathBuf {
+    fallback.lock().unwrap().clone().unwrap_or_else(config_dir)
+}
+
+/// The platform's per-user config directory, used unless a fallback is set.
+fn config_dir() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let appdata = std::env::var_os("APPDATA").unwrap_or_default();
+        return Path::new(&appdata).join("bitcoin-rpc-web");
+    }
+    if cfg!(target_os = "macos") {
+        return rpc::expand_home("~/Library/Application Support/bitcoin-rpc-web");
+    }
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Path::new(&dir).join("bitcoin-rpc-web"),
+        _ => rpc::expand_home("~/.config/bitcoin-rpc-web"),
+    }
+}
+
+/// Write then rename so a crash mid-write can't truncate the file. A failed
+/// rename is retried once before giving up.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, contents)?;
+    if std::fs::rename(&tmp, path).is_ok() {
+        return Ok(());
+    }
+    std::thread::sleep(RENAME_RETRY_DELAY);
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+/// Why a save failed, in terms the user can act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    PermissionDenied,
+    ReadOnly,
+    NoSpace,
+    CrossDevice,
+    Other,
+}
+
+impl Problem {
+    pub fn classify(e: &io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => Problem::PermissionDenied,
+            io::ErrorKind::ReadOnlyFilesystem => Problem::ReadOnly,
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => Problem::NoSpace,
+            io::ErrorKind::CrossesDevices => Problem::CrossDevice,
+            _ => Problem::Other,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Problem::PermissionDenied => "permission_denied",
+            Problem::ReadOnly => "read_only",
+            Problem::NoSpace => "no_space",
+            Problem::CrossDevice => "cross_device",
+            Problem::Other => "other",
+        }
+    }
+
+    pub fn guidance(self) -> &'static str {
+        match self {
+            Problem::PermissionDenied => {
+                "This user can't write to the config directory. Fix its permissions or save to another location."
+            }
+            Problem::ReadOnly => {
+                "The config directory is on a read-only filesystem, as in some sandboxes. Save to another location."
+            }
+            Problem::NoSpace => {
+                "The disk holding the config directory is full. Free some space or save to another location."
+            }
+            Problem::CrossDevice => {
+                "The saved file couldn't be moved into place across filesystems. Save to another location."
+            }
+            Problem::Other => "The file couldn't be saved.",
+        }
+    }
+}
+
+/// The reply for a save that failed on disk, with a suggested directory to
+/// offer instead.
+pub fn save_error_json(e: &io::Error, path: &Path) -> Value {
+    let problem = Problem::classify(e);
+    let exe = std::env::current_exe().ok();
+    let fallback = suggested_fallback(exe.as_deref(), &dir());
+    json!({
+        "error": format!("{} ({}: {e})", problem.guidance(), path.display()),
+        "problem": problem.name(),
+        "fallback": fallback.map(|p| p.display().to_string()),
+    })
+}
+
+/// A directory next to the executable, unless that is where files already go.
+pub fn suggested_fallback(exe: Option<&Path>, current: &Path) -> Option<PathBuf> {
+    let dir = exe?.parent()?.join(FALLBACK_DIR_NAME);
+    (dir != current).then_some(dir)
+}
+
+/// The directory the user typed, or the suggestion when they left it empty.
+/// Relative paths are refused: a GUI app's working directory is arbitrary.
+pub fn resolve_fallback(input: &str, suggested: Option<&Path>) -> Result<PathBuf, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return suggested
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "enter a directory to save to".to_string());
+    }
+    let path = rpc::expand_home(input);
+    if !path.is_absolute() {
+        return Err(format!("{input} is not an absolute path"));
+    }
+    Ok(path)
+}
+
+/// Checks that `dir` can be written, then sends later loads and saves there.
+pub fn use_fallback(dir: PathBuf) -> io::Result<()> {
+    use_fallback_with(&FALLBACK_DIR, dir)
+}
+
+fn use_fallback_with(fallback: &Mutex<Option<PathBuf>>, dir: PathBuf) -> io::Result<()> {
+    std::fs::create_dir_all(&dir)?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"")?;
+    let _ = std::fs::remove_file(&probe);
+    *fallback.lock().unwrap() = Some(dir);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Problem, dir_with, resolve_fallback, save_error_json, suggested_fallback,
+        use_fallback_with, write_atomic,
+    };
+    use std::io::{Error, ErrorKind};
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    #[test]
+    fn io_errors_map_to_guidance() {
+        let cases = [
+            (ErrorKind::PermissionDenied, Problem::PermissionDenied),
+            (ErrorKind::ReadOnlyFilesystem, Problem::ReadOnly),
+            (ErrorKind::StorageFull, Problem::NoSpace),
+            (ErrorKind::QuotaExceeded, Problem::NoSpace),
+            (ErrorKind::CrossesDevices, Problem::CrossDevice),
+            (ErrorKind::NotFound, Problem::Other),
+        ];
+        for (kind, problem) in cases {
+            assert_eq!(Problem::classify(&Error::from(kind)), problem);
+        }
+        let v = save_error_json(
+            &Error::from(ErrorKind::ReadOnlyFilesystem),
+            Path::new("/cfg/custom-cards.json"),
+        );
+        assert_eq!(v["problem"], "read_only");
+        let error = v["error"].as_str().unwrap();
+        assert!(error.contains("/cfg/custom-cards.json"));
+    }
+
+    #[test]
+    fn fallback_paths_resolve() {
+        let exe = Path::new("/opt/app/bitcoin-rpc-web");
+        let suggested = suggested_fallback(Some(exe), Path::new("/home/u/.config/x")).unwrap();
+        assert_eq!(suggested, Path::new("/opt/app/bitcoin-rpc-web-config"));
+        assert_eq!(suggested_fallback(Some(exe), &suggested), None);
+        assert_eq!(suggested_fallback(None, &suggested), None);
+
+        assert_eq!(
+            resolve_fallback("  ", Some(&suggested)),
+            Ok(suggested.clone())
+        );
+        assert!(resolve_fallback("", None).is_err());
+        assert_eq!(
+            resolve_fallback("/srv/cfg", Some(&suggested)),
+            Ok("/srv/cfg".into())
+        );
+        assert!(resolve_fallback("cfg", Some(&suggested)).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_only_dir_fails_and_fallback_round_trips() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = std::env::temp_dir().join(format!("config-store-{}", std::process::id()));
+        let locked = base.join("locked");
+        std::fs::create_dir_all(&locked).unwrap();
+        let mode = |m| std::fs::Permissions::from_mode(m);
+        std::fs::set_permissions(&locked, mode(0o555)).unwrap();
+        let result = write_atomic(&locked.join("custom-cards.json"), "{}");
+        // Root ignores permission bits; the classification is only checked
+        // when the write was actually refused.
+        if let Err(e) = result {
+            assert_eq!(Problem::classify(&e), Problem::PermissionDenied);
+        }
+
+        // A slot of its own, so tests running alongside keep the real dir.
+        let slot = Mutex::new(None);
+        let fallback = base.join("fallback");
+        assert_ne!(dir_with(&slot), fallback);
+        use_fallback_with(&slot, fallback.clone()).unwrap();
+        assert_eq!(dir_with(&slot), fallback);
+        write_atomic(
+            &dir_with(&slot).join("custom-cards.json"),
+            r#"{"cards":[]}"#,
+        )
+        .unwrap();
+        let text = std::fs::read_to_string(fallback.join("custom-cards.json")).unwrap();
+        assert_eq!(text, r#"{"cards":[]}"#);
+
+        std::fs::set_permissions(&locked, mode(0o755)).unwrap();
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,201 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+use tracing::warn;
+
+use crate::config_store;
+use crate::rpc_history;
+
+const FILE_NAME: &str = "console-draft.json";
+/// Holds the time of the last clean exit. A draft saved after it was left
+/// behind by a crash or a kill.
+const MARKER_NAME: &str = "clean-shutdown";
+
+/// Set once the window is closing, so a debounced save still in flight
+/// can't write a draft after the exit was recorded.
+static EXITING: AtomicBool = AtomicBool::new(false);
+
+/// The method form as typed but not yet run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub method: String,
+    pub input_values: Vec<String>,
+    pub saved_ms: u64,
+}
+
+impl Draft {
+    fn from_json(v: &Value) -> Option<Self> {
+        let method = v["method"].as_str().filter(|m| !m.is_empty())?;
+        let input_values = v["input_values"]
+            .as_array()?
+            .iter()
+            .map(|s| s.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            method: method.to_string(),
+            input_values,
+            saved_ms: v["saved_ms"].as_u64()?,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "method": self.method,
+            "input_values": self.input_values,
+            "saved_ms": self.saved_ms,
+        })
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+pub fn draft_path() -> PathBuf {
+    config_store::file_path(FILE_NAME)
+}
+
+pub fn marker_path() -> PathBuf {
+    config_store::file_path(MARKER_NAME)
+}
+
+/// A missing, unreadable or corrupted draft counts as none.
+fn load(path: &Path) -> Option<Draft> {
+    let text = std::fs::read_to_string(path).ok()?;
+    Draft::from_json(&serde_json::from_str(&text).ok()?)
+}
+
+fn clean_shutdown_ms(marker: &Path) -> Option<u64> {
+    std::fs::read_to_string(marker).ok()?.trim().parse().ok()
+}
+
+/// The draft to offer on startup: one saved after the last clean exit, or
+/// any draft when the app has never exited cleanly.
+pub fn pending(draft: &Path, marker: &Path) -> Option<Draft> {
+    let saved = load(draft)?;
+    clean_shutdown_ms(marker)
+        .is_none_or(|clean_ms| saved.saved_ms > clean_ms)
+        .then_some(saved)
+}
+
+/// Replaces the draft with `{ "method", "input_values" }` from the frontend.
+/// A form with nothing typed, or for a method whose params can carry a
+/// passphrase or key, removes the draft instead. Returns whether one was
+/// written.
+pub fn save(path: &Path, body: &str, now_ms: u64) -> io::Result<bool> {
+    if EXITING.load(Ordering::SeqCst) {
+        return Ok(false);
+    }
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let mut v: Value = serde_json::from_str(body).map_err(|e| invalid(e.to_string()))?;
+    v["saved_ms"] = now_ms.into();
+    let draft = Draft::from_json(&v).ok_or_else(|| invalid("invalid draft".into()))?;
+    if rpc_history::is_denied(&draft.method) || draft.input_values.iter().all(|s| s.is_empty()) {
+        delete(path)?;
+        return Ok(false);
+    }
+    config_store::write_atomic(path, &draft.to_json().to_string())?;
+    Ok(true)
+}
+
+pub fn delete(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Drops the draft and records the exit. The marker still covers a draft
+/// whose removal failed.
+fn mark_clean_shutdown(draft: &Path, marker: &Path, now_ms: u64) -> io::Result<()> {
+    delete(draft)?;
+    config_store::write_atomic(marker, &now_ms.to_string())
+}
+
+/// Called as the window closes.
+pub fn on_clean_exit() {
+    EXITING.store(true, Ordering::SeqCst);
+    if let Err(e) = mark_clean_shutdown(&draft_path(), &marker_path(), now_ms()) {
+        warn!(error = %e, "clean shutdown not recorded");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{delete, mark_clean_shutdown, pending, save};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn paths(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("console-draft-{name}-{}", std::process::id()));
+        (dir.join("console-draft.json"), dir.join("clean-shutdown"))
+    }
+
+    fn body(method: &str, inputs: &[&str]) -> String {
+        json!({ "method": method, "input_values": inputs }).to_string()
+    }
+
+    #[test]
+    fn draft_is_offered_without_a_marker() {
+        let (draft, marker) = paths("no-marker");
+        assert_eq!(pending(&draft, &marker), None);
+        assert!(save(&draft, &body("getblock", &["abc", "2"]), 1_000).unwrap());
+        let offered = pending(&draft, &marker).unwrap();
+        assert_eq!(offered.method, "getblock");
+        assert_eq!(offered.input_values, ["abc", "2"]);
+        assert_eq!(offered.saved_ms, 1_000);
+        let _ = std::fs::remove_dir_all(draft.parent().unwrap());
+    }
+
+    #[test]
+    fn marker_decides_whether_a_draft_is_stale() {
+        let (draft, marker) = paths("marker");
+        save(&draft, &body("getblock", &["abc"]), 1_000).unwrap();
+        mark_clean_shutdown(&draft, &marker, 2_000).unwrap();
+        assert!(!draft.exists());
+
+        // A draft older than the recorded exit, as when its removal failed.
+        save(&draft, &body("getblock", &["abc"]), 1_500).unwrap();
+        assert_eq!(pending(&draft, &marker), None);
+
+        // Edits after the next launch, then a crash.
+        save(&draft, &body("getblock", &["def"]), 3_000).unwrap();
+        assert_eq!(pending(&draft, &marker).unwrap().input_values, ["def"]);
+
+        // An unreadable marker is treated as no clean exit.
+        std::fs::write(&marker, "garbage").unwrap();
+        save(&draft, &body("getblock", &["abc"]), 1_500).unwrap();
+        assert!(pending(&draft, &marker).is_some());
+        let _ = std::fs::remove_dir_all(draft.parent().unwrap());
+    }
+
+    #[test]
+    fn corrupted_or_secret_drafts_are_never_offered() {
+        let (draft, marker) = paths("corrupt");
+        std::fs::create_dir_all(draft.parent().unwrap()).unwrap();
+        for text in [
+            "{not json",
+            r#"{"method":"getblock"}"#,
+            r#"{"method":"getblock","input_values":[1],"saved_ms":5}"#,
+            r#"{"method":"","input_values":[],"saved_ms":5}"#,
+        ] {
+            std::fs::write(&draft, text).unwrap();
+            assert_eq!(pending(&draft, &marker), None, "{text}");
+        }
+        assert!(save(&draft, "not json", 5).is_err());
+
+        save(&draft, &body("getblock", &["abc"]), 5).unwrap();
+        assert!(!save(&draft, &body("walletpassphrase", &["hunter2", "60"]), 6).unwrap());
+        assert!(!draft.exists());
+        assert!(!save(&draft, &body("getblock", &["", ""]), 7).unwrap());
+        assert_eq!(pending(&draft, &marker), None);
+        delete(&draft).unwrap();
+        let _ = std::fs::remove_dir_all(draft.parent().unwrap());
+    }
+}
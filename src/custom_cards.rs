@@ -0,0 +1,490 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+
+use crate::config_store;
+use crate::rpc::{self, RpcConfig};
+use crate::rpc_limiter::RpcBudget;
+
+const FILE_NAME: &str = "custom-cards.json";
+pub const MAX_CARDS: usize = 20;
+/// Automatic refreshes of one card closer together than this are skipped;
+/// a block arriving right after a full refresh would otherwise fetch twice.
+pub const MIN_AUTO_REFRESH_MS: u64 = 1_000;
+/// Read-only method families a card may call. Cards refresh unattended, so
+/// anything that moves funds, mines or stops the node is refused.
+const READ_ONLY_PREFIXES: [&str; 4] = ["get", "list", "estimate", "decode"];
+const READ_ONLY_METHODS: [&str; 3] = ["uptime", "validateaddress", "testmempoolaccept"];
+/// `get*` calls that change wallet state: each one hands out a new address.
+const MUTATING_GETTERS: [&str; 2] = ["getnewaddress", "getrawchangeaddress"];
+
+/// When a card refreshes on its own. Every card can also be refreshed by hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cadence {
+    FullRefresh,
+    Block,
+    Manual,
+}
+
+impl Cadence {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "full" => Some(Self::FullRefresh),
+            "block" => Some(Self::Block),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::FullRefresh => "full",
+            Self::Block => "block",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// What asked for a refresh: the dashboard's full poll, a new chain tip, or
+/// the user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    FullRefresh,
+    Block,
+    Manual,
+}
+
+impl Trigger {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "full" => Some(Self::FullRefresh),
+            "block" => Some(Self::Block),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// Cards never refreshed are always due so they show something on first load.
+pub fn is_due(
+    cadence: Cadence,
+    trigger: Trigger,
+    last_refreshed_ms: Option<u64>,
+    now_ms: u64,
+) -> bool {
+    let Some(last) = last_refreshed_ms else {
+        return true;
+    };
+    match trigger {
+        Trigger::Manual => true,
+        _ if now_ms.saturating_sub(last) < MIN_AUTO_REFRESH_MS => false,
+        Trigger::FullRefresh => cadence == Cadence::FullRefresh,
+        Trigger::Block => cadence == Cadence::Block,
+    }
+}
+
+/// The rule `is_read_only` applies, so the frontend only suggests methods a
+/// card can use.
+pub fn read_only_rule() -> Value {
+    json!({
+        "prefixes": READ_ONLY_PREFIXES,
+        "methods": READ_ONLY_METHODS,
+        "except": MUTATING_GETTERS,
+    })
+}
+
+pub fn is_read_only(method: &str) -> bool {
+    if MUTATING_GETTERS.contains(&method) {
+        return false;
+    }
+    READ_ONLY_METHODS.contains(&method) || READ_ONLY_PREFIXES.iter().any(|p| method.starts_with(p))
+}
+
+/// A user-defined dashboard card showing one RPC reply, or one field of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCard {
+    pub id: String,
+    pub title: String,
+    pub method: String,
+    pub params: Value,
+    pub cadence: Cadence,
+    /// JSON pointer into the result; empty shows the whole result.
+    pub field: String,
+}
+
+impl CustomCard {
+    pub fn from_json(v: &Value) -> Result<Self, String> {
+        let text = |key: &str| v[key].as_str().unwrap_or("").trim().to_string();
+        let id = text("id");
+        if id.is_empty() {
+            return Err("card without an id".into());
+        }
+        let method = text("method");
+        if method.is_empty() || method.contains(char::is_whitespace) {
+            return Err(format!("card {id}: invalid method {method:?}"));
+        }
+        if !is_read_only(&method) {
+            return Err(format!(
+                "card {id}: {method} is not a read-only call; cards only run get*, list*, estimate* and decode* methods"
+            ));
+        }
+        let params = match &v["params"] {
+            Value::Null => json!([]),
+            p @ (Value::Array(_) | Value::Object(_)) => p.clone(),
+            _ => return Err(format!("card {id}: params must be an array or object")),
+        };
+        let cadence = Cadence::parse(v["cadence"].as_str().unwrap_or("full"))
+            .ok_or_else(|| format!("card {id}: unknown cadence {}", v["cadence"]))?;
+        let field = text("field");
+        if !field.is_empty() && !field.starts_with('/') {
+            return Err(format!("card {id}: field {field:?} must start with /"));
+        }
+        let title = match text("title") {
+            t if t.is_empty() => method.clone(),
+            t => t,
+        };
+        Ok(Self {
+            id,
+            title,
+            method,
+            params,
+            cadence,
+            field,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "title": self.title,
+            "method": self.method,
+            "params": self.params,
+            "cadence": self.cadence.as_str(),
+            "field": self.field,
+        })
+    }
+}
+
+/// Resolves a JSON pointer made of object keys and array indices, with the
+/// usual `~1` for `/` and `~0` for `~`. An empty pointer is the whole value.
+pub fn select<'a>(value: &'a Value, pointer: &str) -> Result<&'a Value, String> {
+    if pointer.is_empty() {
+        return Ok(value);
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("field {pointer:?} must start with /"));
+    };
+    let mut current = value;
+    for token in rest.split('/') {
+        let key = token.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Object(map) => map
+                .get(&key)
+                .ok_or_else(|| format!("no field {key:?} in the result"))?,
+            Value::Array(items) => key
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| items.get(i))
+                .ok_or_else(|| format!("no index {key:?} in a list of {}", items.len()))?,
+            other => return Err(format!("cannot look up {key:?} in {other}")),
+        };
+    }
+    Ok(current)
+}
+
+pub fn cards_path() -> PathBuf {
+    config_store::file_path(FILE_NAME)
+}
+
+/// The saved cards; a missing or unreadable file, or an invalid entry, is
+/// left out rather than failing the dashboard.
+pub fn load(path: &Path) -> Vec<CustomCard> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .and_then(|v| {
+            v["cards"].as_array().map(|cards| {
+                cards
+                    .iter()
+                    .filter_map(|c| CustomCard::from_json(c).ok())
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+pub fn to_json(cards: &[CustomCard]) -> Value {
+    json!({ "cards": cards.iter().map(CustomCard::to_json).collect::<Vec<_>>() })
+}
+
+/// Replaces the saved cards with `{ "cards": [...] }` from the frontend.
+/// Nothing is written unless every card is valid.
+pub fn save(path: &Path, body: &str) -> io::Result<Vec<CustomCard>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let v: Value = serde_json::from_str(body).map_err(|e| invalid(e.to_string()))?;
+    let entries = v["cards"]
+        .as_array()
+        .ok_or_else(|| invalid("missing cards".into()))?;
+    if entries.len() > MAX_CARDS {
+        return Err(invalid(format!("at most {MAX_CARDS} custom cards")));
+    }
+    let mut cards: Vec<CustomCard> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let card = CustomCard::from_json(entry).map_err(invalid)?;
+        if cards.iter().any(|c| c.id == card.id) {
+            return Err(invalid(format!("duplicate card id {}", card.id)));
+        }
+        cards.push(card);
+    }
+    config_store::write_atomic(path, &to_json(&cards).to_string())?;
+    Ok(cards)
+}
+
+/// The cards a refresh should fetch. `only` limits a manual refresh to one
+/// card; `last_refreshed` maps card ids to epoch milliseconds.
+pub fn due_cards<'a>(
+    cards: &'a [CustomCard],
+    trigger: Trigger,
+    only: Option<&str>,
+    last_refreshed: &Value,
+    now_ms: u64,
+) -> Vec<&'a CustomCard> {
+    cards
+        .iter()
+        .filter(|c| only.is_none_or(|id| c.id == id))
+        .filter(|c| is_due(c.cadence, trigger, last_refreshed[&c.id].as_u64(), now_ms))
+        .collect()
+}
+
+/// Fetches the due cards in one batch. The body is `{ trigger, id?,
+/// last_refreshed }`; each fetched card comes back with its value or error.
+pub fn refresh(
+    path: &Path,
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    body: &str,
+) -> Result<Value, String> {
+    let v: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let trigger = Trigger::parse(v["trigger"].as_str().unwrap_or(""))
+        .ok_or_else(|| format!("unknown trigger {}", v["trigger"]))?;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let cards = load(path);
+    let due = due_cards(
+        &cards,
+        trigger,
+        v["id"].as_str(),
+        &v["last_refreshed"],
+        now_ms,
+    );
+    if due.is_empty() {
+        return Ok(json!({ "cards": [], "refreshed_ms": now_ms }));
+    }
+
+    let calls: Vec<_> = due
+        .iter()
+        .map(|c| json!({ "method": c.method, "params": c.params }))
+        .collect();
+    let results = match rpc::wallet_batch(config, budget, &calls) {
+        Ok(results) => results
+            .into_iter()
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect(),
+        Err(e) => vec![Err(e.to_string()); due.len()],
+    };
+    let out: Vec<_> = due
+        .iter()
+        .zip(results)
+        .map(
+            |(card, result)| match result.and_then(|r| select(&r, &card.field).cloned()) {
+                Ok(value) => json!({ "id": card.id, "value": value }),
+                Err(error) => json!({ "id": card.id, "error": error }),
+            },
+        )
+        .collect();
+    Ok(json!({ "cards": out, "refreshed_ms": now_ms }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Cadence, CustomCard, MIN_AUTO_REFRESH_MS, Trigger, due_cards, is_due, is_read_only, load,
+        save, select,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn select_follows_keys_and_indices() {
+        let v = json!({ "pooledtx": 12, "warnings": ["a", "b"], "a/b": { "~x": true } });
+        assert_eq!(select(&v, "").unwrap(), &v);
+        assert_eq!(select(&v, "/pooledtx").unwrap(), 12);
+        assert_eq!(select(&v, "/warnings/1").unwrap(), "b");
+        assert_eq!(select(&v, "/a~1b/~0x").unwrap(), true);
+        assert_eq!(
+            select(&v, "/missing").unwrap_err(),
+            "no field \"missing\" in the result"
+        );
+        assert_eq!(
+            select(&v, "/warnings/2").unwrap_err(),
+            "no index \"2\" in a list of 2"
+        );
+        assert!(select(&v, "/pooledtx/0").is_err());
+        assert!(select(&v, "pooledtx").is_err());
+    }
+
+    #[test]
+    fn cadence_policy() {
+        let now = 10_000_000;
+        let long_ago = Some(now - 60_000);
+        let just_now = Some(now - MIN_AUTO_REFRESH_MS / 2);
+        for cadence in [Cadence::FullRefresh, Cadence::Block, Cadence::Manual] {
+            for trigger in [Trigger::FullRefresh, Trigger::Block, Trigger::Manual] {
+                assert!(is_due(cadence, trigger, None, now));
+            }
+            assert!(is_due(cadence, Trigger::Manual, just_now, now));
+        }
+        assert!(is_due(
+            Cadence::FullRefresh,
+            Trigger::FullRefresh,
+            long_ago,
+            now
+        ));
+        assert!(!is_due(
+            Cadence::FullRefresh,
+            Trigger::FullRefresh,
+            just_now,
+            now
+        ));
+        assert!(!is_due(Cadence::FullRefresh, Trigger::Block, long_ago, now));
+        assert!(is_due(Cadence::Block, Trigger::Block, long_ago, now));
+        assert!(!is_due(Cadence::Block, Trigger::Block, just_now, now));
+        assert!(!is_due(Cadence::Block, Trigger::FullRefresh, long_ago, now));
+        assert!(!is_due(
+            Cadence::Manual,
+            Trigger::FullRefresh,
+            long_ago,
+            now
+        ));
+        assert!(!is_due(Cadence::Manual, Trigger::Block, long_ago, now));
+    }
+
+    #[test]
+    fn cards_validate_and_round_trip() {
+        let card = CustomCard::from_json(&json!({
+            "id": "c1", "method": "getmininginfo", "field": "/pooledtx", "cadence": "block",
+        }))
+        .unwrap();
+        assert_eq!(card.title, "getmininginfo");
+        assert_eq!(card.params, json!([]));
+        assert_eq!(card.cadence, Cadence::Block);
+        assert_eq!(CustomCard::from_json(&card.to_json()).unwrap(), card);
+
+        assert!(CustomCard::from_json(&json!({ "method": "getblockcount" })).is_err());
+        assert!(CustomCard::from_json(&json!({ "id": "x", "method": "get info" })).is_err());
+        assert!(
+            CustomCard::from_json(&json!({ "id": "x", "method": "getblockcount", "params": "1" }))
+                .is_err()
+        );
+        assert!(
+            CustomCard::from_json(
+                &json!({ "id": "x", "method": "getblockcount", "cadence": "hourly" })
+            )
+            .is_err()
+        );
+        assert!(
+            CustomCard::from_json(
+                &json!({ "id": "x", "method": "getblockcount", "field": "size" })
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn due_cards_respects_the_requested_card() {
+        let cards: Vec<_> = [("a", "full"), ("b", "block"), ("c", "manual")]
+            .iter()
+            .map(|(id, cadence)| {
+                CustomCard::from_json(&json!({ "id": id, "method": "uptime", "cadence": cadence }))
+                    .unwrap()
+            })
+            .collect();
+        let now = 10_000_000;
+        let last = json!({ "a": now - 60_000, "b": now - 60_000, "c": now - 60_000 });
+        let ids = |due: Vec<&CustomCard>| due.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(due_cards(&cards, Trigger::FullRefresh, None, &last, now)),
+            ["a"]
+        );
+        assert_eq!(
+            ids(due_cards(&cards, Trigger::Block, None, &last, now)),
+            ["b"]
+        );
+        assert_eq!(
+            ids(due_cards(&cards, Trigger::Manual, Some("c"), &last, now)),
+            ["c"]
+        );
+        assert_eq!(
+            ids(due_cards(&cards, Trigger::Block, None, &json!({}), now)),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn save_rejects_invalid_lists_without_writing() {
+        let dir = std::env::temp_dir().join(format!("custom-cards-{}", std::process::id()));
+        let path = dir.join("custom-cards.json");
+        let body = r#"{"cards":[{"id":"a","method":"getblockcount"},{"id":"b","method":"uptime","cadence":"manual"}]}"#;
+        assert_eq!(save(&path, body).unwrap().len(), 2);
+        let dup = r#"{"cards":[{"id":"a","method":"uptime"},{"id":"a","method":"uptime"}]}"#;
+        assert!(save(&path, dup).is_err());
+        assert!(save(&path, r#"{"cards":[{"id":"a"}]}"#).is_err());
+        let cards = load(&path);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[1].cadence, Cadence::Manual);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cards_only_run_read_only_methods() {
+        for method in [
+            "getblockcount",
+            "listunspent",
+            "estimatesmartfee",
+            "decodescript",
+            "uptime",
+        ] {
+            assert!(is_read_only(method), "{method}");
+        }
+        for method in [
+            "stop",
+            "sendtoaddress",
+            "generatetoaddress",
+            "walletpassphrase",
+            "getnewaddress",
+            "invalidateblock",
+        ] {
+            assert!(!is_read_only(method), "{method}");
+            let card = json!({ "id": "x", "method": method });
+            assert!(CustomCard::from_json(&card).is_err(), "{method}");
+        }
+
+        // A card saved before the restriction is dropped on load.
+        let dir = std::env::temp_dir().join(format!("custom-cards-ro-{}", std::process::id()));
+        let path = dir.join("custom-cards.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &path,
+            r#"{"cards":[{"id":"a","method":"stop"},{"id":"b","method":"uptime"}]}"#,
+        )
+        .unwrap();
+        let cards = load(&path);
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].id, "b");
+        assert!(save(&path, r#"{"cards":[{"id":"a","method":"sendtoaddress"}]}"#).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,412 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{Value, json};
+
+use crate::config_store;
+use crate::logging;
+use crate::rpc;
+
+const DIR_NAME: &str = "exports";
+/// Lives in the config directory even when exports go elsewhere.
+const INDEX_NAME: &str = "exports.json";
+const MAX_INDEX_ENTRIES: usize = 100;
+const MAX_LABEL_CHARS: usize = 40;
+const MAX_EXPORT_BYTES: usize = 64 * 1024 * 1024;
+const EXTENSIONS: [&str; 3] = ["csv", "json", "txt"];
+/// Names tried before giving up when every one already exists.
+const MAX_NAME_ATTEMPTS: u32 = 100;
+
+/// Numbers exports within the process, so two in the same second never
+/// share a name.
+static COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// Serializes updates to the index, which is read, changed and rewritten.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// One written file, as listed in the Exports view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportEntry {
+    pub path: String,
+    pub kind: String,
+    pub size: u64,
+    pub created_ms: u64,
+}
+
+impl ExportEntry {
+    fn from_json(v: &Value) -> Option<Self> {
+        Some(Self {
+            path: v["path"].as_str().filter(|p| !p.is_empty())?.to_string(),
+            kind: v["kind"].as_str().unwrap_or_default().to_string(),
+            size: v["size"].as_u64()?,
+            created_ms: v["created_ms"].as_u64()?,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path,
+            "kind": self.kind,
+            "size": self.size,
+            "created_ms": self.created_ms,
+        })
+    }
+}
+
+/// Makes a user-supplied label safe as part of a file name: ASCII letters,
+/// digits, `-` and `_` are kept, anything else (separators, dots, spaces,
+/// reserved characters) becomes a single `-`, and the result is cut to
+/// `MAX_LABEL_CHARS`.
+pub fn sanitize_label(label: &str) -> String {
+    let mut out = String::new();
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out: String = out
+        .trim_matches('-')
+        .chars()
+        .take(MAX_LABEL_CHARS)
+        .collect();
+    out.trim_end_matches('-').to_string()
+}
+
+/// `20240501T120000Z`, for file names.
+fn compact_utc(now_ms: u64) -> String {
+    let iso = logging::format_timestamp_ms(now_ms);
+    let seconds = iso.split('.').next().unwrap_or_default();
+    format!("{}Z", seconds.replace(['-', ':'], ""))
+}
+
+/// `<kind>[-<label>]-<UTC time>-<n>.<ext>`. The stem always carries the
+/// timestamp, so it can't be a reserved device name such as `CON`.
+pub fn file_name(kind: &str, label: &str, extension: &str, now_ms: u64, n: u64) -> String {
+    let kind = sanitize_label(kind);
+    let kind = if kind.is_empty() { "export" } else { &kind };
+    let label = sanitize_label(label);
+    let stamp = compact_utc(now_ms);
+    if label.is_empty() {
+        format!("{kind}-{stamp}-{n}.{extension}")
+    } else {
+        format!("{kind}-{label}-{stamp}-{n}.{extension}")
+    }
+}
+
+/// The configured directory, or `exports` in the config directory when
+/// empty. Relative paths are refused: a GUI app's working directory is
+/// arbitrary.
+pub fn resolve_dir(configured: &str) -> Result<PathBuf, String> {
+    let configured = configured.trim();
+    if configured.is_empty() {
+        return Ok(config_store::file_path(DIR_NAME));
+    }
+    let path = rpc::expand_home(configured);
+    if !path.is_absolute() {
+        return Err(format!(
+            "Exports folder must be an absolute path, not {configured:?}"
+        ));
+    }
+    Ok(path)
+}
+
+fn ensure_dir(dir: &Path) -> io::Result<()> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is a file, not a folder", dir.display()),
+        ));
+    }
+    std::fs::create_dir_all(dir).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("can't create the exports folder {}: {e}", dir.display()),
+        )
+    })
+}
+
+pub struct ExportManager {
+    dir: PathBuf,
+    index: PathBuf,
+}
+
+impl ExportManager {
+    pub fn new(dir: PathBuf, index: PathBuf) -> Self {
+        Self { dir, index }
+    }
+
+    /// Exports go to the configured folder; the index stays in the config
+    /// directory. A folder that doesn't resolve was refused when the config
+    /// was applied, so the default stands in.
+    pub fn from_config(configured: &str) -> Self {
+        let dir = resolve_dir(configured).unwrap_or_else(|_| config_store::file_path(DIR_NAME));
+        Self::new(dir, config_store::file_path(INDEX_NAME))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes a new file and records it. An existing file is never
+    /// overwritten: a taken name moves on to the next number.
+    pub fn save(
+        &self,
+        kind: &str,
+        label: &str,
+        extension: &str,
+        contents: &[u8],
+        now_ms: u64,
+    ) -> io::Result<ExportEntry> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        if !EXTENSIONS.contains(&extension) {
+            return Err(invalid(format!("unsupported export type {extension:?}")));
+        }
+        if contents.len() > MAX_EXPORT_BYTES {
+            return Err(invalid(format!(
+                "export is larger than {} MiB",
+                MAX_EXPORT_BYTES / 1024 / 1024
+            )));
+        }
+        ensure_dir(&self.dir)?;
+        let mut attempts = 0;
+        let (path, mut file) = loop {
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = self.dir.join(file_name(kind, label, extension, now_ms, n));
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => break (path, file),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    attempts += 1;
+                    if attempts >= MAX_NAME_ATTEMPTS {
+                        return Err(e);
+                    }
+                }
+                Err(e) => {
+                    return Err(io::Error::new(e.kind(), format!("{}: {e}", path.display())));
+                }
+            }
+        };
+        if let Err(e) = file.write_all(contents).and_then(|()| file.sync_all()) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e);
+        }
+        let entry = ExportEntry {
+            path: path.display().to_string(),
+            kind: sanitize_label(kind),
+            size: contents.len() as u64,
+            created_ms: now_ms,
+        };
+        let _guard = INDEX_LOCK.lock().unwrap();
+        let mut entries = load_index(&self.index);
+        entries.insert(0, entry.clone());
+        entries.truncate(MAX_INDEX_ENTRIES);
+        save_index(&self.index, &entries)?;
+        Ok(entry)
+    }
+
+    /// Recorded exports whose files still exist, newest first.
+    pub fn list(&self) -> Vec<ExportEntry> {
+        load_index(&self.index)
+            .into_iter()
+            .filter(|e| Path::new(&e.path).is_file())
+            .collect()
+    }
+
+    /// Deletes a file this manager wrote. Paths not in the index are
+    /// refused, so the request can't name an arbitrary file.
+    pub fn delete(&self, path: &str) -> io::Result<()> {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        let mut entries = load_index(&self.index);
+        let Some(pos) = entries.iter().position(|e| e.path == path) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "not a recorded export",
+            ));
+        };
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        entries.remove(pos);
+        save_index(&self.index, &entries)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "dir": self.dir.display().to_string(),
+            "entries": self.list().iter().map(ExportEntry::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn load_index(path: &Path) -> Vec<ExportEntry> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .and_then(|v| {
+            v["entries"]
+                .as_array()
+                .map(|a| a.iter().filter_map(ExportEntry::from_json).collect())
+        })
+        .unwrap_or_default()
+}
+
+fn save_index(path: &Path, entries: &[ExportEntry]) -> io::Result<()> {
+    let body = json!({ "entries": entries.iter().map(ExportEntry::to_json).collect::<Vec<_>>() });
+    config_store::write_atomic(path, &body.to_string())
+}
+
+/// Shows the folder in the system file manager, creating it first.
+pub fn open_folder(dir: &Path) -> io::Result<()> {
+    ensure_dir(dir)?;
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let mut child = std::process::Command::new(program).arg(dir).spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportManager, MAX_LABEL_CHARS, file_name, resolve_dir, sanitize_label};
+    use std::path::PathBuf;
+
+    const MAY_1_2024_NOON_MS: u64 = 1_714_564_800_000;
+
+    fn manager(name: &str) -> (ExportManager, PathBuf) {
+        let root = std::env::temp_dir().join(format!("exports-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        (
+            ExportManager::new(root.join("out"), root.join("exports.json")),
+            root,
+        )
+    }
+
+    #[test]
+    fn labels_lose_separators_and_reserved_characters() {
+        let cases = [
+            ("getblock", "getblock"),
+            ("../../etc/passwd", "etc-passwd"),
+            ("..\\..\\Windows\\win.ini", "Windows-win-ini"),
+            ("a/b\\c:d*e?f\"g<h>i|j", "a-b-c-d-e-f-g-h-i-j"),
+            ("peers table (redacted)", "peers-table-redacted"),
+            ("  spaced   out  ", "spaced-out"),
+            ("tab\there\nnewline\0nul", "tab-here-newline-nul"),
+            ("größe", "gr-e"),
+            ("keep_under-score", "keep_under-score"),
+            ("...", ""),
+            ("", ""),
+        ];
+        for (label, clean) in cases {
+            assert_eq!(sanitize_label(label), clean, "{label:?}");
+        }
+    }
+
+    #[test]
+    fn long_labels_are_cut_without_a_trailing_dash() {
+        let long = "x".repeat(500);
+        assert_eq!(sanitize_label(&long).len(), MAX_LABEL_CHARS);
+        let dashed = format!("{}/{}", "a".repeat(MAX_LABEL_CHARS - 1), "b".repeat(10));
+        assert_eq!(sanitize_label(&dashed), "a".repeat(MAX_LABEL_CHARS - 1));
+    }
+
+    #[test]
+    fn file_names_carry_kind_label_time_and_counter() {
+        let t = MAY_1_2024_NOON_MS;
+        assert_eq!(
+            file_name("peers", "", "csv", t, 3),
+            "peers-20240501T120000Z-3.csv"
+        );
+        assert_eq!(
+            file_name("result", "../getblock", "json", t, 4),
+            "result-getblock-20240501T120000Z-4.json"
+        );
+        assert_eq!(
+            file_name("", "CON", "txt", t, 5),
+            "export-CON-20240501T120000Z-5.txt"
+        );
+        let long = file_name("result", &"y".repeat(1000), "json", t, 6);
+        assert!(long.len() < 100, "{long}");
+        assert!(!long.contains('/') && !long.contains(".."));
+    }
+
+    #[test]
+    fn exports_dir_must_be_absolute() {
+        assert!(resolve_dir("").unwrap().ends_with("exports"));
+        assert!(resolve_dir("relative/dir").is_err());
+        let abs = std::env::temp_dir().display().to_string();
+        assert_eq!(resolve_dir(&abs).unwrap(), PathBuf::from(&abs));
+    }
+
+    #[test]
+    fn saves_never_collide_and_are_indexed() {
+        let (m, root) = manager("save");
+        let t = MAY_1_2024_NOON_MS;
+        let a = m.save("peers", "", "txt", b"one", t).unwrap();
+        let b = m.save("peers", "", "txt", b"two", t).unwrap();
+        assert_ne!(a.path, b.path);
+        assert!(a.path.starts_with(&m.dir().display().to_string()));
+        assert_eq!(std::fs::read_to_string(&a.path).unwrap(), "one");
+        assert_eq!(b.size, 3);
+
+        let listed: Vec<_> = m.list().into_iter().map(|e| e.path).collect();
+        assert_eq!(listed, [b.path.clone(), a.path.clone()]);
+
+        assert!(m.save("peers", "", "exe", b"x", t).is_err());
+        assert!(m.save("peers", "", "../txt", b"x", t).is_err());
+        assert_eq!(m.list().len(), 2);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn delete_only_touches_recorded_exports() {
+        let (m, root) = manager("delete");
+        let kept = m
+            .save("result", "getblock", "json", b"{}", MAY_1_2024_NOON_MS)
+            .unwrap();
+        let outsider = root.join("outsider.txt");
+        std::fs::write(&outsider, "keep me").unwrap();
+        assert!(m.delete(&outsider.display().to_string()).is_err());
+        assert!(outsider.exists());
+
+        // A file removed by hand drops out of the list and can still be
+        // cleared from the index.
+        let gone = m
+            .save("result", "", "json", b"{}", MAY_1_2024_NOON_MS)
+            .unwrap();
+        std::fs::remove_file(&gone.path).unwrap();
+        assert_eq!(m.list(), std::slice::from_ref(&kept));
+        m.delete(&gone.path).unwrap();
+
+        m.delete(&kept.path).unwrap();
+        assert!(!std::path::Path::new(&kept.path).exists());
+        assert!(m.list().is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn a_file_where_the_folder_should_be_is_reported() {
+        let (_, root) = manager("blocked");
+        std::fs::create_dir_all(&root).unwrap();
+        let blocked = root.join("out");
+        std::fs::write(&blocked, "").unwrap();
+        let m = ExportManager::new(blocked, root.join("exports.json"));
+        let err = m.save("peers", "", "txt", b"x", 0).unwrap_err();
+        assert!(err.to_string().contains("not a folder"), "{err}");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
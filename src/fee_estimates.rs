@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+
+use crate::relay_policy;
+use crate::rpc::{self, RpcConfig, RpcError};
+use crate::rpc_limiter::RpcBudget;
+
+/// Confirmation targets shown on the dashboard, in blocks.
+pub const TARGETS: [u64; 5] = [1, 3, 6, 25, 144];
+
+/// One estimatesmartfee answer. `feerate` is BTC/kvB; it is missing when
+/// the node lacks data for the target, and `note` then says why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeEstimate {
+    pub target: u64,
+    pub blocks: Option<u64>,
+    pub feerate: Option<f64>,
+    pub note: Option<String>,
+}
+
+impl FeeEstimate {
+    fn from_reply(target: u64, reply: Result<Value, RpcError>) -> Self {
+        let v = match reply {
+            Ok(v) => v,
+            Err(e) => {
+                return Self {
+                    target,
+                    blocks: None,
+                    feerate: None,
+                    note: Some(e.to_string()),
+                };
+            }
+        };
+        let feerate = v["feerate"].as_f64().filter(|r| r.is_finite() && *r >= 0.0);
+        let note = match feerate {
+            Some(_) => None,
+            None => Some(
+                v["errors"]
+                    .as_array()
+                    .and_then(|errors| errors.first())
+                    .and_then(Value::as_str)
+                    .unwrap_or("no estimate")
+                    .to_string(),
+            ),
+        };
+        Self {
+            target,
+            blocks: v["blocks"].as_u64(),
+            feerate,
+            note,
+        }
+    }
+
+    pub fn sat_per_vb(&self) -> Option<f64> {
+        self.feerate
+            .map(|r| (r * 100_000.0 * 1000.0).round() / 1000.0)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "target": self.target,
+            "blocks": self.blocks,
+            "sat_vb": self.sat_per_vb(),
+            "text": self.feerate.map_or_else(|| "n/a".to_string(), relay_policy::sat_per_vb),
+            "note": self.note,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeEstimates {
+    pub estimates: Vec<FeeEstimate>,
+}
+
+impl FeeEstimates {
+    /// One reply per entry of `TARGETS`. A target the node can't estimate
+    /// stays in the list as n/a rather than failing the rest.
+    pub fn from_replies(replies: Vec<Result<Value, RpcError>>) -> Self {
+        let mut replies = replies.into_iter();
+        let estimates = TARGETS
+            .iter()
+            .map(|&target| {
+                let reply = replies
+                    .next()
+                    .unwrap_or_else(|| Err(RpcError::InvalidResponse("missing reply".into())));
+                FeeEstimate::from_reply(target, reply)
+            })
+            .collect();
+        Self { estimates }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "estimates": self.estimates.iter().map(FeeEstimate::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Asks for every target in one batch. Only a failed round trip is an
+/// error; per-target failures are carried in the estimates.
+pub fn fetch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+) -> Result<FeeEstimates, RpcError> {
+    let calls: Vec<Value> = TARGETS
+        .iter()
+        .map(|target| json!({ "method": "estimatesmartfee", "params": [target] }))
+        .collect();
+    Ok(FeeEstimates::from_replies(rpc::node_batch(
+        config, budget, &calls,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FeeEstimates, TARGETS};
+    use crate::rpc::RpcError;
+    use serde_json::json;
+
+    #[test]
+    fn replies_become_sat_per_vb_in_target_order() {
+        let replies = TARGETS
+            .iter()
+            .map(|&t| Ok(json!({ "feerate": 0.0001 / t as f64, "blocks": t })))
+            .collect();
+        let fees = FeeEstimates::from_replies(replies);
+        let targets: Vec<_> = fees.estimates.iter().map(|e| e.target).collect();
+        assert_eq!(targets, TARGETS);
+        assert_eq!(fees.estimates[0].sat_per_vb(), Some(10.0));
+        assert_eq!(fees.estimates[1].sat_per_vb(), Some(3.333));
+        let first = &fees.to_json()["estimates"][0];
+        assert_eq!(first["text"], "10 sat/vB");
+        assert_eq!(first["blocks"], 1);
+        assert!(first["note"].is_null());
+    }
+
+    #[test]
+    fn insufficient_data_and_errors_are_n_a() {
+        let fees = FeeEstimates::from_replies(vec![
+            Ok(json!({ "errors": ["Insufficient data or no feerate found"], "blocks": 1 })),
+            Err(RpcError::InvalidResponse("boom".into())),
+            Ok(json!({ "feerate": 0.00002, "blocks": 7 })),
+        ]);
+        let v = fees.to_json();
+        assert_eq!(v["estimates"][0]["text"], "n/a");
+        assert_eq!(
+            v["estimates"][0]["note"],
+            "Insufficient data or no feerate found"
+        );
+        assert_eq!(v["estimates"][1]["text"], "n/a");
+        assert!(v["estimates"][1]["note"].as_str().unwrap().contains("boom"));
+        assert_eq!(v["estimates"][2]["sat_vb"], 2.0);
+        // The node may answer for a longer target than asked.
+        assert_eq!(v["estimates"][2]["blocks"], 7);
+        // A short batch reply leaves the remaining targets n/a.
+        assert_eq!(v["estimates"].as_array().unwrap().len(), TARGETS.len());
+        assert_eq!(v["estimates"][4]["text"], "n/a");
+    }
+}
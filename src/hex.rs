@@ -0,0 +1,102 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    OddLength(usize),
+    InvalidChar { index: usize, ch: char },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength(len) => write!(f, "hex string has odd length {len}"),
+            HexError::InvalidChar { index, ch } => {
+                write!(f, "invalid hex character {ch:?} at position {index}")
+            }
+        }
+    }
+}
+
+pub fn encode(data: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut s = String::with_capacity(data.len() * 2);
+    for &b in data {
+        s.push(DIGITS[(b >> 4) as usize] as char);
+        s.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    s
+}
+
+pub fn encode_prefix(data: &[u8], n: usize) -> String {
+    encode(&data[..data.len().min(n)])
+}
+
+pub fn decode(input: &str) -> Result<Vec<u8>, HexError> {
+    let bytes = input.as_bytes();
+    if let Some((index, ch)) = input.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(HexError::InvalidChar { index, ch });
+    }
+    if !bytes.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(bytes.len()));
+    }
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|pair| (nibble(pair[0]) << 4) | nibble(pair[1]))
+        .collect())
+}
+
+fn nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => unreachable!("validated as hex digit"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HexError, decode, encode, encode_prefix};
+
+    #[test]
+    fn round_trips_all_byte_values() {
+        let data: Vec<u8> = (0..=255).collect();
+        let hex = encode(&data);
+        assert_eq!(hex.len(), 512);
+        assert_eq!(decode(&hex).unwrap(), data);
+        assert_eq!(decode(&hex.to_uppercase()).unwrap(), data);
+    }
+
+    #[test]
+    fn empty_input_is_empty_output() {
+        assert_eq!(encode(&[]), "");
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn rejects_odd_length() {
+        assert_eq!(decode("abc"), Err(HexError::OddLength(3)));
+    }
+
+    #[test]
+    fn rejects_invalid_chars_with_position() {
+        assert_eq!(
+            decode("00zz"),
+            Err(HexError::InvalidChar { index: 2, ch: 'z' })
+        );
+        assert_eq!(
+            decode("+1"),
+            Err(HexError::InvalidChar { index: 0, ch: '+' })
+        );
+        assert_eq!(
+            decode("0é"),
+            Err(HexError::InvalidChar { index: 1, ch: 'é' })
+        );
+    }
+
+    #[test]
+    fn prefix_is_clamped_to_input_length() {
+        assert_eq!(encode_prefix(&[0xde, 0xad, 0xbe, 0xef], 2), "dead");
+        assert_eq!(encode_prefix(&[0x01], 80), "01");
+    }
+}
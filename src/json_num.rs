@@ -0,0 +1,74 @@
+use serde_json::Value;
+
+/// Reads a non-essential unsigned field without failing the whole reply.
+///
+/// - Missing or null fields give 0 silently; older nodes omit some of them.
+/// - Integers that fit in a u64 pass through unchanged.
+/// - Non-negative floats, including ones past u64::MAX, saturate into range.
+/// - Negative numbers, strings, and other types give 0.
+///
+/// The last two cases also add a warning. Strings are never parsed as
+/// numbers, because fields like chainwork are hex strings that only look
+/// numeric.
+pub fn lenient_u64(value: &Value, field: &str, warnings: &mut Vec<String>) -> u64 {
+    match value {
+        Value::Null => 0,
+        Value::Number(n) => {
+            if let Some(v) = n.as_u64() {
+                return v;
+            }
+            match n.as_f64() {
+                Some(f) if f >= 0.0 => {
+                    warnings.push(format!("{field}: {n} is not an integer, saturated"));
+                    // `as` saturates at u64::MAX and truncates the fraction.
+                    f as u64
+                }
+                _ => {
+                    warnings.push(format!("{field}: negative value {n}, using 0"));
+                    0
+                }
+            }
+        }
+        other => {
+            warnings.push(format!("{field}: expected a number, got {other}"));
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lenient_u64;
+    use serde_json::{Value, json};
+
+    fn read(value: Value) -> (u64, usize) {
+        let mut warnings = Vec::new();
+        let v = lenient_u64(&value, "size", &mut warnings);
+        (v, warnings.len())
+    }
+
+    #[test]
+    fn in_range_integers_are_exact() {
+        assert_eq!(read(json!(0)), (0, 0));
+        assert_eq!(read(json!(u64::MAX)), (u64::MAX, 0));
+    }
+
+    #[test]
+    fn missing_is_silent_zero() {
+        assert_eq!(read(Value::Null), (0, 0));
+    }
+
+    #[test]
+    fn large_and_fractional_floats_saturate_with_a_warning() {
+        assert_eq!(read(json!(1e20)), (u64::MAX, 1));
+        assert_eq!(read(json!(12.9)), (12, 1));
+    }
+
+    #[test]
+    fn negatives_and_strings_fall_back_to_zero_with_a_warning() {
+        assert_eq!(read(json!(-1)), (0, 1));
+        assert_eq!(read(json!(-1e20)), (0, 1));
+        assert_eq!(read(json!("1000")), (0, 1));
+        assert_eq!(read(json!(true)), (0, 1));
+    }
+}
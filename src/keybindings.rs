@@ -0,0 +1,330 @@
+use serde_json::{Value, json};
+
+/// A rebindable keyboard action.
+#[derive(Debug, PartialEq)]
+pub struct Action {
+    pub id: &'static str,
+    pub default_key: &'static str,
+    pub label: &'static str,
+}
+
+pub const ACTIONS: [Action; 12] = [
+    Action {
+        id: "execute",
+        default_key: "Ctrl+Enter",
+        label: "Run the selected method again",
+    },
+    Action {
+        id: "focus-search",
+        default_key: "/",
+        label: "Search methods",
+    },
+    Action {
+        id: "show-dashboard",
+        default_key: "Alt+D",
+        label: "Show the dashboard",
+    },
+    Action {
+        id: "history-older",
+        default_key: "[",
+        label: "Older dashboard snapshot",
+    },
+    Action {
+        id: "history-newer",
+        default_key: "]",
+        label: "Newer dashboard snapshot",
+    },
+    Action {
+        id: "peer-next",
+        default_key: "J",
+        label: "Select the next peer",
+    },
+    Action {
+        id: "peer-prev",
+        default_key: "K",
+        label: "Select the previous peer",
+    },
+    Action {
+        id: "peer-open",
+        default_key: "Enter",
+        label: "Open or close the selected peer",
+    },
+    Action {
+        id: "tab-prev",
+        default_key: "Ctrl+PageUp",
+        label: "Previous result tab",
+    },
+    Action {
+        id: "tab-next",
+        default_key: "Ctrl+PageDown",
+        label: "Next result tab",
+    },
+    Action {
+        id: "toggle-password",
+        default_key: "Alt+P",
+        label: "Show or hide the RPC password",
+    },
+    Action {
+        id: "shortcuts",
+        default_key: "?",
+        label: "Show keyboard shortcuts",
+    },
+];
+
+/// Non-character keys, spelled as the webview's `KeyboardEvent.key`.
+const NAMED_KEYS: [&str; 15] = [
+    "Enter",
+    "Escape",
+    "Tab",
+    "Space",
+    "Backspace",
+    "Delete",
+    "Insert",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "ArrowUp",
+    "ArrowDown",
+    "ArrowLeft",
+    "ArrowRight",
+];
+
+/// A key plus modifiers. `format` gives the canonical descriptor the
+/// frontend builds from key events, e.g. `Ctrl+Shift+K` or `?`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    pub key: String,
+}
+
+impl KeyCombo {
+    /// Accepts modifiers in any order and case (`ctrl+k`, `Cmd+Shift+p`);
+    /// `Ctrl++` binds the plus key.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (mods, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(mods) = s.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+        let mut combo = Self {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key: normalize_key(key)?,
+        };
+        for modifier in mods.split('+').filter(|m| !m.is_empty()) {
+            match modifier.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => combo.ctrl = true,
+                "alt" | "option" => combo.alt = true,
+                "shift" => combo.shift = true,
+                "meta" | "cmd" | "super" => combo.meta = true,
+                _ => return None,
+            }
+        }
+        // Shift is already part of a symbol ("?" is Shift+/ on most
+        // layouts), so it only counts for letters and named keys.
+        if is_symbol(&combo.key) {
+            combo.shift = false;
+        }
+        Some(combo)
+    }
+
+    pub fn format(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.meta {
+            parts.push("Meta");
+        }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
+
+fn is_symbol(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_ascii_alphabetic())
+}
+
+/// Letters are upper-cased; named keys take their canonical spelling, with
+/// `F1`-`F12`, `Esc`, `Up`/`Down`/`Left`/`Right` also accepted.
+fn normalize_key(key: &str) -> Option<String> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (!c.is_whitespace()).then(|| c.to_ascii_uppercase().to_string());
+    }
+    let lower = key.trim().to_ascii_lowercase();
+    let alias = match lower.as_str() {
+        "esc" => Some("Escape"),
+        "up" => Some("ArrowUp"),
+        "down" => Some("ArrowDown"),
+        "left" => Some("ArrowLeft"),
+        "right" => Some("ArrowRight"),
+        _ => None,
+    };
+    if let Some(name) = alias {
+        return Some(name.to_string());
+    }
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok())
+        && (1..=12).contains(&n)
+    {
+        return Some(format!("F{n}"));
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(&lower))
+        .map(|name| name.to_string())
+}
+
+/// Each action's binding: the saved descriptor when it parses, the default
+/// otherwise. An empty saved descriptor leaves the action unbound.
+pub fn resolve(saved: &Value) -> Vec<(&'static Action, Option<KeyCombo>)> {
+    ACTIONS
+        .iter()
+        .map(|action| {
+            let combo = match saved[action.id].as_str() {
+                Some(s) if s.trim().is_empty() => None,
+                Some(s) => KeyCombo::parse(s).or_else(|| KeyCombo::parse(action.default_key)),
+                None => KeyCombo::parse(action.default_key),
+            };
+            (action, combo)
+        })
+        .collect()
+}
+
+/// Pairs of actions bound to the same key, in table order.
+pub fn conflicts(
+    bindings: &[(&'static Action, Option<KeyCombo>)],
+) -> Vec<(&'static str, &'static str)> {
+    let mut out = Vec::new();
+    for (i, (a, combo)) in bindings.iter().enumerate() {
+        let Some(combo) = combo else {
+            continue;
+        };
+        for (b, other) in &bindings[i + 1..] {
+            if other.as_ref() == Some(combo) {
+                out.push((a.id, b.id));
+            }
+        }
+    }
+    out
+}
+
+/// The resolved table for the frontend, with any conflicts.
+pub fn to_json(saved: &Value) -> Value {
+    let bindings = resolve(saved);
+    let rows: Vec<_> = bindings
+        .iter()
+        .map(|(action, combo)| {
+            let key = combo.as_ref().map(KeyCombo::format).unwrap_or_default();
+            json!({
+                "action": action.id,
+                "label": action.label,
+                "key": key,
+                "default": action.default_key,
+                "custom": key != action.default_key,
+            })
+        })
+        .collect();
+    let conflicts: Vec<_> = conflicts(&bindings)
+        .into_iter()
+        .map(|(a, b)| json!([a, b]))
+        .collect();
+    json!({ "bindings": rows, "conflicts": conflicts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ACTIONS, KeyCombo, conflicts, resolve, to_json};
+    use serde_json::json;
+
+    fn canonical(s: &str) -> Option<String> {
+        KeyCombo::parse(s).map(|c| c.format())
+    }
+
+    #[test]
+    fn descriptors_parse_to_a_canonical_form() {
+        assert_eq!(canonical("ctrl+enter").as_deref(), Some("Ctrl+Enter"));
+        assert_eq!(
+            canonical("Shift+cmd+alt+ctrl+k").as_deref(),
+            Some("Ctrl+Alt+Shift+Meta+K")
+        );
+        assert_eq!(canonical("Shift+?").as_deref(), Some("?"));
+        assert_eq!(canonical("Ctrl++").as_deref(), Some("Ctrl++"));
+        assert_eq!(canonical("+").as_deref(), Some("+"));
+        assert_eq!(canonical("alt+esc").as_deref(), Some("Alt+Escape"));
+        assert_eq!(canonical("f5").as_deref(), Some("F5"));
+        assert_eq!(canonical("space").as_deref(), Some("Space"));
+        assert_eq!(canonical("Ctrl+PageDown").as_deref(), Some("Ctrl+PageDown"));
+        assert_eq!(canonical(""), None);
+        assert_eq!(canonical("Ctrl"), None);
+        assert_eq!(canonical("Hyper+K"), None);
+        assert_eq!(canonical("F13"), None);
+        assert_eq!(canonical("Ctrl+ "), None);
+    }
+
+    #[test]
+    fn every_default_is_canonical_and_unique() {
+        for action in &ACTIONS {
+            assert_eq!(
+                canonical(action.default_key).as_deref(),
+                Some(action.default_key)
+            );
+        }
+        assert!(conflicts(&resolve(&json!({}))).is_empty());
+    }
+
+    #[test]
+    fn saved_bindings_override_and_fall_back() {
+        let saved = json!({
+            "execute": "ctrl+r",
+            "focus-search": "not a key",
+            "shortcuts": "",
+            "unknown-action": "X",
+        });
+        let v = to_json(&saved);
+        let row = |id: &str| {
+            v["bindings"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|r| r["action"] == id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(row("execute")["key"], "Ctrl+R");
+        assert_eq!(row("execute")["custom"], true);
+        assert_eq!(row("focus-search")["key"], "/");
+        assert_eq!(row("focus-search")["custom"], false);
+        assert_eq!(row("shortcuts")["key"], "");
+        assert_eq!(v["bindings"].as_array().unwrap().len(), ACTIONS.len());
+    }
+
+    #[test]
+    fn conflicts_name_both_actions() {
+        let saved = json!({ "tab-next": "Alt+D", "history-newer": "[", "shortcuts": "" });
+        let v = to_json(&saved);
+        assert_eq!(
+            v["conflicts"],
+            json!([
+                ["show-dashboard", "tab-next"],
+                ["history-older", "history-newer"]
+            ])
+        );
+    }
+}
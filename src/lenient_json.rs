@@ -0,0 +1,309 @@
+use serde_json::{Value, json};
+
+const DOUBLE_QUOTES: [char; 4] = ['"', '\u{201C}', '\u{201D}', '\u{201E}'];
+const SINGLE_QUOTES: [char; 3] = ['\'', '\u{2018}', '\u{2019}'];
+
+/// What the lenient pass had to change.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Fixes {
+    pub smart_quotes: bool,
+    pub comments: bool,
+    pub trailing_commas: bool,
+    pub single_quotes: bool,
+}
+
+impl Fixes {
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.smart_quotes, "smart quotes"),
+            (self.comments, "comments"),
+            (self.trailing_commas, "trailing commas"),
+            (self.single_quotes, "single quotes"),
+        ]
+        .into_iter()
+        .filter_map(|(applied, name)| applied.then_some(name))
+        .collect()
+    }
+
+    fn any(&self) -> bool {
+        self.smart_quotes || self.comments || self.trailing_commas || self.single_quotes
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// Already valid; the text is left as typed.
+    Strict,
+    Cleaned {
+        json: String,
+        fixes: Fixes,
+    },
+    /// Neither pass parsed. Carries the strict parser's error.
+    Invalid(String),
+}
+
+/// Strict parse first; the lenient rewrite is only offered when it turns
+/// failing text into valid JSON.
+pub fn clean(text: &str) -> Outcome {
+    let strict_error = match serde_json::from_str::<Value>(text) {
+        Ok(_) => return Outcome::Strict,
+        Err(e) => e.to_string(),
+    };
+    match normalize(text) {
+        Some((json, fixes)) if serde_json::from_str::<Value>(&json).is_ok() => {
+            Outcome::Cleaned { json, fixes }
+        }
+        _ => Outcome::Invalid(strict_error),
+    }
+}
+
+/// Rewrites JSON as pasted from docs or JS source: smart quotes become
+/// ASCII, `//` and `/* */` comments and trailing commas go, and
+/// single-quoted strings become double-quoted. Nothing inside a string is
+/// touched. `None` when there was nothing to fix or a string or comment
+/// doesn't end where it should.
+pub fn normalize(text: &str) -> Option<(String, Fixes)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut fixes = Fixes::default();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if DOUBLE_QUOTES.contains(&c) || SINGLE_QUOTES.contains(&c) {
+            i = copy_string(&chars, i, &mut out, &mut fixes)?;
+        } else if let Some(end) = comment_end(&chars, i) {
+            fixes.comments = true;
+            i = end?;
+        } else if c == ',' && matches!(next_significant(&chars, i + 1)?, Some(']' | '}')) {
+            fixes.trailing_commas = true;
+            i += 1;
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    if !fixes.any() {
+        return None;
+    }
+    // Removed comments leave blank lines and trailing spaces behind. A JSON
+    // string can't span lines, so this never reaches inside one.
+    let lines: Vec<&str> = out
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+    Some((lines.join("\n"), fixes))
+}
+
+/// Copies the string opening at `start` as a double-quoted JSON string and
+/// returns the index after it. An ASCII quote is only closed by the same
+/// quote, so a curly quote inside a normal string stays text. A string the
+/// rewrite produced must be followed by something that can follow a value,
+/// which rejects apostrophes like `'it's'`.
+fn copy_string(chars: &[char], start: usize, out: &mut String, fixes: &mut Fixes) -> Option<usize> {
+    let open = chars[start];
+    let single = SINGLE_QUOTES.contains(&open);
+    let closes = |c: char| match open {
+        '"' | '\'' => c == open,
+        _ if single => SINGLE_QUOTES.contains(&c),
+        _ => DOUBLE_QUOTES.contains(&c),
+    };
+    let mut rewritten = open != '"';
+    out.push('"');
+    let mut i = start + 1;
+    loop {
+        let c = *chars.get(i)?;
+        i += 1;
+        if c == '\\' {
+            let escaped = *chars.get(i)?;
+            i += 1;
+            // `\'` is only an escape inside single quotes; JSON has no such
+            // escape, and the quote needs none once the string is double.
+            if !(single && escaped == '\'') {
+                out.push('\\');
+            }
+            out.push(escaped);
+        } else if closes(c) {
+            rewritten |= c != '"';
+            break;
+        } else {
+            if single && c == '"' {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+    out.push('"');
+    if !rewritten {
+        return Some(i);
+    }
+    fixes.single_quotes |= single;
+    fixes.smart_quotes |= !['"', '\''].contains(&open) || !['"', '\''].contains(&chars[i - 1]);
+    match next_significant(chars, i)? {
+        None | Some(',' | ':' | ']' | '}') => Some(i),
+        Some(_) => None,
+    }
+}
+
+/// If a comment starts at `i`, where it ends: `Some(None)` for a block
+/// comment that never closes.
+fn comment_end(chars: &[char], i: usize) -> Option<Option<usize>> {
+    if chars[i] != '/' {
+        return None;
+    }
+    match chars.get(i + 1) {
+        // The newline is kept so the next line stays separate.
+        Some('/') => Some(Some(
+            (i..chars.len())
+                .find(|&j| chars[j] == '\n')
+                .unwrap_or(chars.len()),
+        )),
+        Some('*') => Some(
+            (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map(|j| j + 2),
+        ),
+        _ => None,
+    }
+}
+
+/// The next character at or after `i` that isn't whitespace or inside a
+/// comment; `Some(None)` at the end of input, `None` for an unclosed comment.
+fn next_significant(chars: &[char], mut i: usize) -> Option<Option<char>> {
+    while i < chars.len() {
+        if let Some(end) = comment_end(chars, i) {
+            i = end?;
+        } else if chars[i].is_whitespace() {
+            i += 1;
+        } else {
+            return Some(Some(chars[i]));
+        }
+    }
+    Some(None)
+}
+
+/// `{ "text": "..." }` from the frontend.
+pub fn clean_json(body: &Value) -> Value {
+    match clean(body["text"].as_str().unwrap_or_default()) {
+        Outcome::Strict => json!({ "status": "strict" }),
+        Outcome::Cleaned { json, fixes } => json!({
+            "status": "cleaned",
+            "json": json,
+            "fixes": fixes.names(),
+        }),
+        Outcome::Invalid(error) => json!({ "status": "invalid", "error": error }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, clean, clean_json, normalize};
+    use serde_json::json;
+
+    #[test]
+    fn lenient_rewrites() {
+        // (pasted, rewritten); `None` where the pass must give up.
+        let cases: &[(&str, Option<&str>)] = &[
+            ("[\u{201C}abc\u{201D}, 1]", Some(r#"["abc", 1]"#)),
+            (
+                "{\u{201C}a\u{201D}: \u{201E}b\u{201C}}",
+                Some(r#"{"a": "b"}"#),
+            ),
+            (r#"{'a': 'b'}"#, Some(r#"{"a": "b"}"#)),
+            ("[\u{2018}x\u{2019}]", Some(r#"["x"]"#)),
+            (r#"['say "hi"']"#, Some(r#"["say \"hi\""]"#)),
+            (r#"['it\'s']"#, Some(r#"["it's"]"#)),
+            (r#"['a\nb']"#, Some(r#"["a\nb"]"#)),
+            (r#"{"a": 1,}"#, Some(r#"{"a": 1}"#)),
+            ("[1, 2, ]", Some("[1, 2 ]")),
+            ("[1,\n  2,\n]", Some("[1,\n  2\n]")),
+            ("[1, /* last */ ]", Some("[1  ]")),
+            ("[1, // two\n]", Some("[1\n]")),
+            (
+                "[\n  \"http://x\", // the node\n  2\n]",
+                Some("[\n  \"http://x\",\n  2\n]"),
+            ),
+            ("// leading\n[1]", Some("[1]")),
+            ("[/* a */ 1]", Some("[ 1]")),
+            ("[1 /* a\nb */, 2]", Some("[1 , 2]")),
+            // Commas, brackets and comment markers inside strings stay.
+            (r#"["a,]", 1,]"#, Some(r#"["a,]", 1]"#)),
+            (
+                r#"["// keep", "/* keep */",]"#,
+                Some(r#"["// keep", "/* keep */"]"#),
+            ),
+            (r#"['a, b', 'c // d',]"#, Some(r#"["a, b", "c // d"]"#)),
+            (r#"["x\"//y", 1,]"#, Some(r#"["x\"//y", 1]"#)),
+            // A curly quote inside an ASCII string is text.
+            (
+                "[\"he said \u{201C}hi\u{201D}\", 'x']",
+                Some("[\"he said \u{201C}hi\u{201D}\", \"x\"]"),
+            ),
+            ("[\"don\u{2019}t\", 1,]", Some("[\"don\u{2019}t\", 1]")),
+            // Nothing to fix.
+            ("[1, 2]", None),
+            ("[1,, 2]", None),
+            // An apostrophe is not a closing quote.
+            ("['it's']", None),
+            ("[\u{2018}it\u{2019}s\u{2019}]", None),
+            (r#"["unterminated"#, None),
+            ("['unterminated]", None),
+            ("[1 /* open", None),
+            ("[1, /* open", None),
+        ];
+        for (pasted, rewritten) in cases {
+            let got = normalize(pasted).map(|(text, _)| text);
+            assert_eq!(got.as_deref(), *rewritten, "{pasted}");
+        }
+    }
+
+    #[test]
+    fn strict_input_is_never_touched() {
+        for text in [
+            r#"["// not a comment", "a, ]"]"#,
+            r#"{"a": "it's", "b": "“"}"#,
+            "[\"\u{201C}quoted\u{201D}\"]",
+            "  [1,\n 2]  ",
+        ] {
+            assert_eq!(clean(text), Outcome::Strict, "{text}");
+        }
+    }
+
+    #[test]
+    fn cleaned_output_parses_and_lists_fixes() {
+        let Outcome::Cleaned { json, fixes } =
+            clean("{'a': [1, 2,], \u{201C}b\u{201D}: 2 // two\n}")
+        else {
+            panic!("expected a cleanup");
+        };
+        assert_eq!(json, "{\"a\": [1, 2], \"b\": 2\n}");
+        assert_eq!(
+            fixes.names(),
+            [
+                "smart quotes",
+                "comments",
+                "trailing commas",
+                "single quotes"
+            ]
+        );
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v, json!({ "a": [1, 2], "b": 2 }));
+    }
+
+    #[test]
+    fn failing_both_passes_reports_the_strict_error() {
+        let strict = serde_json::from_str::<serde_json::Value>("[1,, 2,]")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(clean("[1,, 2,]"), Outcome::Invalid(strict.clone()));
+        assert_eq!(
+            clean_json(&json!({ "text": "[1,, 2,]" })),
+            json!({ "status": "invalid", "error": strict })
+        );
+        assert_eq!(
+            clean_json(&json!({ "text": "[1, 2,]" })),
+            json!({ "status": "cleaned", "json": "[1, 2]", "fixes": ["trailing commas"] })
+        );
+        assert_eq!(clean_json(&json!({ "text": "[]" }))["status"], "strict");
+    }
+}
@@ -0,0 +1,156 @@
+use bitcoin::consensus::encode::{deserialize, deserialize_partial};
+use bitcoin::{Script, Transaction, block};
+use serde_json::json;
+
+use crate::hex;
+
+fn script_type(script: &Script) -> &'static str {
+    if script.is_p2pkh() {
+        "pubkeyhash"
+    } else if script.is_p2sh() {
+        "scripthash"
+    } else if script.is_p2wpkh() {
+        "witness_v0_keyhash"
+    } else if script.is_p2wsh() {
+        "witness_v0_scripthash"
+    } else if script.is_p2tr() {
+        "witness_v1_taproot"
+    } else if script.is_op_return() {
+        "nulldata"
+    } else if script.is_p2pk() {
+        "pubkey"
+    } else if script.is_multisig() {
+        "multisig"
+    } else {
+        "nonstandard"
+    }
+}
+
+fn tx_json(tx: &Transaction) -> serde_json::Value {
+    let vin: Vec<_> = tx
+        .input
+        .iter()
+        .map(|input| {
+            let witness: Vec<_> = input.witness.iter().map(hex::encode).collect();
+            let mut v = if tx.is_coinbase() {
+                json!({ "coinbase": input.script_sig.to_hex_string() })
+            } else {
+                json!({
+                    "txid": input.previous_output.txid.to_string(),
+                    "vout": input.previous_output.vout,
+                    "scriptSig": { "hex": input.script_sig.to_hex_string() },
+                })
+            };
+            if !witness.is_empty() {
+                v["txinwitness"] = json!(witness);
+            }
+            v["sequence"] = json!(input.sequence.0);
+            v
+        })
+        .collect();
+    let vout: Vec<_> = tx
+        .output
+        .iter()
+        .enumerate()
+        .map(|(n, output)| {
+            json!({
+                "value": output.value.to_btc(),
+                "n": n,
+                "scriptPubKey": {
+                    "hex": output.script_pubkey.to_hex_string(),
+                    "type": script_type(&output.script_pubkey),
+                },
+            })
+        })
+        .collect();
+    json!({
+        "txid": tx.compute_txid().to_string(),
+        "hash": tx.compute_wtxid().to_string(),
+        "version": tx.version.0,
+        "size": tx.total_size(),
+        "vsize": tx.vsize(),
+        "weight": tx.weight().to_wu(),
+        "locktime": tx.lock_time.to_consensus_u32(),
+        "vin": vin,
+        "vout": vout,
+    })
+}
+
+/// `decoderawtransaction`-shaped JSON built without a node, for when RPC is
+/// unavailable. Addresses are left out because the network isn't known.
+/// Invalid input gives `{"error": ...}`.
+pub fn decode_rawtx_local(hex_str: &str) -> serde_json::Value {
+    let bytes = match hex::decode(hex_str.trim()) {
+        Ok(bytes) => bytes,
+        Err(e) => return json!({ "error": e.to_string() }),
+    };
+    match deserialize::<Transaction>(&bytes) {
+        Ok(tx) => tx_json(&tx),
+        Err(e) => json!({ "error": format!("invalid transaction: {e}") }),
+    }
+}
+
+/// Txid of a serialized transaction, as shown by the node.
+pub fn txid(raw: &[u8]) -> Option<String> {
+    deserialize::<Transaction>(raw)
+        .ok()
+        .map(|tx| tx.compute_txid().to_string())
+}
+
+/// Hash of a serialized block, from its 80-byte header.
+pub fn block_hash(raw: &[u8]) -> Option<String> {
+    deserialize_partial::<block::Header>(raw)
+        .ok()
+        .map(|(header, _)| header.block_hash().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_hash, decode_rawtx_local, txid};
+    use crate::hex;
+
+    // The genesis block: header followed by its single coinbase transaction.
+    const GENESIS_HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    const GENESIS_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    #[test]
+    fn genesis_coinbase_decodes_like_the_node() {
+        let v = decode_rawtx_local(GENESIS_COINBASE);
+        assert_eq!(
+            v["txid"],
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert_eq!(v["size"], 204);
+        assert_eq!(v["vsize"], 204);
+        assert_eq!(v["vin"][0]["sequence"], 0xffff_ffffu32);
+        assert!(
+            v["vin"][0]["coinbase"]
+                .as_str()
+                .unwrap()
+                .starts_with("04ffff001d")
+        );
+        assert_eq!(v["vout"][0]["value"], 50.0);
+        assert_eq!(v["vout"][0]["scriptPubKey"]["type"], "pubkey");
+    }
+
+    #[test]
+    fn invalid_input_reports_an_error() {
+        assert!(decode_rawtx_local("zz")["error"].is_string());
+        assert!(decode_rawtx_local("0100")["error"].is_string());
+    }
+
+    #[test]
+    fn hashes_for_raw_notifications() {
+        let block = hex::decode(&format!("{GENESIS_HEADER}01{GENESIS_COINBASE}")).unwrap();
+        assert_eq!(
+            block_hash(&block).as_deref(),
+            Some("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+        );
+        let tx = hex::decode(GENESIS_COINBASE).unwrap();
+        assert_eq!(
+            txid(&tx).as_deref(),
+            Some("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")
+        );
+        assert_eq!(txid(&tx[..10]), None);
+    }
+}
@@ -1,15 +1,322 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt as fmt_layer};
+
+const RECENT_LOG_CAPACITY: usize = 2000;
+const MAX_MESSAGE_BYTES: usize = 512;
+const MAX_TARGET_BYTES: usize = 96;
+
 pub fn init() {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("bitcoin_rpc_web=info"));
 
-    let subscriber = tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
+    let stdout = fmt_layer::layer()
         .with_writer(std::io::stdout)
         .with_target(true)
         .with_level(true)
         .with_thread_ids(true)
         .with_ansi(false)
-        .finish();
+        .with_filter(env_filter);
+
+    // Captured independently of RUST_LOG so bug reports always have context.
+    let recent = RecentLogLayer {
+        buffer: Arc::clone(recent_logs()),
+    }
+    .with_filter(
+        Targets::new()
+            .with_target("bitcoin_rpc_web", LevelFilter::DEBUG)
+            .with_default(LevelFilter::WARN),
+    );
+
+    let _ = tracing_subscriber::registry()
+        .with(stdout)
+        .with(recent)
+        .try_init();
+}
+
+pub fn recent_logs() -> &'static Arc<LogBuffer> {
+    static BUFFER: OnceLock<Arc<LogBuffer>> = OnceLock::new();
+    BUFFER.get_or_init(|| LogBuffer::new(RECENT_LOG_CAPACITY))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvent {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Bounded ring of recent events. Writers never wait: if the buffer is busy
+/// the event is counted as dropped instead of blocking the logging thread.
+pub struct LogBuffer {
+    capacity: usize,
+    ring: Mutex<Ring>,
+    dropped: AtomicU64,
+}
+
+#[derive(Default)]
+struct Ring {
+    next_seq: u64,
+    events: VecDeque<LogEvent>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            capacity: capacity.max(1),
+            ring: Mutex::new(Ring::default()),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub fn record(&self, level: Level, target: &str, message: String) {
+        let mut event = LogEvent {
+            seq: 0,
+            timestamp_ms: now_ms(),
+            level,
+            target: truncate(target.to_string(), MAX_TARGET_BYTES),
+            message: truncate(message, MAX_MESSAGE_BYTES),
+        };
+        let mut ring = match self.ring.try_lock() {
+            Ok(ring) => ring,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        event.seq = ring.next_seq;
+        ring.next_seq += 1;
+        if ring.events.len() == self.capacity {
+            ring.events.pop_front();
+        }
+        ring.events.push_back(event);
+    }
+
+    /// Events at `min_level` or more severe, oldest first.
+    pub fn snapshot(&self, min_level: Level) -> Vec<LogEvent> {
+        let ring = self.ring.lock().unwrap_or_else(|p| p.into_inner());
+        ring.events
+            .iter()
+            .filter(|e| e.level <= min_level)
+            .cloned()
+            .collect()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn render_text(&self, min_level: Level) -> String {
+        let mut out = String::new();
+        for e in self.snapshot(min_level) {
+            let _ = writeln!(
+                out,
+                "{} {:>5} {}: {}",
+                format_timestamp_ms(e.timestamp_ms),
+                e.level,
+                e.target,
+                e.message
+            );
+        }
+        let dropped = self.dropped();
+        if dropped > 0 {
+            let _ = writeln!(out, "({dropped} events dropped while the buffer was busy)");
+        }
+        out
+    }
+}
+
+struct RecentLogLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let meta = event.metadata();
+        self.buffer
+            .record(*meta.level(), meta.target(), visitor.finish());
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(mut self) -> String {
+        if !self.fields.is_empty() {
+            if !self.message.is_empty() {
+                self.message.push(' ');
+            }
+            self.message.push_str(&self.fields);
+        }
+        self.message
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // Stop formatting once the message can no longer fit.
+        if self.message.len() + self.fields.len() > MAX_MESSAGE_BYTES {
+            return;
+        }
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
+
+fn truncate(mut s: String, max: usize) -> String {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+        s.push('…');
+    }
+    s
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub fn format_timestamp_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Howard Hinnant's days-to-civil conversion.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogBuffer, MAX_MESSAGE_BYTES, RecentLogLayer, format_timestamp_ms};
+    use std::sync::Arc;
+    use std::thread;
+    use tracing::Level;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn concurrent_writers_keep_order_and_cap() {
+        let buffer = LogBuffer::new(100);
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let buffer = Arc::clone(&buffer);
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        buffer.record(Level::INFO, "test", format!("t{t} e{i}"));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let events = buffer.snapshot(Level::TRACE);
+        assert_eq!(events.len(), 100);
+        assert!(events.windows(2).all(|w| w[0].seq < w[1].seq));
+        let recorded = events.last().unwrap().seq + 1;
+        assert_eq!(recorded + buffer.dropped(), 2000);
+    }
+
+    #[test]
+    fn busy_buffer_drops_instead_of_blocking() {
+        let buffer = LogBuffer::new(10);
+        let held = buffer.ring.lock().unwrap();
+        buffer.record(Level::WARN, "test", "lost".into());
+        drop(held);
+        buffer.record(Level::WARN, "test", "kept".into());
+        assert_eq!(buffer.dropped(), 1);
+        let events = buffer.snapshot(Level::TRACE);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "kept");
+        assert!(buffer.render_text(Level::WARN).contains("1 events dropped"));
+    }
+
+    #[test]
+    fn long_messages_are_truncated_and_levels_filtered() {
+        let buffer = LogBuffer::new(10);
+        buffer.record(Level::ERROR, "test", "é".repeat(MAX_MESSAGE_BYTES));
+        buffer.record(Level::DEBUG, "test", "noise".into());
+        let warn = buffer.snapshot(Level::WARN);
+        assert_eq!(warn.len(), 1);
+        assert!(warn[0].message.len() <= MAX_MESSAGE_BYTES + '…'.len_utf8());
+        assert!(warn[0].message.ends_with('…'));
+        assert_eq!(buffer.snapshot(Level::DEBUG).len(), 2);
+    }
+
+    #[test]
+    fn layer_captures_message_and_fields() {
+        let buffer = LogBuffer::new(10);
+        let subscriber = tracing_subscriber::registry().with(RecentLogLayer {
+            buffer: Arc::clone(&buffer),
+        });
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(method = "getblockcount", status = 500, "rpc failed");
+        });
+        let events = buffer.snapshot(Level::WARN);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].message,
+            "rpc failed method=\"getblockcount\" status=500"
+        );
+    }
 
-    let _ = tracing::subscriber::set_global_default(subscriber);
+    #[test]
+    fn timestamps_render_as_utc() {
+        assert_eq!(format_timestamp_ms(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp_ms(1_704_153_600_123),
+            "2024-01-02T00:00:00.123Z"
+        );
+    }
 }
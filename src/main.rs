@@ -1,15 +1,46 @@
 use std::sync::{Arc, Mutex};
 
+mod base64;
+mod bitcoin_conf;
+mod block_summary;
+mod build_info;
+mod changelog;
+mod cli;
+mod config_store;
+mod console_draft;
+mod custom_cards;
+mod exports;
+mod fee_estimates;
+mod hex;
+mod json_num;
+mod keybindings;
+mod lenient_json;
+mod local_decode;
 mod logging;
+mod mempool_histogram;
+mod mempool_summary;
 mod music;
+mod node_compat;
+mod node_log;
+mod params_key;
+mod peer_export;
+mod percent;
 mod protocol;
+mod relay_policy;
+mod rolling_rate;
 mod rpc;
+mod rpc_history;
 mod rpc_limiter;
+mod status_indicator;
+mod status_server;
+mod sync_stall;
 mod thread_pool;
+mod usage_metrics;
 mod zmq;
 
 struct RuntimeTuning {
     rpc_threads: usize,
+    node_concurrency: usize,
     zmq_poll_threads: usize,
 }
 
@@ -30,6 +61,12 @@ fn runtime_tuning() -> RuntimeTuning {
     let default_zmq_poll = (cpus / 2).clamp(1, 4);
     RuntimeTuning {
         rpc_threads: bounded_from_env("RPC_THREADS", default_rpc, 1, 64),
+        node_concurrency: bounded_from_env(
+            "RPC_NODE_CONCURRENCY",
+            rpc_limiter::DEFAULT_NODE_CONCURRENCY,
+            1,
+            64,
+        ),
         zmq_poll_threads: bounded_from_env("ZMQ_POLL_THREADS", default_zmq_poll, 1, 32),
     }
 }
@@ -42,17 +79,24 @@ struct AppContext {
     music_runtime: Arc<music::MusicRuntime>,
     zmq_state: Arc<zmq::ZmqSharedState>,
     zmq_handle: Arc<Mutex<Option<zmq::ZmqHandle>>>,
+    status_state: Arc<status_server::StatusState>,
+    node_log_state: Arc<Mutex<node_log::NodeLogState>>,
+    node_log_handle: Arc<Mutex<Option<node_log::NodeLogHandle>>>,
 }
 
 fn build_app_context(tuning: &RuntimeTuning) -> AppContext {
+    let zmq_state = Arc::new(zmq::ZmqSharedState::default());
     AppContext {
         config: Arc::new(Mutex::new(rpc::RpcConfig::default())),
-        rpc_limiter: rpc_limiter::RpcLimiter::new(tuning.rpc_threads),
+        rpc_limiter: rpc_limiter::RpcLimiter::new(tuning.rpc_threads, tuning.node_concurrency),
         rpc_pool: thread_pool::ThreadPool::new(tuning.rpc_threads),
         zmq_poll_pool: thread_pool::ThreadPool::new(tuning.zmq_poll_threads),
         music_runtime: Arc::new(music::start_music()),
-        zmq_state: Arc::new(zmq::ZmqSharedState::default()),
+        status_state: Arc::new(status_server::StatusState::new(Arc::clone(&zmq_state))),
+        zmq_state,
         zmq_handle: Arc::new(Mutex::new(None)),
+        node_log_state: Arc::new(Mutex::new(node_log::NodeLogState::default())),
+        node_log_handle: Arc::new(Mutex::new(None)),
     }
 }
 
@@ -63,11 +107,20 @@ fn shutdown_zmq(zmq_handle: &Arc<Mutex<Option<zmq::ZmqHandle>>>) {
     }
 }
 
+fn shutdown_node_log(node_log_handle: &Arc<Mutex<Option<node_log::NodeLogHandle>>>) {
+    if let Some(h) = node_log_handle.lock().unwrap().take() {
+        node_log::stop_tailer(h);
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn main() {
     use gtk::prelude::*;
     use wry::WebViewBuilderExtUnix;
 
+    if let Some(code) = cli::run_from_args() {
+        std::process::exit(code);
+    }
     logging::init();
     let tuning = runtime_tuning();
 
@@ -81,22 +134,16 @@ fn main() {
     window.add(&vbox);
 
     let app = build_app_context(&tuning);
+    let _status_server = status_server::start_from_env(&app.status_state);
 
-    let _webview = protocol::build_webview(
-        app.config,
-        app.rpc_limiter,
-        app.rpc_pool,
-        app.zmq_poll_pool,
-        app.music_runtime,
-        app.zmq_state,
-        Arc::clone(&app.zmq_handle),
-    )
-    .build_gtk(&vbox)
-    .unwrap();
+    let _webview = protocol::build_webview(&app).build_gtk(&vbox).unwrap();
 
     let zmq_handle_for_shutdown = Arc::clone(&app.zmq_handle);
+    let node_log_handle_for_shutdown = Arc::clone(&app.node_log_handle);
     window.connect_delete_event(move |_, _| {
         shutdown_zmq(&zmq_handle_for_shutdown);
+        shutdown_node_log(&node_log_handle_for_shutdown);
+        console_draft::on_clean_exit();
         gtk::main_quit();
         gtk::glib::Propagation::Stop
     });
@@ -117,17 +164,7 @@ impl winit::application::ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         let attrs = winit::window::Window::default_attributes().with_title("Bitcoin Core RPC");
         let window = event_loop.create_window(attrs).unwrap();
-        let webview = protocol::build_webview(
-            Arc::clone(&self.ctx.config),
-            Arc::clone(&self.ctx.rpc_limiter),
-            Arc::clone(&self.ctx.rpc_pool),
-            Arc::clone(&self.ctx.zmq_poll_pool),
-            Arc::clone(&self.ctx.music_runtime),
-            Arc::clone(&self.ctx.zmq_state),
-            Arc::clone(&self.ctx.zmq_handle),
-        )
-        .build(&window)
-        .unwrap();
+        let webview = protocol::build_webview(&self.ctx).build(&window).unwrap();
         self.window = Some(window);
         self.webview = Some(webview);
     }
@@ -140,6 +177,7 @@ impl winit::application::ApplicationHandler for App {
     ) {
         if let winit::event::WindowEvent::CloseRequested = event {
             shutdown_zmq(&self.ctx.zmq_handle);
+            shutdown_node_log(&self.ctx.node_log_handle);
             event_loop.exit();
         }
     }
@@ -147,6 +185,9 @@ impl winit::application::ApplicationHandler for App {
 
 #[cfg(not(target_os = "linux"))]
 fn main() {
+    if let Some(code) = cli::run_from_args() {
+        std::process::exit(code);
+    }
     logging::init();
     let tuning = runtime_tuning();
 
@@ -156,6 +197,9 @@ fn main() {
         webview: None,
         ctx: build_app_context(&tuning),
     };
+    let _status_server = status_server::start_from_env(&app.ctx.status_state);
     event_loop.run_app(&mut app).unwrap();
     shutdown_zmq(&app.ctx.zmq_handle);
+    shutdown_node_log(&app.ctx.node_log_handle);
+    console_draft::on_clean_exit();
 }
@@ -0,0 +1,267 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+
+use crate::rpc::{self, RpcConfig, RpcError};
+use crate::rpc_limiter::RpcBudget;
+
+/// Above this many entries `getrawmempool true` gets too large to fetch on
+/// every refresh (roughly 600 bytes per entry), so a sample is used instead.
+pub const MAX_VERBOSE_ENTRIES: u64 = 20_000;
+pub const SAMPLE_SIZE: usize = 2_000;
+
+/// Lower bucket edges in sat/vB; the last bucket is open-ended.
+const BUCKET_EDGES: [f64; 5] = [0.0, 2.0, 5.0, 10.0, 20.0];
+const BUCKET_LABELS: [&str; 5] = ["<2", "2-5", "5-10", "10-20", "20+"];
+/// Eighth-height block characters, shortest first.
+const COLUMN_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bucket {
+    pub count: u64,
+    pub vsize: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MempoolHistogram {
+    pub buckets: [Bucket; 5],
+    /// Transactions in the mempool according to getmempoolinfo.
+    pub total: u64,
+    /// Entries actually bucketed; less than `total` when sampled.
+    pub examined: u64,
+}
+
+impl MempoolHistogram {
+    fn add(&mut self, feerate: f64, vsize: u64) {
+        let index = BUCKET_EDGES
+            .iter()
+            .rposition(|&edge| feerate >= edge)
+            .unwrap_or(0);
+        self.buckets[index].count += 1;
+        self.buckets[index].vsize += vsize;
+        self.examined += 1;
+    }
+
+    pub fn is_estimate(&self) -> bool {
+        self.examined < self.total
+    }
+
+    /// `n` scaled up to the whole mempool when sampled.
+    fn scaled(&self, n: u64) -> u64 {
+        match self.examined {
+            0 => 0,
+            examined if self.is_estimate() => {
+                (n as f64 * self.total as f64 / examined as f64).round() as u64
+            }
+            _ => n,
+        }
+    }
+
+    /// Lower feerate edge in sat/vB and scaled vsize of each bucket.
+    pub fn fee_histogram(&self) -> Vec<(f64, u64)> {
+        BUCKET_EDGES
+            .iter()
+            .zip(&self.buckets)
+            .map(|(&edge, b)| (edge, self.scaled(b.vsize)))
+            .collect()
+    }
+
+    /// Bucket totals, scaled up to the whole mempool when sampled.
+    pub fn to_json(&self) -> serde_json::Value {
+        let buckets: Vec<_> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                json!({
+                    "label": BUCKET_LABELS[i],
+                    "min": BUCKET_EDGES[i],
+                    "max": BUCKET_EDGES.get(i + 1),
+                    "count": self.scaled(b.count),
+                    "vsize": self.scaled(b.vsize),
+                })
+            })
+            .collect();
+        let vsizes: Vec<_> = self.fee_histogram().iter().map(|&(_, v)| v).collect();
+        json!({
+            "buckets": buckets,
+            "chart": column_chart(&vsizes),
+            "total": self.total,
+            "examined": self.examined,
+            "estimated": self.is_estimate(),
+        })
+    }
+}
+
+/// One block character per value with the largest at full height. Zero is a
+/// space so an empty bucket never reads as a small one.
+pub fn column_chart(values: &[u64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            if v == 0 {
+                return ' ';
+            }
+            let level = (v as f64 / max as f64 * 8.0).ceil() as usize;
+            COLUMN_CHARS[level.clamp(1, 8) - 1]
+        })
+        .collect()
+}
+
+/// Feerate in sat/vB and vsize of a getrawmempool/getmempoolentry entry.
+pub fn entry_feerate(entry: &serde_json::Value) -> Option<(f64, u64)> {
+    let vsize = entry["vsize"].as_u64().or_else(|| entry["size"].as_u64())?;
+    // "fee" is the pre-0.21 field, kept for older nodes.
+    let fee_btc = entry["fees"]["base"]
+        .as_f64()
+        .or_else(|| entry["fee"].as_f64())?;
+    if vsize == 0 {
+        return None;
+    }
+    let sats = (fee_btc * 100_000_000.0).round();
+    Some((sats / vsize as f64, vsize))
+}
+
+pub fn from_entries<'a>(
+    entries: impl IntoIterator<Item = &'a serde_json::Value>,
+    total: u64,
+) -> MempoolHistogram {
+    let mut histogram = MempoolHistogram {
+        total,
+        ..MempoolHistogram::default()
+    };
+    for (feerate, vsize) in entries.into_iter().filter_map(entry_feerate) {
+        histogram.add(feerate, vsize);
+    }
+    histogram.total = histogram.total.max(histogram.examined);
+    histogram
+}
+
+/// Up to `n` evenly spaced txids. Txids are hashes, so this is as good as a
+/// random sample.
+fn sample_txids(txids: &[serde_json::Value], n: usize) -> Vec<&str> {
+    let n = n.min(txids.len());
+    (0..n)
+        .filter_map(|i| txids[i * txids.len() / n].as_str())
+        .collect()
+}
+
+/// Verbose mempool entries and the mempool's size. Above
+/// `MAX_VERBOSE_ENTRIES` the entries are a sample of `SAMPLE_SIZE`.
+pub fn fetch_entries(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+) -> Result<(Vec<serde_json::Value>, u64), RpcError> {
+    let call = |method: &str, params: serde_json::Value| {
+        rpc::node_batch(
+            config,
+            budget,
+            &[json!({ "method": method, "params": params })],
+        )?
+        .pop()
+        .unwrap_or_else(|| Err(RpcError::InvalidResponse("empty batch reply".into())))
+    };
+
+    let size = call("getmempoolinfo", json!([]))?["size"]
+        .as_u64()
+        .unwrap_or(0);
+    if size <= MAX_VERBOSE_ENTRIES {
+        let mempool = call("getrawmempool", json!([true]))?;
+        let entries = match mempool {
+            serde_json::Value::Object(m) => m.into_iter().map(|(_, v)| v).collect(),
+            _ => Vec::new(),
+        };
+        return Ok((entries, size));
+    }
+
+    let txids = call("getrawmempool", json!([false]))?;
+    let txids = txids.as_array().map(Vec::as_slice).unwrap_or_default();
+    let calls: Vec<_> = sample_txids(txids, SAMPLE_SIZE)
+        .into_iter()
+        .map(|txid| json!({ "method": "getmempoolentry", "params": [txid] }))
+        .collect();
+    // Entries mined or evicted since the txid list was taken come back as
+    // errors and are simply left out of the sample.
+    let entries: Vec<_> = rpc::node_batch(config, budget, &calls)?
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect();
+    Ok((entries, txids.len() as u64))
+}
+
+pub fn fetch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+) -> Result<MempoolHistogram, RpcError> {
+    let (entries, total) = fetch_entries(config, budget)?;
+    Ok(from_entries(&entries, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{column_chart, from_entries, sample_txids};
+    use serde_json::json;
+
+    fn entry(fee_btc: f64, vsize: u64) -> serde_json::Value {
+        json!({ "vsize": vsize, "fees": { "base": fee_btc } })
+    }
+
+    #[test]
+    fn entries_land_in_feerate_buckets() {
+        let entries = [
+            entry(0.000_001, 1000), // 0.1 sat/vB
+            entry(0.000_002, 100),  // 2 sat/vB, on the edge
+            entry(0.000_009, 100),  // 9 sat/vB
+            entry(0.000_5, 200),    // 250 sat/vB
+            json!({ "vsize": 100 }),
+        ];
+        let h = from_entries(&entries, 4);
+        let counts: Vec<_> = h.buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [1, 1, 1, 0, 1]);
+        assert_eq!(h.buckets[0].vsize, 1000);
+        assert!(!h.is_estimate());
+        assert_eq!(h.to_json()["buckets"][4]["max"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn older_fee_field_is_accepted() {
+        let h = from_entries(&[json!({ "size": 200, "fee": 0.000_03 })], 1);
+        assert_eq!(h.buckets[3].count, 1);
+    }
+
+    #[test]
+    fn sampled_histogram_is_scaled_to_total() {
+        let entries = [entry(0.000_001, 100), entry(0.000_03, 300)];
+        let h = from_entries(&entries, 1000);
+        assert!(h.is_estimate());
+        let v = h.to_json();
+        assert_eq!(v["estimated"], true);
+        assert_eq!(v["buckets"][0]["count"], 500);
+        assert_eq!(v["buckets"][3]["vsize"], 150_000);
+    }
+
+    #[test]
+    fn column_chart_scales_to_the_largest_bucket() {
+        assert_eq!(column_chart(&[0, 1, 50, 100, 13]), " ▁▄█▂");
+        assert_eq!(column_chart(&[0, 0]), "  ");
+        assert_eq!(column_chart(&[]), "");
+
+        let entries = [entry(0.000_001, 100), entry(0.000_03, 300)];
+        let h = from_entries(&entries, 2);
+        assert_eq!(
+            h.fee_histogram(),
+            [(0.0, 100), (2.0, 0), (5.0, 0), (10.0, 300), (20.0, 0)]
+        );
+        assert_eq!(h.to_json()["chart"], "▃  █ ");
+    }
+
+    #[test]
+    fn sample_is_spread_and_bounded() {
+        let txids: Vec<_> = (0..10_001).map(|i| json!(format!("{i:064x}"))).collect();
+        let sample = sample_txids(&txids, 2000);
+        assert_eq!(sample.len(), 2000);
+        assert_eq!(sample[0], txids[0].as_str().unwrap());
+        assert_eq!(sample_txids(&txids[..3], 2000).len(), 3);
+    }
+}
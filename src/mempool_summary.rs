@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+
+use crate::mempool_histogram;
+use crate::rpc::{RpcConfig, RpcError};
+use crate::rpc_limiter::RpcBudget;
+
+/// Upper ancestor count of each bucket, counting the transaction itself;
+/// the last bucket is open-ended.
+const ANCESTOR_BUCKETS: [(u64, &str); 4] = [(1, "1"), (5, "2-5"), (10, "6-10"), (u64::MAX, ">10")];
+const CHART_WIDTH: usize = 20;
+
+/// Fees and package depth across the mempool, from `getrawmempool true`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerboseMempoolSummary {
+    pub total_fees_btc: f64,
+    /// Total fees over total vsize, so large transactions weigh more.
+    pub avg_fee_rate_sat_vb: f64,
+    /// Transactions per ancestor-count bucket.
+    pub ancestor_buckets: Vec<(String, usize)>,
+    /// Transactions in the mempool.
+    pub total: u64,
+    /// Entries actually read; less than `total` when sampled.
+    pub examined: u64,
+}
+
+impl VerboseMempoolSummary {
+    /// A sample is scaled up to `total`.
+    pub fn from_entries(entries: &[Value], total: u64) -> Self {
+        let mut fee_sats = 0.0;
+        let mut vsize = 0u64;
+        let mut counts = [0usize; ANCESTOR_BUCKETS.len()];
+        let mut examined = 0u64;
+        for entry in entries {
+            let Some((feerate, entry_vsize)) = mempool_histogram::entry_feerate(entry) else {
+                continue;
+            };
+            examined += 1;
+            fee_sats += (feerate * entry_vsize as f64).round();
+            vsize += entry_vsize;
+            if let Some(ancestors) = entry["ancestorcount"].as_u64() {
+                let i = ANCESTOR_BUCKETS
+                    .iter()
+                    .position(|&(max, _)| ancestors <= max)
+                    .unwrap_or(ANCESTOR_BUCKETS.len() - 1);
+                counts[i] += 1;
+            }
+        }
+        let total = total.max(examined);
+        let scale = if examined == 0 {
+            0.0
+        } else {
+            total as f64 / examined as f64
+        };
+        Self {
+            total_fees_btc: (fee_sats * scale).round() / 100_000_000.0,
+            avg_fee_rate_sat_vb: if vsize == 0 {
+                0.0
+            } else {
+                fee_sats / vsize as f64
+            },
+            ancestor_buckets: ANCESTOR_BUCKETS
+                .iter()
+                .zip(counts)
+                .map(|(&(_, label), n)| (label.to_string(), (n as f64 * scale).round() as usize))
+                .collect(),
+            total,
+            examined,
+        }
+    }
+
+    pub fn is_estimate(&self) -> bool {
+        self.examined < self.total
+    }
+
+    pub fn to_json(&self) -> Value {
+        let buckets: Vec<_> = self
+            .ancestor_buckets
+            .iter()
+            .map(|(label, count)| json!({ "label": label, "count": count }))
+            .collect();
+        json!({
+            "total_fees_btc": self.total_fees_btc,
+            "avg_fee_rate_sat_vb": (self.avg_fee_rate_sat_vb * 10.0).round() / 10.0,
+            "ancestor_buckets": buckets,
+            "chart": bar_chart(&self.ancestor_buckets, CHART_WIDTH),
+            "total": self.total,
+            "examined": self.examined,
+            "estimated": self.is_estimate(),
+        })
+    }
+}
+
+/// One line per row: the label, a bar scaled so the largest count fills
+/// `width`, and the count. A non-zero count always gets at least one block.
+pub fn bar_chart(rows: &[(String, usize)], width: usize) -> String {
+    let max = rows.iter().map(|&(_, n)| n).max().unwrap_or(0);
+    let label_width = rows
+        .iter()
+        .map(|(l, _)| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    rows.iter()
+        .map(|(label, n)| {
+            let len = match *n {
+                0 => 0,
+                n => ((n as f64 / max as f64) * width as f64).round().max(1.0) as usize,
+            };
+            let bar = "█".repeat(len);
+            format!("{label:>label_width$} {bar:<width$} {n}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn fetch_verbose_summary(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+) -> Result<VerboseMempoolSummary, RpcError> {
+    let (entries, total) = mempool_histogram::fetch_entries(config, budget)?;
+    Ok(VerboseMempoolSummary::from_entries(&entries, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VerboseMempoolSummary, bar_chart};
+    use serde_json::json;
+
+    fn entry(fee_btc: f64, vsize: u64, ancestors: u64) -> serde_json::Value {
+        json!({ "vsize": vsize, "fees": { "base": fee_btc }, "ancestorcount": ancestors })
+    }
+
+    #[test]
+    fn entries_are_bucketed_by_ancestor_count() {
+        let entries = [
+            entry(0.000_01, 100, 1),  // 10 sat/vB
+            entry(0.000_02, 200, 2),  // 10 sat/vB
+            entry(0.000_05, 100, 5),  // 50 sat/vB
+            entry(0.000_01, 100, 6),  // 10 sat/vB
+            entry(0.000_01, 500, 25), // 2 sat/vB
+            json!({ "vsize": 100, "fees": { "base": 0.000_01 } }),
+            json!({ "vsize": 100 }),
+        ];
+        let s = VerboseMempoolSummary::from_entries(&entries, 6);
+        let counts: Vec<_> = s.ancestor_buckets.iter().map(|(_, n)| *n).collect();
+        assert_eq!(counts, [1, 2, 1, 1]);
+        assert_eq!(s.ancestor_buckets[3].0, ">10");
+        assert!((s.total_fees_btc - 0.000_11).abs() < 1e-12);
+        // 11,000 sats over 1,100 vB.
+        assert!((s.avg_fee_rate_sat_vb - 10.0).abs() < 1e-9);
+        assert!(!s.is_estimate());
+        assert_eq!(s.to_json()["ancestor_buckets"][1]["count"], 2);
+    }
+
+    #[test]
+    fn sample_is_scaled_to_the_mempool() {
+        let entries = [entry(0.000_01, 100, 1), entry(0.000_03, 100, 3)];
+        let s = VerboseMempoolSummary::from_entries(&entries, 1000);
+        assert!(s.is_estimate());
+        assert_eq!(s.ancestor_buckets[0].1, 500);
+        assert_eq!(s.ancestor_buckets[1].1, 500);
+        assert!((s.total_fees_btc - 0.02).abs() < 1e-12);
+        assert!((s.avg_fee_rate_sat_vb - 20.0).abs() < 1e-9);
+
+        let empty = VerboseMempoolSummary::from_entries(&[], 0);
+        assert_eq!(empty.avg_fee_rate_sat_vb, 0.0);
+        assert_eq!(empty.to_json()["estimated"], false);
+    }
+
+    #[test]
+    fn bar_chart_scales_to_the_largest_row() {
+        let rows = [
+            ("1".to_string(), 80),
+            ("2-5".to_string(), 20),
+            ("6-10".to_string(), 1),
+            (">10".to_string(), 0),
+        ];
+        assert_eq!(
+            bar_chart(&rows, 8),
+            "   1 ████████ 80\n 2-5 ██       20\n6-10 █        1\n >10          0"
+        );
+        assert_eq!(bar_chart(&[], 8), "");
+    }
+}
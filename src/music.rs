@@ -4,6 +4,161 @@ pub struct MusicRuntime {
     inner: imp::InnerRuntime,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayOrder {
+    Shuffle,
+    Alphabetical,
+    FileOrder,
+}
+
+impl PlayOrder {
+    pub fn parse(s: &str) -> Option<PlayOrder> {
+        match s {
+            "shuffle" => Some(PlayOrder::Shuffle),
+            "alphabetical" => Some(PlayOrder::Alphabetical),
+            "file" => Some(PlayOrder::FileOrder),
+            _ => None,
+        }
+    }
+
+    #[cfg(any(feature = "audio", test))]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PlayOrder::Shuffle => "shuffle",
+            PlayOrder::Alphabetical => "alphabetical",
+            PlayOrder::FileOrder => "file",
+        }
+    }
+}
+
+/// SplitMix64; small, and any seed (including 0) gives a usable stream.
+#[cfg(any(feature = "audio", test))]
+struct Rng(u64);
+
+#[cfg(any(feature = "audio", test))]
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, rejecting the biased tail of the range.
+    fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    fn shuffle(&mut self, items: &mut [usize]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Track order over a fixed list of names. Order changes are held until the
+/// next track boundary so the playing track isn't interrupted.
+#[cfg(any(feature = "audio", test))]
+struct Playlist {
+    names: Vec<String>,
+    order: PlayOrder,
+    pending: Option<PlayOrder>,
+    rng: Rng,
+    queue: Vec<usize>,
+    pos: usize,
+}
+
+#[cfg(any(feature = "audio", test))]
+impl Playlist {
+    fn new(names: Vec<String>, order: PlayOrder, seed: u64) -> Playlist {
+        let mut playlist = Playlist {
+            queue: (0..names.len()).collect(),
+            names,
+            order,
+            pending: None,
+            rng: Rng(seed),
+            pos: 0,
+        };
+        playlist.queue = playlist.cycle(None);
+        playlist
+    }
+
+    /// Index into `names` of the current track, or `None` for an empty list.
+    fn current(&self) -> Option<usize> {
+        self.queue.get(self.pos).copied()
+    }
+
+    fn order(&self) -> PlayOrder {
+        self.pending.unwrap_or(self.order)
+    }
+
+    fn set_order(&mut self, order: PlayOrder) {
+        self.pending = Some(order);
+    }
+
+    fn next(&mut self) -> Option<usize> {
+        let current = self.current()?;
+        self.apply_pending(current);
+        self.pos += 1;
+        if self.pos == self.queue.len() {
+            self.queue = self.cycle(Some(current));
+            self.pos = 0;
+        }
+        self.current()
+    }
+
+    fn prev(&mut self) -> Option<usize> {
+        let current = self.current()?;
+        self.apply_pending(current);
+        self.pos = self.pos.checked_sub(1).unwrap_or(self.queue.len() - 1);
+        self.current()
+    }
+
+    /// One pass over every track. A shuffled pass never starts with
+    /// `just_played`, so wrapping around can't repeat a track back to back.
+    fn cycle(&mut self, just_played: Option<usize>) -> Vec<usize> {
+        let mut queue: Vec<usize> = (0..self.names.len()).collect();
+        match self.order {
+            PlayOrder::Shuffle => {
+                self.rng.shuffle(&mut queue);
+                if queue.len() > 1 && just_played == queue.first().copied() {
+                    let swap = 1 + self.rng.below(queue.len() - 1);
+                    queue.swap(0, swap);
+                }
+            }
+            PlayOrder::Alphabetical => {
+                queue.sort_by_key(|&i| self.names[i].to_lowercase());
+            }
+            PlayOrder::FileOrder => {}
+        }
+        queue
+    }
+
+    /// Rebuilds the queue in the new order around the playing track, so the
+    /// switch shows from the next track on.
+    fn apply_pending(&mut self, current: usize) {
+        let Some(order) = self.pending.take() else {
+            return;
+        };
+        self.order = order;
+        let mut queue = self.cycle(None);
+        if order == PlayOrder::Shuffle {
+            queue.retain(|&i| i != current);
+            queue.insert(0, current);
+        }
+        self.pos = queue.iter().position(|&i| i == current).unwrap_or(0);
+        self.queue = queue;
+    }
+}
+
 pub fn is_enabled() -> bool {
     imp::is_enabled()
 }
@@ -14,6 +169,13 @@ pub fn start_music() -> MusicRuntime {
     }
 }
 
+impl MusicRuntime {
+    /// Takes effect at the next track boundary. False when audio is disabled.
+    pub fn set_order(&self, order: PlayOrder) -> bool {
+        imp::set_order(&self.inner, order)
+    }
+}
+
 pub fn handle_music_request(
     path: &str,
     query: &str,
@@ -22,22 +184,36 @@ pub fn handle_music_request(
     if !path.starts_with("/music/") {
         return None;
     }
+    if path == "/music/order" {
+        let result = match PlayOrder::parse(query) {
+            Some(order) if runtime.set_order(order) => r#"{"ok":true}"#.into(),
+            Some(_) => r#"{"ok":false,"error":"audio feature disabled"}"#.into(),
+            None => r#"{"ok":false,"error":"unknown play order"}"#.into(),
+        };
+        return Some(result);
+    }
     Some(imp::handle_music_request(path, query, &runtime.inner))
 }
 
 #[cfg(feature = "audio")]
 mod imp {
+    use std::sync::atomic::{AtomicU32, Ordering};
     use std::sync::mpsc;
     use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
-    use tracing::{debug, warn};
+    use tracing::{info, warn};
     use xmrs::import::amiga::amiga_module::AmigaModule;
     use xmrs::module::Module;
     use xmrsplayer::xmrsplayer::XmrsPlayer;
 
+    use super::{PlayOrder, Playlist};
+
     const SAMPLE_RATE: u32 = 48000;
+    /// MODs hard-pan channels fully left or right, which is tiring on
+    /// headphones, so start halfway between mono and the original mix.
+    const DEFAULT_PAN_WIDTH: f32 = 0.5;
 
     struct Tune {
         name: &'static str,
@@ -54,6 +230,8 @@ mod imp {
         Next,
         Prev,
         SetVolume(f32),
+        SetPanWidth(f32),
+        SetOrder(PlayOrder),
         ToggleMute,
     }
 
@@ -64,21 +242,53 @@ mod imp {
         playing: bool,
         volume: f32,
         muted: bool,
+        pan_width: f32,
+        order: PlayOrder,
+        seed: u64,
     }
 
+    /// An f32 shared with the audio thread, so width changes apply to the
+    /// playing track without rebuilding the sink.
+    #[derive(Clone)]
+    struct SharedPanWidth(Arc<AtomicU32>);
+
+    impl SharedPanWidth {
+        fn new(width: f32) -> Self {
+            Self(Arc::new(AtomicU32::new(width.to_bits())))
+        }
+
+        fn get(&self) -> f32 {
+            f32::from_bits(self.0.load(Ordering::Relaxed))
+        }
+
+        fn set(&self, width: f32) {
+            self.0.store(width.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Narrows a stereo frame: 0.0 folds it to mono, 1.0 leaves it untouched.
+    fn pan(l: f32, r: f32, width: f32) -> (f32, f32) {
+        let mid = (l + r) * 0.5;
+        let side = (l - r) * 0.5 * width;
+        (mid + side, mid - side)
+    }
+
+    /// Interleaved L/R frames rendered 1024 at a time.
     struct ModSource {
         player: XmrsPlayer<'static>,
+        pan_width: SharedPanWidth,
         buffer: Vec<f32>,
         pos: usize,
     }
 
     impl ModSource {
-        fn new(module: &'static Module) -> Self {
+        fn new(module: &'static Module, pan_width: SharedPanWidth) -> Self {
             let mut player = XmrsPlayer::new(module, SAMPLE_RATE as f32, 0, false);
             player.set_max_loop_count(2);
             player.amplification = 0.5;
             Self {
                 player,
+                pan_width,
                 buffer: Vec::with_capacity(2048),
                 pos: 0,
             }
@@ -92,12 +302,13 @@ mod imp {
             if self.pos >= self.buffer.len() {
                 self.buffer.clear();
                 self.pos = 0;
+                let width = self.pan_width.get();
                 for _ in 0..1024 {
                     match self.player.sample(true) {
                         Some((l, r)) => {
-                            let mix = (l + r) * 0.5;
-                            self.buffer.push(mix);
-                            self.buffer.push(mix);
+                            let (l, r) = pan(l, r, width);
+                            self.buffer.push(l);
+                            self.buffer.push(r);
                         }
                         None => break,
                     }
@@ -134,26 +345,49 @@ mod imp {
         true
     }
 
+    /// `MUSIC_SEED` fixes the shuffle so an ordering can be reproduced;
+    /// otherwise it comes from the clock. Either way it is logged.
+    fn shuffle_seed() -> u64 {
+        std::env::var("MUSIC_SEED")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos() as u64
+            })
+    }
+
     pub(super) fn start_music() -> InnerRuntime {
-        let mut tunes = load_tunes();
-        shuffle(&mut tunes);
-        debug!(tracks = tunes.len(), "initialized music runtime");
+        let tunes = load_tunes();
+        let seed = shuffle_seed();
+        let mut playlist = Playlist::new(
+            tunes.iter().map(|t| t.name.to_string()).collect(),
+            PlayOrder::Shuffle,
+            seed,
+        );
+        info!(seed, tracks = tunes.len(), "initialized music runtime");
 
         let (tx, rx) = mpsc::channel();
         let state = Arc::new(Mutex::new(MusicState {
-            current_track: 0,
+            current_track: playlist.current().unwrap_or(0),
             track_count: tunes.len(),
-            track_name: tunes.first().map_or("", |t| t.name).to_string(),
+            track_name: playlist.current().map_or("", |i| tunes[i].name).to_string(),
             playing: !tunes.is_empty(),
             volume: 1.0,
             muted: false,
+            pan_width: DEFAULT_PAN_WIDTH,
+            order: playlist.order(),
+            seed,
         }));
         let st = Arc::clone(&state);
+        let pan_width = SharedPanWidth::new(DEFAULT_PAN_WIDTH);
 
         std::thread::spawn(move || {
-            if tunes.is_empty() {
+            let Some(first) = playlist.current() else {
                 return;
-            }
+            };
 
             let (_stream, handle) = match OutputStream::try_default() {
                 Ok(s) => s,
@@ -163,7 +397,14 @@ mod imp {
                 }
             };
 
-            let mut sink = make_sink(&handle, tunes[0].module, 1.0);
+            let mut sink = make_sink(&handle, tunes[first].module, 1.0, &pan_width);
+            let change_track = |s: &mut MusicState, sink: &mut Sink, track: usize| {
+                s.current_track = track;
+                s.track_name = tunes[track].name.to_string();
+                let vol = if s.muted { 0.0 } else { s.volume };
+                sink.stop();
+                *sink = make_sink(&handle, tunes[track].module, vol, &pan_width);
+            };
 
             loop {
                 match rx.recv_timeout(Duration::from_millis(500)) {
@@ -180,24 +421,16 @@ mod imp {
                                 }
                             }
                             MusicCmd::Next => {
-                                s.current_track = (s.current_track + 1) % tunes.len();
-                                s.track_name = tunes[s.current_track].name.to_string();
-                                s.playing = true;
-                                let vol = if s.muted { 0.0 } else { s.volume };
-                                drop(sink);
-                                sink = make_sink(&handle, tunes[s.current_track].module, vol);
+                                if let Some(track) = playlist.next() {
+                                    s.playing = true;
+                                    change_track(&mut s, &mut sink, track);
+                                }
                             }
                             MusicCmd::Prev => {
-                                s.current_track = if s.current_track == 0 {
-                                    tunes.len() - 1
-                                } else {
-                                    s.current_track - 1
-                                };
-                                s.track_name = tunes[s.current_track].name.to_string();
-                                s.playing = true;
-                                let vol = if s.muted { 0.0 } else { s.volume };
-                                drop(sink);
-                                sink = make_sink(&handle, tunes[s.current_track].module, vol);
+                                if let Some(track) = playlist.prev() {
+                                    s.playing = true;
+                                    change_track(&mut s, &mut sink, track);
+                                }
                             }
                             MusicCmd::SetVolume(v) => {
                                 s.volume = v.clamp(0.0, 1.0);
@@ -205,6 +438,14 @@ mod imp {
                                     sink.set_volume(s.volume);
                                 }
                             }
+                            MusicCmd::SetPanWidth(w) => {
+                                s.pan_width = w.clamp(0.0, 1.0);
+                                pan_width.set(s.pan_width);
+                            }
+                            MusicCmd::SetOrder(order) => {
+                                playlist.set_order(order);
+                                s.order = order;
+                            }
                             MusicCmd::ToggleMute => {
                                 s.muted = !s.muted;
                                 sink.set_volume(if s.muted { 0.0 } else { s.volume });
@@ -214,11 +455,9 @@ mod imp {
                     Err(mpsc::RecvTimeoutError::Timeout) => {
                         if sink.empty() {
                             let mut s = st.lock().unwrap();
-                            s.current_track = (s.current_track + 1) % tunes.len();
-                            s.track_name = tunes[s.current_track].name.to_string();
-                            let vol = if s.muted { 0.0 } else { s.volume };
-                            drop(sink);
-                            sink = make_sink(&handle, tunes[s.current_track].module, vol);
+                            if let Some(track) = playlist.next() {
+                                change_track(&mut s, &mut sink, track);
+                            }
                         }
                     }
                     Err(mpsc::RecvTimeoutError::Disconnected) => break,
@@ -229,6 +468,10 @@ mod imp {
         InnerRuntime { tx, state }
     }
 
+    pub(super) fn set_order(runtime: &InnerRuntime, order: PlayOrder) -> bool {
+        runtime.tx.send(MusicCmd::SetOrder(order)).is_ok()
+    }
+
     pub(super) fn handle_music_request(path: &str, query: &str, runtime: &InnerRuntime) -> String {
         match path {
             "/music/status" => {
@@ -241,6 +484,9 @@ mod imp {
                     "playing": s.playing,
                     "volume": s.volume,
                     "muted": s.muted,
+                    "pan_width": s.pan_width,
+                    "order": s.order.as_str(),
+                    "seed": s.seed.to_string(),
                 })
                 .to_string()
             }
@@ -261,6 +507,11 @@ mod imp {
                 let _ = runtime.tx.send(MusicCmd::SetVolume(v));
                 r#"{"ok":true}"#.into()
             }
+            "/music/panwidth" => {
+                let w: f32 = query.parse().unwrap_or(DEFAULT_PAN_WIDTH);
+                let _ = runtime.tx.send(MusicCmd::SetPanWidth(w));
+                r#"{"ok":true}"#.into()
+            }
             "/music/mute" => {
                 let _ = runtime.tx.send(MusicCmd::ToggleMute);
                 r#"{"ok":true}"#.into()
@@ -304,25 +555,30 @@ mod imp {
             .collect()
     }
 
-    fn make_sink(handle: &OutputStreamHandle, module: &'static Module, volume: f32) -> Sink {
+    fn make_sink(
+        handle: &OutputStreamHandle,
+        module: &'static Module,
+        volume: f32,
+        pan_width: &SharedPanWidth,
+    ) -> Sink {
         let sink = Sink::try_new(handle).unwrap();
-        let source = ModSource::new(module);
+        let source = ModSource::new(module, pan_width.clone());
         sink.append(source);
         sink.set_volume(volume);
         sink
     }
 
-    fn shuffle(tunes: &mut [Tune]) {
-        let mut seed = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos() as u64;
-        for i in (1..tunes.len()).rev() {
-            seed ^= seed << 13;
-            seed ^= seed >> 7;
-            seed ^= seed << 17;
-            let j = (seed as usize) % (i + 1);
-            tunes.swap(i, j);
+    #[cfg(test)]
+    mod tests {
+        use super::pan;
+
+        #[test]
+        fn pan_width_scales_stereo_separation() {
+            assert_eq!(pan(1.0, 0.0, 0.0), (0.5, 0.5));
+            assert_eq!(pan(1.0, 0.0, 1.0), (1.0, 0.0));
+            assert_eq!(pan(1.0, 0.0, 0.5), (0.75, 0.25));
+            // Centred content is unaffected by width.
+            assert_eq!(pan(0.3, 0.3, 0.7), (0.3, 0.3));
         }
     }
 }
@@ -339,6 +595,10 @@ mod imp {
         InnerRuntime
     }
 
+    pub(super) fn set_order(_runtime: &InnerRuntime, _order: super::PlayOrder) -> bool {
+        false
+    }
+
     pub(super) fn handle_music_request(
         path: &str,
         _query: &str,
@@ -347,8 +607,103 @@ mod imp {
         match path {
             "/music/status" => r#"{"enabled":false}"#.into(),
             "/music/playpause" | "/music/next" | "/music/prev" | "/music/volume"
-            | "/music/mute" => r#"{"ok":false,"error":"audio feature disabled"}"#.into(),
+            | "/music/panwidth" | "/music/mute" => {
+                r#"{"ok":false,"error":"audio feature disabled"}"#.into()
+            }
             _ => r#"{"error":"unknown music endpoint"}"#.into(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PlayOrder, Playlist};
+
+    fn names() -> Vec<String> {
+        ["Toy Story", "resii", "Hymn to Aurora", "Sundance"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn take(playlist: &mut Playlist, n: usize) -> Vec<usize> {
+        (0..n).map(|_| playlist.next().unwrap()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_order() {
+        let mut a = Playlist::new(names(), PlayOrder::Shuffle, 42);
+        let mut b = Playlist::new(names(), PlayOrder::Shuffle, 42);
+        assert_eq!(take(&mut a, 20), take(&mut b, 20));
+    }
+
+    #[test]
+    fn each_shuffled_cycle_plays_every_track_once() {
+        let mut playlist = Playlist::new(names(), PlayOrder::Shuffle, 7);
+        let mut cycle: Vec<_> = std::iter::once(playlist.current().unwrap())
+            .chain(take(&mut playlist, 3))
+            .collect();
+        cycle.sort();
+        assert_eq!(cycle, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn wrapping_never_repeats_the_last_track() {
+        for seed in 0..200 {
+            let mut playlist = Playlist::new(names(), PlayOrder::Shuffle, seed);
+            let mut last = playlist.current().unwrap();
+            for _ in 0..40 {
+                let next = playlist.next().unwrap();
+                assert_ne!(next, last, "seed {seed}");
+                last = next;
+            }
+        }
+        let mut single = Playlist::new(vec!["Only".into()], PlayOrder::Shuffle, 1);
+        assert_eq!(single.next(), Some(0));
+    }
+
+    #[test]
+    fn fixed_orders() {
+        let mut playlist = Playlist::new(names(), PlayOrder::Alphabetical, 0);
+        assert_eq!(playlist.current(), Some(2));
+        assert_eq!(take(&mut playlist, 4), [1, 3, 0, 2]);
+        assert_eq!(playlist.prev(), Some(0));
+
+        let mut playlist = Playlist::new(names(), PlayOrder::FileOrder, 0);
+        assert_eq!(take(&mut playlist, 4), [1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn order_changes_wait_for_the_next_track() {
+        let mut playlist = Playlist::new(names(), PlayOrder::FileOrder, 0);
+        playlist.next();
+        playlist.set_order(PlayOrder::Alphabetical);
+        assert_eq!(playlist.current(), Some(1));
+        assert_eq!(playlist.order(), PlayOrder::Alphabetical);
+        // "resii" is followed by "Sundance" alphabetically.
+        assert_eq!(playlist.next(), Some(3));
+
+        playlist.set_order(PlayOrder::Shuffle);
+        let rest = take(&mut playlist, 3);
+        assert!(!rest.contains(&3));
+    }
+
+    #[test]
+    fn empty_playlist_has_no_tracks() {
+        let mut playlist = Playlist::new(Vec::new(), PlayOrder::Shuffle, 3);
+        assert_eq!(playlist.current(), None);
+        assert_eq!(playlist.next(), None);
+        assert_eq!(playlist.prev(), None);
+    }
+
+    #[test]
+    fn play_orders_round_trip() {
+        for order in [
+            PlayOrder::Shuffle,
+            PlayOrder::Alphabetical,
+            PlayOrder::FileOrder,
+        ] {
+            assert_eq!(PlayOrder::parse(order.as_str()), Some(order));
+        }
+        assert_eq!(PlayOrder::parse("random"), None);
+    }
+}
@@ -0,0 +1,156 @@
+use serde_json::json;
+
+/// Oldest node the dashboard can run against; `uptime` and the multiwallet
+/// RPCs it relies on are from 0.15–0.17.
+pub const MINIMUM_USABLE: u64 = 170_000;
+/// From this version on every feature in [`FEATURES`] works.
+pub const MINIMUM_FULLY_SUPPORTED: u64 = 230_000;
+
+/// A dashboard or wallet feature and the version whose RPC fields it needs.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GatedFeature {
+    pub feature: &'static str,
+    pub since: u64,
+}
+
+pub const FEATURES: [GatedFeature; 8] = [
+    GatedFeature {
+        feature: "Peer permission flags (Perms column)",
+        since: 190_000,
+    },
+    GatedFeature {
+        feature: "Local service names",
+        since: 190_000,
+    },
+    GatedFeature {
+        feature: "Inbound/outbound connection counts",
+        since: 210_000,
+    },
+    GatedFeature {
+        feature: "Unbroadcast transaction count",
+        since: 210_000,
+    },
+    GatedFeature {
+        feature: "Block fee totals in Recent Blocks",
+        since: 210_000,
+    },
+    GatedFeature {
+        feature: "Descriptor wallets and watch-only import",
+        since: 210_000,
+    },
+    GatedFeature {
+        feature: "Fee bumping (psbtbumpfee)",
+        since: 210_000,
+    },
+    GatedFeature {
+        feature: "Mempool total fees",
+        since: 230_000,
+    },
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Support {
+    Full,
+    Degraded(Vec<&'static GatedFeature>),
+    Unusable,
+}
+
+pub fn support(version: u64) -> Support {
+    if version < MINIMUM_USABLE {
+        return Support::Unusable;
+    }
+    let degraded: Vec<_> = FEATURES.iter().filter(|f| version < f.since).collect();
+    if degraded.is_empty() {
+        Support::Full
+    } else {
+        Support::Degraded(degraded)
+    }
+}
+
+/// `getnetworkinfo` version number as Core prints it: 210100 is "0.21.1",
+/// 250000 is "25.0" (the leading "0." was dropped at 22.0).
+pub fn format_version(version: u64) -> String {
+    let major = version / 10_000;
+    let minor = version / 100 % 100;
+    let patch = version % 100;
+    let base = if major < 22 {
+        format!("0.{major}.{minor}")
+    } else {
+        format!("{major}.{minor}")
+    };
+    if patch == 0 {
+        base
+    } else {
+        format!("{base}.{patch}")
+    }
+}
+
+pub fn to_json(version: u64) -> serde_json::Value {
+    let (level, degraded) = match support(version) {
+        Support::Full => ("full", Vec::new()),
+        Support::Degraded(features) => ("degraded", features),
+        Support::Unusable => ("unusable", Vec::new()),
+    };
+    let degraded: Vec<_> = degraded
+        .iter()
+        .map(|f| json!({ "feature": f.feature, "since": format_version(f.since) }))
+        .collect();
+    json!({
+        "version": version,
+        "version_text": format_version(version),
+        "level": level,
+        "minimum_usable": format_version(MINIMUM_USABLE),
+        "minimum_fully_supported": format_version(MINIMUM_FULLY_SUPPORTED),
+        "degraded": degraded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FEATURES, MINIMUM_FULLY_SUPPORTED, Support, format_version, support, to_json};
+
+    #[test]
+    fn fully_supported_matches_the_newest_gate() {
+        let newest = FEATURES.iter().map(|f| f.since).max().unwrap();
+        assert_eq!(MINIMUM_FULLY_SUPPORTED, newest);
+    }
+
+    #[test]
+    fn representative_versions() {
+        assert_eq!(support(160_300), Support::Unusable);
+        let Support::Degraded(old) = support(170_100) else {
+            panic!("0.17.1 should be degraded");
+        };
+        assert_eq!(old.len(), FEATURES.len());
+        let Support::Degraded(v20) = support(200_100) else {
+            panic!("0.20.1 should be degraded");
+        };
+        assert!(v20.iter().all(|f| f.since > 200_100));
+        assert_eq!(v20.len(), 6);
+        let Support::Degraded(v22) = support(220_000) else {
+            panic!("22.0 should be degraded");
+        };
+        assert_eq!(v22.len(), 1);
+        assert_eq!(support(230_000), Support::Full);
+        assert_eq!(support(280_100), Support::Full);
+    }
+
+    #[test]
+    fn versions_format_like_core() {
+        assert_eq!(format_version(170_100), "0.17.1");
+        assert_eq!(format_version(210_000), "0.21.0");
+        assert_eq!(format_version(220_000), "22.0");
+        assert_eq!(format_version(250_100), "25.1");
+        assert_eq!(format_version(260_002), "26.0.2");
+    }
+
+    #[test]
+    fn json_lists_degraded_features() {
+        let v = to_json(200_000);
+        assert_eq!(v["level"], "degraded");
+        assert_eq!(v["version_text"], "0.20.0");
+        assert_eq!(v["degraded"][0]["since"], "0.21.0");
+        assert_eq!(to_json(150_000)["level"], "unusable");
+        assert_eq!(to_json(270_000)["degraded"].as_array().unwrap().len(), 0);
+    }
+}
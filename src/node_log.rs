@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use crate::rpc::{self, RpcConfig};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How often a poll wait checks for shutdown.
+const SHUTDOWN_TICK: Duration = Duration::from_millis(100);
+/// Where the first read starts from the end, so opening a large debug.log
+/// shows its recent lines without reading all of it.
+const BACKLOG_BYTES: u64 = 1024 * 1024;
+/// Read per poll at most, so a burst is taken in steps.
+const MAX_READ_PER_POLL: u64 = 4 * 1024 * 1024;
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+/// Longer lines are cut. Bitcoin Core's own lines are far shorter.
+const MAX_LINE_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Debug,
+}
+
+impl Severity {
+    /// Reads the `[error]`, `[warning]` and `[category:debug]` tags newer
+    /// nodes write after the timestamp, and the `Error:`/`Warning:` message
+    /// prefixes of older ones. Anything else is info.
+    pub fn classify(line: &str) -> Self {
+        let mut rest = line.trim_start();
+        // The timestamp, when logtimestamps is on.
+        if rest.as_bytes().first().is_some_and(u8::is_ascii_digit) {
+            rest = rest.split_once(' ').map_or("", |(_, r)| r);
+        }
+        let mut severity = None;
+        while let Some((tag, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let level = tag.rsplit(':').next().unwrap_or(tag);
+            severity = severity.or(match level {
+                "error" => Some(Self::Error),
+                "warning" => Some(Self::Warning),
+                "debug" | "trace" => Some(Self::Debug),
+                _ => None,
+            });
+            rest = after.trim_start();
+        }
+        severity.unwrap_or_else(|| {
+            let lower = rest.get(..8).unwrap_or(rest).to_ascii_lowercase();
+            if lower.starts_with("error:") {
+                Self::Error
+            } else if lower.starts_with("warning:") {
+                Self::Warning
+            } else {
+                Self::Info
+            }
+        })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+pub struct LogLine {
+    pub cursor: u64,
+    pub text: String,
+    pub severity: Severity,
+}
+
+pub struct NodeLogState {
+    /// The file being tailed, empty when none is configured.
+    pub path: String,
+    pub limit: usize,
+    pub next_cursor: u64,
+    pub lines: VecDeque<LogLine>,
+    pub error: Option<String>,
+}
+
+impl Default for NodeLogState {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            limit: rpc::DEFAULT_LOG_TAIL_LINES,
+            next_cursor: 1,
+            lines: VecDeque::new(),
+            error: None,
+        }
+    }
+}
+
+impl NodeLogState {
+    fn push(&mut self, text: String) {
+        while self.lines.len() >= self.limit.max(1) {
+            self.lines.pop_front();
+        }
+        let cursor = self.next_cursor;
+        self.next_cursor = self.next_cursor.saturating_add(1);
+        self.lines.push_back(LogLine {
+            severity: Severity::classify(&text),
+            cursor,
+            text,
+        });
+    }
+
+    fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.lines.len() > limit {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Lines after `since`; `truncated` when some were dropped before the
+    /// caller saw them.
+    pub fn to_json(&self, since: u64) -> Value {
+        let lines: Vec<Value> = self
+            .lines
+            .iter()
+            .filter(|l| l.cursor > since)
+            .map(|l| json!({ "cursor": l.cursor, "text": l.text, "severity": l.severity.as_str() }))
+            .collect();
+        let truncated = since > 0
+            && self
+                .lines
+                .front()
+                .is_some_and(|l| l.cursor > since.saturating_add(1));
+        json!({
+            "path": self.path,
+            "limit": self.limit,
+            "cursor": self.next_cursor - 1,
+            "truncated": truncated,
+            "error": self.error,
+            "lines": lines,
+        })
+    }
+}
+
+/// Identifies the file behind a path, so a rotation that moves the old log
+/// aside and creates a new one is noticed even when the new one is larger.
+#[cfg(unix)]
+fn file_id(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Without inode numbers only truncation is noticed.
+#[cfg(not(unix))]
+fn file_id(_meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Reads the lines appended to a file, starting over from the top when it
+/// is truncated or replaced.
+struct Tail {
+    path: PathBuf,
+    file: Option<File>,
+    id: Option<(u64, u64)>,
+    pos: u64,
+    partial: Vec<u8>,
+    /// Set when reading starts mid-file, until the first newline.
+    skip_partial: bool,
+}
+
+impl Tail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            id: None,
+            pos: 0,
+            partial: Vec::new(),
+            skip_partial: false,
+        }
+    }
+
+    /// Complete lines written since the last poll. A missing file is an
+    /// error; the last open one is kept until a new one appears.
+    fn poll(&mut self) -> io::Result<Vec<String>> {
+        let meta = std::fs::metadata(&self.path)?;
+        let replaced = self.id.is_some() && file_id(&meta) != self.id;
+        if self.file.is_none() || replaced || meta.len() < self.pos {
+            self.reopen(meta.len())?;
+        }
+        let mut lines = Vec::new();
+        let Some(file) = self.file.as_mut() else {
+            return Ok(lines);
+        };
+        let mut buf = vec![0; READ_CHUNK_BYTES];
+        let mut read = 0;
+        while read < MAX_READ_PER_POLL {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            read += n as u64;
+            self.pos += n as u64;
+            split_lines(
+                &buf[..n],
+                &mut self.partial,
+                &mut self.skip_partial,
+                &mut lines,
+            );
+        }
+        Ok(lines)
+    }
+
+    /// The first open starts near the end; after a rotation the new file is
+    /// read from the top.
+    fn reopen(&mut self, len: u64) -> io::Result<()> {
+        let start = match self.file {
+            None => len.saturating_sub(BACKLOG_BYTES),
+            Some(_) => {
+                debug!(path = %self.path.display(), "node log rotated");
+                0
+            }
+        };
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        self.id = file_id(&file.metadata()?);
+        self.file = Some(file);
+        self.pos = start;
+        self.partial.clear();
+        self.skip_partial = start > 0;
+        Ok(())
+    }
+}
+
+/// Appends the complete lines in `bytes` to `lines`, carrying an unfinished
+/// one over in `partial`, which never grows past `MAX_LINE_BYTES`.
+fn split_lines(bytes: &[u8], partial: &mut Vec<u8>, skip: &mut bool, lines: &mut Vec<String>) {
+    for chunk in bytes.split_inclusive(|&b| b == b'\n') {
+        let (body, ends) = match chunk.strip_suffix(b"\n") {
+            Some(body) => (body, true),
+            None => (chunk, false),
+        };
+        if !*skip {
+            let room = MAX_LINE_BYTES.saturating_sub(partial.len());
+            partial.extend_from_slice(&body[..body.len().min(room)]);
+        }
+        if ends {
+            if !*skip {
+                let line = String::from_utf8_lossy(partial);
+                lines.push(line.trim_end_matches('\r').to_string());
+            }
+            partial.clear();
+            *skip = false;
+        }
+    }
+}
+
+pub struct NodeLogHandle {
+    path: String,
+    shutdown: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+/// Brings the tailer in line with the stored config: a new line limit
+/// applies at once, a new path restarts it. The handle lock is held
+/// throughout, so concurrent updates can't leave two tailers running.
+pub fn apply_config(
+    config: &Mutex<RpcConfig>,
+    state: &Arc<Mutex<NodeLogState>>,
+    handle: &Mutex<Option<NodeLogHandle>>,
+) {
+    let mut handle = handle.lock().unwrap();
+    let (path, limit) = {
+        let cfg = config.lock().unwrap();
+        (cfg.debug_log_path.clone(), cfg.log_tail_lines)
+    };
+    state.lock().unwrap().set_limit(limit);
+    if handle.as_ref().map_or("", |h| h.path.as_str()) == path {
+        return;
+    }
+    if let Some(h) = handle.take() {
+        stop_tailer(h);
+    }
+    {
+        let mut s = state.lock().unwrap();
+        s.lines.clear();
+        s.error = None;
+        s.path = path.clone();
+    }
+    if !path.is_empty() {
+        *handle = Some(start_tailer(&path, Arc::clone(state)));
+    }
+}
+
+pub fn start_tailer(path: &str, state: Arc<Mutex<NodeLogState>>) -> NodeLogHandle {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&shutdown);
+    let file = rpc::expand_home(path);
+    let thread = std::thread::spawn(move || {
+        debug!(path = %file.display(), "started node log tailer");
+        let mut tail = Tail::new(file);
+        while !flag.load(Ordering::Relaxed) {
+            let polled = tail.poll();
+            {
+                let mut s = state.lock().unwrap();
+                match polled {
+                    Ok(lines) => {
+                        s.error = None;
+                        for line in lines {
+                            s.push(line);
+                        }
+                    }
+                    Err(e) => {
+                        if s.error.is_none() {
+                            warn!(path = %tail.path.display(), error = %e, "node log unreadable");
+                        }
+                        s.error = Some(e.to_string());
+                    }
+                }
+            }
+            wait_for_next_poll(&flag);
+        }
+        debug!("stopped node log tailer");
+    });
+    NodeLogHandle {
+        path: path.to_string(),
+        shutdown,
+        thread,
+    }
+}
+
+fn wait_for_next_poll(shutdown: &AtomicBool) {
+    let deadline = Instant::now() + POLL_INTERVAL;
+    while !shutdown.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        std::thread::sleep(SHUTDOWN_TICK.min(deadline - now));
+    }
+}
+
+pub fn stop_tailer(handle: NodeLogHandle) {
+    handle.shutdown.store(true, Ordering::Relaxed);
+    let _ = handle.thread.join();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_LINE_BYTES, NodeLogState, Severity, Tail};
+    use std::io::Write;
+
+    #[test]
+    fn severity_from_tags_and_prefixes() {
+        let cases = [
+            ("[error] Failed to open", Severity::Error),
+            ("[net:error] socket send error", Severity::Error),
+            ("[warning] Disk space is low", Severity::Warning),
+            ("[msghand] [net:debug] received: ping", Severity::Debug),
+            ("[validation:trace] ...", Severity::Debug),
+            ("ERROR: ReadBlockFromDisk failed", Severity::Error),
+            ("Warning: unknown new rules", Severity::Warning),
+            ("UpdateTip: new best=00ab height=1", Severity::Info),
+            ("[init] Bitcoin Core version v28.0", Severity::Info),
+        ];
+        for (message, severity) in cases {
+            let line = format!("2024-05-01T12:00:00Z {message}");
+            assert_eq!(Severity::classify(&line), severity, "{line}");
+        }
+        assert_eq!(Severity::classify("Error: no timestamp"), Severity::Error);
+        assert_eq!(Severity::classify(""), Severity::Info);
+    }
+
+    #[test]
+    fn buffer_is_capped_and_reports_gaps() {
+        let mut state = NodeLogState::default();
+        state.set_limit(3);
+        for i in 0..5 {
+            state.push(format!("line {i}"));
+        }
+        let texts: Vec<_> = state.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["line 2", "line 3", "line 4"]);
+        assert_eq!(state.to_json(1)["truncated"], true);
+        assert_eq!(state.to_json(2)["truncated"], false);
+        assert_eq!(state.to_json(4)["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(state.to_json(0)["cursor"], 5);
+
+        state.set_limit(1);
+        assert_eq!(state.lines.len(), 1);
+    }
+
+    #[test]
+    fn tail_follows_appends_truncation_and_replacement() {
+        let dir = std::env::temp_dir().join(format!("node-log-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("debug.log");
+        let append = |text: &str| {
+            let mut f = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .unwrap();
+            f.write_all(text.as_bytes()).unwrap();
+        };
+
+        let mut tail = Tail::new(path.clone());
+        assert!(tail.poll().is_err());
+        append("one\ntwo\r\nthr");
+        assert_eq!(tail.poll().unwrap(), ["one", "two"]);
+        append("ee\n");
+        assert_eq!(tail.poll().unwrap(), ["three"]);
+        assert!(tail.poll().unwrap().is_empty());
+
+        // copytruncate-style rotation.
+        std::fs::write(&path, "new\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), ["new"]);
+
+        // Moved aside and recreated, already longer than the old file.
+        std::fs::rename(&path, dir.join("debug.log.1")).unwrap();
+        assert!(tail.poll().is_err());
+        append("after rotation, a longer first line\n");
+        assert_eq!(
+            tail.poll().unwrap(),
+            ["after rotation, a longer first line"]
+        );
+
+        append(&format!("{}\n", "x".repeat(MAX_LINE_BYTES * 2)));
+        assert_eq!(tail.poll().unwrap()[0].len(), MAX_LINE_BYTES);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn first_open_starts_at_a_line_boundary_near_the_end() {
+        let dir = std::env::temp_dir().join(format!("node-log-backlog-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("debug.log");
+        let line = "a".repeat(99);
+        let body = format!("{line}\n").repeat(20_000);
+        std::fs::write(&path, body).unwrap();
+
+        let lines = Tail::new(path).poll().unwrap();
+        assert!(lines.len() < 20_000 && lines.len() > 10_000);
+        assert!(lines.iter().all(|l| *l == line));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
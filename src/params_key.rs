@@ -0,0 +1,101 @@
+use serde_json::Value;
+
+/// Canonical form of a params JSON string, for spotting history entries and
+/// presets that differ only in whitespace or object key order.
+///
+/// Object keys are sorted and insignificant whitespace dropped. Numbers keep
+/// serde_json's own representation, so `1.0` and `1` stay distinct (a float
+/// and an integer are not the same argument to every RPC) and `1e2` becomes
+/// `100.0`. Input that isn't JSON falls back to the trimmed raw string.
+pub fn canonical_params(raw: &str) -> String {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(value) => {
+            let mut out = String::new();
+            write_canonical(&value, &mut out);
+            out
+        }
+        Err(_) => raw.trim().to_string(),
+    }
+}
+
+// Sorted explicitly rather than relying on serde_json's map type, which
+// keeps insertion order when the `preserve_order` feature is enabled.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Dedupe key for a call: the method plus its canonical params.
+pub fn call_key(method: &str, raw_params: &str) -> String {
+    format!("{method} {}", canonical_params(raw_params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_key, canonical_params};
+
+    #[test]
+    fn whitespace_and_key_order_are_ignored() {
+        assert_eq!(canonical_params("[ 1, true ]"), "[1,true]");
+        assert_eq!(canonical_params("[1,true]"), "[1,true]");
+        assert_eq!(
+            canonical_params(r#"{ "b": [ {"y":1, "x":2} ], "a": "s p a c e" }"#),
+            r#"{"a":"s p a c e","b":[{"x":2,"y":1}]}"#
+        );
+    }
+
+    #[test]
+    fn integer_and_float_stay_distinct() {
+        assert_eq!(canonical_params("[1.0]"), "[1.0]");
+        assert_eq!(canonical_params("[1]"), "[1]");
+        assert_ne!(canonical_params("[1.0]"), canonical_params("[1]"));
+        assert_eq!(canonical_params("[1e2]"), "[100.0]");
+        assert_eq!(canonical_params("[1e-5]"), "[0.00001]");
+    }
+
+    #[test]
+    fn canonical_form_is_stable() {
+        let once = canonical_params(r#"{"z": null, "a": [1.5, "é", {"k": false}]}"#);
+        assert_eq!(canonical_params(&once), once);
+    }
+
+    #[test]
+    fn invalid_json_falls_back_to_trimmed_text() {
+        assert_eq!(canonical_params("  [1, "), "[1,");
+        assert_eq!(canonical_params("  "), "");
+    }
+
+    #[test]
+    fn call_keys_dedupe_equivalent_calls() {
+        assert_eq!(
+            call_key("getblock", r#"[ "00ab", 2 ]"#),
+            call_key("getblock", r#"["00ab",2]"#)
+        );
+        assert_ne!(call_key("getblock", "[]"), call_key("getblockhash", "[]"));
+    }
+}
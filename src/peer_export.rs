@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::rpc::{self, RpcConfig, RpcError};
+use crate::rpc_limiter::RpcBudget;
+
+/// Characters of an onion/i2p host kept when redacting.
+const OVERLAY_PREFIX_LEN: usize = 8;
+
+const COLUMNS: [&str; 12] = [
+    "id",
+    "addr",
+    "network",
+    "direction",
+    "type",
+    "client",
+    "version",
+    "ping_ms",
+    "synced_blocks",
+    "bytes_sent",
+    "bytes_recv",
+    "permissions",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// Salt for the lifetime of the process, so pseudonyms can't be compared
+/// across sessions.
+fn session_salt() -> [u8; 16] {
+    static SALT: OnceLock<[u8; 16]> = OnceLock::new();
+    *SALT.get_or_init(|| {
+        let mut salt = [0u8; 16];
+        for (i, chunk) in salt.chunks_mut(8).enumerate() {
+            let mut h = RandomState::new().build_hasher();
+            h.write_usize(i);
+            if let Ok(d) = SystemTime::now().duration_since(UNIX_EPOCH) {
+                h.write_u128(d.as_nanos());
+            }
+            chunk.copy_from_slice(&h.finish().to_le_bytes());
+        }
+        salt
+    })
+}
+
+/// Host and port of a getpeerinfo `addr`, handling `[v6]:port` and bare IPv6.
+fn split_addr(addr: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = addr.strip_prefix('[')
+        && let Some((host, tail)) = rest.split_once(']')
+    {
+        return (host, tail.strip_prefix(':'));
+    }
+    match addr.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host, Some(port)),
+        _ => (addr, None),
+    }
+}
+
+fn is_overlay(host: &str) -> bool {
+    host.ends_with(".onion") || host.ends_with(".i2p")
+}
+
+fn family(host: &str) -> &'static str {
+    if host.contains(':') { "ipv6" } else { "ipv4" }
+}
+
+/// A, B, …, Z, AA, AB, …
+fn letters(mut n: usize) -> String {
+    let mut out = Vec::new();
+    loop {
+        out.push(b'A' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Maps clearnet hosts to `peer-ipv4-A`-style names and truncates onion/i2p
+/// hosts. Letters follow a salted hash of the host, so one export is
+/// consistent (ports don't matter) but order leaks nothing across sessions.
+pub struct Redactor {
+    names: HashMap<String, String>,
+}
+
+impl Redactor {
+    pub fn new<'a>(salt: &[u8], addrs: impl IntoIterator<Item = &'a str>) -> Redactor {
+        let mut hosts: Vec<(Vec<u8>, &str)> = Vec::new();
+        for addr in addrs {
+            let (host, _) = split_addr(addr);
+            if is_overlay(host) || hosts.iter().any(|(_, h)| *h == host) {
+                continue;
+            }
+            let digest = Sha256::new()
+                .chain_update(salt)
+                .chain_update(host.as_bytes())
+                .finalize()
+                .to_vec();
+            hosts.push((digest, host));
+        }
+        hosts.sort();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let names = hosts
+            .into_iter()
+            .map(|(_, host)| {
+                let n = counts.entry(family(host)).or_default();
+                let name = format!("peer-{}-{}", family(host), letters(*n));
+                *n += 1;
+                (host.to_string(), name)
+            })
+            .collect();
+        Redactor { names }
+    }
+
+    pub fn redact(&self, addr: &str) -> String {
+        let (host, port) = split_addr(addr);
+        if is_overlay(host) {
+            let prefix: String = host.chars().take(OVERLAY_PREFIX_LEN).collect();
+            return match port {
+                Some(port) => format!("{prefix}…:{port}"),
+                None => format!("{prefix}…"),
+            };
+        }
+        self.names
+            .get(host)
+            .cloned()
+            .unwrap_or_else(|| "peer-redacted".to_string())
+    }
+}
+
+fn row(peer: &serde_json::Value, redactor: Option<&Redactor>) -> Vec<String> {
+    let text = |v: &serde_json::Value| match v {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let addr = peer["addr"].as_str().unwrap_or_default();
+    let permissions = peer["permissions"]
+        .as_array()
+        .map(|p| {
+            p.iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    let direction = if peer["inbound"].as_bool().unwrap_or(false) {
+        "in"
+    } else {
+        "out"
+    };
+    vec![
+        text(&peer["id"]),
+        redactor.map_or_else(|| addr.to_string(), |r| r.redact(addr)),
+        text(&peer["network"]),
+        direction.to_string(),
+        text(&peer["connection_type"]),
+        text(&peer["subver"]),
+        text(&peer["version"]),
+        peer["pingtime"]
+            .as_f64()
+            .map(|s| format!("{:.0}", s * 1000.0))
+            .unwrap_or_default(),
+        text(&peer["synced_blocks"]),
+        text(&peer["bytessent"]),
+        text(&peer["bytesrecv"]),
+        permissions,
+    ]
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// The peer table as text or CSV. `addrlocal`/`addrbind` are never included
+/// since they identify this node rather than the peer.
+pub fn render(peers: &[serde_json::Value], format: Format, salt: Option<&[u8]>) -> String {
+    let redactor =
+        salt.map(|salt| Redactor::new(salt, peers.iter().filter_map(|p| p["addr"].as_str())));
+    let mut rows = vec![COLUMNS.iter().map(|c| c.to_string()).collect::<Vec<_>>()];
+    rows.extend(peers.iter().map(|p| row(p, redactor.as_ref())));
+
+    let mut out = String::new();
+    match format {
+        Format::Csv => {
+            for r in &rows {
+                let fields: Vec<_> = r.iter().map(|f| csv_field(f)).collect();
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+        }
+        Format::Text => {
+            let widths: Vec<usize> = (0..COLUMNS.len())
+                .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+                .collect();
+            for r in &rows {
+                let cells: Vec<_> = r
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &w)| format!("{cell:<w$}"))
+                    .collect();
+                out.push_str(cells.join("  ").trim_end());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+pub fn fetch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    format: Format,
+    redact: bool,
+) -> Result<String, RpcError> {
+    let peers = rpc::node_batch(
+        config,
+        budget,
+        &[json!({ "method": "getpeerinfo", "params": [] })],
+    )?
+    .pop()
+    .unwrap_or_else(|| Err(RpcError::InvalidResponse("empty batch reply".into())))?;
+    let peers = peers.as_array().map(Vec::as_slice).unwrap_or_default();
+    let salt = session_salt();
+    Ok(render(peers, format, redact.then_some(&salt[..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Redactor, letters, render, split_addr};
+    use serde_json::json;
+
+    #[test]
+    fn addresses_split_into_host_and_port() {
+        assert_eq!(split_addr("1.2.3.4:8333"), ("1.2.3.4", Some("8333")));
+        assert_eq!(
+            split_addr("[2001:db8::1]:8333"),
+            ("2001:db8::1", Some("8333"))
+        );
+        assert_eq!(split_addr("2001:db8::1"), ("2001:db8::1", None));
+        assert_eq!(split_addr("abc.onion:8333"), ("abc.onion", Some("8333")));
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_within_an_export() {
+        let addrs = [
+            "1.2.3.4:8333",
+            "[2001:db8::1]:8333",
+            "5.6.7.8:1234",
+            "1.2.3.4:50000",
+        ];
+        let r = Redactor::new(b"salt", addrs);
+        assert_eq!(r.redact("1.2.3.4:8333"), r.redact("1.2.3.4:50000"));
+        assert_ne!(r.redact("1.2.3.4:8333"), r.redact("5.6.7.8:1234"));
+        assert!(r.redact("5.6.7.8:1234").starts_with("peer-ipv4-"));
+        assert_eq!(r.redact("[2001:db8::1]:8333"), "peer-ipv6-A");
+        assert_eq!(r.redact("9.9.9.9:1"), "peer-redacted");
+    }
+
+    #[test]
+    fn different_salts_assign_letters_differently() {
+        let addrs: Vec<_> = (1..=12).map(|i| format!("10.0.0.{i}:8333")).collect();
+        let names = |salt: &[u8]| {
+            let r = Redactor::new(salt, addrs.iter().map(String::as_str));
+            addrs.iter().map(|a| r.redact(a)).collect::<Vec<_>>()
+        };
+        assert_ne!(names(b"session one"), names(b"session two"));
+        assert_eq!(names(b"session one"), names(b"session one"));
+    }
+
+    #[test]
+    fn overlay_hosts_are_truncated() {
+        let r = Redactor::new(b"salt", []);
+        assert_eq!(
+            r.redact("vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion:8333"),
+            "vww6ybal…:8333"
+        );
+        assert_eq!(r.redact("ukeu3k5o.b32.i2p:0"), "ukeu3k5o…:0");
+    }
+
+    #[test]
+    fn export_keeps_other_columns_and_quotes_csv() {
+        let peers = [json!({
+            "id": 7,
+            "addr": "203.0.113.9:8333",
+            "addrlocal": "198.51.100.1:8333",
+            "network": "ipv4",
+            "inbound": false,
+            "connection_type": "outbound-full-relay",
+            "subver": "/Satoshi:27.0.0(a,b)/",
+            "version": 70016,
+            "pingtime": 0.0421,
+            "permissions": ["noban", "relay"],
+        })];
+        let csv = render(&peers, Format::Csv, Some(b"salt"));
+        let line = csv.lines().nth(1).unwrap();
+        assert_eq!(
+            line,
+            "7,peer-ipv4-A,ipv4,out,outbound-full-relay,\"/Satoshi:27.0.0(a,b)/\",70016,42,,,,noban relay"
+        );
+        assert!(!csv.contains("198.51.100.1"));
+        let text = render(&peers, Format::Text, None);
+        assert!(text.lines().nth(1).unwrap().contains("203.0.113.9:8333"));
+    }
+
+    #[test]
+    fn letters_continue_past_z() {
+        assert_eq!(letters(0), "A");
+        assert_eq!(letters(25), "Z");
+        assert_eq!(letters(26), "AA");
+        assert_eq!(letters(27), "AB");
+    }
+}
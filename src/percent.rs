@@ -0,0 +1,118 @@
+use crate::hex;
+
+/// Decodes `application/x-www-form-urlencoded` style input from the webview.
+/// Malformed escapes are kept literally and invalid UTF-8 is replaced, so any
+/// byte sequence decodes without error.
+pub fn decode(input: &str) -> String {
+    let b = input.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%'
+            && let Some(byte) = b.get(i + 1..i + 3).and_then(decode_pair)
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(if b[i] == b'+' { b' ' } else { b[i] });
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn decode_pair(digits: &[u8]) -> Option<u8> {
+    let digits = std::str::from_utf8(digits).ok()?;
+    hex::decode(digits).ok().map(|bytes| bytes[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    fn encode(input: &str) -> String {
+        let mut out = String::with_capacity(input.len() * 3);
+        for &b in input.as_bytes() {
+            if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+                out.push(b as char);
+            } else {
+                out.push_str(&format!("%{b:02X}"));
+            }
+        }
+        out
+    }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    fn arbitrary_string(rng: &mut XorShift) -> String {
+        const ALPHABET: &[&str] = &[
+            "%",
+            "+",
+            "0",
+            "9",
+            "a",
+            "F",
+            "g",
+            "z",
+            " ",
+            "é",
+            "€",
+            "\u{0}",
+            "%2",
+            "%%",
+            "\u{10ffff}",
+        ];
+        let len = (rng.next() % 24) as usize;
+        (0..len)
+            .map(|_| ALPHABET[(rng.next() % ALPHABET.len() as u64) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn decodes_trailing_escape() {
+        assert_eq!(decode("a%41"), "aA");
+        assert_eq!(decode("%41"), "A");
+    }
+
+    #[test]
+    fn nasty_inputs_do_not_panic() {
+        let cases = [
+            ("%", "%"),
+            ("%0", "%0"),
+            ("%%20", "% "),
+            ("a+b%2Bc", "a b+c"),
+            ("%+1", "% 1"),
+            ("%zz", "%zz"),
+            ("%4", "%4"),
+            ("%é", "%é"),
+            ("%C0%AF", "\u{fffd}\u{fffd}"),
+            ("%E2%82%AC", "€"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(decode(input), expected, "input {input:?}");
+        }
+    }
+
+    #[test]
+    fn fuzz_round_trips_and_never_panics() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..20_000 {
+            let s = arbitrary_string(&mut rng);
+            assert_eq!(decode(&encode(&s)), s);
+            let _ = decode(&s);
+            if !s.contains(['%', '+']) {
+                assert_eq!(decode(&s), s);
+            }
+        }
+    }
+}
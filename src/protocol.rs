@@ -2,26 +2,57 @@ use std::borrow::Cow;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use tracing::{debug, warn};
+use tracing::{Level, debug, warn};
 use wry::http::Response;
 use wry::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE};
 
+use crate::AppContext;
+use crate::bitcoin_conf;
+use crate::block_summary;
+use crate::build_info;
+use crate::changelog;
+use crate::config_store;
+use crate::console_draft;
+use crate::custom_cards;
+use crate::exports;
+use crate::fee_estimates;
+use crate::keybindings;
+use crate::lenient_json;
+use crate::local_decode;
+use crate::logging;
+use crate::mempool_histogram;
+use crate::mempool_summary;
 use crate::music;
-use crate::rpc::{self, RpcConfig};
-use crate::rpc_limiter::RpcLimiter;
+use crate::node_compat;
+use crate::node_log;
+use crate::params_key;
+use crate::peer_export;
+use crate::percent;
+use crate::relay_policy;
+use crate::rpc::{self, RpcConfig, RpcError};
+use crate::rpc_history;
+use crate::rpc_limiter::{RpcBudget, RpcLimiter};
+use crate::status_indicator;
+use crate::sync_stall;
 use crate::thread_pool::ThreadPool;
-use crate::zmq::{self, ZmqHandle, ZmqSharedState};
+use crate::usage_metrics;
+use crate::zmq::{self, ZmqSharedState};
 
-pub fn build_webview(
-    config: Arc<Mutex<RpcConfig>>,
-    rpc_limiter: Arc<RpcLimiter>,
-    rpc_pool: Arc<ThreadPool>,
-    zmq_poll_pool: Arc<ThreadPool>,
-    music_runtime: Arc<music::MusicRuntime>,
-    zmq_state: Arc<ZmqSharedState>,
-    zmq_handle: Arc<Mutex<Option<ZmqHandle>>>,
-) -> wry::WebViewBuilder<'static> {
-    let cfg = Arc::clone(&config);
+pub fn build_webview(app: &AppContext) -> wry::WebViewBuilder<'static> {
+    let cfg = Arc::clone(&app.config);
+    let rpc_limiter = Arc::clone(&app.rpc_limiter);
+    let rpc_jobs = RpcJobs {
+        limiter: Arc::clone(&app.rpc_limiter),
+        pool: Arc::clone(&app.rpc_pool),
+        config: Arc::clone(&app.config),
+    };
+    let zmq_poll_pool = Arc::clone(&app.zmq_poll_pool);
+    let music_runtime = Arc::clone(&app.music_runtime);
+    let zmq_state = Arc::clone(&app.zmq_state);
+    let zmq_handle = Arc::clone(&app.zmq_handle);
+    let status_state = Arc::clone(&app.status_state);
+    let node_log_state = Arc::clone(&app.node_log_state);
+    let node_log_handle = Arc::clone(&app.node_log_handle);
     wry::WebViewBuilder::new()
         .with_asynchronous_custom_protocol("app".into(), move |_id, req, responder| {
             let path = req.uri().path().to_string();
@@ -30,73 +61,540 @@ pub fn build_webview(
 
             if path == "/rpc" {
                 let body = request_body(&req, &query);
-                if let Some(permit) = rpc_limiter.try_acquire() {
-                    let responder = Arc::new(Mutex::new(Some(responder)));
-                    let cfg = Arc::clone(&cfg);
-                    let async_responder = Arc::clone(&responder);
-                    if rpc_pool
-                        .execute(move || {
-                            let _permit = permit;
-                            let result = rpc::do_rpc(&body, &cfg);
-                            respond_once(&async_responder, json_response(&result));
-                        })
-                        .is_err()
-                    {
-                        warn!("rpc worker pool unavailable");
-                        respond_once(&responder, json_error_response("rpc worker pool unavailable"));
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    json_response(&rpc::do_rpc(&body, cfg, budget))
+                });
+                return;
+            }
+
+            if path == "/mempool/histogram" {
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match mempool_histogram::fetch(cfg, budget) {
+                        Ok(histogram) => json_value_response(histogram.to_json()),
+                        Err(e) => {
+                            warn!(error = %e, "mempool histogram failed");
+                            json_error_response(&e.to_string())
+                        }
                     }
-                } else {
-                    warn!("rpc request rejected due to in-flight limit");
-                    responder.respond(json_error_response("rpc worker pool saturated; try again"));
-                }
+                });
+                return;
+            }
+
+            if path == "/mempool/verbose-summary" {
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match mempool_summary::fetch_verbose_summary(cfg, budget) {
+                        Ok(summary) => json_value_response(summary.to_json()),
+                        Err(e) => {
+                            warn!(error = %e, "verbose mempool summary failed");
+                            json_error_response(&e.to_string())
+                        }
+                    }
+                });
+                return;
+            }
+
+            if path == "/block/summary" {
+                let Some(hash) = query_param(&query, "hash") else {
+                    responder.respond(json_error_response("missing hash"));
+                    return;
+                };
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match block_summary::fetch(cfg, budget, &hash) {
+                        Ok(summary) => json_value_response(summary.to_json()),
+                        Err(e) => {
+                            warn!(error = %e, hash = %hash, "block summary failed");
+                            json_error_response(&e.to_string())
+                        }
+                    }
+                });
+                return;
+            }
+
+            if path == "/blocks/recent" {
+                let Some(tip) = query_param_u64(&query, "tip") else {
+                    responder.respond(json_error_response("missing tip"));
+                    return;
+                };
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match block_summary::fetch_recent(cfg, budget, tip) {
+                        Ok(headers) => json_value_response(serde_json::json!({
+                            "blocks": headers
+                                .iter()
+                                .map(block_summary::HeaderSummary::to_json)
+                                .collect::<Vec<_>>(),
+                        })),
+                        Err(e) => {
+                            warn!(error = %e, tip, "recent blocks failed");
+                            json_error_response(&e.to_string())
+                        }
+                    }
+                });
+                return;
+            }
+
+            if path == "/peers/export" {
+                let format = query_param(&query, "format")
+                    .as_deref()
+                    .and_then(peer_export::Format::parse)
+                    .unwrap_or(peer_export::Format::Text);
+                let redact = query_param(&query, "redact").as_deref() == Some("1");
+                let save = query_param(&query, "save").as_deref() == Some("1");
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match peer_export::fetch(cfg, budget, format, redact) {
+                        Ok(text) if save => {
+                            let exports_dir = cfg.lock().unwrap().exports_dir.clone();
+                            let label = if redact { "redacted" } else { "" };
+                            match exports::ExportManager::from_config(&exports_dir).save(
+                                "peers",
+                                label,
+                                format.extension(),
+                                text.as_bytes(),
+                                console_draft::now_ms(),
+                            ) {
+                                Ok(entry) => json_value_response(
+                                    serde_json::json!({ "saved": entry.to_json() }),
+                                ),
+                                Err(e) => {
+                                    warn!(error = %e, "peer export not saved");
+                                    json_error_response(&e.to_string())
+                                }
+                            }
+                        }
+                        Ok(text) => {
+                            json_value_response(serde_json::json!({ "text": text }))
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "peer export failed");
+                            json_error_response(&e.to_string())
+                        }
+                    }
+                });
+                return;
+            }
+
+            if path == "/fee-estimates" {
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    match fee_estimates::fetch(cfg, budget) {
+                        Ok(fees) => json_value_response(fees.to_json()),
+                        Err(e) => json_error_response(&e.to_string()),
+                    }
+                });
+                return;
+            }
+
+            if path == "/zmq/decode-rawtx" {
+                // Either a hex string or the cursor of a buffered rawtx message.
+                let hex = query_param(&query, "hex").or_else(|| {
+                    let cursor = query_param_u64(&query, "cursor")?;
+                    let state = zmq_state.state.lock().unwrap();
+                    state
+                        .messages
+                        .iter()
+                        .find(|m| m.cursor == cursor)
+                        .and_then(|m| m.body_full_hex.clone())
+                });
+                let Some(hex) = hex else {
+                    responder.respond(json_error_response("no raw transaction for that message"));
+                    return;
+                };
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    decode_rawtx_response(cfg, budget, &hex)
+                });
+                return;
+            }
+
+            if path == "/rpc/stats" {
+                let stats = rpc_limiter.budget().stats();
+                responder.respond(json_value_response(serde_json::json!({
+                    "max_outstanding": stats.max_outstanding,
+                    "outstanding": stats.outstanding,
+                    "queued_interactive": stats.queued_interactive,
+                    "queued_background": stats.queued_background,
+                    "busy_backoff": stats.busy_backoff,
+                })));
                 return;
             }
 
             if path == "/config" {
                 let body = request_body(&req, &query);
-                let result = rpc::update_config(&body, &cfg);
+                let result = zmq::apply_config(&body, &cfg, &zmq_state, &zmq_handle);
+                node_log::apply_config(&cfg, &node_log_state, &node_log_handle);
+                let response = if result.errors.is_empty() {
+                    json_response(r#"{"ok":true}"#)
+                } else {
+                    json_value_response(serde_json::json!({ "ok": false, "errors": result.errors }))
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/config/bitcoin-conf" {
+                let conf_path = query_param(&query, "path").unwrap_or_default();
+                let response = match bitcoin_conf::load(&conf_path) {
+                    Ok(imported) => json_value_response(imported.to_json()),
+                    Err(e) => {
+                        warn!(error = %e, "bitcoin.conf import failed");
+                        json_error_response(&e.to_string())
+                    }
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/metrics" {
+                let metrics_path = usage_metrics::metrics_path();
+                let mut value = usage_metrics::load(&metrics_path).to_json();
+                value["path"] = metrics_path.display().to_string().into();
+                responder.respond(json_value_response(value));
+                return;
+            }
+
+            if path == "/metrics/record" {
+                let body = request_body(&req, &query);
+                let response = match usage_metrics::record(&usage_metrics::metrics_path(), &body) {
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => {
+                        warn!(error = %e, "usage metrics not recorded");
+                        json_error_response(&e.to_string())
+                    }
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/metrics/delete" {
+                let response = match usage_metrics::delete(&usage_metrics::metrics_path()) {
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/exports" {
+                let exports_dir = cfg.lock().unwrap().exports_dir.clone();
+                responder.respond(json_value_response(
+                    exports::ExportManager::from_config(&exports_dir).to_json(),
+                ));
+                return;
+            }
+
+            if path == "/exports/save" {
+                let body = request_body(&req, &query);
+                let msg: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                let exports_dir = cfg.lock().unwrap().exports_dir.clone();
+                let response = match exports::ExportManager::from_config(&exports_dir).save(
+                    msg["kind"].as_str().unwrap_or("export"),
+                    msg["label"].as_str().unwrap_or(""),
+                    msg["extension"].as_str().unwrap_or("txt"),
+                    msg["text"].as_str().unwrap_or("").as_bytes(),
+                    console_draft::now_ms(),
+                ) {
+                    Ok(entry) => json_value_response(serde_json::json!({ "saved": entry.to_json() })),
+                    Err(e) => {
+                        warn!(error = %e, "export not saved");
+                        json_error_response(&e.to_string())
+                    }
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/exports/delete" {
+                let body = request_body(&req, &query);
+                let msg: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                let exports_dir = cfg.lock().unwrap().exports_dir.clone();
+                let response = match exports::ExportManager::from_config(&exports_dir)
+                    .delete(msg["path"].as_str().unwrap_or(""))
                 {
-                    let limit = cfg.lock().unwrap().zmq_buffer_limit;
-                    let mut state = zmq_state.state.lock().unwrap();
-                    state.buffer_limit = limit;
-                    while state.messages.len() > state.buffer_limit {
-                        state.messages.pop_front();
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/exports/open-folder" {
+                let exports_dir = cfg.lock().unwrap().exports_dir.clone();
+                let manager = exports::ExportManager::from_config(&exports_dir);
+                let response = match exports::open_folder(manager.dir()) {
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => {
+                        warn!(error = %e, "exports folder not opened");
+                        json_error_response(&e.to_string())
                     }
-                }
-                if result.zmq_changed {
-                    let mut handle = zmq_handle.lock().unwrap();
-                    if let Some(h) = handle.take() {
-                        zmq::stop_zmq_subscriber(h);
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/console-draft" {
+                let draft =
+                    console_draft::pending(&console_draft::draft_path(), &console_draft::marker_path());
+                responder.respond(json_value_response(serde_json::json!({
+                    "draft": draft.map(|d| d.to_json()),
+                })));
+                return;
+            }
+
+            if path == "/console-draft/save" {
+                let body = request_body(&req, &query);
+                let response = match console_draft::save(
+                    &console_draft::draft_path(),
+                    &body,
+                    console_draft::now_ms(),
+                ) {
+                    Ok(saved) => json_value_response(serde_json::json!({ "saved": saved })),
+                    Err(e) => {
+                        warn!(error = %e, "console draft not saved");
+                        json_error_response(&e.to_string())
                     }
-                    let addr = cfg.lock().unwrap().zmq_address.clone();
-                    if !addr.is_empty() {
-                        *handle = Some(zmq::start_zmq_subscriber(&addr, Arc::clone(&zmq_state)));
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/console-draft/delete" {
+                let response = match console_draft::delete(&console_draft::draft_path()) {
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/rpc-history" {
+                let entries = rpc_history::load(&rpc_history::history_path());
+                responder.respond(json_value_response(rpc_history::to_json(&entries)));
+                return;
+            }
+
+            if path == "/rpc-history/save" {
+                let body = request_body(&req, &query);
+                let response = match rpc_history::save(&rpc_history::history_path(), &body) {
+                    Ok(saved) => json_value_response(serde_json::json!({ "saved": saved })),
+                    Err(e) => {
+                        warn!(error = %e, "call history not saved");
+                        json_error_response(&e.to_string())
                     }
-                }
-                let resp_body = if result.insecure_blocked {
-                    r#"{"ok":true,"insecure_blocked":true}"#
-                } else {
-                    r#"{"ok":true}"#
                 };
-                responder.respond(json_response(resp_body));
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/rpc-history/delete" {
+                let response = match rpc_history::delete(&rpc_history::history_path()) {
+                    Ok(()) => json_response(r#"{"ok":true}"#),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/custom-cards" {
+                let cards = custom_cards::load(&custom_cards::cards_path());
+                let mut body = custom_cards::to_json(&cards);
+                body["read_only"] = custom_cards::read_only_rule();
+                responder.respond(json_value_response(body));
+                return;
+            }
+
+            if path == "/custom-cards/save" {
+                let body = request_body(&req, &query);
+                let cards_path = custom_cards::cards_path();
+                let response = match custom_cards::save(&cards_path, &body) {
+                    Ok(cards) => json_value_response(custom_cards::to_json(&cards)),
+                    Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+                        json_error_response(&e.to_string())
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "custom cards not saved");
+                        json_value_response(config_store::save_error_json(&e, &cards_path))
+                    }
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/config-store/fallback" {
+                let body = request_body(&req, &query);
+                let input = serde_json::from_str::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|v| v["dir"].as_str().map(str::to_string))
+                    .unwrap_or_default();
+                let exe = std::env::current_exe().ok();
+                let suggested =
+                    config_store::suggested_fallback(exe.as_deref(), &config_store::dir());
+                let response = match config_store::resolve_fallback(&input, suggested.as_deref()) {
+                    Err(e) => json_error_response(&e),
+                    Ok(dir) => match config_store::use_fallback(dir.clone()) {
+                        Ok(()) => json_value_response(
+                            serde_json::json!({ "ok": true, "dir": dir.display().to_string() }),
+                        ),
+                        Err(e) => {
+                            warn!(error = %e, dir = %dir.display(), "fallback directory not usable");
+                            json_value_response(config_store::save_error_json(&e, &dir))
+                        }
+                    },
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/custom-cards/refresh" {
+                let body = request_body(&req, &query);
+                rpc_jobs.spawn(responder, move |cfg, budget| {
+                    let path = custom_cards::cards_path();
+                    custom_cards::refresh(&path, cfg, budget, &body)
+                        .map_or_else(|e| json_error_response(&e), json_value_response)
+                });
+                return;
+            }
+
+            if path == "/node/compat" {
+                let response = match query_param_u64(&query, "version") {
+                    Some(version) => json_value_response(node_compat::to_json(version)),
+                    None => json_error_response("missing version"),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/relay-policy" {
+                let body = request_body(&req, &query);
+                let response = match serde_json::from_str::<serde_json::Value>(&body) {
+                    Ok(v) => json_value_response(
+                        relay_policy::PolicySummary::from_rpc(&v["mempool"], &v["network"])
+                            .to_json(),
+                    ),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/peer-fee-floor" {
+                let body = request_body(&req, &query);
+                let response = match serde_json::from_str::<serde_json::Value>(&body) {
+                    Ok(v) => json_value_response(relay_policy::peer_fee_floor_json(&v)),
+                    Err(e) => json_error_response(&e.to_string()),
+                };
+                responder.respond(response);
+                return;
+            }
+
+            if path == "/keybindings" {
+                let saved = query_param(&query, "saved")
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default();
+                responder.respond(json_value_response(keybindings::to_json(&saved)));
+                return;
+            }
+
+            if path == "/json/clean" {
+                let body = request_body(&req, &query);
+                let value = serde_json::from_str(&body).unwrap_or_default();
+                responder.respond(json_value_response(lenient_json::clean_json(&value)));
+                return;
+            }
+
+            if path == "/sync-stall" {
+                let body = request_body(&req, &query);
+                let value = serde_json::from_str(&body).unwrap_or_default();
+                responder.respond(json_value_response(sync_stall::detect_json(&value)));
+                return;
+            }
+
+            if path == "/status-indicators" {
+                responder.respond(json_value_response(status_indicator::to_json()));
+                return;
+            }
+
+            if path == "/params/key" {
+                let method = query_param(&query, "method").unwrap_or_default();
+                let params = query_param(&query, "params").unwrap_or_default();
+                responder.respond(json_value_response(serde_json::json!({
+                    "key": params_key::call_key(&method, &params),
+                    "params": params_key::canonical_params(&params),
+                })));
                 return;
             }
 
             if path == "/allow-insecure-rpc" {
                 let allowed = rpc::allow_insecure();
-                responder.respond(json_value_response(serde_json::json!({ "allowed": allowed })));
+                responder.respond(json_value_response(
+                    serde_json::json!({ "allowed": allowed }),
+                ));
                 return;
             }
 
             if path == "/features" {
                 responder.respond(json_value_response(serde_json::json!({
-                    "audio": music::is_enabled()
+                    "audio": music::is_enabled(),
+                    "build": build_info::to_json(),
+                    "status_page": status_state.address().map(|a| a.to_string()),
+                })));
+                return;
+            }
+
+            if path == "/status-page/snapshot" {
+                let body = request_body(&req, &query);
+                let stored = serde_json::from_str(&body)
+                    .is_ok_and(|v: serde_json::Value| status_state.update_snapshot(&v));
+                responder.respond(if stored {
+                    json_response(r#"{"ok":true}"#)
+                } else {
+                    json_error_response("invalid dashboard snapshot")
+                });
+                return;
+            }
+
+            if path == "/changelog" {
+                let seen = query_param(&query, "seen");
+                responder.respond(json_value_response(changelog::response(seen.as_deref())));
+                return;
+            }
+
+            if path == "/logs" {
+                // Warnings and errors by default; `level=debug` includes everything captured.
+                let level = match query_param(&query, "level").as_deref() {
+                    Some("debug") => Level::DEBUG,
+                    _ => Level::WARN,
+                };
+                let logs = logging::recent_logs();
+                responder.respond(json_value_response(serde_json::json!({
+                    "text": logs.render_text(level),
+                    "dropped": logs.dropped(),
                 })));
                 return;
             }
 
+            if path == "/zmq/reconnect" {
+                let requested = match zmq_handle.lock().unwrap().as_ref() {
+                    Some(h) => {
+                        h.reconnect_now();
+                        true
+                    }
+                    None => false,
+                };
+                responder.respond(json_value_response(serde_json::json!({ "ok": requested })));
+                return;
+            }
+
+            if path == "/node-log" {
+                let since = query_param_u64(&query, "since").unwrap_or(0);
+                let value = node_log_state.lock().unwrap().to_json(since);
+                responder.respond(json_value_response(value));
+                return;
+            }
+
             if path == "/zmq/messages" {
                 let since = query_param_u64(&query, "since").unwrap_or(0);
+                let messages_query = zmq::MessagesQuery {
+                    since,
+                    limit: query_param_u64(&query, "limit").map(|n| n as usize),
+                    summary_only: query_param(&query, "summary_only").as_deref() == Some("1"),
+                };
                 let wait_ms = query_param_u64(&query, "wait_ms")
                     .unwrap_or(0)
                     .clamp(0, 30_000);
@@ -112,19 +610,22 @@ pub fn build_webview(
                                 s.messages.back().is_none_or(|m| m.cursor <= since)
                             });
                         }
-                        let result = zmq_messages_response(&state, since);
+                        let result = zmq_messages_response(&state, messages_query);
                         respond_once(&async_responder, json_response(&result));
                     })
                     .is_err()
                 {
                     warn!("zmq poll worker pool unavailable");
-                    respond_once(&responder, json_error_response("zmq poll worker pool unavailable"));
+                    respond_once(
+                        &responder,
+                        json_error_response("zmq poll worker pool unavailable"),
+                    );
                 }
                 return;
             }
 
             if let Some(result) =
-                music::handle_music_request(&path, &percent_decode(&query), &music_runtime)
+                music::handle_music_request(&path, &percent::decode(&query), &music_runtime)
             {
                 responder.respond(json_response(&result));
                 return;
@@ -136,6 +637,77 @@ pub fn build_webview(
         .with_url("app://localhost/index.html")
 }
 
+/// Runs RPC-backed routes on the worker pool, at most as many at once as the
+/// limiter allows.
+struct RpcJobs {
+    limiter: Arc<RpcLimiter>,
+    pool: Arc<ThreadPool>,
+    config: Arc<Mutex<RpcConfig>>,
+}
+
+impl RpcJobs {
+    /// Responds with what `job` returns, or with an error straight away when
+    /// every slot is taken or the pool has shut down.
+    fn spawn<F>(&self, responder: wry::RequestAsyncResponder, job: F)
+    where
+        F: FnOnce(&Arc<Mutex<RpcConfig>>, &Arc<RpcBudget>) -> Response<Cow<'static, [u8]>>
+            + Send
+            + 'static,
+    {
+        let Some(permit) = self.limiter.try_acquire() else {
+            warn!("rpc request rejected due to in-flight limit");
+            responder.respond(json_error_response("rpc worker pool saturated; try again"));
+            return;
+        };
+        let responder = Arc::new(Mutex::new(Some(responder)));
+        let config = Arc::clone(&self.config);
+        let budget = Arc::clone(self.limiter.budget());
+        let async_responder = Arc::clone(&responder);
+        if self
+            .pool
+            .execute(move || {
+                let _permit = permit;
+                respond_once(&async_responder, job(&config, &budget));
+            })
+            .is_err()
+        {
+            warn!("rpc worker pool unavailable");
+            respond_once(
+                &responder,
+                json_error_response("rpc worker pool unavailable"),
+            );
+        }
+    }
+}
+
+/// `decoderawtransaction` on the node, or the local decoder when the node
+/// can't be reached or rejects the request.
+fn decode_rawtx_response(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    hex: &str,
+) -> Response<Cow<'static, [u8]>> {
+    let call = serde_json::json!({ "method": "decoderawtransaction", "params": [hex] });
+    let remote = rpc::node_batch(config, budget, &[call]).and_then(|mut replies| {
+        replies
+            .pop()
+            .unwrap_or_else(|| Err(RpcError::InvalidResponse("empty batch reply".into())))
+    });
+    match remote {
+        Ok(decoded) => {
+            json_value_response(serde_json::json!({ "result": decoded, "source": "rpc" }))
+        }
+        Err(e) => {
+            debug!(error = %e, "decoderawtransaction over rpc failed; decoding locally");
+            let decoded = local_decode::decode_rawtx_local(hex);
+            if let Some(message) = decoded["error"].as_str() {
+                return json_error_response(message);
+            }
+            json_value_response(serde_json::json!({ "result": decoded, "source": "local" }))
+        }
+    }
+}
+
 fn json_response(body: &str) -> Response<Cow<'static, [u8]>> {
     Response::builder()
         .header(CONTENT_TYPE, "application/json")
@@ -157,9 +729,10 @@ fn respond_once(
     response: Response<Cow<'static, [u8]>>,
 ) {
     if let Ok(mut guard) = responder.lock()
-        && let Some(async_responder) = guard.take() {
-            async_responder.respond(response);
-        }
+        && let Some(async_responder) = guard.take()
+    {
+        async_responder.respond(response);
+    }
 }
 
 fn serve_asset(path: &str) -> Response<Cow<'static, [u8]>> {
@@ -182,25 +755,6 @@ fn serve_asset(path: &str) -> Response<Cow<'static, [u8]>> {
         .unwrap()
 }
 
-fn percent_decode(input: &str) -> String {
-    let mut out = Vec::new();
-    let b = input.as_bytes();
-    let mut i = 0;
-    while i < b.len() {
-        if b[i] == b'%' && i + 2 < b.len()
-            && let Ok(byte) =
-                u8::from_str_radix(std::str::from_utf8(&b[i + 1..i + 3]).unwrap_or(""), 16)
-            {
-                out.push(byte);
-                i += 3;
-                continue;
-            }
-        out.push(if b[i] == b'+' { b' ' } else { b[i] });
-        i += 1;
-    }
-    String::from_utf8_lossy(&out).to_string()
-}
-
 fn request_body(req: &wry::http::Request<Vec<u8>>, query: &str) -> String {
     if req.method() == wry::http::Method::POST {
         if let Some(encoded) = req
@@ -208,7 +762,7 @@ fn request_body(req: &wry::http::Request<Vec<u8>>, query: &str) -> String {
             .get("x-app-json")
             .and_then(|v| v.to_str().ok())
         {
-            let decoded = percent_decode(encoded);
+            let decoded = percent::decode(encoded);
             if !decoded.is_empty() {
                 return decoded;
             }
@@ -218,57 +772,27 @@ fn request_body(req: &wry::http::Request<Vec<u8>>, query: &str) -> String {
             return String::from_utf8_lossy(body).to_string();
         }
     }
-    percent_decode(query)
+    percent::decode(query)
+}
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let mut iter = pair.splitn(2, '=');
+        let k = iter.next()?;
+        let v = iter.next().unwrap_or("");
+        (k == key).then(|| percent::decode(v))
+    })
 }
 
 fn query_param_u64(query: &str, key: &str) -> Option<u64> {
-    query
-        .split('&')
-        .find_map(|pair| {
-            let mut iter = pair.splitn(2, '=');
-            let k = iter.next()?;
-            let v = iter.next().unwrap_or("");
-            (k == key).then_some(percent_decode(v))
-        })
-        .and_then(|v| v.parse::<u64>().ok())
+    query_param(query, key).and_then(|v| v.parse::<u64>().ok())
 }
 
-fn zmq_messages_response(zmq_state: &Arc<ZmqSharedState>, since: u64) -> String {
+fn zmq_messages_response(zmq_state: &Arc<ZmqSharedState>, query: zmq::MessagesQuery) -> String {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
     let s = zmq_state.state.lock().unwrap();
-    let mut truncated = false;
-    let messages: Vec<serde_json::Value> = s
-        .messages
-        .iter()
-        .filter(|m| m.cursor > since)
-        .map(|m| {
-            serde_json::json!({
-                "cursor": m.cursor,
-                "topic": m.topic,
-                "body_hex": m.body_hex,
-                "body_size": m.body_size,
-                "sequence": m.sequence,
-                "timestamp": m.timestamp,
-                "event_hash": m.event_hash,
-            })
-        })
-        .collect();
-    if since > 0
-        && !messages.is_empty()
-        && s.messages
-            .iter()
-            .find(|m| m.cursor > since)
-            .is_some_and(|m| m.cursor > since.saturating_add(1))
-    {
-        truncated = true;
-    }
-    let cursor = s.messages.back().map_or(0, |m| m.cursor);
-    serde_json::json!({
-        "connected": s.connected,
-        "address": s.address,
-        "buffer_limit": s.buffer_limit,
-        "cursor": cursor,
-        "truncated": truncated,
-        "messages": messages,
-    })
-    .to_string()
+    s.messages_json(query, now_ms).to_string()
 }
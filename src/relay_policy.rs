@@ -0,0 +1,339 @@
+use serde_json::{Value, json};
+
+use crate::node_compat::format_version;
+
+/// Relay policy defaults as of a Core release.
+#[derive(Debug, PartialEq)]
+pub struct PolicyDefaults {
+    pub since: u64,
+    pub full_rbf: bool,
+    /// `minrelaytxfee` in BTC/kvB.
+    pub min_relay_fee: f64,
+    /// `incrementalrelayfee` in BTC/kvB.
+    pub incremental_relay_fee: f64,
+}
+
+/// Oldest first; a node gets the last entry at or below its version.
+pub const DEFAULTS: [PolicyDefaults; 3] = [
+    PolicyDefaults {
+        since: 0,
+        full_rbf: false,
+        min_relay_fee: 0.000_01,
+        incremental_relay_fee: 0.000_01,
+    },
+    PolicyDefaults {
+        since: 280_000,
+        full_rbf: true,
+        min_relay_fee: 0.000_01,
+        incremental_relay_fee: 0.000_01,
+    },
+    PolicyDefaults {
+        since: 290_100,
+        full_rbf: true,
+        min_relay_fee: 0.000_001,
+        incremental_relay_fee: 0.000_001,
+    },
+];
+
+/// getmempoolinfo reports `fullrbf` from this version on.
+const FULL_RBF_REPORTED_SINCE: u64 = 240_000;
+
+pub fn defaults_for(version: u64) -> &'static PolicyDefaults {
+    DEFAULTS
+        .iter()
+        .rev()
+        .find(|d| version >= d.since)
+        .unwrap_or(&DEFAULTS[0])
+}
+
+/// The relay settings a node reports. `None` means the node is too old to
+/// report the setting.
+#[derive(Debug, PartialEq)]
+pub struct PolicySummary {
+    pub version: u64,
+    pub full_rbf: Option<bool>,
+    pub min_relay_fee: Option<f64>,
+    pub incremental_relay_fee: Option<f64>,
+}
+
+impl PolicySummary {
+    /// getmempoolinfo is preferred; getnetworkinfo's `relayfee` and
+    /// `incrementalfee` cover nodes from before getmempoolinfo had the fees.
+    pub fn from_rpc(mempool: &Value, network: &Value) -> Self {
+        Self {
+            version: network["version"].as_u64().unwrap_or(0),
+            full_rbf: mempool["fullrbf"].as_bool(),
+            min_relay_fee: mempool["minrelaytxfee"]
+                .as_f64()
+                .or_else(|| network["relayfee"].as_f64()),
+            incremental_relay_fee: mempool["incrementalrelayfee"]
+                .as_f64()
+                .or_else(|| network["incrementalfee"].as_f64()),
+        }
+    }
+
+    /// One line per setting that differs from the node version's default.
+    pub fn deviations(&self) -> Vec<String> {
+        let defaults = defaults_for(self.version);
+        let mut hints = Vec::new();
+        match self.full_rbf {
+            Some(true) if !defaults.full_rbf => hints.push(
+                "Full RBF is enabled: unsignalled replacements are accepted, which most nodes of this version refuse."
+                    .to_string(),
+            ),
+            Some(false) if defaults.full_rbf => hints.push(
+                "Full RBF is disabled: replacements without a BIP125 signal are refused, so this node can miss ones its peers relay."
+                    .to_string(),
+            ),
+            _ => {}
+        }
+        if let Some(fee) = self.min_relay_fee {
+            let default = defaults.min_relay_fee;
+            if fee > default {
+                hints.push(format!(
+                    "Minimum relay fee {} is above the {} default: transactions paying less are relayed by peers but never reach this mempool.",
+                    sat_per_vb(fee),
+                    sat_per_vb(default)
+                ));
+            } else if fee < default {
+                hints.push(format!(
+                    "Minimum relay fee {} is below the {} default: this node accepts transactions most peers won't relay.",
+                    sat_per_vb(fee),
+                    sat_per_vb(default)
+                ));
+            }
+        }
+        if let Some(fee) = self.incremental_relay_fee
+            && fee != defaults.incremental_relay_fee
+        {
+            hints.push(format!(
+                "Incremental relay fee {} differs from the {} default, so replacements need a different fee bump here than elsewhere.",
+                sat_per_vb(fee),
+                sat_per_vb(defaults.incremental_relay_fee)
+            ));
+        }
+        hints
+    }
+
+    pub fn to_json(&self) -> Value {
+        let unknown = |since: u64| format!("unknown (pre-{} node)", format_version(since));
+        let full_rbf = match self.full_rbf {
+            Some(true) => "enabled".to_string(),
+            Some(false) => "disabled".to_string(),
+            None => unknown(FULL_RBF_REPORTED_SINCE),
+        };
+        let fee = |fee: Option<f64>| fee.map_or_else(|| "unknown".to_string(), sat_per_vb);
+        json!({
+            "settings": [
+                {
+                    "name": "Full RBF",
+                    "value": full_rbf,
+                    "about": "Accept replacements that did not signal BIP125",
+                },
+                {
+                    "name": "Min relay fee",
+                    "value": fee(self.min_relay_fee),
+                    "about": "Lowest feerate accepted into the mempool and relayed",
+                },
+                {
+                    "name": "Incremental fee",
+                    "value": fee(self.incremental_relay_fee),
+                    "about": "Feerate a replacement must add on top of what it replaces",
+                },
+            ],
+            "hints": self.deviations(),
+        })
+    }
+}
+
+/// Nearest-rank percentile, so the result is always one of `values`; the
+/// median of an even count is the lower middle value.
+pub fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// The fee filters outbound peers sent us, in BTC/kvB: the lowest feerate
+/// each will take from us, so a rough floor for getting a transaction out.
+#[derive(Debug, PartialEq)]
+pub struct PeerFeeFloor {
+    pub peers: usize,
+    pub median: f64,
+    pub p90: f64,
+}
+
+impl PeerFeeFloor {
+    /// Inbound peers are left out since anyone can open one. Peers without a
+    /// `minfeefilter` (none sent yet) don't count either.
+    pub fn from_peers(peers: &[Value]) -> Option<Self> {
+        let filters: Vec<f64> = peers
+            .iter()
+            .filter(|p| p["inbound"].as_bool() == Some(false))
+            .filter_map(|p| p["minfeefilter"].as_f64())
+            .filter(|f| f.is_finite() && *f >= 0.0)
+            .collect();
+        Some(Self {
+            peers: filters.len(),
+            median: percentile(&filters, 50.0)?,
+            p90: percentile(&filters, 90.0)?,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let noun = if self.peers == 1 { "peer" } else { "peers" };
+        json!({
+            "peers": self.peers,
+            "median": self.median,
+            "p90": self.p90,
+            "text": format!(
+                "~{} median, {} p90 ({} outbound {noun})",
+                sat_per_vb(self.median),
+                sat_per_vb(self.p90),
+                self.peers
+            ),
+        })
+    }
+}
+
+/// `{ "peers": [...] }` as from getpeerinfo.
+pub fn peer_fee_floor_json(body: &Value) -> Value {
+    let peers = body["peers"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    json!({ "floor": PeerFeeFloor::from_peers(peers).map(|f| f.to_json()) })
+}
+
+/// BTC/kvB as sat/vB, e.g. 0.00001 as "1 sat/vB".
+pub fn sat_per_vb(btc_per_kvb: f64) -> String {
+    let sats = (btc_per_kvb * 100_000.0 * 1000.0).round() / 1000.0;
+    format!("{sats} sat/vB")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DEFAULTS, PeerFeeFloor, PolicySummary, defaults_for, peer_fee_floor_json, percentile,
+        sat_per_vb,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn defaults_are_ordered_and_picked_by_version() {
+        assert!(DEFAULTS.windows(2).all(|w| w[0].since < w[1].since));
+        assert!(!defaults_for(270_100).full_rbf);
+        assert!(defaults_for(280_000).full_rbf);
+        assert_eq!(defaults_for(290_000).min_relay_fee, 0.000_01);
+        assert_eq!(defaults_for(300_000).min_relay_fee, 0.000_001);
+    }
+
+    #[test]
+    fn default_policy_has_no_hints() {
+        let mempool = json!({ "fullrbf": true, "minrelaytxfee": 0.000_001, "incrementalrelayfee": 0.000_001 });
+        let p = PolicySummary::from_rpc(&mempool, &json!({ "version": 300_000 }));
+        assert!(p.deviations().is_empty());
+        assert_eq!(p.to_json()["settings"][1]["value"], "0.1 sat/vB");
+    }
+
+    #[test]
+    fn deviations_are_relative_to_the_node_version() {
+        let mempool =
+            json!({ "fullrbf": true, "minrelaytxfee": 0.0001, "incrementalrelayfee": 0.000_01 });
+        let old = PolicySummary::from_rpc(&mempool, &json!({ "version": 260_000 }));
+        let hints = old.deviations();
+        assert_eq!(hints.len(), 2);
+        assert!(hints[0].starts_with("Full RBF is enabled"));
+        assert!(hints[1].contains("10 sat/vB is above the 1 sat/vB default"));
+
+        let new = PolicySummary::from_rpc(
+            &json!({ "fullrbf": false, "minrelaytxfee": 0.000_001, "incrementalrelayfee": 0.000_001 }),
+            &json!({ "version": 290_100 }),
+        );
+        assert_eq!(new.deviations().len(), 1);
+        assert!(new.deviations()[0].starts_with("Full RBF is disabled"));
+    }
+
+    #[test]
+    fn old_nodes_fall_back_to_getnetworkinfo_and_report_unknown() {
+        let p = PolicySummary::from_rpc(
+            &json!({ "size": 10 }),
+            &json!({ "version": 210_000, "relayfee": 0.000_01, "incrementalfee": 0.000_01 }),
+        );
+        assert_eq!(p.full_rbf, None);
+        assert_eq!(p.min_relay_fee, Some(0.000_01));
+        assert!(p.deviations().is_empty());
+        assert_eq!(
+            p.to_json()["settings"][0]["value"],
+            "unknown (pre-24.0 node)"
+        );
+        let bare = PolicySummary::from_rpc(&json!({}), &json!({}));
+        assert_eq!(bare.to_json()["settings"][2]["value"], "unknown");
+    }
+
+    #[test]
+    fn percentiles_pick_a_member() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[3.0], 50.0), Some(3.0));
+        assert_eq!(percentile(&[3.0], 90.0), Some(3.0));
+        let values = [5.0, 1.0, 4.0, 2.0, 3.0, 10.0, 6.0, 7.0, 8.0, 9.0];
+        assert_eq!(percentile(&values, 50.0), Some(5.0));
+        assert_eq!(percentile(&values, 90.0), Some(9.0));
+        assert_eq!(percentile(&values, 0.0), Some(1.0));
+        assert_eq!(percentile(&values, 100.0), Some(10.0));
+    }
+
+    #[test]
+    fn fee_floor_counts_outbound_peers_with_a_filter() {
+        let peers = [
+            json!({ "inbound": false, "minfeefilter": 0.000_01 }),
+            json!({ "inbound": false, "minfeefilter": 0.000_021 }),
+            json!({ "inbound": false, "minfeefilter": 0.000_05 }),
+            json!({ "inbound": false }),
+            json!({ "inbound": true, "minfeefilter": 0.01 }),
+            json!({ "minfeefilter": 0.01 }),
+        ];
+        let floor = PeerFeeFloor::from_peers(&peers).unwrap();
+        assert_eq!(floor.peers, 3);
+        assert_eq!(floor.median, 0.000_021);
+        assert_eq!(floor.p90, 0.000_05);
+        assert_eq!(
+            floor.to_json()["text"],
+            "~2.1 sat/vB median, 5 sat/vB p90 (3 outbound peers)"
+        );
+
+        // Only inbound peers, or none that sent a filter, give no floor.
+        assert_eq!(PeerFeeFloor::from_peers(&peers[3..]), None);
+        assert_eq!(peer_fee_floor_json(&json!({}))["floor"], json!(null));
+    }
+
+    #[test]
+    fn fee_floor_handles_one_peer_and_regtest_zeros() {
+        let one =
+            PeerFeeFloor::from_peers(&[json!({ "inbound": false, "minfeefilter": 0.000_01 })]);
+        assert_eq!(
+            one.unwrap().to_json()["text"],
+            "~1 sat/vB median, 1 sat/vB p90 (1 outbound peer)"
+        );
+        let zeros: Vec<_> = (0..4)
+            .map(|_| json!({ "inbound": false, "minfeefilter": 0.0 }))
+            .collect();
+        let floor = peer_fee_floor_json(&json!({ "peers": zeros }));
+        assert_eq!(floor["floor"]["median"], 0.0);
+        assert_eq!(
+            floor["floor"]["text"],
+            "~0 sat/vB median, 0 sat/vB p90 (4 outbound peers)"
+        );
+    }
+
+    #[test]
+    fn fees_print_in_sat_per_vbyte() {
+        assert_eq!(sat_per_vb(0.000_01), "1 sat/vB");
+        assert_eq!(sat_per_vb(0.000_001), "0.1 sat/vB");
+        assert_eq!(sat_per_vb(0.000_012_34), "1.234 sat/vB");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+/// Bytes per second over a sliding window. Samples are summed into one
+/// bucket per second, so a burst of thousands of messages costs one entry.
+pub struct RollingRate {
+    window_ms: u64,
+    started_ms: Option<u64>,
+    buckets: VecDeque<(u64, u64)>,
+}
+
+impl RollingRate {
+    pub fn new(window_ms: u64) -> RollingRate {
+        RollingRate {
+            window_ms,
+            started_ms: None,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, now_ms: u64, bytes: u64) {
+        self.started_ms.get_or_insert(now_ms);
+        let second = now_ms / 1000;
+        match self.buckets.back_mut() {
+            Some((s, total)) if *s >= second => *total += bytes,
+            _ => self.buckets.push_back((second, bytes)),
+        }
+        let cutoff = self.cutoff(now_ms);
+        while self.buckets.front().is_some_and(|(s, _)| *s < cutoff) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Average over the window, or over the time since the first sample
+    /// while that is shorter, so a new counter isn't diluted by time it
+    /// wasn't running. Falls to zero once the feed has been idle a window.
+    pub fn per_second(&self, now_ms: u64) -> f64 {
+        let Some(started_ms) = self.started_ms else {
+            return 0.0;
+        };
+        let cutoff = self.cutoff(now_ms);
+        let bytes: u64 = self
+            .buckets
+            .iter()
+            .filter(|(s, _)| *s >= cutoff)
+            .map(|(_, b)| b)
+            .sum();
+        let span_ms = now_ms
+            .saturating_sub(started_ms)
+            .clamp(1000, self.window_ms.max(1000));
+        bytes as f64 * 1000.0 / span_ms as f64
+    }
+
+    fn cutoff(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.window_ms) / 1000
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RollingRate;
+
+    #[test]
+    fn steady_feed_reports_its_rate() {
+        let mut rate = RollingRate::new(60_000);
+        for i in 0..120 {
+            rate.record(1_000_000 + i * 1000, 500);
+        }
+        let r = rate.per_second(1_000_000 + 119_000);
+        assert!((r - 500.0).abs() < 10.0, "{r}");
+    }
+
+    #[test]
+    fn new_counter_is_not_diluted() {
+        let mut rate = RollingRate::new(60_000);
+        rate.record(10_000, 2000);
+        rate.record(11_000, 2000);
+        assert_eq!(rate.per_second(12_000), 2000.0);
+        assert_eq!(RollingRate::new(60_000).per_second(12_000), 0.0);
+    }
+
+    #[test]
+    fn bursts_share_a_bucket() {
+        let mut rate = RollingRate::new(60_000);
+        for _ in 0..10_000 {
+            rate.record(5_000_250, 100);
+        }
+        assert_eq!(rate.buckets.len(), 1);
+        assert_eq!(rate.per_second(5_001_250), 1_000_000.0);
+    }
+
+    #[test]
+    fn idle_gaps_decay_to_zero() {
+        let mut rate = RollingRate::new(60_000);
+        for i in 0..60 {
+            rate.record(i * 1000, 1000);
+        }
+        let busy = rate.per_second(60_000);
+        let half = rate.per_second(90_000);
+        assert!(half < busy && half > 0.0, "{busy} {half}");
+        assert_eq!(rate.per_second(125_000), 0.0);
+        // A burst after the gap only counts itself.
+        rate.record(200_000, 6000);
+        assert_eq!(rate.per_second(200_000), 100.0);
+        assert!(rate.buckets.len() == 1);
+    }
+}
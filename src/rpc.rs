@@ -1,37 +1,358 @@
+use std::fmt;
 use std::net::{IpAddr, Ipv4Addr};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use tracing::{debug, warn};
 
+use crate::base64;
+use crate::exports;
+use crate::rpc_limiter::{RpcBudget, RpcPriority};
+
 pub const DEFAULT_ZMQ_BUFFER_LIMIT: usize = 5000;
 pub const MIN_ZMQ_BUFFER_LIMIT: usize = 50;
 pub const MAX_ZMQ_BUFFER_LIMIT: usize = 100000;
+pub const DEFAULT_LOG_TAIL_LINES: usize = 1000;
+pub const MIN_LOG_TAIL_LINES: usize = 100;
+pub const MAX_LOG_TAIL_LINES: usize = 20000;
+pub const MIN_RPC_TIMEOUT_SECS: u64 = 1;
+pub const MAX_RPC_TIMEOUT_SECS: u64 = 300;
+/// Deadline until the user picks one, so a node that accepts connections
+/// but never answers can't hold a worker forever.
+pub const DEFAULT_RPC_TIMEOUT_SECS: u64 = 30;
 
-pub struct RpcConfig {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuthMode {
+    UserPassword,
+    /// Bitcoin Core's `.cookie` file. User/password are kept but not sent.
+    CookieFile {
+        path: String,
+    },
+}
+
+impl AuthMode {
+    /// Reads the `auth_mode` and `cookie_path` keys of a config message.
+    /// Without `auth_mode`, a non-empty `cookie_path` selects the cookie, as
+    /// older saved configs only had the path.
+    fn from_config(msg: &serde_json::Value, current: &AuthMode) -> AuthMode {
+        let path = msg["cookie_path"]
+            .as_str()
+            .map(|p| p.trim().to_string())
+            .or_else(|| match current {
+                AuthMode::CookieFile { path } => Some(path.clone()),
+                AuthMode::UserPassword => None,
+            });
+        let cookie = match msg["auth_mode"].as_str() {
+            Some("cookie") => true,
+            Some(_) => false,
+            None if msg.get("cookie_path").is_some() => {
+                path.as_ref().is_some_and(|p| !p.is_empty())
+            }
+            None => return current.clone(),
+        };
+        if cookie {
+            AuthMode::CookieFile {
+                path: path.unwrap_or_default(),
+            }
+        } else {
+            AuthMode::UserPassword
+        }
+    }
+}
+
+/// How an `https` URL is secured. The mode must agree with the URL's
+/// scheme; it never widens which hosts are allowed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Plain `http`.
+    #[default]
+    None,
+    /// Verify the server against the built-in roots, or the pinned CA.
+    System,
+    /// Accept any certificate, e.g. a self-signed one. Only allowed with
+    /// `DANGER_INSECURE_RPC=1`.
+    InsecureSkipVerify,
+}
+
+impl TlsMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            TlsMode::None => "none",
+            TlsMode::System => "system",
+            TlsMode::InsecureSkipVerify => "insecure",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        [TlsMode::None, TlsMode::System, TlsMode::InsecureSkipVerify]
+            .into_iter()
+            .find(|mode| mode.name() == s)
+    }
+
+    /// The mode an unconfigured URL implies: verified TLS for `https`.
+    pub fn for_url(url: &str) -> Self {
+        if is_https(url) {
+            TlsMode::System
+        } else {
+            TlsMode::None
+        }
+    }
+
+    /// Reads the `tls_mode` key of a config message. Configs saved before
+    /// the selector existed keep the current mode while it still fits the
+    /// URL, and otherwise get the one the URL implies.
+    fn from_config(msg: &serde_json::Value, url: &str, current: TlsMode) -> TlsMode {
+        match msg["tls_mode"].as_str().and_then(TlsMode::parse) {
+            Some(mode) => mode,
+            None if (current != TlsMode::None) == is_https(url) => current,
+            None => TlsMode::for_url(url),
+        }
+    }
+}
+
+/// The fields that determine which node we talk to and how we authenticate.
+/// The HTTP client is rebuilt only when these change. The wallet is not part
+/// of it: switching wallets only changes the request path on the same node.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ConnectionConfig {
     pub url: String,
     pub user: String,
     pub password: String,
+    pub auth_mode: AuthMode,
+    /// PEM file of CA certificates trusted for `https` URLs instead of the
+    /// built-in roots, e.g. a reverse proxy's self-signed CA. Empty for none.
+    pub ca_cert_path: String,
+    pub tls_mode: TlsMode,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8332".into(),
+            user: String::new(),
+            password: String::new(),
+            auth_mode: AuthMode::UserPassword,
+            ca_cert_path: String::new(),
+            tls_mode: TlsMode::None,
+        }
+    }
+}
+
+pub struct RpcConfig {
+    pub connection: ConnectionConfig,
     pub wallet: String,
     pub zmq_address: String,
     pub zmq_buffer_limit: usize,
+    /// The node's debug.log, tailed when set.
+    pub debug_log_path: String,
+    pub log_tail_lines: usize,
+    /// Where exported files go; empty for the config directory.
+    pub exports_dir: String,
+    /// Deadline for a whole request, or `None` to wait as long as the OS does.
+    pub timeout_secs: Option<u64>,
+    client: Arc<RpcClient>,
 }
 
 impl Default for RpcConfig {
     fn default() -> Self {
         Self {
-            url: "http://127.0.0.1:8332".into(),
-            user: String::new(),
-            password: String::new(),
+            connection: ConnectionConfig::default(),
             wallet: String::new(),
             zmq_address: String::new(),
             zmq_buffer_limit: DEFAULT_ZMQ_BUFFER_LIMIT,
+            debug_log_path: String::new(),
+            log_tail_lines: DEFAULT_LOG_TAIL_LINES,
+            exports_dir: String::new(),
+            timeout_secs: Some(DEFAULT_RPC_TIMEOUT_SECS),
+            client: RpcClient::new(
+                0,
+                Some(DEFAULT_RPC_TIMEOUT_SECS),
+                &ConnectionConfig::default(),
+            ),
+        }
+    }
+}
+
+/// An HTTP agent with its own connection pool. Requests in flight keep the
+/// client they started with, so a rotation never interrupts them.
+pub struct RpcClient {
+    generation: u64,
+    timeout_secs: Option<u64>,
+    agent: ureq::Agent,
+    /// Credentials read from the cookie file, dropped again on a 401 because
+    /// the node writes a new cookie every time it starts.
+    cookie: Mutex<Option<(String, String)>>,
+    /// Why the configured CA file could not be used. Every request fails
+    /// with it rather than silently falling back to the built-in roots.
+    ca_error: Option<String>,
+}
+
+impl RpcClient {
+    fn new(generation: u64, timeout_secs: Option<u64>, connection: &ConnectionConfig) -> Arc<Self> {
+        let mut tls = ureq::tls::TlsConfig::builder();
+        let mut ca_error = None;
+        let ca_cert_path = &connection.ca_cert_path;
+        if connection.tls_mode == TlsMode::InsecureSkipVerify {
+            warn!("rpc certificate verification disabled");
+            tls = tls.disable_verification(true);
+        } else if !ca_cert_path.is_empty() {
+            match load_ca_certs(ca_cert_path) {
+                Ok(certs) => tls = tls.root_certs(ureq::tls::RootCerts::from(certs)),
+                Err(e) => ca_error = Some(format!("CA certificate {ca_cert_path}: {e}")),
+            }
+        }
+        Arc::new(Self {
+            generation,
+            timeout_secs,
+            cookie: Mutex::new(None),
+            ca_error,
+            agent: ureq::Agent::config_builder()
+                .http_status_as_error(false)
+                .timeout_global(timeout_secs.map(Duration::from_secs))
+                .tls_config(tls.build())
+                .build()
+                .new_agent(),
+        })
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn post_json(
+        &self,
+        url: &str,
+        connection: &ConnectionConfig,
+        payload: &str,
+    ) -> Result<(u16, String), RpcError> {
+        let (status, body) = self.post_json_once(url, connection, payload)?;
+        if status != 401 || connection.auth_mode == AuthMode::UserPassword {
+            return Ok((status, body));
+        }
+        // The cookie we sent may be from before a node restart.
+        debug!("rpc auth rejected; re-reading cookie file");
+        *self.cookie.lock().unwrap() = None;
+        self.post_json_once(url, connection, payload)
+    }
+
+    fn post_json_once(
+        &self,
+        url: &str,
+        connection: &ConnectionConfig,
+        payload: &str,
+    ) -> Result<(u16, String), RpcError> {
+        if let Some(e) = &self.ca_error {
+            return Err(RpcError::Transport(e.clone()));
+        }
+        let (user, password) = self.credentials(connection)?;
+        let tls = url.starts_with("https://");
+        let mut resp = self
+            .agent
+            .post(url)
+            .header("Authorization", &basic_auth(&user, &password))
+            .content_type("application/json")
+            .send(payload.as_bytes())
+            .map_err(|e| classify_transport_error(e, tls))?;
+        let status = resp.status().as_u16();
+        let body = resp.body_mut().read_to_string().unwrap_or_default();
+        if work_queue_full(status, &body) {
+            return Err(RpcError::NodeBusy);
+        }
+        Ok((status, body))
+    }
+
+    fn credentials(&self, connection: &ConnectionConfig) -> Result<(String, String), RpcError> {
+        let AuthMode::CookieFile { path } = &connection.auth_mode else {
+            return Ok((connection.user.clone(), connection.password.clone()));
+        };
+        let mut cached = self.cookie.lock().unwrap();
+        if let Some(credentials) = cached.as_ref() {
+            return Ok(credentials.clone());
+        }
+        let credentials = read_cookie(path)?;
+        *cached = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+impl RpcConfig {
+    pub fn client(&self) -> Arc<RpcClient> {
+        Arc::clone(&self.client)
+    }
+
+    /// Sets the request deadline, clamped to 1–300 s, and rebuilds the client
+    /// if it changed. Requests already in flight keep their old deadline.
+    pub fn set_timeout_secs(&mut self, timeout_secs: Option<u64>) {
+        let timeout_secs =
+            timeout_secs.map(|secs| secs.clamp(MIN_RPC_TIMEOUT_SECS, MAX_RPC_TIMEOUT_SECS));
+        self.timeout_secs = timeout_secs;
+        if self.client.timeout_secs != timeout_secs {
+            self.rotate_client();
+        }
+    }
+
+    /// Replaces the connection, rebuilding the client only if it changed.
+    pub fn set_connection(&mut self, connection: ConnectionConfig) {
+        if connection != self.connection {
+            self.connection = connection;
+            self.rotate_client();
+        }
+    }
+
+    fn rotate_client(&mut self) {
+        let generation = self.client.generation() + 1;
+        debug!(generation, timeout_secs = ?self.timeout_secs, "rotating rpc client");
+        self.client = RpcClient::new(generation, self.timeout_secs, &self.connection);
+    }
+}
+
+#[derive(Debug)]
+pub enum RpcError {
+    /// The node could not be reached or dropped the connection.
+    Connection(String),
+    /// Any other transport failure, e.g. an unresolvable host.
+    Transport(String),
+    Http {
+        status: u16,
+        body: String,
+    },
+    Rpc {
+        code: i64,
+        message: String,
+    },
+    InvalidResponse(String),
+    /// The cookie file is missing, unreadable or malformed.
+    Cookie(String),
+    /// Core's RPC work queue is full; the request was never run.
+    NodeBusy,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Connection(e) => write!(f, "could not connect to node: {e}"),
+            RpcError::Transport(e) => write!(f, "transport error: {e}"),
+            RpcError::Http { status, body } if body.is_empty() => write!(f, "HTTP {status}"),
+            RpcError::Http { status, body } => write!(f, "HTTP {status}: {body}"),
+            RpcError::Rpc { code, message } => write!(f, "{message} (code {code})"),
+            RpcError::InvalidResponse(e) => write!(f, "invalid response: {e}"),
+            RpcError::Cookie(e) => write!(f, "cookie file: {e}"),
+            RpcError::NodeBusy => write!(
+                f,
+                "node is busy (RPC work queue full); retry in a few seconds, or raise -rpcworkqueue"
+            ),
         }
     }
 }
 
 pub struct ConfigUpdateResult {
     pub zmq_changed: bool,
-    pub insecure_blocked: bool,
+    /// Why the update was rejected; nothing was applied when non-empty.
+    pub errors: Vec<String>,
+    /// The ZMQ settings as of this update, read under the same lock.
+    pub zmq_address: String,
+    pub zmq_buffer_limit: usize,
 }
 
 pub fn allow_insecure() -> bool {
@@ -43,116 +364,651 @@ pub fn allow_insecure() -> bool {
     })
 }
 
-pub fn do_rpc(body: &str, config: &Arc<Mutex<RpcConfig>>) -> String {
+/// The `id` and optional `client_tag` echoed on every reply to a webview
+/// request, so the frontend can match replies to concurrent calls.
+struct ReplyIds {
+    id: serde_json::Value,
+    client_tag: Option<serde_json::Value>,
+}
+
+impl ReplyIds {
+    /// Keeps the caller's id, or assigns a fresh one when it is missing.
+    fn from_request(msg: &serde_json::Value) -> Self {
+        let id = match &msg["id"] {
+            serde_json::Value::Null => fallback_id(),
+            id => id.clone(),
+        };
+        let client_tag = Some(&msg["client_tag"]).filter(|t| !t.is_null()).cloned();
+        Self { id, client_tag }
+    }
+
+    fn unparsed() -> Self {
+        Self {
+            id: fallback_id(),
+            client_tag: None,
+        }
+    }
+
+    fn stamp(&self, mut reply: serde_json::Value) -> String {
+        reply["id"] = self.id.clone();
+        if let Some(tag) = &self.client_tag {
+            reply["client_tag"] = tag.clone();
+        }
+        reply.to_string()
+    }
+
+    fn error(&self, message: String) -> String {
+        self.stamp(serde_json::json!({ "error": message }))
+    }
+
+    /// Flags a busy node so the dashboard can back off instead of alarming.
+    fn rpc_error(&self, err: &RpcError) -> String {
+        match err {
+            RpcError::NodeBusy => self.stamp(serde_json::json!({
+                "error": err.to_string(),
+                "node_busy": true,
+            })),
+            _ => self.error(err.to_string()),
+        }
+    }
+}
+
+/// A process-unique id for requests that did not bring one. It is a string
+/// so it can never collide with the numeric ids the frontend hands out.
+fn fallback_id() -> serde_json::Value {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    format!("rpc-web-{}", NEXT.fetch_add(1, Ordering::Relaxed)).into()
+}
+
+pub fn do_rpc(body: &str, config: &Arc<Mutex<RpcConfig>>, budget: &Arc<RpcBudget>) -> String {
     debug!(bytes = body.len(), "rpc request received");
     let msg: serde_json::Value = match serde_json::from_str(body) {
         Ok(v) => v,
         Err(e) => {
             warn!(error = %e, "rpc request JSON parse failed");
-            return json_error(e.to_string());
+            return ReplyIds::unparsed().error(e.to_string());
         }
     };
+    let ids = ReplyIds::from_request(&msg);
 
-    let method = msg["method"].as_str().unwrap_or("");
-    let params = &msg["params"];
+    let priority = RpcPriority::from_request(&msg["priority"]);
 
     let cfg = config.lock().unwrap();
-    let mut url = cfg.url.clone();
-    let user = cfg.user.clone();
-    let password = cfg.password.clone();
-    let wallet = cfg.wallet.clone();
+    let connection = cfg.connection.clone();
+    let client = cfg.client();
+    let mut url = connection.url.clone();
+    // An explicit "wallet" in the request overrides the configured one; an
+    // empty string targets the node endpoint (needed for e.g. unloadwallet).
+    let wallet = msg["wallet"]
+        .as_str()
+        .map_or_else(|| cfg.wallet.clone(), str::to_string);
     drop(cfg);
 
     if !wallet.is_empty() {
         url = format!("{url}/wallet/{wallet}");
     }
 
+    // {"batch": [{method, params}, ...]} sends one JSON-RPC batch and answers
+    // with an array of {result, error} aligned to the request order.
+    if let Some(calls) = msg["batch"].as_array() {
+        let payload = batch_envelope(calls).to_string();
+        let _slot = budget.acquire(priority);
+        debug!(calls = calls.len(), url = %url, ?priority, "rpc batch POST");
+        let outcome = client.post_json(&url, &connection, &payload);
+        note_busy(budget, &outcome);
+        return match outcome {
+            Ok((status, out)) => {
+                debug!(status, bytes = out.len(), "rpc batch response");
+                match parse_batch_response(calls.len(), status, &out) {
+                    Ok(results) => {
+                        serde_json::Value::Array(results.iter().map(batch_element_json).collect())
+                            .to_string()
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "rpc batch failed");
+                        ids.error(e.to_string())
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(error = %e, "rpc transport error");
+                ids.rpc_error(&e)
+            }
+        };
+    }
+
+    let method = msg["method"].as_str().unwrap_or("");
     let envelope = serde_json::json!({
         "jsonrpc": "2.0",
-        "id": 1,
+        "id": &ids.id,
         "method": method,
-        "params": params,
+        "params": &msg["params"],
     });
 
     let payload = envelope.to_string();
-    debug!(method, url = %url, "rpc POST");
-    match rpc_agent()
-        .post(&url)
-        .header("Authorization", &basic_auth(&user, &password))
-        .content_type("application/json")
-        .send(payload.as_bytes())
-    {
-        Ok(mut resp) => {
-            let status = resp.status();
-            let out = resp.body_mut().read_to_string().unwrap_or_default();
-            debug!(method, status = %status, bytes = out.len(), "rpc response");
-            out
+    let _slot = budget.acquire(priority);
+    debug!(method, url = %url, ?priority, "rpc POST");
+    let outcome = client.post_json(&url, &connection, &payload);
+    note_busy(budget, &outcome);
+    match &outcome {
+        Ok((status, out)) => debug!(method, status, bytes = out.len(), "rpc response"),
+        Err(e) => warn!(method, error = %e, "rpc transport error"),
+    }
+    call_reply(&ids, outcome)
+}
+
+/// The webview parses whatever comes back as JSON, so node replies pass
+/// through with the request's ids stamped on and anything else is described.
+fn call_reply(ids: &ReplyIds, outcome: Result<(u16, String), RpcError>) -> String {
+    match outcome {
+        Ok((status, out)) => match serde_json::from_str::<serde_json::Value>(&out) {
+            Ok(reply) if reply.is_object() => ids.stamp(reply),
+            _ => ids.error(non_json_error(status, &out).to_string()),
+        },
+        Err(e) => ids.rpc_error(&e),
+    }
+}
+
+/// Core's plain-text reply when its RPC work queue is full. Older releases
+/// send it with 500, newer ones with 503.
+const WORK_QUEUE_EXCEEDED: &str = "Work queue depth exceeded";
+
+fn work_queue_full(status: u16, body: &str) -> bool {
+    matches!(status, 500 | 503) && body.trim() == WORK_QUEUE_EXCEEDED
+}
+
+/// Any reply from the node counts as not busy; transport failures say
+/// nothing either way.
+fn note_busy(budget: &RpcBudget, outcome: &Result<(u16, String), RpcError>) {
+    match outcome {
+        Ok(_) => budget.record_busy(false),
+        Err(RpcError::NodeBusy) => budget.record_busy(true),
+        Err(_) => {}
+    }
+}
+
+fn batch_envelope(calls: &[serde_json::Value]) -> serde_json::Value {
+    calls
+        .iter()
+        .enumerate()
+        .map(|(id, call)| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": call["method"].as_str().unwrap_or(""),
+                "params": &call["params"],
+            })
+        })
+        .collect()
+}
+
+/// Matches batch replies back to request positions. Elements are placed by
+/// id; ones with a null or unusable id (Core answers an element it could not
+/// parse that way) fall back to their position in the reply. A single error
+/// object instead of an array means the whole batch was rejected.
+fn parse_batch_response(
+    len: usize,
+    status: u16,
+    body: &str,
+) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+    let Ok(v) = serde_json::from_str::<serde_json::Value>(body) else {
+        return Err(non_json_error(status, body));
+    };
+    let elements = match v {
+        serde_json::Value::Array(elements) => elements,
+        ref obj if !obj["error"].is_null() => {
+            let (code, message) = rpc_error_parts(&obj["error"]);
+            return Err(RpcError::Rpc {
+                code,
+                message: format!("batch rejected: {message}"),
+            });
         }
-        Err(e) => {
-            warn!(method, error = %e, "rpc transport error");
-            json_error(e.to_string())
+        _ if status != 200 => {
+            return Err(RpcError::Http {
+                status,
+                body: body.trim().to_string(),
+            });
+        }
+        _ => {
+            return Err(RpcError::InvalidResponse(
+                "expected an array in reply to a batch request".into(),
+            ));
+        }
+    };
+
+    let mut slots: Vec<Option<Result<serde_json::Value, RpcError>>> =
+        std::iter::repeat_with(|| None).take(len).collect();
+    let mut unplaced = Vec::new();
+    for (pos, element) in elements.into_iter().enumerate() {
+        let id = element["id"]
+            .as_u64()
+            .and_then(|id| usize::try_from(id).ok());
+        match id {
+            Some(id) if id < len && slots[id].is_none() => {
+                slots[id] = Some(batch_element_result(element, None));
+            }
+            _ => unplaced.push((pos, element)),
+        }
+    }
+    for (pos, element) in unplaced {
+        let Some(index) = (pos < len && slots[pos].is_none())
+            .then_some(pos)
+            .or_else(|| slots.iter().position(Option::is_none))
+        else {
+            warn!(pos, "dropping unmatched batch reply element");
+            continue;
+        };
+        slots[index] = Some(batch_element_result(element, Some(index)));
+    }
+
+    Ok(slots
+        .into_iter()
+        .enumerate()
+        .map(|(i, slot)| {
+            slot.unwrap_or_else(|| {
+                Err(RpcError::InvalidResponse(format!(
+                    "no reply for batch element {i}"
+                )))
+            })
+        })
+        .collect())
+}
+
+fn batch_element_result(
+    mut element: serde_json::Value,
+    guessed_index: Option<usize>,
+) -> Result<serde_json::Value, RpcError> {
+    if element["error"].is_null() {
+        return Ok(element["result"].take());
+    }
+    let (code, message) = rpc_error_parts(&element["error"]);
+    let message = match guessed_index {
+        Some(i) => format!("batch element {i}: {message}"),
+        None => message,
+    };
+    Err(RpcError::Rpc { code, message })
+}
+
+fn batch_element_json(result: &Result<serde_json::Value, RpcError>) -> serde_json::Value {
+    match result {
+        Ok(value) => serde_json::json!({ "result": value, "error": null }),
+        Err(RpcError::Rpc { code, message }) => {
+            serde_json::json!({ "result": null, "error": { "code": code, "message": message } })
         }
+        Err(e) => serde_json::json!({ "result": null, "error": { "message": e.to_string() } }),
     }
 }
 
-fn json_error(message: String) -> String {
-    serde_json::json!({ "error": message }).to_string()
+fn rpc_error_parts(err: &serde_json::Value) -> (i64, String) {
+    let message = match err {
+        serde_json::Value::String(s) => s.clone(),
+        _ => err["message"]
+            .as_str()
+            .unwrap_or("unknown error")
+            .to_string(),
+    };
+    (err["code"].as_i64().unwrap_or(0), message)
+}
+
+/// Describes a reply that is not JSON at all, typically an auth failure with
+/// an empty body or an HTML page from a proxy or web server.
+fn non_json_error(status: u16, body: &str) -> RpcError {
+    let trimmed = body.trim();
+    let head = trimmed
+        .chars()
+        .take(256)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") || head.contains("<body") {
+        return RpcError::InvalidResponse(format!(
+            "HTTP {status} returned an HTML page; the URL may point at a proxy or web server rather than bitcoind"
+        ));
+    }
+    match status {
+        200 => RpcError::InvalidResponse(trimmed.chars().take(200).collect()),
+        401 if trimmed.is_empty() => RpcError::Http {
+            status,
+            body: "authentication failed".into(),
+        },
+        _ => RpcError::Http {
+            status,
+            body: trimmed.chars().take(200).collect(),
+        },
+    }
 }
 
-fn rpc_agent() -> &'static ureq::Agent {
-    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
-    AGENT.get_or_init(|| {
-        ureq::Agent::config_builder()
-            .http_status_as_error(false)
-            .build()
-            .new_agent()
+/// Performs a single call outside the webview and returns the decoded result.
+pub fn call(
+    config: &RpcConfig,
+    method: &str,
+    params: &serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let mut url = config.connection.url.clone();
+    if !config.wallet.is_empty() {
+        url = format!("{url}/wallet/{}", config.wallet);
+    }
+    let payload = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
     })
+    .to_string();
+    let (status, body) = config
+        .client
+        .post_json(&url, &config.connection, &payload)?;
+    parse_call_response(status, &body)
 }
 
-pub fn update_config(body: &str, config: &Arc<Mutex<RpcConfig>>) -> ConfigUpdateResult {
-    let msg: serde_json::Value = match serde_json::from_str(body) {
-        Ok(v) => v,
-        Err(_) => {
-            return ConfigUpdateResult {
-                zmq_changed: false,
-                insecure_blocked: false,
-            };
+/// Sends a batch to the node endpoint (never a wallet) for work the backend
+/// aggregates itself. Holds one background budget slot for the round trip.
+pub fn node_batch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    calls: &[serde_json::Value],
+) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+    background_batch(config, budget, calls, false)
+}
+
+/// Like `node_batch`, but sent to the configured wallet when there is one,
+/// as the webview's own calls are. Wallet endpoints serve node RPCs too.
+pub fn wallet_batch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    calls: &[serde_json::Value],
+) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+    background_batch(config, budget, calls, true)
+}
+
+fn background_batch(
+    config: &Arc<Mutex<RpcConfig>>,
+    budget: &Arc<RpcBudget>,
+    calls: &[serde_json::Value],
+    use_wallet: bool,
+) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+    let cfg = config.lock().unwrap();
+    let connection = cfg.connection.clone();
+    let client = cfg.client();
+    let mut url = connection.url.clone();
+    if use_wallet && !cfg.wallet.is_empty() {
+        url = format!("{url}/wallet/{}", cfg.wallet);
+    }
+    drop(cfg);
+
+    let payload = batch_envelope(calls).to_string();
+    let _slot = budget.acquire(RpcPriority::Background);
+    let outcome = client.post_json(&url, &connection, &payload);
+    note_busy(budget, &outcome);
+    let (status, body) = outcome?;
+    parse_batch_response(calls.len(), status, &body)
+}
+
+/// `tls` marks an `https` request, whose handshake failures would otherwise
+/// read like generic I/O errors.
+fn classify_transport_error(err: ureq::Error, tls: bool) -> RpcError {
+    use std::io::ErrorKind;
+    match err {
+        // rustls reports a rejected certificate, or a plain-HTTP server
+        // behind an https URL, as InvalidData.
+        ureq::Error::Io(ref e) if tls && e.kind() == ErrorKind::InvalidData => {
+            RpcError::Transport(format!("TLS handshake failed: {e}"))
         }
+        ureq::Error::Rustls(e) => RpcError::Transport(format!("TLS handshake failed: {e}")),
+        ureq::Error::Tls(e) => RpcError::Transport(format!("TLS error: {e}")),
+        ureq::Error::Io(ref e)
+            if matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+            ) =>
+        {
+            RpcError::Connection(err.to_string())
+        }
+        ureq::Error::ConnectionFailed => RpcError::Connection(err.to_string()),
+        // A hung node: the configured deadline fired, not a refused connection.
+        ureq::Error::Timeout(timeout) => {
+            RpcError::Transport(format!("request timed out ({timeout})"))
+        }
+        other => RpcError::Transport(other.to_string()),
+    }
+}
+
+fn parse_call_response(status: u16, body: &str) -> Result<serde_json::Value, RpcError> {
+    let Ok(mut v) = serde_json::from_str::<serde_json::Value>(body) else {
+        return Err(non_json_error(status, body));
     };
+    let err = &v["error"];
+    if !err.is_null() {
+        let (code, message) = rpc_error_parts(err);
+        return Err(RpcError::Rpc { code, message });
+    }
+    if status != 200 {
+        return Err(RpcError::Http {
+            status,
+            body: body.trim().to_string(),
+        });
+    }
+    Ok(v["result"].take())
+}
 
+pub fn update_config(body: &str, config: &Arc<Mutex<RpcConfig>>) -> ConfigUpdateResult {
     let mut cfg = config.lock().unwrap();
-    let mut insecure_blocked = false;
+    let Ok(msg) = serde_json::from_str::<serde_json::Value>(body) else {
+        return ConfigUpdateResult {
+            zmq_changed: false,
+            errors: vec!["config update is not valid JSON".into()],
+            zmq_address: cfg.zmq_address.clone(),
+            zmq_buffer_limit: cfg.zmq_buffer_limit,
+        };
+    };
+    let mut connection = cfg.connection.clone();
+    if let Some(path) = msg["ca_cert_path"].as_str() {
+        connection.ca_cert_path = path.trim().into();
+    }
+    let ca_pinned = !connection.ca_cert_path.is_empty();
+    if let Err(errors) = validate_config_update(&msg, ca_pinned) {
+        warn!(?errors, "config update rejected");
+        return ConfigUpdateResult {
+            zmq_changed: false,
+            errors,
+            zmq_address: cfg.zmq_address.clone(),
+            zmq_buffer_limit: cfg.zmq_buffer_limit,
+        };
+    }
     if let Some(url) = msg["url"].as_str() {
-        if is_safe_rpc_host(url) || allow_insecure() {
-            cfg.url = url.into();
-        } else {
-            warn!(url, "blocked non-local RPC URL");
-            insecure_blocked = true;
-        }
+        connection.url = url.into();
     }
     if let Some(user) = msg["user"].as_str() {
-        cfg.user = user.into();
+        connection.user = user.into();
     }
     if let Some(password) = msg["password"].as_str() {
-        cfg.password = password.into();
+        connection.password = password.into();
+    }
+    connection.auth_mode = AuthMode::from_config(&msg, &connection.auth_mode);
+    connection.tls_mode = TlsMode::from_config(&msg, &connection.url, connection.tls_mode);
+    // The UI pushes its whole config on every change; keep the warm
+    // connection pool unless the node or credentials actually changed.
+    cfg.set_connection(connection);
+    // null clears the deadline; omitting the key leaves it alone.
+    if let Some(value) = msg.get("timeout_secs") {
+        cfg.set_timeout_secs(parse_usize(value).map(|n| n as u64));
     }
     if let Some(wallet) = msg["wallet"].as_str() {
         cfg.wallet = wallet.into();
     }
     let mut zmq_changed = false;
     if let Some(addr) = msg["zmq_address"].as_str()
-        && cfg.zmq_address != addr {
-            cfg.zmq_address = addr.into();
-            zmq_changed = true;
-        }
+        && cfg.zmq_address != addr
+    {
+        cfg.zmq_address = addr.into();
+        zmq_changed = true;
+    }
     if let Some(limit) = parse_usize(&msg["zmq_buffer_limit"]) {
         cfg.zmq_buffer_limit = limit.clamp(MIN_ZMQ_BUFFER_LIMIT, MAX_ZMQ_BUFFER_LIMIT);
     }
+    if let Some(path) = msg["debug_log_path"].as_str() {
+        cfg.debug_log_path = path.trim().into();
+    }
+    if let Some(lines) = parse_usize(&msg["log_tail_lines"]) {
+        cfg.log_tail_lines = lines.clamp(MIN_LOG_TAIL_LINES, MAX_LOG_TAIL_LINES);
+    }
+    if let Some(dir) = msg["exports_dir"].as_str() {
+        cfg.exports_dir = dir.trim().into();
+    }
 
     ConfigUpdateResult {
         zmq_changed,
-        insecure_blocked,
+        errors: Vec::new(),
+        zmq_address: cfg.zmq_address.clone(),
+        zmq_buffer_limit: cfg.zmq_buffer_limit,
+    }
+}
+
+/// Checks a config update from the UI before any of it is applied. Every
+/// problem is reported, not just the first, so the UI can list them all.
+/// `ca_pinned` is whether the update leaves a CA certificate configured.
+pub fn validate_config_update(msg: &serde_json::Value, ca_pinned: bool) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let tls_mode = match msg["tls_mode"].as_str() {
+        Some(name) => {
+            let mode = TlsMode::parse(name);
+            if mode.is_none() {
+                errors.push(format!("Unknown TLS mode {name:?}"));
+            }
+            mode
+        }
+        None => None,
+    };
+    if tls_mode == Some(TlsMode::InsecureSkipVerify) && !allow_insecure() {
+        errors.push("Skipping certificate verification requires DANGER_INSECURE_RPC=1".into());
+    }
+    // A pinned CA only vouches for the host while certificates are checked.
+    let ca_pinned = ca_pinned && tls_mode != Some(TlsMode::InsecureSkipVerify);
+    if let Some(url) = msg["url"].as_str() {
+        match (tls_mode, is_https(url)) {
+            (Some(TlsMode::None), true) => {
+                errors.push("An https RPC URL needs a TLS mode other than None".into())
+            }
+            (Some(TlsMode::System | TlsMode::InsecureSkipVerify), false) => {
+                errors.push("TLS needs an https:// RPC URL".into())
+            }
+            _ => {}
+        }
+
+        match url.split_once("://") {
+            Some((scheme, rest))
+                if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") =>
+            {
+                if let Err(e) = explicit_port(rest) {
+                    errors.push(format!("RPC URL {e}"));
+                } else if !is_allowed_rpc_url(url, ca_pinned) {
+                    errors.push(
+                        "Non-local RPC address blocked. Use an https URL with a TLS CA certificate, or set DANGER_INSECURE_RPC=1 to override."
+                            .into(),
+                    );
+                }
+            }
+            Some((scheme, _)) => {
+                errors.push(format!(
+                    "RPC URL scheme must be http or https, not {scheme:?}"
+                ));
+            }
+            None => errors.push("RPC URL must start with http:// or https://".into()),
+        }
+    }
+    if let Some(interval) = msg.get("pollInterval")
+        && parse_usize(interval).is_none_or(|secs| secs < 1)
+    {
+        errors.push("Poll interval must be at least 1 second".into());
+    }
+    // Anything but whole seconds would otherwise read as "no deadline".
+    if let Some(timeout) = msg.get("timeout_secs")
+        && !timeout.is_null()
+        && parse_usize(timeout).is_none()
+    {
+        errors.push("Timeout must be whole seconds; leave it empty for no deadline".into());
+    }
+    let zmq = msg["zmq_address"].as_str().unwrap_or("");
+    if !zmq.is_empty() {
+        match zmq.strip_prefix("tcp://").map(explicit_port) {
+            None => errors.push("ZMQ address must start with tcp://".into()),
+            Some(Ok(Some(_))) => {}
+            Some(Ok(None)) => {
+                errors.push("ZMQ address needs a port, e.g. tcp://127.0.0.1:28332".into())
+            }
+            Some(Err(e)) => errors.push(format!("ZMQ address {e}")),
+        }
+    }
+    if let Some(dir) = msg["exports_dir"].as_str()
+        && let Err(e) = exports::resolve_dir(dir)
+    {
+        errors.push(e);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The port of the authority at the start of `rest` (whatever follows
+/// `scheme://`), or `None` when there is no explicit port.
+fn explicit_port(rest: &str) -> Result<Option<u16>, String> {
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host_port = authority.rsplit('@').next().unwrap_or(authority);
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(v6) => {
+            let (host, after) = v6
+                .split_once(']')
+                .ok_or("has an unterminated IPv6 address")?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+    if host.is_empty() {
+        return Err("has no host".into());
+    }
+    let Some(port) = port else {
+        return Ok(None);
+    };
+    match port.parse::<u32>() {
+        Ok(n) if (1..=65_535).contains(&n) => Ok(Some(n as u16)),
+        Ok(n) => Err(format!("port {n} is outside 1-65535")),
+        Err(_) => Err(format!("port {port:?} is not a number")),
+    }
+}
+
+/// Local and private hosts are always allowed. A remote host is allowed
+/// over `https` when a CA is pinned, as the traffic is then encrypted to a
+/// server the user vouched for.
+pub fn is_allowed_rpc_url(url: &str, ca_pinned: bool) -> bool {
+    is_safe_rpc_host(url) || (ca_pinned && is_https(url)) || allow_insecure()
+}
+
+fn is_https(url: &str) -> bool {
+    url.get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+/// The certificates in a PEM file. Private keys and other blocks are skipped.
+fn load_ca_certs(path: &str) -> Result<Vec<ureq::tls::Certificate<'static>>, String> {
+    let pem = std::fs::read(expand_home(path)).map_err(|e| e.to_string())?;
+    let mut certs = Vec::new();
+    for item in ureq::tls::parse_pem(&pem) {
+        if let ureq::tls::PemItem::Certificate(cert) = item.map_err(|e| e.to_string())? {
+            certs.push(cert);
+        }
+    }
+    if certs.is_empty() {
+        return Err("no PEM certificates found".into());
     }
+    Ok(certs)
 }
 
 fn is_safe_rpc_host(url: &str) -> bool {
@@ -206,35 +1062,39 @@ fn is_cgnat(v4: Ipv4Addr) -> bool {
     octets[0] == 100 && (64..=127).contains(&octets[1])
 }
 
+/// Expands a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> std::path::PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => match std::env::var_os("HOME") {
+            Some(home) => std::path::Path::new(&home).join(rest),
+            None => std::path::PathBuf::from(path),
+        },
+        None => std::path::PathBuf::from(path),
+    }
+}
+
+/// Reads `__cookie__:<hex>` (or any `user:password`) from a cookie file.
+fn read_cookie(path: &str) -> Result<(String, String), RpcError> {
+    let path = expand_home(path);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| RpcError::Cookie(format!("{}: {e}", path.display())))?;
+    parse_cookie(&contents)
+        .ok_or_else(|| RpcError::Cookie(format!("{}: expected user:password", path.display())))
+}
+
+fn parse_cookie(contents: &str) -> Option<(String, String)> {
+    let (user, password) = contents.trim().split_once(':')?;
+    if user.is_empty() || password.is_empty() {
+        return None;
+    }
+    Some((user.to_string(), password.to_string()))
+}
+
 fn basic_auth(user: &str, password: &str) -> String {
     use std::io::Write;
     let mut buf = Vec::new();
     write!(buf, "{user}:{password}").unwrap();
-    format!("Basic {}", base64_encode(&buf))
-}
-
-fn base64_encode(data: &[u8]) -> String {
-    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut out = String::new();
-    for chunk in data.chunks(3) {
-        let b0 = chunk[0] as u32;
-        let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
-        let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
-        let triple = (b0 << 16) | (b1 << 8) | b2;
-        out.push(CHARS[(triple >> 18 & 0x3F) as usize] as char);
-        out.push(CHARS[(triple >> 12 & 0x3F) as usize] as char);
-        if chunk.len() > 1 {
-            out.push(CHARS[(triple >> 6 & 0x3F) as usize] as char);
-        } else {
-            out.push('=');
-        }
-        if chunk.len() > 2 {
-            out.push(CHARS[(triple & 0x3F) as usize] as char);
-        } else {
-            out.push('=');
-        }
-    }
-    out
+    format!("Basic {}", base64::encode_standard(&buf))
 }
 
 fn parse_usize(value: &serde_json::Value) -> Option<usize> {
@@ -247,9 +1107,14 @@ fn parse_usize(value: &serde_json::Value) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::{
-        MAX_ZMQ_BUFFER_LIMIT, MIN_ZMQ_BUFFER_LIMIT, RpcConfig, is_safe_rpc_host, json_error,
-        update_config,
+        AuthMode, MAX_LOG_TAIL_LINES, MAX_RPC_TIMEOUT_SECS, MAX_ZMQ_BUFFER_LIMIT,
+        MIN_LOG_TAIL_LINES, MIN_RPC_TIMEOUT_SECS, MIN_ZMQ_BUFFER_LIMIT, ReplyIds, RpcConfig,
+        RpcError, TlsMode, allow_insecure, basic_auth, batch_envelope, call, call_reply, do_rpc,
+        explicit_port, is_allowed_rpc_url, is_safe_rpc_host, load_ca_certs, node_batch,
+        parse_batch_response, parse_call_response, parse_cookie, read_cookie, update_config,
+        validate_config_update, work_queue_full,
     };
+    use crate::rpc_limiter::RpcBudget;
     use std::sync::{Arc, Mutex};
 
     #[test]
@@ -278,6 +1143,53 @@ mod tests {
         assert!(!is_safe_rpc_host("not-a-url"));
     }
 
+    #[test]
+    fn remote_https_hosts_need_a_pinned_ca() {
+        assert!(is_allowed_rpc_url("https://node.example.com/", true));
+        assert!(is_allowed_rpc_url("HTTPS://8.8.8.8:8332", true));
+        assert!(is_allowed_rpc_url("http://127.0.0.1:8332", false));
+        if !allow_insecure() {
+            assert!(!is_allowed_rpc_url("https://node.example.com/", false));
+            assert!(!is_allowed_rpc_url("http://node.example.com/", true));
+        }
+
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let result = update_config(
+            r#"{"url":"https://node.example.com","ca_cert_path":" /tmp/ca.pem "}"#,
+            &cfg,
+        );
+        assert!(result.errors.is_empty());
+        let connection = cfg.lock().unwrap().connection.clone();
+        assert_eq!(connection.url, "https://node.example.com");
+        assert_eq!(connection.ca_cert_path, "/tmp/ca.pem");
+    }
+
+    #[test]
+    fn ca_files_are_read_and_failures_are_reported() {
+        let dir = std::env::temp_dir().join(format!("rpc-ca-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pem = dir.join("ca.pem");
+        std::fs::write(
+            &pem,
+            "junk\n-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        assert_eq!(load_ca_certs(pem.to_str().unwrap()).unwrap().len(), 1);
+        let empty = dir.join("empty.pem");
+        std::fs::write(&empty, "not a certificate").unwrap();
+        assert!(load_ca_certs(empty.to_str().unwrap()).is_err());
+
+        let mut config = RpcConfig::default();
+        let mut connection = config.connection.clone();
+        connection.ca_cert_path = dir.join("missing.pem").to_str().unwrap().into();
+        config.set_connection(connection);
+        match call(&config, "getblockcount", &serde_json::json!([])) {
+            Err(RpcError::Transport(e)) => assert!(e.contains("CA certificate"), "{e}"),
+            other => panic!("expected a transport error, got {other:?}"),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn zmq_buffer_limit_is_clamped_to_safe_bounds() {
         let cfg = Arc::new(Mutex::new(RpcConfig::default()));
@@ -289,10 +1201,661 @@ mod tests {
         assert_eq!(cfg.lock().unwrap().zmq_buffer_limit, MAX_ZMQ_BUFFER_LIMIT);
     }
 
+    #[test]
+    fn log_tail_settings_are_trimmed_and_clamped() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+
+        update_config(
+            r#"{"debug_log_path":" ~/.bitcoin/debug.log ","log_tail_lines":5}"#,
+            &cfg,
+        );
+        assert_eq!(cfg.lock().unwrap().debug_log_path, "~/.bitcoin/debug.log");
+        assert_eq!(cfg.lock().unwrap().log_tail_lines, MIN_LOG_TAIL_LINES);
+
+        update_config(r#"{"log_tail_lines":"1000000"}"#, &cfg);
+        assert_eq!(cfg.lock().unwrap().debug_log_path, "~/.bitcoin/debug.log");
+        assert_eq!(cfg.lock().unwrap().log_tail_lines, MAX_LOG_TAIL_LINES);
+    }
+
+    #[test]
+    fn auth_mode_follows_config_messages() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let mode = || cfg.lock().unwrap().connection.auth_mode.clone();
+        let cookie = |path: &str| AuthMode::CookieFile { path: path.into() };
+
+        update_config(r#"{"auth_mode":"cookie","cookie_path":" /c "}"#, &cfg);
+        assert_eq!(mode(), cookie("/c"));
+        // Switching back keeps nothing of the cookie; omitting both keys
+        // leaves the mode alone.
+        update_config(r#"{"auth_mode":"password","cookie_path":"/c"}"#, &cfg);
+        assert_eq!(mode(), AuthMode::UserPassword);
+        update_config(r#"{"auth_mode":"cookie"}"#, &cfg);
+        assert_eq!(mode(), cookie(""));
+        update_config(r#"{"wallet":"w"}"#, &cfg);
+        assert_eq!(mode(), cookie(""));
+
+        // Configs saved before auth_mode existed only carry the path.
+        update_config(r#"{"cookie_path":"/old"}"#, &cfg);
+        assert_eq!(mode(), cookie("/old"));
+        update_config(r#"{"cookie_path":""}"#, &cfg);
+        assert_eq!(mode(), AuthMode::UserPassword);
+    }
+
+    #[test]
+    fn client_survives_non_connection_changes() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let generation = || cfg.lock().unwrap().client().generation();
+        update_config(
+            r#"{"url":"http://127.0.0.1:8332","user":"u","password":"p"}"#,
+            &cfg,
+        );
+        let initial = generation();
+        assert_eq!(initial, 1);
+
+        update_config(
+            r#"{"url":"http://127.0.0.1:8332","user":"u","password":"p","wallet":"w","zmq_buffer_limit":500}"#,
+            &cfg,
+        );
+        update_config(r#"{"pollInterval":"10"}"#, &cfg);
+        assert_eq!(generation(), initial);
+
+        update_config(r#"{"password":"new"}"#, &cfg);
+        assert_eq!(generation(), initial + 1);
+        update_config(r#"{"url":"http://127.0.0.1:18443"}"#, &cfg);
+        assert_eq!(generation(), initial + 2);
+    }
+
+    #[test]
+    fn timeout_is_clamped_and_rotates_client_only_on_change() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let state = || {
+            let cfg = cfg.lock().unwrap();
+            (cfg.timeout_secs, cfg.client().generation())
+        };
+        update_config(r#"{"timeout_secs":"0"}"#, &cfg);
+        assert_eq!(state(), (Some(MIN_RPC_TIMEOUT_SECS), 1));
+        update_config(r#"{"timeout_secs":1}"#, &cfg);
+        assert_eq!(state(), (Some(MIN_RPC_TIMEOUT_SECS), 1));
+        update_config(r#"{"timeout_secs":9999}"#, &cfg);
+        assert_eq!(state(), (Some(MAX_RPC_TIMEOUT_SECS), 2));
+        update_config(r#"{"wallet":"w"}"#, &cfg);
+        assert_eq!(state(), (Some(MAX_RPC_TIMEOUT_SECS), 2));
+        for bad in [r#""""#, r#""soon""#, "-5", "2.5", "true"] {
+            let result = update_config(&format!(r#"{{"timeout_secs":{bad}}}"#), &cfg);
+            assert_eq!(result.errors.len(), 1, "{bad}");
+            assert_eq!(state(), (Some(MAX_RPC_TIMEOUT_SECS), 2), "{bad}");
+        }
+        update_config(r#"{"timeout_secs":null}"#, &cfg);
+        assert_eq!(state(), (None, 3));
+    }
+
+    #[test]
+    fn tls_mode_must_match_the_url() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let tls_mode = || cfg.lock().unwrap().connection.tls_mode;
+        // Saved configs without a mode get the one the URL implies.
+        update_config(r#"{"url":"https://127.0.0.1:8332"}"#, &cfg);
+        assert_eq!(tls_mode(), TlsMode::System);
+        update_config(r#"{"url":"http://127.0.0.1:8332"}"#, &cfg);
+        assert_eq!(tls_mode(), TlsMode::None);
+
+        let generation = cfg.lock().unwrap().client().generation();
+        let result = update_config(
+            r#"{"url":"https://127.0.0.1:8332","tls_mode":"none"}"#,
+            &cfg,
+        );
+        assert_eq!(
+            result.errors,
+            ["An https RPC URL needs a TLS mode other than None"]
+        );
+        let result = update_config(
+            r#"{"url":"http://127.0.0.1:8332","tls_mode":"system"}"#,
+            &cfg,
+        );
+        assert_eq!(result.errors, ["TLS needs an https:// RPC URL"]);
+        let result = update_config(r#"{"tls_mode":"native"}"#, &cfg);
+        assert_eq!(result.errors, ["Unknown TLS mode \"native\""]);
+        assert_eq!(cfg.lock().unwrap().client().generation(), generation);
+
+        update_config(
+            r#"{"url":"https://127.0.0.1:8332","tls_mode":"system"}"#,
+            &cfg,
+        );
+        assert_eq!(tls_mode(), TlsMode::System);
+        assert_eq!(cfg.lock().unwrap().client().generation(), generation + 1);
+
+        let insecure = r#"{"url":"https://127.0.0.1:8332","tls_mode":"insecure"}"#;
+        if allow_insecure() {
+            assert!(update_config(insecure, &cfg).errors.is_empty());
+            assert_eq!(tls_mode(), TlsMode::InsecureSkipVerify);
+        } else {
+            assert_eq!(
+                update_config(insecure, &cfg).errors,
+                ["Skipping certificate verification requires DANGER_INSECURE_RPC=1"]
+            );
+            // TLS alone doesn't let a public host through.
+            let result = update_config(
+                r#"{"url":"https://node.example.com","tls_mode":"system"}"#,
+                &cfg,
+            );
+            assert!(result.errors[0].starts_with("Non-local RPC address blocked"));
+        }
+    }
+
+    #[test]
+    fn blocked_url_does_not_rotate_client() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        if allow_insecure() {
+            return;
+        }
+        let result = update_config(r#"{"url":"http://8.8.8.8:8332"}"#, &cfg);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].starts_with("Non-local RPC address blocked"));
+        assert_eq!(cfg.lock().unwrap().client().generation(), 0);
+    }
+
+    #[test]
+    fn config_updates_report_every_problem() {
+        let msg = serde_json::json!({
+            "url": "ftp://127.0.0.1:8332",
+            "pollInterval": "0",
+            "zmq_address": "127.0.0.1:28332",
+            "exports_dir": "exports",
+        });
+        let errors = validate_config_update(&msg, false).unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "RPC URL scheme must be http or https, not \"ftp\"",
+                "Poll interval must be at least 1 second",
+                "ZMQ address must start with tcp://",
+                "Exports folder must be an absolute path, not \"exports\"",
+            ]
+        );
+
+        let ok = serde_json::json!({
+            "url": "http://127.0.0.1:8332/",
+            "pollInterval": "5",
+            "zmq_address": "tcp://127.0.0.1:28332",
+            "exports_dir": "",
+        });
+        assert_eq!(validate_config_update(&ok, false), Ok(()));
+        assert_eq!(
+            validate_config_update(&serde_json::json!({}), false),
+            Ok(())
+        );
+        assert_eq!(
+            validate_config_update(
+                &serde_json::json!({ "zmq_address": "tcp://127.0.0.1" }),
+                false
+            )
+            .unwrap_err(),
+            ["ZMQ address needs a port, e.g. tcp://127.0.0.1:28332"]
+        );
+        assert_eq!(
+            validate_config_update(&serde_json::json!({ "url": "localhost:8332" }), false)
+                .unwrap_err(),
+            ["RPC URL must start with http:// or https://"]
+        );
+
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let result = update_config(&msg.to_string(), &cfg);
+        assert_eq!(result.errors.len(), 4);
+        assert_eq!(
+            cfg.lock().unwrap().connection.url,
+            RpcConfig::default().connection.url
+        );
+        assert!(cfg.lock().unwrap().zmq_address.is_empty());
+        assert!(cfg.lock().unwrap().exports_dir.is_empty());
+    }
+
+    #[test]
+    fn explicit_ports_are_range_checked() {
+        assert_eq!(explicit_port("127.0.0.1:8332/wallet/w"), Ok(Some(8332)));
+        assert_eq!(explicit_port("user:pw@node.local"), Ok(None));
+        assert_eq!(explicit_port("[::1]:18443"), Ok(Some(18443)));
+        assert_eq!(explicit_port("[::1]"), Ok(None));
+        assert_eq!(
+            explicit_port("host:0"),
+            Err("port 0 is outside 1-65535".into())
+        );
+        assert_eq!(
+            explicit_port("host:65536"),
+            Err("port 65536 is outside 1-65535".into())
+        );
+        assert_eq!(
+            explicit_port("host:83a2"),
+            Err("port \"83a2\" is not a number".into())
+        );
+        assert_eq!(
+            explicit_port("host:"),
+            Err("port \"\" is not a number".into())
+        );
+        assert_eq!(explicit_port(":8332"), Err("has no host".into()));
+        assert!(explicit_port("[::1:8332").is_err());
+    }
+
+    #[test]
+    fn zmq_restart_is_keyed_on_zmq_address_only() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        assert!(update_config(r#"{"zmq_address":"tcp://127.0.0.1:28332"}"#, &cfg).zmq_changed);
+        assert!(!update_config(r#"{"zmq_address":"tcp://127.0.0.1:28332"}"#, &cfg).zmq_changed);
+        assert!(
+            !update_config(r#"{"url":"http://127.0.0.1:18443","wallet":"w"}"#, &cfg).zmq_changed
+        );
+        assert!(!update_config(r#"{"zmq_buffer_limit":100}"#, &cfg).zmq_changed);
+    }
+
     #[test]
     fn error_json_is_valid_and_escaped() {
-        let out = json_error("bad \"quote\"\nline".to_string());
+        let out = ReplyIds::unparsed().error("bad \"quote\"\nline".to_string());
         let v: serde_json::Value = serde_json::from_str(&out).expect("valid JSON error envelope");
         assert_eq!(v["error"].as_str(), Some("bad \"quote\"\nline"));
     }
+
+    #[test]
+    fn call_responses_map_to_structured_errors() {
+        let warmup =
+            r#"{"result":null,"error":{"code":-28,"message":"Loading block index..."},"id":1}"#;
+        assert!(matches!(
+            parse_call_response(500, warmup),
+            Err(RpcError::Rpc { code: -28, .. })
+        ));
+        assert!(matches!(
+            parse_call_response(401, ""),
+            Err(RpcError::Http { status: 401, .. })
+        ));
+        assert_eq!(
+            parse_call_response(200, r#"{"result":42,"error":null,"id":1}"#).unwrap(),
+            serde_json::json!(42)
+        );
+        assert!(matches!(
+            parse_call_response(200, "not json"),
+            Err(RpcError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn single_call_tolerates_null_or_missing_id() {
+        assert_eq!(
+            parse_call_response(200, r#"{"result":"ok","error":null,"id":null}"#).unwrap(),
+            serde_json::json!("ok")
+        );
+        assert_eq!(
+            parse_call_response(200, r#"{"result":"ok"}"#).unwrap(),
+            serde_json::json!("ok")
+        );
+    }
+
+    #[test]
+    fn proxy_html_error_is_described() {
+        let html = "<!DOCTYPE html>\n<html><head><title>502 Bad Gateway</title></head>\
+                    <body><center><h1>502 Bad Gateway</h1></center></body></html>";
+        for status in [200, 502] {
+            let err = parse_call_response(status, html).unwrap_err();
+            assert!(err.to_string().contains("HTML page"), "{err}");
+            assert!(parse_batch_response(2, status, html).is_err());
+        }
+        assert_eq!(
+            parse_call_response(401, "").unwrap_err().to_string(),
+            "HTTP 401: authentication failed"
+        );
+    }
+
+    fn reply_ids(body: &str) -> ReplyIds {
+        ReplyIds::from_request(&serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn node_replies_echo_the_request_id_and_tag() {
+        let ids = reply_ids(r#"{"method":"getblockcount","id":7,"client_tag":{"tab":3}}"#);
+        let ok = call_reply(
+            &ids,
+            Ok((200, r#"{"result":5,"error":null,"id":7}"#.into())),
+        );
+        let v: serde_json::Value = serde_json::from_str(&ok).unwrap();
+        assert_eq!((v["result"].as_u64(), v["id"].as_u64()), (Some(5), Some(7)));
+        assert_eq!(v["client_tag"], serde_json::json!({ "tab": 3 }));
+
+        // A proxy that rewrites or drops the id doesn't break correlation.
+        let node_err = r#"{"result":null,"error":{"code":-8,"message":"bad"},"id":null}"#;
+        let err = call_reply(&ids, Ok((500, node_err.into())));
+        let v: serde_json::Value = serde_json::from_str(&err).unwrap();
+        assert_eq!(v["error"]["code"].as_i64(), Some(-8));
+        assert_eq!(v["id"].as_u64(), Some(7));
+        assert_eq!(v["client_tag"]["tab"].as_u64(), Some(3));
+    }
+
+    #[test]
+    fn local_errors_echo_the_request_id() {
+        let ids = reply_ids(r#"{"method":"getblockcount","id":"a"}"#);
+        let transport = RpcError::Transport("connection refused".into());
+        let v: serde_json::Value = serde_json::from_str(&call_reply(&ids, Err(transport))).unwrap();
+        assert_eq!(v["id"].as_str(), Some("a"));
+        assert!(v["error"].as_str().unwrap().contains("connection refused"));
+        assert!(v.get("client_tag").is_none());
+
+        let html = call_reply(&ids, Ok((502, "<html>Bad Gateway</html>".into())));
+        let v: serde_json::Value = serde_json::from_str(&html).unwrap();
+        assert_eq!(v["id"].as_str(), Some("a"));
+        assert!(v["error"].is_string());
+    }
+
+    #[test]
+    fn missing_ids_get_unique_fallbacks() {
+        let (a, b) = (
+            reply_ids(r#"{"method":"x"}"#),
+            reply_ids(r#"{"method":"x","id":null}"#),
+        );
+        assert!(a.id.is_string());
+        assert_ne!(a.id, b.id);
+
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let budget = RpcBudget::new(1);
+        let malformed: serde_json::Value =
+            serde_json::from_str(&do_rpc("{not json", &cfg, &budget)).unwrap();
+        assert!(malformed["error"].is_string());
+        assert!(malformed["id"].as_str().unwrap().starts_with("rpc-web-"));
+    }
+
+    #[test]
+    fn batch_envelope_numbers_elements() {
+        let calls = [
+            serde_json::json!({"method": "getblockcount", "params": []}),
+            serde_json::json!({"method": "uptime"}),
+        ];
+        let v = batch_envelope(&calls);
+        assert_eq!(v[0]["id"], 0);
+        assert_eq!(v[1]["id"], 1);
+        assert_eq!(v[1]["method"], "uptime");
+    }
+
+    #[test]
+    fn batch_results_align_by_id() {
+        let body = r#"[
+            {"result":"b","error":null,"id":1},
+            {"result":"a","error":null,"id":0},
+            {"result":null,"error":{"code":-32601,"message":"Method not found"},"id":2}
+        ]"#;
+        let out = parse_batch_response(3, 200, body).unwrap();
+        assert_eq!(out[0].as_ref().unwrap(), "a");
+        assert_eq!(out[1].as_ref().unwrap(), "b");
+        assert!(matches!(
+            &out[2],
+            Err(RpcError::Rpc { code: -32601, message }) if message == "Method not found"
+        ));
+    }
+
+    #[test]
+    fn shuffled_dashboard_batch_keeps_fields_with_their_calls() {
+        let calls: Vec<_> = [
+            "getblockchaininfo",
+            "getmempoolinfo",
+            "getnetworkinfo",
+            "uptime",
+        ]
+        .iter()
+        .map(|m| serde_json::json!({ "method": m, "params": [] }))
+        .collect();
+        let replies = [
+            serde_json::json!({ "result": { "blocks": 840000 }, "error": null }),
+            serde_json::json!({ "result": { "size": 3210 }, "error": null }),
+            serde_json::json!({ "result": { "connections": 10 }, "error": null }),
+            serde_json::json!({ "result": 86400, "error": null }),
+        ];
+        let ids: Vec<_> = batch_envelope(&calls)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["id"].clone())
+            .collect();
+        for order in [[3, 1, 0, 2], [2, 3, 1, 0], [1, 0, 3, 2]] {
+            let body: Vec<_> = order
+                .iter()
+                .map(|&i| {
+                    let mut reply = replies[i].clone();
+                    reply["id"] = ids[i].clone();
+                    reply
+                })
+                .collect();
+            let body = serde_json::Value::Array(body).to_string();
+            let out = parse_batch_response(calls.len(), 200, &body).unwrap();
+            assert_eq!(out[0].as_ref().unwrap()["blocks"], 840000, "{order:?}");
+            assert_eq!(out[1].as_ref().unwrap()["size"], 3210, "{order:?}");
+            assert_eq!(out[2].as_ref().unwrap()["connections"], 10, "{order:?}");
+            assert_eq!(out[3].as_ref().unwrap(), 86400, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn batch_null_id_element_reports_position() {
+        // Core's reply when the second batch element is not an object.
+        let body = r#"[
+            {"result":800000,"error":null,"id":0},
+            {"result":null,"error":{"code":-32600,"message":"Invalid Request object"},"id":null},
+            {"result":12,"error":null,"id":2}
+        ]"#;
+        let out = parse_batch_response(3, 200, body).unwrap();
+        assert_eq!(out[0].as_ref().unwrap(), 800000);
+        match &out[1] {
+            Err(RpcError::Rpc { code, message }) => {
+                assert_eq!(*code, -32600);
+                assert_eq!(message, "batch element 1: Invalid Request object");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(out[2].as_ref().unwrap(), 12);
+    }
+
+    #[test]
+    fn batch_missing_elements_are_errors() {
+        let out = parse_batch_response(2, 200, r#"[{"result":1,"error":null,"id":0}]"#).unwrap();
+        assert!(out[0].is_ok());
+        assert!(matches!(&out[1], Err(RpcError::InvalidResponse(m)) if m.contains("element 1")));
+    }
+
+    #[test]
+    fn whole_batch_error_envelope_is_single_error() {
+        // Core answers malformed batch JSON with one object and HTTP 500.
+        let body = r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+        match parse_batch_response(3, 500, body) {
+            Err(RpcError::Rpc { code, message }) => {
+                assert_eq!(code, -32700);
+                assert_eq!(message, "batch rejected: Parse error");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(
+            parse_batch_response(1, 200, r#"{"result":1}"#),
+            Err(RpcError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn basic_auth_header_is_padded_base64() {
+        assert_eq!(basic_auth("user", "pass"), "Basic dXNlcjpwYXNz");
+        assert_eq!(basic_auth("alice", "pw"), "Basic YWxpY2U6cHc=");
+        assert_eq!(
+            basic_auth("__cookie__", "ab12"),
+            "Basic X19jb29raWVfXzphYjEy"
+        );
+    }
+
+    #[test]
+    fn cookie_contents_are_parsed() {
+        assert_eq!(
+            parse_cookie("__cookie__:ab12\n"),
+            Some(("__cookie__".into(), "ab12".into()))
+        );
+        assert_eq!(parse_cookie("no-separator"), None);
+        assert_eq!(parse_cookie("__cookie__:"), None);
+    }
+
+    #[test]
+    fn missing_cookie_file_is_a_cookie_error() {
+        let err = read_cookie("/nonexistent/bitcoin-rpc-web/.cookie").unwrap_err();
+        assert!(matches!(err, RpcError::Cookie(_)));
+        assert!(
+            err.to_string()
+                .contains("/nonexistent/bitcoin-rpc-web/.cookie")
+        );
+    }
+
+    /// Reads one HTTP request off `stream` and returns its Authorization
+    /// header.
+    fn read_request(stream: &std::net::TcpStream) -> String {
+        use std::io::{BufRead, BufReader, Read};
+
+        let mut reader = BufReader::new(stream);
+        let (mut auth, mut len) = (String::new(), 0);
+        reader.read_line(&mut String::new()).unwrap();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let Some((key, value)) = line.trim_end().split_once(": ") else {
+                break;
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "authorization" => auth = value.to_string(),
+                "content-length" => len = value.parse().unwrap(),
+                _ => {}
+            }
+        }
+        reader.read_exact(&mut vec![0; len]).unwrap();
+        auth
+    }
+
+    fn http_reply(status: &str, content_type: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// A node that answers the first `busy` requests with Core's full
+    /// work queue reply and the next `ok` with 7, as a single call or batch.
+    fn scripted_node(busy: usize, ok: usize) -> (String, std::thread::JoinHandle<()>) {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().take(busy + ok).enumerate() {
+                let mut stream = stream.unwrap();
+                read_request(&stream);
+                let resp = if i < busy {
+                    http_reply(
+                        "500 Internal Server Error",
+                        "text/html",
+                        "Work queue depth exceeded",
+                    )
+                } else {
+                    http_reply(
+                        "200 OK",
+                        "application/json",
+                        r#"[{"result":7,"error":null,"id":0}]"#,
+                    )
+                };
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn work_queue_reply_is_recognised() {
+        assert!(work_queue_full(500, "Work queue depth exceeded"));
+        assert!(work_queue_full(503, "Work queue depth exceeded\n"));
+        assert!(!work_queue_full(500, "Internal error"));
+        assert!(!work_queue_full(200, "Work queue depth exceeded"));
+        assert!(RpcError::NodeBusy.to_string().contains("retry"));
+    }
+
+    #[test]
+    fn busy_node_backs_off_until_it_answers() {
+        let (url, server) = scripted_node(3, 3);
+        let mut config = RpcConfig::default();
+        config.connection.url = url;
+        let cfg = Arc::new(Mutex::new(config));
+        let budget = RpcBudget::new(1);
+
+        // The webview sees the flag rather than an opaque HTTP 500.
+        let reply: serde_json::Value = serde_json::from_str(&do_rpc(
+            r#"{"batch":[{"method":"getblockcount"}],"id":1}"#,
+            &cfg,
+            &budget,
+        ))
+        .unwrap();
+        assert_eq!(reply["node_busy"], true);
+        assert!(reply["error"].as_str().unwrap().contains("-rpcworkqueue"));
+
+        let calls = [serde_json::json!({ "method": "getblockcount", "params": [] })];
+        let mut factors = vec![budget.stats().busy_backoff];
+        for _ in 0..2 {
+            assert!(matches!(
+                node_batch(&cfg, &budget, &calls),
+                Err(RpcError::NodeBusy)
+            ));
+            factors.push(budget.stats().busy_backoff);
+        }
+        for _ in 0..3 {
+            let replies = node_batch(&cfg, &budget, &calls).unwrap();
+            assert_eq!(replies[0].as_ref().unwrap(), &serde_json::json!(7));
+            factors.push(budget.stats().busy_backoff);
+        }
+        assert_eq!(factors, [2, 4, 8, 4, 2, 1]);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn cookie_is_reread_after_auth_failure() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let cookie = std::env::temp_dir().join(format!("rpc-web-cookie-{}", std::process::id()));
+        std::fs::write(&cookie, "__cookie__:old").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // Accepts the old cookie once, then only the new one.
+        let server = std::thread::spawn(move || {
+            let mut auths = Vec::new();
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let auth = read_request(&stream);
+                let ok = auth == basic_auth("__cookie__", "new")
+                    || (auths.is_empty() && auth == basic_auth("__cookie__", "old"));
+                auths.push(auth);
+                let resp = if ok {
+                    http_reply(
+                        "200 OK",
+                        "application/json",
+                        r#"{"result":7,"error":null,"id":1}"#,
+                    )
+                } else {
+                    "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .into()
+                };
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+            auths
+        });
+
+        let mut config = RpcConfig::default();
+        config.connection.url = format!("http://{addr}");
+        config.connection.auth_mode = AuthMode::CookieFile {
+            path: cookie.display().to_string(),
+        };
+        let params = serde_json::json!([]);
+        assert_eq!(call(&config, "getblockcount", &params).unwrap(), 7);
+        // Node restart: a new cookie is written; the cached one gets a 401.
+        std::fs::write(&cookie, "__cookie__:new").unwrap();
+        assert_eq!(call(&config, "getblockcount", &params).unwrap(), 7);
+
+        let auths = server.join().unwrap();
+        assert_eq!(auths.len(), 3);
+        assert_eq!(auths[1], basic_auth("__cookie__", "old"));
+        assert_eq!(auths[2], basic_auth("__cookie__", "new"));
+        let _ = std::fs::remove_file(&cookie);
+    }
 }
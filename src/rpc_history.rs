@@ -0,0 +1,270 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::{config_store, params_key};
+
+const FILE_NAME: &str = "history.json";
+
+/// The frontend keeps the same number in memory.
+const MAX_ENTRIES: usize = 50;
+
+/// Methods whose params or reply can carry a passphrase, a private key or a
+/// private descriptor. They stay in the session list but never reach disk.
+const DENY_LIST: [&str; 17] = [
+    "createwallet",
+    "deriveaddresses",
+    "dumpprivkey",
+    "dumpwallet",
+    "encryptwallet",
+    "getdescriptorinfo",
+    "gethdkeys",
+    "importdescriptors",
+    "importmulti",
+    "importprivkey",
+    "listdescriptors",
+    "migratewallet",
+    "sethdseed",
+    "signmessagewithprivkey",
+    "signrawtransactionwithkey",
+    "walletpassphrase",
+    "walletpassphrasechange",
+];
+
+pub fn is_denied(method: &str) -> bool {
+    DENY_LIST.contains(&method)
+}
+
+/// One executed call. The reply is not kept: it can be large, and the
+/// inputs are enough to run the call again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub method: String,
+    pub params: Value,
+    /// The form inputs as typed, so a restore shows exactly what was run.
+    pub input_values: Vec<String>,
+    pub at_ms: u64,
+    pub duration_ms: Option<u64>,
+    pub error: bool,
+    pub pinned: bool,
+}
+
+impl HistoryEntry {
+    pub fn from_json(v: &Value) -> Option<Self> {
+        let method = v["method"].as_str().filter(|m| !m.is_empty())?;
+        Some(Self {
+            method: method.to_string(),
+            params: v["params"]
+                .as_array()
+                .map(|a| Value::Array(a.clone()))
+                .unwrap_or_else(|| json!([])),
+            input_values: v["input_values"]
+                .as_array()
+                .map(|a| {
+                    a.iter()
+                        .map(|s| s.as_str().unwrap_or_default().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            at_ms: v["at_ms"].as_u64()?,
+            duration_ms: v["duration_ms"].as_u64(),
+            error: v["error"].as_bool().unwrap_or(false),
+            pinned: v["pinned"].as_bool().unwrap_or(false),
+        })
+    }
+
+    /// Calls that differ only in params whitespace or object key order
+    /// share a key, and so one history entry.
+    pub fn key(&self) -> String {
+        params_key::call_key(&self.method, &self.params.to_string())
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "key": self.key(),
+            "method": self.method,
+            "params": self.params,
+            "input_values": self.input_values,
+            "at_ms": self.at_ms,
+            "duration_ms": self.duration_ms,
+            "error": self.error,
+            "pinned": self.pinned,
+        })
+    }
+}
+
+/// Valid entries that may be stored, newest first as sent, capped at the
+/// in-memory limit. A repeated call keeps only its newest entry, which stays
+/// pinned if any of the copies was.
+fn storable(entries: &[Value]) -> Vec<HistoryEntry> {
+    let mut out: Vec<HistoryEntry> = Vec::new();
+    let mut keys: Vec<String> = Vec::new();
+    for entry in entries.iter().filter_map(HistoryEntry::from_json) {
+        if is_denied(&entry.method) {
+            continue;
+        }
+        let key = entry.key();
+        match keys.iter().position(|k| *k == key) {
+            Some(i) => out[i].pinned |= entry.pinned,
+            None if out.len() < MAX_ENTRIES => {
+                keys.push(key);
+                out.push(entry);
+            }
+            None => {}
+        }
+    }
+    out
+}
+
+pub fn history_path() -> PathBuf {
+    config_store::file_path(FILE_NAME)
+}
+
+/// The saved history; a missing or unreadable file counts as empty. Denied
+/// methods are dropped here too, in case the file was edited by hand.
+pub fn load(path: &Path) -> Vec<HistoryEntry> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .and_then(|v| v["entries"].as_array().map(|a| storable(a)))
+        .unwrap_or_default()
+}
+
+pub fn to_json(entries: &[HistoryEntry]) -> Value {
+    json!({ "entries": entries.iter().map(HistoryEntry::to_json).collect::<Vec<_>>() })
+}
+
+/// Replaces the saved history with `{ "entries": [...] }` from the
+/// frontend, leaving out denied methods and entries that don't parse.
+/// Returns how many were written.
+pub fn save(path: &Path, body: &str) -> io::Result<usize> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let v: Value = serde_json::from_str(body).map_err(|e| invalid(e.to_string()))?;
+    let entries = v["entries"]
+        .as_array()
+        .ok_or_else(|| invalid("missing entries".into()))?;
+    let entries = storable(entries);
+    config_store::write_atomic(path, &to_json(&entries).to_string())?;
+    Ok(entries.len())
+}
+
+pub fn delete(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DENY_LIST, MAX_ENTRIES, delete, is_denied, load, save, to_json};
+    use serde_json::json;
+
+    fn entry(method: &str, at_ms: u64) -> serde_json::Value {
+        json!({
+            "method": method,
+            "params": ["p"],
+            "input_values": ["p"],
+            "at_ms": at_ms,
+            "duration_ms": 12,
+            "error": false,
+            "pinned": false,
+        })
+    }
+
+    #[test]
+    fn deny_list_covers_secret_bearing_methods() {
+        for method in [
+            "walletpassphrase",
+            "walletpassphrasechange",
+            "encryptwallet",
+            "dumpprivkey",
+            "importprivkey",
+            "listdescriptors",
+        ] {
+            assert!(is_denied(method), "{method}");
+        }
+        assert!(!is_denied("getblockchaininfo"));
+        assert!(DENY_LIST.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn saved_history_round_trips_without_denied_calls() {
+        let path = std::env::temp_dir().join(format!("rpc-history-{}.json", std::process::id()));
+        let mut pinned = entry("getblock", 3);
+        pinned["pinned"] = json!(true);
+        let body = json!({
+            "entries": [
+                entry("walletpassphrase", 5),
+                entry("getblockcount", 4),
+                pinned,
+                { "method": "", "at_ms": 2 },
+                { "method": "getpeerinfo" },
+                entry("encryptwallet", 1),
+            ],
+        });
+        assert_eq!(save(&path, &body.to_string()).unwrap(), 2);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("walletpassphrase") && !text.contains("encryptwallet"));
+
+        let loaded = load(&path);
+        let methods: Vec<_> = loaded.iter().map(|e| e.method.as_str()).collect();
+        assert_eq!(methods, ["getblockcount", "getblock"]);
+        assert!(loaded[1].pinned);
+        assert_eq!(loaded[0].duration_ms, Some(12));
+        assert_eq!(loaded[0].input_values, ["p"]);
+
+        delete(&path).unwrap();
+        assert!(load(&path).is_empty());
+        delete(&path).unwrap();
+    }
+
+    #[test]
+    fn saves_are_capped_and_bad_bodies_rejected() {
+        let path =
+            std::env::temp_dir().join(format!("rpc-history-cap-{}.json", std::process::id()));
+        let entries: Vec<_> = (0..MAX_ENTRIES as u64 + 10)
+            .map(|i| {
+                let mut e = entry("getblockhash", i);
+                e["params"] = json!([i]);
+                e
+            })
+            .collect();
+        let written = save(&path, &json!({ "entries": entries }).to_string()).unwrap();
+        assert_eq!(written, MAX_ENTRIES);
+        assert_eq!(load(&path)[0].at_ms, 0);
+
+        assert!(save(&path, "not json").is_err());
+        assert!(save(&path, "{}").is_err());
+        delete(&path).unwrap();
+    }
+
+    #[test]
+    fn repeated_calls_keep_the_newest_entry_and_any_pin() {
+        let path =
+            std::env::temp_dir().join(format!("rpc-history-dedupe-{}.json", std::process::id()));
+        let mut spaced = entry("getblock", 5);
+        spaced["params"] = json!([{ "verbosity": 1, "hash": "00ab" }]);
+        let mut pinned = entry("getblock", 3);
+        pinned["params"] = json!([{ "hash": "00ab", "verbosity": 1 }]);
+        pinned["pinned"] = json!(true);
+        let mut float = entry("getblock", 2);
+        float["params"] = json!([{ "hash": "00ab", "verbosity": 1.0 }]);
+        let body = json!({
+            "entries": [spaced, entry("getblockcount", 4), pinned, float, entry("getblockcount", 1)],
+        });
+        assert_eq!(save(&path, &body.to_string()).unwrap(), 3);
+
+        let loaded = load(&path);
+        let stamps: Vec<_> = loaded.iter().map(|e| e.at_ms).collect();
+        assert_eq!(stamps, [5, 4, 2]);
+        assert!(loaded[0].pinned);
+        assert!(!loaded[2].pinned);
+        assert_eq!(
+            to_json(&loaded)["entries"][0]["key"],
+            r#"getblock [{"hash":"00ab","verbosity":1}]"#
+        );
+        delete(&path).unwrap();
+    }
+}
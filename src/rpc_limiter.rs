@@ -1,9 +1,15 @@
-use std::sync::Arc;
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+pub const DEFAULT_NODE_CONCURRENCY: usize = 4;
+/// Furthest the dashboard stretches its poll interval for a busy node.
+pub const MAX_BUSY_BACKOFF: u32 = 8;
 
 pub struct RpcLimiter {
     max_in_flight: usize,
     in_flight: AtomicUsize,
+    budget: Arc<RpcBudget>,
 }
 
 pub struct RpcPermit {
@@ -11,13 +17,18 @@ pub struct RpcPermit {
 }
 
 impl RpcLimiter {
-    pub fn new(max_in_flight: usize) -> Arc<Self> {
+    pub fn new(max_in_flight: usize, node_concurrency: usize) -> Arc<Self> {
         Arc::new(Self {
             max_in_flight,
             in_flight: AtomicUsize::new(0),
+            budget: RpcBudget::new(node_concurrency),
         })
     }
 
+    pub fn budget(&self) -> &Arc<RpcBudget> {
+        &self.budget
+    }
+
     pub fn try_acquire(self: &Arc<Self>) -> Option<RpcPermit> {
         let mut current = self.in_flight.load(Ordering::Relaxed);
         loop {
@@ -46,3 +57,244 @@ impl Drop for RpcPermit {
         self.limiter.in_flight.fetch_sub(1, Ordering::AcqRel);
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcPriority {
+    Interactive,
+    Background,
+}
+
+impl RpcPriority {
+    pub fn from_request(value: &serde_json::Value) -> Self {
+        if value.as_str() == Some("interactive") {
+            RpcPriority::Interactive
+        } else {
+            RpcPriority::Background
+        }
+    }
+}
+
+/// Caps outstanding HTTP requests to the node. Waiters are served FIFO within
+/// a priority class, and interactive calls always go ahead of background ones.
+pub struct RpcBudget {
+    max_outstanding: usize,
+    state: Mutex<BudgetState>,
+    available: Condvar,
+    busy: Mutex<BusyBackoff>,
+}
+
+/// How far to stretch the dashboard's poll interval while the node reports
+/// a full work queue. Each busy reply doubles the factor, so the first one
+/// already skips the next scheduled refresh; each good reply halves it, so
+/// the rate recovers gradually rather than piling straight back on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusyBackoff {
+    factor: u32,
+}
+
+impl Default for BusyBackoff {
+    fn default() -> Self {
+        Self { factor: 1 }
+    }
+}
+
+impl BusyBackoff {
+    pub fn record(&mut self, busy: bool) {
+        self.factor = if busy {
+            (self.factor * 2).min(MAX_BUSY_BACKOFF)
+        } else {
+            (self.factor / 2).max(1)
+        };
+    }
+
+    pub fn factor(&self) -> u32 {
+        self.factor
+    }
+}
+
+#[derive(Default)]
+struct BudgetState {
+    outstanding: usize,
+    next_ticket: u64,
+    interactive: VecDeque<u64>,
+    background: VecDeque<u64>,
+}
+
+impl BudgetState {
+    fn head(&self) -> Option<u64> {
+        self.interactive
+            .front()
+            .or_else(|| self.background.front())
+            .copied()
+    }
+}
+
+pub struct BudgetPermit {
+    budget: Arc<RpcBudget>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetStats {
+    pub max_outstanding: usize,
+    pub outstanding: usize,
+    pub queued_interactive: usize,
+    pub queued_background: usize,
+    /// Poll interval multiplier; above 1 while backing off a busy node.
+    pub busy_backoff: u32,
+}
+
+impl RpcBudget {
+    pub fn new(max_outstanding: usize) -> Arc<Self> {
+        Arc::new(Self {
+            max_outstanding: max_outstanding.max(1),
+            state: Mutex::new(BudgetState::default()),
+            available: Condvar::new(),
+            busy: Mutex::new(BusyBackoff::default()),
+        })
+    }
+
+    pub fn acquire(self: &Arc<Self>, priority: RpcPriority) -> BudgetPermit {
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        match priority {
+            RpcPriority::Interactive => state.interactive.push_back(ticket),
+            RpcPriority::Background => state.background.push_back(ticket),
+        }
+        loop {
+            if state.outstanding < self.max_outstanding && state.head() == Some(ticket) {
+                match priority {
+                    RpcPriority::Interactive => state.interactive.pop_front(),
+                    RpcPriority::Background => state.background.pop_front(),
+                };
+                state.outstanding += 1;
+                drop(state);
+                // The next waiter may fit in a remaining slot.
+                self.available.notify_all();
+                return BudgetPermit {
+                    budget: Arc::clone(self),
+                };
+            }
+            state = self.available.wait(state).unwrap();
+        }
+    }
+
+    pub fn stats(&self) -> BudgetStats {
+        let state = self.state.lock().unwrap();
+        BudgetStats {
+            max_outstanding: self.max_outstanding,
+            outstanding: state.outstanding,
+            queued_interactive: state.interactive.len(),
+            queued_background: state.background.len(),
+            busy_backoff: self.busy.lock().unwrap().factor(),
+        }
+    }
+
+    pub fn record_busy(&self, busy: bool) {
+        self.busy.lock().unwrap().record(busy);
+    }
+}
+
+impl Drop for BudgetPermit {
+    fn drop(&mut self) {
+        self.budget.state.lock().unwrap().outstanding -= 1;
+        self.budget.available.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BusyBackoff, MAX_BUSY_BACKOFF, RpcBudget, RpcPriority};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn wait_for_queued(budget: &RpcBudget, queued: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            let stats = budget.stats();
+            if stats.queued_interactive + stats.queued_background == queued {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("timed out waiting for {queued} queued callers");
+    }
+
+    #[test]
+    fn interactive_callers_jump_background_queue() {
+        let budget = RpcBudget::new(1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let held = budget.acquire(RpcPriority::Background);
+
+        let mut handles = Vec::new();
+        for (label, priority) in [
+            ("bg1", RpcPriority::Background),
+            ("bg2", RpcPriority::Background),
+            ("console", RpcPriority::Interactive),
+        ] {
+            let waiter = Arc::clone(&budget);
+            let order = Arc::clone(&order);
+            let queued_before = handles.len();
+            handles.push(thread::spawn(move || {
+                let _permit = waiter.acquire(priority);
+                order.lock().unwrap().push(label);
+                thread::sleep(Duration::from_millis(5));
+            }));
+            wait_for_queued(&budget, queued_before + 1);
+        }
+
+        drop(held);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["console", "bg1", "bg2"]);
+        assert_eq!(budget.stats().outstanding, 0);
+    }
+
+    #[test]
+    fn batch_and_console_call_complete_at_budget_one() {
+        let budget = RpcBudget::new(1);
+        let mut handles = Vec::new();
+        for i in 0..6 {
+            let budget = Arc::clone(&budget);
+            let priority = if i == 3 {
+                RpcPriority::Interactive
+            } else {
+                RpcPriority::Background
+            };
+            handles.push(thread::spawn(move || {
+                let _permit = budget.acquire(priority);
+                thread::sleep(Duration::from_millis(2));
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let stats = budget.stats();
+        assert_eq!(stats.outstanding, 0);
+        assert_eq!(stats.queued_interactive + stats.queued_background, 0);
+    }
+
+    #[test]
+    fn zero_budget_is_raised_to_one() {
+        let budget = RpcBudget::new(0);
+        assert_eq!(budget.stats().max_outstanding, 1);
+        drop(budget.acquire(RpcPriority::Interactive));
+    }
+
+    #[test]
+    fn busy_backoff_doubles_to_a_cap_and_recovers_gradually() {
+        let mut backoff = BusyBackoff::default();
+        assert_eq!(backoff.factor(), 1);
+        let mut factors = Vec::new();
+        for busy in [true, true, true, true, false, false, false, false] {
+            backoff.record(busy);
+            factors.push(backoff.factor());
+        }
+        assert_eq!(
+            factors,
+            [2, 4, MAX_BUSY_BACKOFF, MAX_BUSY_BACKOFF, 4, 2, 1, 1]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use serde_json::{Value, json};
+
+/// How good or bad a status is. Each one carries a color and a glyph plus a
+/// word, so the meaning never rests on color alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Ok, Severity::Warn, Severity::Error];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Ok => "ok",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn color(self) -> &'static str {
+        match self {
+            Severity::Ok => "#3fb950",
+            Severity::Warn => "#f0883e",
+            Severity::Error => "#f85149",
+        }
+    }
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Severity::Ok => "\u{2713}",
+            Severity::Warn => "!",
+            Severity::Error => "\u{2715}",
+        }
+    }
+
+    /// Leads banner text, e.g. "Warning: ZMQ disconnected".
+    pub fn word(self) -> &'static str {
+        match self {
+            Severity::Ok => "OK",
+            Severity::Warn => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+/// Upper bounds in milliseconds, checked in order; slower pings are
+/// errors. Tor and I2P peers commonly sit in the middle tier.
+const PING_TIERS: [(f64, Severity); 2] = [(250.0, Severity::Ok), (1000.0, Severity::Warn)];
+
+/// The table the frontend renders every indicator from.
+pub fn to_json() -> Value {
+    let severities: serde_json::Map<String, Value> = Severity::ALL
+        .iter()
+        .map(|s| {
+            let entry = json!({ "color": s.color(), "glyph": s.glyph(), "word": s.word() });
+            (s.name().to_string(), entry)
+        })
+        .collect();
+    let ping: Vec<_> = PING_TIERS
+        .iter()
+        .map(|(max, s)| json!({ "below_ms": max, "severity": s.name() }))
+        .collect();
+    json!({
+        "severities": severities,
+        "ping": ping,
+        "ping_otherwise": Severity::Error.name(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PING_TIERS, Severity, to_json};
+
+    #[test]
+    fn each_severity_has_its_own_color_glyph_and_word() {
+        let mapped: Vec<_> = Severity::ALL
+            .iter()
+            .map(|s| (s.color(), s.glyph(), s.word()))
+            .collect();
+        assert_eq!(
+            mapped,
+            [
+                ("#3fb950", "\u{2713}", "OK"),
+                ("#f0883e", "!", "Warning"),
+                ("#f85149", "\u{2715}", "Error"),
+            ]
+        );
+        for (i, a) in mapped.iter().enumerate() {
+            for b in &mapped[i + 1..] {
+                assert!(a.0 != b.0 && a.1 != b.1 && a.2 != b.2);
+            }
+        }
+    }
+
+    #[test]
+    fn ping_tiers_ascend_from_ok_to_warn() {
+        let severities: Vec<_> = PING_TIERS.iter().map(|(_, s)| *s).collect();
+        assert_eq!(severities, [Severity::Ok, Severity::Warn]);
+        assert!(PING_TIERS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn json_table_matches_the_enum() {
+        let v = to_json();
+        for s in Severity::ALL {
+            let entry = &v["severities"][s.name()];
+            assert_eq!(entry["color"], s.color());
+            assert_eq!(entry["glyph"], s.glyph());
+            assert_eq!(entry["word"], s.word());
+        }
+        assert_eq!(v["ping"][0]["below_ms"], 250.0);
+        assert_eq!(v["ping"][1]["severity"], "warn");
+        assert_eq!(v["ping_otherwise"], "error");
+    }
+}
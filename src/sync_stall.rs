@@ -0,0 +1,192 @@
+use serde_json::{Value, json};
+
+/// Blocks unchanged this long while headers are ahead, outside IBD.
+const BLOCKS_STALL_MS: u64 = 10 * 60_000;
+/// Neither counter moving this long while a peer has more headers.
+const HEADERS_STALL_MS: u64 = 10 * 60_000;
+
+/// One dashboard poll. `peer_headers` is the highest `synced_headers` any
+/// peer reported, or `None` before peers are known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub at_ms: u64,
+    pub blocks: u64,
+    pub headers: u64,
+    pub peer_headers: Option<u64>,
+}
+
+impl Sample {
+    pub fn from_json(v: &Value) -> Option<Self> {
+        Some(Self {
+            at_ms: v["at_ms"].as_u64()?,
+            blocks: v["blocks"].as_u64()?,
+            headers: v["headers"].as_u64()?,
+            peer_headers: v["peer_headers"].as_u64(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallKind {
+    /// Headers are ahead but no block has been connected.
+    Blocks,
+    /// Nothing moves although peers have headers we don't.
+    Headers,
+}
+
+impl StallKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            StallKind::Blocks => "blocks",
+            StallKind::Headers => "headers",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stall {
+    pub kind: StallKind,
+    pub since_ms: u64,
+    pub duration_ms: u64,
+}
+
+/// The time of the first sample in the trailing run that shares the latest
+/// sample's value. A value that never changed in the window gives the
+/// window's start, so the duration is a lower bound.
+fn unchanged_since(samples: &[Sample], value: impl Fn(&Sample) -> u64) -> Option<u64> {
+    let latest = value(samples.last()?);
+    samples
+        .iter()
+        .rev()
+        .take_while(|s| value(s) == latest)
+        .last()
+        .map(|s| s.at_ms)
+}
+
+/// Checks samples, oldest first, for a stalled sync. A header stall wins
+/// when both apply, since blocks can't advance without headers either.
+pub fn detect(samples: &[Sample], ibd: bool) -> Option<Stall> {
+    let latest = samples.last()?;
+    let blocks_since = unchanged_since(samples, |s| s.blocks)?;
+    let headers_since = unchanged_since(samples, |s| s.headers)?;
+    let stall = |kind, since_ms: u64| Stall {
+        kind,
+        since_ms,
+        duration_ms: latest.at_ms.saturating_sub(since_ms),
+    };
+
+    let both_since = blocks_since.max(headers_since);
+    let behind_peers = latest.peer_headers.is_some_and(|p| p > latest.headers);
+    if behind_peers && latest.at_ms.saturating_sub(both_since) >= HEADERS_STALL_MS {
+        return Some(stall(StallKind::Headers, both_since));
+    }
+    // During IBD headers run far ahead of blocks by design.
+    if !ibd
+        && latest.headers > latest.blocks
+        && latest.at_ms.saturating_sub(blocks_since) >= BLOCKS_STALL_MS
+    {
+        return Some(stall(StallKind::Blocks, blocks_since));
+    }
+    None
+}
+
+/// `{ "samples": [...], "ibd": bool }` from the frontend.
+pub fn detect_json(body: &Value) -> Value {
+    let samples: Vec<Sample> = body["samples"]
+        .as_array()
+        .map(|a| a.iter().filter_map(Sample::from_json).collect())
+        .unwrap_or_default();
+    let stall = detect(&samples, body["ibd"].as_bool().unwrap_or(false));
+    json!({
+        "stall": stall.map(|s| json!({
+            "kind": s.kind.name(),
+            "since_ms": s.since_ms,
+            "duration_ms": s.duration_ms,
+        })),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sample, Stall, StallKind, detect, detect_json};
+    use serde_json::json;
+
+    const MIN: u64 = 60_000;
+
+    /// One sample a minute from (blocks, headers, peer_headers).
+    fn series(points: &[(u64, u64, Option<u64>)]) -> Vec<Sample> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, &(blocks, headers, peer_headers))| Sample {
+                at_ms: i as u64 * MIN,
+                blocks,
+                headers,
+                peer_headers,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ibd_with_headers_far_ahead_is_normal() {
+        let advancing: Vec<_> = (0..20)
+            .map(|i| (100 + i * 50, 900_000, Some(900_000)))
+            .collect();
+        assert_eq!(detect(&series(&advancing), true), None);
+        // Even a slow stretch during IBD isn't a blocks stall.
+        let slow: Vec<_> = (0..20).map(|_| (100, 900_000, Some(900_000))).collect();
+        assert_eq!(detect(&series(&slow), true), None);
+    }
+
+    #[test]
+    fn steady_state_between_blocks_is_quiet() {
+        let quiet: Vec<_> = (0..30).map(|_| (850_000, 850_000, Some(850_000))).collect();
+        assert_eq!(detect(&series(&quiet), false), None);
+        assert_eq!(detect(&[], false), None);
+    }
+
+    #[test]
+    fn blocks_stall_when_headers_are_ahead() {
+        let mut points = vec![(850_000, 850_000, Some(850_000)); 3];
+        points.extend((0..12).map(|i| (850_001, 850_002 + i / 4, Some(850_004))));
+        let samples = series(&points);
+        assert_eq!(
+            detect(&samples, false),
+            Some(Stall {
+                kind: StallKind::Blocks,
+                since_ms: 3 * MIN,
+                duration_ms: 11 * MIN,
+            })
+        );
+        // Nine minutes in, it's still too early to call.
+        assert_eq!(detect(&samples[..13], false), None);
+    }
+
+    #[test]
+    fn header_stall_needs_a_peer_ahead() {
+        let mut points = vec![(850_000, 850_000, Some(850_000)); 11];
+        assert_eq!(detect(&series(&points), false), None);
+        points.last_mut().unwrap().2 = Some(850_003);
+        let stall = detect(&series(&points), false).unwrap();
+        assert_eq!(stall.kind, StallKind::Headers);
+        assert_eq!(stall.duration_ms, 10 * MIN);
+    }
+
+    #[test]
+    fn recovery_clears_the_warning() {
+        let mut points: Vec<_> = (0..12).map(|_| (850_001, 850_003, Some(850_003))).collect();
+        assert!(detect(&series(&points), false).is_some());
+        points.push((850_003, 850_003, Some(850_003)));
+        assert_eq!(detect(&series(&points), false), None);
+
+        let body = json!({
+            "samples": [
+                { "at_ms": 0, "blocks": 10, "headers": 12 },
+                { "at_ms": 11 * MIN, "blocks": 10, "headers": 12, "peer_headers": 12 },
+            ],
+            "ibd": false,
+        });
+        assert_eq!(detect_json(&body)["stall"]["kind"], "blocks");
+        assert_eq!(detect_json(&json!({}))["stall"], json!(null));
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::config_store;
+
+/// Days of history kept, counting the current one.
+pub const RETENTION_DAYS: i64 = 90;
+const FILE_NAME: &str = "metrics.json";
+
+/// Counters for one local calendar day. Nothing here identifies the user or
+/// the node; method and view names are the only strings kept.
+#[derive(Debug, Default, PartialEq)]
+pub struct DayCounts {
+    pub launches: u64,
+    pub view_secs: BTreeMap<String, u64>,
+    pub rpc_calls: BTreeMap<String, u64>,
+    pub dashboard_refreshes: u64,
+    pub dashboard_failures: u64,
+}
+
+impl DayCounts {
+    fn from_json(v: &Value) -> Self {
+        let counts = |key: &str| -> BTreeMap<String, u64> {
+            v[key]
+                .as_object()
+                .map(|m| {
+                    m.iter()
+                        .filter_map(|(k, n)| n.as_u64().map(|n| (k.clone(), n)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            launches: v["launches"].as_u64().unwrap_or(0),
+            view_secs: counts("view_secs"),
+            rpc_calls: counts("rpc_calls"),
+            dashboard_refreshes: v["dashboard_refreshes"].as_u64().unwrap_or(0),
+            dashboard_failures: v["dashboard_failures"].as_u64().unwrap_or(0),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "launches": self.launches,
+            "view_secs": self.view_secs,
+            "rpc_calls": self.rpc_calls,
+            "dashboard_refreshes": self.dashboard_refreshes,
+            "dashboard_failures": self.dashboard_failures,
+        })
+    }
+
+    fn add(&mut self, other: &DayCounts) {
+        let add_map = |into: &mut BTreeMap<String, u64>, from: &BTreeMap<String, u64>| {
+            for (k, n) in from {
+                let slot = into.entry(k.clone()).or_default();
+                *slot = slot.saturating_add(*n);
+            }
+        };
+        self.launches = self.launches.saturating_add(other.launches);
+        add_map(&mut self.view_secs, &other.view_secs);
+        add_map(&mut self.rpc_calls, &other.rpc_calls);
+        self.dashboard_refreshes = self
+            .dashboard_refreshes
+            .saturating_add(other.dashboard_refreshes);
+        self.dashboard_failures = self
+            .dashboard_failures
+            .saturating_add(other.dashboard_failures);
+    }
+}
+
+/// Daily counters keyed by local date (`YYYY-MM-DD`). The frontend knows the
+/// local time zone, so it picks the date and this side only merges.
+#[derive(Debug, Default, PartialEq)]
+pub struct UsageMetrics {
+    pub days: BTreeMap<String, DayCounts>,
+}
+
+impl UsageMetrics {
+    /// Entries with a malformed date are dropped rather than failing the load.
+    pub fn from_json(v: &Value) -> Self {
+        let days = v["days"]
+            .as_object()
+            .map(|m| {
+                m.iter()
+                    .filter(|(date, _)| day_number(date).is_some())
+                    .map(|(date, counts)| (date.clone(), DayCounts::from_json(counts)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { days }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let days: serde_json::Map<String, Value> = self
+            .days
+            .iter()
+            .map(|(date, counts)| (date.clone(), counts.to_json()))
+            .collect();
+        json!({ "days": days })
+    }
+
+    pub fn add_day(&mut self, date: &str, counts: &DayCounts) {
+        self.days.entry(date.to_string()).or_default().add(counts);
+    }
+
+    /// Drops days more than `RETENTION_DAYS - 1` before `today`.
+    pub fn prune(&mut self, today: &str) {
+        let Some(today) = day_number(today) else {
+            return;
+        };
+        self.days
+            .retain(|date, _| day_number(date).is_some_and(|d| today - d < RETENTION_DAYS));
+    }
+}
+
+/// Days since 1970-01-01 for a `YYYY-MM-DD` date.
+fn day_number(date: &str) -> Option<i64> {
+    let mut parts = date.split('-');
+    let (y, m, d) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || y.len() != 4 || m.len() != 2 || d.len() != 2 {
+        return None;
+    }
+    let (y, m, d): (i64, i64, i64) = (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+    let leap = (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let days_in_month = match m {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if d < 1 || d > days_in_month {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil.
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+pub fn metrics_path() -> PathBuf {
+    config_store::file_path(FILE_NAME)
+}
+
+/// The stored metrics; a missing or unreadable file counts as empty.
+pub fn load(path: &Path) -> UsageMetrics {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .map(|v| UsageMetrics::from_json(&v))
+        .unwrap_or_default()
+}
+
+/// Adds one `{ "date": ..., <DayCounts> }` delta from the frontend, prunes
+/// relative to that date and rewrites the file.
+pub fn record(path: &Path, body: &str) -> io::Result<()> {
+    let delta: Value =
+        serde_json::from_str(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let date = delta["date"]
+        .as_str()
+        .filter(|d| day_number(d).is_some())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing or invalid date"))?;
+    let mut metrics = load(path);
+    metrics.add_day(date, &DayCounts::from_json(&delta));
+    metrics.prune(date);
+    config_store::write_atomic(path, &metrics.to_json().to_string())
+}
+
+pub fn delete(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DayCounts, RETENTION_DAYS, UsageMetrics, day_number, load, record};
+    use serde_json::json;
+
+    #[test]
+    fn day_numbers_follow_the_calendar() {
+        assert_eq!(day_number("1970-01-01"), Some(0));
+        assert_eq!(day_number("2000-03-01"), Some(11_017));
+        assert_eq!(
+            day_number("2024-02-29").unwrap() + 1,
+            day_number("2024-03-01").unwrap()
+        );
+        assert_eq!(day_number("2023-02-29"), None);
+        assert_eq!(day_number("2024-1-01"), None);
+        assert_eq!(day_number("2024-01-01T00"), None);
+    }
+
+    #[test]
+    fn merging_adds_counters_per_day() {
+        let mut m = UsageMetrics::default();
+        let day = |launches, method: &str| DayCounts {
+            launches,
+            rpc_calls: [(method.to_string(), 2)].into(),
+            ..DayCounts::default()
+        };
+        m.add_day("2026-10-16", &day(1, "getblock"));
+        m.add_day("2026-10-16", &day(1, "getblock"));
+        m.add_day("2026-10-17", &day(0, "getpeerinfo"));
+        assert_eq!(m.days["2026-10-16"].launches, 2);
+        assert_eq!(m.days["2026-10-16"].rpc_calls["getblock"], 4);
+        assert_eq!(m.days["2026-10-17"].rpc_calls["getpeerinfo"], 2);
+
+        let round_trip = UsageMetrics::from_json(&m.to_json());
+        assert_eq!(round_trip, m);
+    }
+
+    #[test]
+    fn prune_keeps_the_retention_window() {
+        let mut m = UsageMetrics::from_json(&json!({ "days": {
+            "2026-07-19": { "launches": 1 },
+            "2026-07-20": { "launches": 1 },
+            "2026-10-17": { "launches": 1 },
+            "not-a-date": { "launches": 1 },
+        }}));
+        assert_eq!(m.days.len(), 3);
+        m.prune("2026-10-17");
+        // 2026-07-20 is exactly RETENTION_DAYS - 1 days back.
+        assert_eq!(
+            day_number("2026-10-17").unwrap() - day_number("2026-07-20").unwrap(),
+            RETENTION_DAYS - 1
+        );
+        assert_eq!(
+            m.days.keys().collect::<Vec<_>>(),
+            ["2026-07-20", "2026-10-17"]
+        );
+    }
+
+    #[test]
+    fn record_merges_into_the_file() {
+        let dir = std::env::temp_dir().join(format!("usage-metrics-{}", std::process::id()));
+        let path = dir.join("metrics.json");
+        let delta = r#"{"date":"2026-10-17","launches":1,"view_secs":{"dashboard":30}}"#;
+        record(&path, delta).unwrap();
+        record(&path, delta).unwrap();
+        assert!(record(&path, r#"{"launches":1}"#).is_err());
+        let m = load(&path);
+        assert_eq!(m.days["2026-10-17"].launches, 2);
+        assert_eq!(m.days["2026-10-17"].view_secs["dashboard"], 60);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,40 @@
-use std::collections::VecDeque;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
+use std::time::{Duration, Instant};
 
 use tracing::{debug, warn};
 
+use crate::hex;
+use crate::local_decode;
+use crate::rolling_rate::RollingRate;
+use crate::rpc::{self, ConfigUpdateResult, RpcConfig};
+
 const DEFAULT_ZMQ_SOCKET_RCVHWM: i32 = 100_000;
 const MIN_ZMQ_SOCKET_RCVHWM: i32 = 1_000;
 const MAX_ZMQ_SOCKET_RCVHWM: i32 = 1_000_000;
+const DEFAULT_ZMQ_MAX_MESSAGE_BYTES: usize = 8 * 1024 * 1024;
+const MIN_ZMQ_MAX_MESSAGE_BYTES: usize = 1024;
+const MAX_ZMQ_MAX_MESSAGE_BYTES: usize = 256 * 1024 * 1024;
+const BODY_HEX_PREFIX_BYTES: usize = 80;
+const SUBSCRIBED_TOPICS: [&str; 4] = ["hashblock", "hashtx", "rawblock", "rawtx"];
+const TOPIC_RATE_WINDOW_MS: u64 = 60_000;
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// A session that has delivered messages and then goes quiet this long is
+/// treated as dead. Mainnet publishes several `hashtx` a second; a quiet
+/// regtest node only costs one idle reconnect per burst.
+const SILENCE_RECONNECT_AFTER: Duration = Duration::from_secs(600);
+/// ZMTP heartbeats, so a half-open connection to a restarted node is
+/// noticed by libzmq instead of waiting forever.
+const HEARTBEAT_IVL_MS: i32 = 10_000;
+const HEARTBEAT_TIMEOUT_MS: i32 = 30_000;
+/// How often a back-off wait checks for shutdown or a reconnect request.
+const RECONNECT_WAIT_TICK: Duration = Duration::from_millis(100);
+/// How far the encoder may fall behind, in frames and in body bytes. Past
+/// either limit frames are still recorded, but without their bodies.
+const ENCODER_QUEUE_FRAMES: usize = 4096;
+const ENCODER_QUEUE_BYTES: usize = 64 * 1024 * 1024;
 
 pub struct ZmqMessage {
     pub cursor: u64,
@@ -15,7 +43,111 @@ pub struct ZmqMessage {
     pub body_size: usize,
     pub sequence: u32,
     pub timestamp: u64,
+    pub timestamp_ms: u64,
     pub event_hash: Option<String>,
+    pub oversized: bool,
+    /// The body was dropped because the encoder had fallen behind.
+    pub backlogged: bool,
+    /// The whole body of `rawblock`/`rawtx` notifications, for decoding.
+    /// Not sent with `/zmq/messages`; blocks can be megabytes.
+    pub body_full_hex: Option<String>,
+}
+
+impl ZmqMessage {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "cursor": self.cursor,
+            "topic": self.topic,
+            "body_hex": self.body_hex,
+            "body_size": self.body_size,
+            "sequence": self.sequence,
+            "timestamp": self.timestamp,
+            "timestamp_ms": self.timestamp_ms,
+            "event_hash": self.event_hash,
+            "oversized": self.oversized,
+            "backlogged": self.backlogged,
+        })
+    }
+}
+
+/// What a `/zmq/messages` poll asks for.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MessagesQuery {
+    /// The newest cursor the caller already has; 0 for none.
+    pub since: u64,
+    /// Keep only the newest this many of the messages after `since`.
+    pub limit: Option<usize>,
+    /// Leave the messages out: connection state, cursor and counts only.
+    pub summary_only: bool,
+}
+
+/// A received notification waiting to be encoded. Bodies above the size cap
+/// are dropped on the subscriber thread and only their length is kept.
+struct RawFrame {
+    topic: String,
+    body: Vec<u8>,
+    body_size: usize,
+    oversized: bool,
+    backlogged: bool,
+    sequence: u32,
+    timestamp: u64,
+    timestamp_ms: u64,
+}
+
+impl RawFrame {
+    fn drop_backlogged_body(&mut self) {
+        self.body = Vec::new();
+        self.backlogged = true;
+    }
+}
+
+/// The subscriber's end of the encoder queue.
+struct FrameSender {
+    tx: mpsc::SyncSender<RawFrame>,
+    queued_bytes: Arc<AtomicUsize>,
+    max_bytes: usize,
+}
+
+impl FrameSender {
+    /// A frame whose body would take the queue over its byte limit, or that
+    /// arrives while the queue is full, goes without its body. Fails once the
+    /// encoder has stopped.
+    fn send(&self, mut frame: RawFrame) -> Result<(), mpsc::SendError<RawFrame>> {
+        let len = frame.body.len();
+        if self.queued_bytes.fetch_add(len, Ordering::AcqRel) + len > self.max_bytes {
+            self.queued_bytes.fetch_sub(len, Ordering::AcqRel);
+            frame.drop_backlogged_body();
+        }
+        match self.tx.try_send(frame) {
+            Ok(()) => Ok(()),
+            Err(mpsc::TrySendError::Full(mut frame)) => {
+                self.queued_bytes
+                    .fetch_sub(frame.body.len(), Ordering::AcqRel);
+                frame.drop_backlogged_body();
+                debug!(topic = %frame.topic, "ZMQ encoder behind; dropping message body");
+                self.tx.send(frame)
+            }
+            Err(mpsc::TrySendError::Disconnected(frame)) => Err(mpsc::SendError(frame)),
+        }
+    }
+}
+
+/// Bytes received on one topic since the subscriber started, counting
+/// oversized bodies at their full size.
+pub struct TopicTraffic {
+    pub messages: u64,
+    pub bytes: u64,
+    pub rate: RollingRate,
+}
+
+impl Default for TopicTraffic {
+    fn default() -> Self {
+        Self {
+            messages: 0,
+            bytes: 0,
+            rate: RollingRate::new(TOPIC_RATE_WINDOW_MS),
+        }
+    }
 }
 
 pub struct ZmqState {
@@ -24,6 +156,10 @@ pub struct ZmqState {
     pub buffer_limit: usize,
     pub next_cursor: u64,
     pub messages: VecDeque<ZmqMessage>,
+    pub topics: BTreeMap<String, TopicTraffic>,
+    /// Consecutive failed connections since the last received message.
+    pub reconnect_attempts: u32,
+    pub last_error: Option<String>,
 }
 
 impl Default for ZmqState {
@@ -34,13 +170,77 @@ impl Default for ZmqState {
             buffer_limit: crate::rpc::DEFAULT_ZMQ_BUFFER_LIMIT,
             next_cursor: 1,
             messages: VecDeque::new(),
+            topics: BTreeMap::new(),
+            reconnect_attempts: 0,
+            last_error: None,
         }
     }
 }
 
+impl ZmqState {
+    /// Per-topic counts and the current byte rate.
+    pub fn topics_json(&self, now_ms: u64) -> serde_json::Value {
+        self.topics
+            .iter()
+            .map(|(topic, t)| {
+                let traffic = serde_json::json!({
+                    "messages": t.messages,
+                    "bytes": t.bytes,
+                    "bytes_per_sec": t.rate.per_second(now_ms),
+                });
+                (topic.clone(), traffic)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    /// Cursor of the oldest message still held. Every cursor before it has
+    /// been evicted, including all of them when the buffer is empty.
+    fn oldest_cursor(&self) -> u64 {
+        self.messages.front().map_or(self.next_cursor, |m| m.cursor)
+    }
+
+    /// The `/zmq/messages` reply. `truncated` means messages after `since`
+    /// were evicted before the caller saw them. A `limit` keeps the newest
+    /// messages, still in cursor order, and sets `has_more` when older
+    /// ones were left out.
+    pub fn messages_json(&self, query: MessagesQuery, now_ms: u64) -> serde_json::Value {
+        let oldest = self.oldest_cursor();
+        let start = self.messages.partition_point(|m| m.cursor <= query.since);
+        let pending = self.messages.len() - start;
+        let mut reply = serde_json::json!({
+            "connected": self.connected,
+            "address": self.address,
+            "buffer_limit": self.buffer_limit,
+            "buffered": self.messages.len(),
+            "oldest_cursor": oldest,
+            "cursor": self.next_cursor.saturating_sub(1),
+            "truncated": query.since > 0 && query.since.saturating_add(1) < oldest,
+            "pending": pending,
+            "topics": self.topics_json(now_ms),
+            "reconnect_attempts": self.reconnect_attempts,
+            "last_error": self.last_error,
+        });
+        if !query.summary_only {
+            let skip = query.limit.map_or(0, |limit| pending.saturating_sub(limit));
+            let messages: Vec<_> = self
+                .messages
+                .range(start + skip..)
+                .map(ZmqMessage::to_json)
+                .collect();
+            reply["messages"] = messages.into();
+            reply["has_more"] = (skip > 0).into();
+        }
+        reply
+    }
+}
+
 pub struct ZmqSharedState {
     pub state: Mutex<ZmqState>,
     pub changed: Condvar,
+    /// Bumped for every subscriber started. A thread whose own generation no
+    /// longer matches has been superseded and exits without touching `state`.
+    generation: AtomicU64,
 }
 
 impl Default for ZmqSharedState {
@@ -48,115 +248,287 @@ impl Default for ZmqSharedState {
         Self {
             state: Mutex::new(ZmqState::default()),
             changed: Condvar::new(),
+            generation: AtomicU64::new(0),
         }
     }
 }
 
+impl ZmqSharedState {
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+}
+
 pub struct ZmqHandle {
+    #[cfg(test)]
+    generation: u64,
     shutdown: Arc<AtomicBool>,
+    reconnect_now: Arc<AtomicBool>,
     thread: std::thread::JoinHandle<()>,
 }
 
+impl ZmqHandle {
+    #[cfg(test)]
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Cuts a back-off wait short; does nothing while connected.
+    pub fn reconnect_now(&self) {
+        self.reconnect_now.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Delay before the next reconnect: 1 s, doubling per failure up to 30 s.
+struct Backoff {
+    next: Duration,
+}
+
+impl Backoff {
+    fn new() -> Self {
+        Self {
+            next: INITIAL_RECONNECT_DELAY,
+        }
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(MAX_RECONNECT_DELAY);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.next = INITIAL_RECONNECT_DELAY;
+    }
+}
+
+/// How a connection ended.
+enum SessionEnd {
+    Shutdown,
+    Failed(String),
+}
+
+/// Applies a `/config` message and restarts the subscriber if the address
+/// changed. The handle lock is held from the config decision through the stop
+/// and start, so concurrent updates restart in the order they were applied
+/// and the running subscriber always matches the stored address.
+pub fn apply_config(
+    body: &str,
+    config: &Arc<Mutex<RpcConfig>>,
+    state: &Arc<ZmqSharedState>,
+    handle: &Mutex<Option<ZmqHandle>>,
+) -> ConfigUpdateResult {
+    let mut handle = handle.lock().unwrap();
+    let result = rpc::update_config(body, config);
+    {
+        let mut s = state.state.lock().unwrap();
+        s.buffer_limit = result.zmq_buffer_limit;
+        while s.messages.len() > s.buffer_limit {
+            s.messages.pop_front();
+        }
+    }
+    if result.zmq_changed {
+        if let Some(h) = handle.take() {
+            stop_zmq_subscriber(h);
+        }
+        if !result.zmq_address.is_empty() {
+            *handle = Some(start_zmq_subscriber(&result.zmq_address, Arc::clone(state)));
+        }
+    }
+    result
+}
+
 pub fn start_zmq_subscriber(address: &str, state: Arc<ZmqSharedState>) -> ZmqHandle {
     let shutdown = Arc::new(AtomicBool::new(false));
+    let reconnect_now = Arc::new(AtomicBool::new(false));
     let flag = Arc::clone(&shutdown);
+    let wake = Arc::clone(&reconnect_now);
     let addr = address.to_string();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
 
     let thread = std::thread::spawn(move || {
         let ctx = zmq2::Context::new();
-        let socket = match ctx.socket(zmq2::SUB) {
-            Ok(s) => s,
-            Err(e) => {
-                warn!(error = %e, "failed to create ZMQ subscriber socket");
+        {
+            let mut s = state.state.lock().unwrap();
+            if !state.is_current(generation) {
                 return;
             }
-        };
-
-        socket.set_rcvtimeo(500).ok();
-        let rcvhwm = zmq_socket_rcvhwm();
-        if socket.set_rcvhwm(rcvhwm).is_err() {
-            warn!(rcvhwm, "failed to apply ZMQ subscriber rcvhwm");
-        } else {
-            debug!(rcvhwm, "configured ZMQ subscriber rcvhwm");
+            s.topics.clear();
         }
-        for topic in &["hashblock", "hashtx"] {
-            socket.set_subscribe(topic.as_bytes()).ok();
-        }
-
-        if let Err(e) = socket.connect(&addr) {
-            warn!(address = %addr, error = %e, "failed to connect ZMQ subscriber");
-            return;
+        let (frames, encoder) = spawn_encoder(
+            Arc::clone(&state),
+            generation,
+            ENCODER_QUEUE_FRAMES,
+            ENCODER_QUEUE_BYTES,
+        );
+        let mut backoff = Backoff::new();
+        loop {
+            match run_session(
+                &ctx,
+                &addr,
+                &state,
+                generation,
+                &flag,
+                &frames,
+                &mut backoff,
+            ) {
+                SessionEnd::Shutdown => break,
+                SessionEnd::Failed(error) => {
+                    let delay = backoff.next_delay();
+                    {
+                        let mut s = state.state.lock().unwrap();
+                        if !state.is_current(generation) {
+                            break;
+                        }
+                        mark_disconnected(&mut s);
+                        s.reconnect_attempts = s.reconnect_attempts.saturating_add(1);
+                        s.last_error = Some(error);
+                    }
+                    state.changed.notify_all();
+                    debug!(
+                        delay_ms = delay.as_millis() as u64,
+                        "waiting to reconnect ZMQ subscriber"
+                    );
+                    wake.store(false, Ordering::Relaxed);
+                    if !wait_to_reconnect(delay, &flag, &wake) {
+                        break;
+                    }
+                }
+            }
         }
+        drop(frames);
+        let _ = encoder.join();
 
-        debug!(address = %addr, "connected ZMQ subscriber");
         {
             let mut s = state.state.lock().unwrap();
-            s.connected = true;
-            s.address = addr;
+            if state.is_current(generation) {
+                mark_disconnected(&mut s);
+            }
         }
         state.changed.notify_all();
+        debug!(generation, "stopped ZMQ subscriber");
+    });
 
-        while !flag.load(Ordering::Relaxed) {
-            let parts = match socket.recv_multipart(0) {
-                Ok(p) => p,
-                Err(zmq2::Error::EAGAIN) => continue,
-                Err(e) => {
-                    warn!(error = %e, "ZMQ receive error");
-                    break;
-                }
-            };
+    ZmqHandle {
+        #[cfg(test)]
+        generation,
+        shutdown,
+        reconnect_now,
+        thread,
+    }
+}
 
-            if parts.len() < 3 {
-                continue;
-            }
+/// One socket's lifetime: connect, then receive until shutdown or an error.
+fn run_session(
+    ctx: &zmq2::Context,
+    addr: &str,
+    state: &ZmqSharedState,
+    generation: u64,
+    flag: &AtomicBool,
+    frames: &FrameSender,
+    backoff: &mut Backoff,
+) -> SessionEnd {
+    let socket = match ctx.socket(zmq2::SUB) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!(error = %e, "failed to create ZMQ subscriber socket");
+            return SessionEnd::Failed(e.to_string());
+        }
+    };
 
-            let topic = String::from_utf8_lossy(&parts[0]).to_string();
-            let body = &parts[1];
-            let body_hex = hex_encode(&body[..body.len().min(80)]);
-            let event_hash = (body.len() >= 32).then(|| hash_from_notification(body));
-            let body_size = body.len();
-            let sequence = if parts[2].len() >= 4 {
-                u32::from_le_bytes([parts[2][0], parts[2][1], parts[2][2], parts[2][3]])
-            } else {
-                0
-            };
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+    socket.set_rcvtimeo(500).ok();
+    let rcvhwm = zmq_socket_rcvhwm();
+    if socket.set_rcvhwm(rcvhwm).is_err() {
+        warn!(rcvhwm, "failed to apply ZMQ subscriber rcvhwm");
+    } else {
+        debug!(rcvhwm, "configured ZMQ subscriber rcvhwm");
+    }
+    socket.set_heartbeat_ivl(HEARTBEAT_IVL_MS).ok();
+    socket.set_heartbeat_timeout(HEARTBEAT_TIMEOUT_MS).ok();
+    for topic in &SUBSCRIBED_TOPICS {
+        socket.set_subscribe(topic.as_bytes()).ok();
+    }
 
-            let mut s = state.state.lock().unwrap();
-            let limit = s.buffer_limit.clamp(
-                crate::rpc::MIN_ZMQ_BUFFER_LIMIT,
-                crate::rpc::MAX_ZMQ_BUFFER_LIMIT,
-            );
-            if s.messages.len() >= limit {
-                s.messages.pop_front();
+    if let Err(e) = socket.connect(addr) {
+        warn!(address = %addr, error = %e, "failed to connect ZMQ subscriber");
+        return SessionEnd::Failed(e.to_string());
+    }
+
+    debug!(address = %addr, generation, "connected ZMQ subscriber");
+    {
+        let mut s = state.state.lock().unwrap();
+        if !state.is_current(generation) {
+            return SessionEnd::Shutdown;
+        }
+        s.connected = true;
+        s.address = addr.to_string();
+    }
+    state.changed.notify_all();
+
+    let max_message_bytes = zmq_max_message_bytes();
+    let mut last_message: Option<Instant> = None;
+    while !flag.load(Ordering::Relaxed) && state.is_current(generation) {
+        let parts = match socket.recv_multipart(0) {
+            Ok(p) => p,
+            Err(zmq2::Error::EAGAIN) => {
+                if is_silent(last_message.map(|at| at.elapsed())) {
+                    warn!(address = %addr, "no ZMQ messages for a while, reconnecting");
+                    let secs = SILENCE_RECONNECT_AFTER.as_secs();
+                    return SessionEnd::Failed(format!("no messages for {secs} s"));
+                }
+                continue;
             }
-            let cursor = s.next_cursor;
-            s.next_cursor = s.next_cursor.saturating_add(1);
-            s.messages.push_back(ZmqMessage {
-                cursor,
-                topic,
-                body_hex,
-                body_size,
-                sequence,
-                timestamp,
-                event_hash,
-            });
-            drop(s);
-            state.changed.notify_all();
+            Err(e) => {
+                warn!(error = %e, "ZMQ receive error");
+                return SessionEnd::Failed(e.to_string());
+            }
+        };
+        if last_message.is_none() {
+            backoff.reset();
+            state.state.lock().unwrap().reconnect_attempts = 0;
         }
+        last_message = Some(Instant::now());
 
-        {
-            let mut s = state.state.lock().unwrap();
-            mark_disconnected(&mut s);
+        let Some(frame) = frame_from_parts(parts, max_message_bytes) else {
+            continue;
+        };
+        if frame.oversized {
+            warn!(
+                topic = %frame.topic,
+                bytes = frame.body_size,
+                max_message_bytes,
+                "dropping oversized ZMQ message body"
+            );
         }
-        state.changed.notify_all();
-        debug!("stopped ZMQ subscriber");
-    });
+        if frames.send(frame).is_err() {
+            return SessionEnd::Shutdown;
+        }
+    }
+    SessionEnd::Shutdown
+}
 
-    ZmqHandle { shutdown, thread }
+/// Silence only counts once the session has received something, so a node
+/// that never publishes isn't reconnected over and over.
+fn is_silent(since_last_message: Option<Duration>) -> bool {
+    since_last_message.is_some_and(|quiet| quiet >= SILENCE_RECONNECT_AFTER)
+}
+
+/// Sleeps for `delay` unless shut down (false) or asked to reconnect now.
+fn wait_to_reconnect(delay: Duration, shutdown: &AtomicBool, wake: &AtomicBool) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        if shutdown.load(Ordering::Relaxed) {
+            return false;
+        }
+        if wake.swap(false, Ordering::Relaxed) {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        std::thread::sleep(RECONNECT_WAIT_TICK.min(deadline - now));
+    }
 }
 
 pub fn stop_zmq_subscriber(handle: ZmqHandle) {
@@ -164,17 +536,113 @@ pub fn stop_zmq_subscriber(handle: ZmqHandle) {
     let _ = handle.thread.join();
 }
 
-fn hex_encode(data: &[u8]) -> String {
-    use std::fmt::Write;
-    let mut s = String::with_capacity(data.len() * 2);
-    for &b in data {
-        write!(s, "{b:02x}").unwrap();
+fn frame_from_parts(mut parts: Vec<Vec<u8>>, max_message_bytes: usize) -> Option<RawFrame> {
+    if parts.len() < 3 {
+        return None;
     }
-    s
+    let sequence = if parts[2].len() >= 4 {
+        u32::from_le_bytes([parts[2][0], parts[2][1], parts[2][2], parts[2][3]])
+    } else {
+        0
+    };
+    let mut body = std::mem::take(&mut parts[1]);
+    let body_size = body.len();
+    let oversized = body_size > max_message_bytes;
+    if oversized {
+        body = Vec::new();
+    }
+    let received = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Some(RawFrame {
+        topic: String::from_utf8_lossy(&parts[0]).to_string(),
+        body,
+        body_size,
+        oversized,
+        backlogged: false,
+        sequence,
+        timestamp: received.as_secs(),
+        timestamp_ms: received.as_millis() as u64,
+    })
+}
+
+/// Encoding happens on its own thread so the receive loop never waits on hex
+/// formatting or the state lock. Frames are processed in arrival order, which
+/// keeps cursors monotonic. The queue is bounded so a publisher that outpaces
+/// the encoder can't grow memory without limit.
+fn spawn_encoder(
+    state: Arc<ZmqSharedState>,
+    generation: u64,
+    max_frames: usize,
+    max_bytes: usize,
+) -> (FrameSender, std::thread::JoinHandle<()>) {
+    let (tx, rx) = mpsc::sync_channel::<RawFrame>(max_frames);
+    let queued_bytes = Arc::new(AtomicUsize::new(0));
+    let drained = Arc::clone(&queued_bytes);
+    let handle = std::thread::spawn(move || {
+        for frame in rx {
+            drained.fetch_sub(frame.body.len(), Ordering::AcqRel);
+            push_frame(&state, generation, frame);
+        }
+    });
+    let sender = FrameSender {
+        tx,
+        queued_bytes,
+        max_bytes,
+    };
+    (sender, handle)
+}
+
+/// Frames from a superseded subscriber are dropped.
+fn push_frame(state: &ZmqSharedState, generation: u64, frame: RawFrame) {
+    let body_hex = hex::encode_prefix(&frame.body, BODY_HEX_PREFIX_BYTES);
+    let event_hash = match frame.topic.as_str() {
+        "rawtx" => local_decode::txid(&frame.body),
+        "rawblock" => local_decode::block_hash(&frame.body),
+        _ => (frame.body.len() >= 32).then(|| hash_from_notification(&frame.body)),
+    };
+    let body_full_hex = (matches!(frame.topic.as_str(), "rawblock" | "rawtx")
+        && !frame.body.is_empty())
+    .then(|| hex::encode(&frame.body));
+
+    let mut s = state.state.lock().unwrap();
+    if !state.is_current(generation) {
+        return;
+    }
+    let traffic = s.topics.entry(frame.topic.clone()).or_default();
+    traffic.messages += 1;
+    traffic.bytes += frame.body_size as u64;
+    traffic
+        .rate
+        .record(frame.timestamp_ms, frame.body_size as u64);
+    let limit = s.buffer_limit.clamp(
+        crate::rpc::MIN_ZMQ_BUFFER_LIMIT,
+        crate::rpc::MAX_ZMQ_BUFFER_LIMIT,
+    );
+    while s.messages.len() >= limit {
+        s.messages.pop_front();
+    }
+    let cursor = s.next_cursor;
+    s.next_cursor = s.next_cursor.saturating_add(1);
+    s.messages.push_back(ZmqMessage {
+        cursor,
+        topic: frame.topic,
+        body_hex,
+        body_size: frame.body_size,
+        sequence: frame.sequence,
+        timestamp: frame.timestamp,
+        timestamp_ms: frame.timestamp_ms,
+        event_hash,
+        oversized: frame.oversized,
+        backlogged: frame.backlogged,
+        body_full_hex,
+    });
+    drop(s);
+    state.changed.notify_all();
 }
 
 fn hash_from_notification(bytes: &[u8]) -> String {
-    hex_encode(&bytes[..32])
+    hex::encode(&bytes[..32])
 }
 
 fn mark_disconnected(state: &mut ZmqState) {
@@ -190,9 +658,172 @@ fn zmq_socket_rcvhwm() -> i32 {
         .clamp(MIN_ZMQ_SOCKET_RCVHWM, MAX_ZMQ_SOCKET_RCVHWM)
 }
 
+fn zmq_max_message_bytes() -> usize {
+    std::env::var("ZMQ_MAX_MESSAGE_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_ZMQ_MAX_MESSAGE_BYTES)
+        .clamp(MIN_ZMQ_MAX_MESSAGE_BYTES, MAX_ZMQ_MAX_MESSAGE_BYTES)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ZmqState, mark_disconnected};
+    use super::{
+        Backoff, ENCODER_QUEUE_BYTES, ENCODER_QUEUE_FRAMES, MessagesQuery, SILENCE_RECONNECT_AFTER,
+        ZmqMessage, ZmqSharedState, ZmqState, apply_config, frame_from_parts, is_silent,
+        mark_disconnected, push_frame, spawn_encoder, start_zmq_subscriber, stop_zmq_subscriber,
+        wait_to_reconnect,
+    };
+    use crate::rpc::RpcConfig;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// Waits up to 5 s for the subscriber state to satisfy `done`.
+    fn wait_for(state: &ZmqSharedState, done: impl Fn(&ZmqState) -> bool) -> bool {
+        let guard = state.state.lock().unwrap();
+        let (guard, _) = state
+            .changed
+            .wait_timeout_while(guard, Duration::from_secs(5), |s| !done(s))
+            .unwrap();
+        done(&guard)
+    }
+
+    fn parts(topic: &str, body: Vec<u8>, sequence: u32) -> Vec<Vec<u8>> {
+        vec![
+            topic.as_bytes().to_vec(),
+            body,
+            sequence.to_le_bytes().to_vec(),
+        ]
+    }
+
+    /// A buffer holding `cursors` after everything before them was evicted.
+    fn buffer(cursors: std::ops::RangeInclusive<u64>) -> ZmqState {
+        let mut state = ZmqState {
+            next_cursor: cursors.end() + 1,
+            ..ZmqState::default()
+        };
+        for cursor in cursors {
+            state.messages.push_back(ZmqMessage {
+                cursor,
+                topic: "hashtx".to_string(),
+                body_hex: String::new(),
+                body_size: 32,
+                sequence: cursor as u32,
+                timestamp: 0,
+                timestamp_ms: 0,
+                event_hash: None,
+                oversized: false,
+                backlogged: false,
+                body_full_hex: None,
+            });
+        }
+        state
+    }
+
+    fn since(since: u64) -> MessagesQuery {
+        MessagesQuery {
+            since,
+            ..MessagesQuery::default()
+        }
+    }
+
+    fn cursors(reply: &serde_json::Value) -> Vec<u64> {
+        reply["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["cursor"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn truncated_means_unseen_messages_were_evicted() {
+        let state = buffer(11..=20);
+        let reply = state.messages_json(since(0), 0);
+        assert_eq!(cursors(&reply), (11..=20).collect::<Vec<_>>());
+        assert_eq!(
+            reply["truncated"], false,
+            "a first poll has nothing missing"
+        );
+        assert_eq!(reply["cursor"], 20);
+        assert_eq!(reply["oldest_cursor"], 11);
+
+        assert_eq!(state.messages_json(since(10), 0)["truncated"], false);
+        assert_eq!(state.messages_json(since(9), 0)["truncated"], true);
+        assert_eq!(state.messages_json(since(15), 0)["truncated"], false);
+
+        // Caught up: nothing new, nothing lost.
+        let reply = state.messages_json(since(20), 0);
+        assert!(cursors(&reply).is_empty());
+        assert_eq!(reply["truncated"], false);
+
+        // Everything the caller hadn't seen is gone and nothing replaced it.
+        let mut drained = buffer(11..=20);
+        drained.messages.clear();
+        let reply = drained.messages_json(since(5), 0);
+        assert_eq!(reply["truncated"], true);
+        assert_eq!(reply["cursor"], 20);
+        assert_eq!(drained.messages_json(since(20), 0)["truncated"], false);
+        assert_eq!(ZmqState::default().messages_json(since(0), 0)["cursor"], 0);
+    }
+
+    #[test]
+    fn limit_keeps_the_newest_messages_in_cursor_order() {
+        let state = buffer(1..=10);
+        let limited = |since, limit| {
+            state.messages_json(
+                MessagesQuery {
+                    since,
+                    limit: Some(limit),
+                    summary_only: false,
+                },
+                0,
+            )
+        };
+        let reply = limited(0, 3);
+        assert_eq!(cursors(&reply), [8, 9, 10]);
+        assert_eq!(reply["has_more"], true);
+        assert_eq!(reply["pending"], 10);
+        assert_eq!(reply["cursor"], 10);
+
+        let reply = limited(7, 3);
+        assert_eq!(cursors(&reply), [8, 9, 10]);
+        assert_eq!(reply["has_more"], false);
+        assert_eq!(limited(7, 50)["has_more"], false);
+
+        let reply = limited(4, 0);
+        assert!(cursors(&reply).is_empty());
+        assert_eq!(reply["has_more"], true);
+        assert_eq!(state.messages_json(since(0), 0)["has_more"], false);
+    }
+
+    #[test]
+    fn summary_leaves_out_the_messages() {
+        let mut state = buffer(5..=9);
+        state.connected = true;
+        state
+            .topics
+            .entry("hashtx".to_string())
+            .or_default()
+            .messages = 9;
+        let reply = state.messages_json(
+            MessagesQuery {
+                since: 6,
+                limit: Some(1),
+                summary_only: true,
+            },
+            0,
+        );
+        assert!(reply.get("messages").is_none());
+        assert!(reply.get("has_more").is_none());
+        assert_eq!(reply["connected"], true);
+        assert_eq!(reply["cursor"], 9);
+        assert_eq!(reply["pending"], 3);
+        assert_eq!(reply["buffered"], 5);
+        assert_eq!(reply["truncated"], false);
+        assert_eq!(reply["topics"]["hashtx"]["messages"], 9);
+    }
 
     #[test]
     fn disconnect_clears_connection_address() {
@@ -205,4 +836,276 @@ mod tests {
         assert!(!state.connected);
         assert!(state.address.is_empty());
     }
+
+    #[test]
+    fn oversized_frames_keep_metadata_only() {
+        let frame = frame_from_parts(parts("rawblock", vec![0xab; 4096], 7), 1024).unwrap();
+        assert!(frame.oversized);
+        assert!(frame.body.is_empty());
+        assert_eq!(frame.body_size, 4096);
+        assert_eq!(frame.sequence, 7);
+
+        let state = ZmqSharedState::default();
+        push_frame(&state, 0, frame);
+        let s = state.state.lock().unwrap();
+        let msg = s.messages.back().unwrap();
+        assert!(msg.oversized);
+        assert_eq!(msg.topic, "rawblock");
+        assert_eq!(msg.body_size, 4096);
+        assert!(msg.body_hex.is_empty());
+        assert!(msg.event_hash.is_none());
+    }
+
+    #[test]
+    fn frames_within_cap_are_encoded() {
+        let frame = frame_from_parts(parts("hashblock", vec![0x11; 32], 1), 1024).unwrap();
+        assert!(!frame.oversized);
+        let state = ZmqSharedState::default();
+        push_frame(&state, 0, frame);
+        let s = state.state.lock().unwrap();
+        let msg = s.messages.back().unwrap();
+        assert_eq!(msg.event_hash.as_deref(), Some("11".repeat(32).as_str()));
+        assert_eq!(msg.body_hex.len(), 64);
+    }
+
+    #[test]
+    fn raw_topics_keep_the_full_body() {
+        let state = ZmqSharedState::default();
+        push_frame(
+            &state,
+            0,
+            frame_from_parts(parts("rawtx", vec![0x22; 200], 1), 1024).unwrap(),
+        );
+        push_frame(
+            &state,
+            0,
+            frame_from_parts(parts("hashtx", vec![0x33; 32], 2), 1024).unwrap(),
+        );
+        let s = state.state.lock().unwrap();
+        assert_eq!(
+            s.messages[0].body_full_hex.as_ref().map(String::len),
+            Some(400)
+        );
+        assert_eq!(s.messages[0].body_hex.len(), 160);
+        // Not a valid transaction, so there is no txid to show.
+        assert!(s.messages[0].event_hash.is_none());
+        assert!(s.messages[1].body_full_hex.is_none());
+    }
+
+    #[test]
+    fn topic_bytes_accumulate_per_topic() {
+        let state = ZmqSharedState::default();
+        let frame = |topic: &str, size: usize, ms: u64| {
+            let mut f = frame_from_parts(parts(topic, vec![0; size], 0), 1024).unwrap();
+            f.timestamp_ms = ms;
+            f
+        };
+        for i in 0..10 {
+            push_frame(&state, 0, frame("rawtx", 250, 1_000_000 + i * 100));
+        }
+        push_frame(&state, 0, frame("hashtx", 32, 1_000_500));
+        // Oversized bodies are dropped but still count at their full size.
+        push_frame(&state, 0, frame("rawblock", 4096, 1_001_000));
+
+        let s = state.state.lock().unwrap();
+        assert_eq!(s.topics.len(), 3);
+        assert_eq!(s.topics["rawtx"].messages, 10);
+        assert_eq!(s.topics["rawtx"].bytes, 2500);
+        assert_eq!(s.topics["hashtx"].bytes, 32);
+        assert_eq!(s.topics["rawblock"].bytes, 4096);
+        assert_eq!(s.topics["rawtx"].rate.per_second(1_001_000), 2500.0);
+        assert_eq!(s.topics["rawtx"].rate.per_second(1_100_000), 0.0);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_thirty_seconds_and_resets() {
+        let mut backoff = Backoff::new();
+        let secs: Vec<u64> = (0..8).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(secs, [1, 2, 4, 8, 16, 30, 30, 30]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn silence_counts_only_after_a_message() {
+        assert!(!is_silent(None));
+        assert!(!is_silent(Some(Duration::ZERO)));
+        assert!(!is_silent(Some(Duration::from_secs(5))));
+        assert!(is_silent(Some(SILENCE_RECONNECT_AFTER)));
+    }
+
+    #[test]
+    fn reconnect_wait_ends_early_on_request_or_shutdown() {
+        let start = Instant::now();
+        let wake = AtomicBool::new(true);
+        assert!(wait_to_reconnect(
+            Duration::from_secs(60),
+            &AtomicBool::new(false),
+            &wake
+        ));
+        assert!(!wait_to_reconnect(
+            Duration::from_secs(60),
+            &AtomicBool::new(true),
+            &AtomicBool::new(false)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn short_multipart_messages_are_ignored() {
+        assert!(frame_from_parts(vec![b"hashtx".to_vec()], 1024).is_none());
+    }
+
+    #[test]
+    fn burst_is_accepted_while_encoder_is_blocked() {
+        let state = Arc::new(ZmqSharedState::default());
+        let (tx, encoder) = spawn_encoder(
+            Arc::clone(&state),
+            0,
+            ENCODER_QUEUE_FRAMES,
+            ENCODER_QUEUE_BYTES,
+        );
+
+        // Hold the state lock so the encoder cannot make progress; the
+        // subscriber side must still be able to hand off the whole burst.
+        let guard = state.state.lock().unwrap();
+        let start = Instant::now();
+        for i in 0..500 {
+            let frame = frame_from_parts(parts("hashtx", vec![i as u8; 64], i), 1024).unwrap();
+            tx.send(frame).unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+        drop(guard);
+
+        drop(tx);
+        encoder.join().unwrap();
+        let s = state.state.lock().unwrap();
+        assert_eq!(s.messages.len(), 500);
+        assert!(
+            s.messages
+                .iter()
+                .zip(s.messages.iter().skip(1))
+                .all(|(a, b)| b.cursor == a.cursor + 1 && b.sequence == a.sequence + 1)
+        );
+    }
+
+    #[test]
+    fn bodies_over_the_queue_byte_limit_are_dropped() {
+        let state = Arc::new(ZmqSharedState::default());
+        let (tx, encoder) = spawn_encoder(Arc::clone(&state), 0, 16, 100);
+        let guard = state.state.lock().unwrap();
+        for i in 0..3 {
+            let frame = frame_from_parts(parts("rawtx", vec![0xab; 60], i), 1024).unwrap();
+            tx.send(frame).unwrap();
+        }
+        drop(guard);
+        drop(tx);
+        encoder.join().unwrap();
+
+        let s = state.state.lock().unwrap();
+        let kept: Vec<_> = s.messages.iter().map(|m| !m.backlogged).collect();
+        assert_eq!(kept, [true, false, false]);
+        assert!(s.messages[0].body_full_hex.is_some());
+        assert!(s.messages[1].body_hex.is_empty() && s.messages[1].body_full_hex.is_none());
+        assert_eq!(s.messages[2].body_size, 60);
+        assert_eq!(s.topics["rawtx"].bytes, 180);
+    }
+
+    #[test]
+    fn a_full_queue_records_metadata_only() {
+        let state = Arc::new(ZmqSharedState::default());
+        let (tx, encoder) = spawn_encoder(Arc::clone(&state), 0, 2, ENCODER_QUEUE_BYTES);
+        let guard = state.state.lock().unwrap();
+        let sender = std::thread::spawn(move || {
+            for i in 0..8 {
+                let frame = frame_from_parts(parts("hashtx", vec![i as u8; 32], i), 1024).unwrap();
+                tx.send(frame).unwrap();
+            }
+        });
+        // The queue holds two frames and the encoder, blocked on the lock,
+        // at most one more. The next frame finds the queue full and waits,
+        // without its body, for room.
+        std::thread::sleep(Duration::from_millis(500));
+        drop(guard);
+        sender.join().unwrap();
+        encoder.join().unwrap();
+
+        let s = state.state.lock().unwrap();
+        let sequences: Vec<_> = s.messages.iter().map(|m| m.sequence).collect();
+        assert_eq!(sequences, (0..8).collect::<Vec<_>>());
+        let first = s.messages.iter().position(|m| m.backlogged);
+        assert!(matches!(first, Some(2 | 3)), "{first:?}");
+        let msg = &s.messages[first.unwrap()];
+        assert!(msg.event_hash.is_none());
+        assert_eq!(msg.body_size, 32);
+        assert!(s.messages[0].event_hash.is_some());
+    }
+
+    #[test]
+    fn superseded_subscriber_exits_without_touching_state() {
+        let state = Arc::new(ZmqSharedState::default());
+        let old = start_zmq_subscriber("tcp://127.0.0.1:29001", Arc::clone(&state));
+        // Simulates a restart that failed to stop the previous subscriber.
+        let new = start_zmq_subscriber("tcp://127.0.0.1:29002", Arc::clone(&state));
+        assert_eq!(new.generation(), old.generation() + 1);
+        assert!(wait_for(&state, |s| s.address == "tcp://127.0.0.1:29002"));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !old.thread.is_finished() {
+            assert!(Instant::now() < deadline, "old subscriber kept running");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let frame = frame_from_parts(parts("hashtx", vec![0; 32], 1), 1024).unwrap();
+        push_frame(&state, old.generation(), frame);
+        {
+            let s = state.state.lock().unwrap();
+            assert!(s.messages.is_empty());
+            assert!(s.connected);
+            assert_eq!(s.address, "tcp://127.0.0.1:29002");
+        }
+        stop_zmq_subscriber(old);
+        stop_zmq_subscriber(new);
+        assert!(!state.state.lock().unwrap().connected);
+    }
+
+    #[test]
+    fn interleaved_config_updates_leave_one_matching_subscriber() {
+        let cfg = Arc::new(Mutex::new(RpcConfig::default()));
+        let state = Arc::new(ZmqSharedState::default());
+        let handle = Arc::new(Mutex::new(None));
+        let writers: Vec<_> = [29010, 29020]
+            .into_iter()
+            .map(|port| {
+                let (cfg, state, handle) = (cfg.clone(), state.clone(), handle.clone());
+                std::thread::spawn(move || {
+                    for addr in ["", "tcp://127.0.0.1:"] {
+                        let addr = if addr.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{addr}{port}")
+                        };
+                        let body = serde_json::json!({ "zmq_address": addr }).to_string();
+                        apply_config(&body, &cfg, &state, &handle);
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let address = cfg.lock().unwrap().zmq_address.clone();
+        assert!(address.ends_with("29010") || address.ends_with("29020"));
+        let running = handle
+            .lock()
+            .unwrap()
+            .take()
+            .expect("a subscriber is running");
+        assert_eq!(
+            running.generation(),
+            state.generation.load(Ordering::SeqCst)
+        );
+        assert!(wait_for(&state, |s| s.connected && s.address == address));
+        stop_zmq_subscriber(running);
+    }
 }
@@ -10,25 +10,191 @@ let dashboardFetchInFlight = false;
 let dashboardFetchQueued = false;
 let zmqRefreshTimer = null;
 let zmqMessageLookup = new Map();
+let zmqDetailCache = new Map();
+let zmqDetailAction = null;
 let zmqRenderTimer = null;
 let dashboardPollingGeneration = 0;
 let zmqPollingGeneration = 0;
 let pendingDashboardParts = new Set();
 let pendingZmqMessages = [];
+let zmqRecentMessages = [];
+let zmqTopicFilter = new Set();
+let zmqTopicTotals = {};
+let zmqFilterAddress = null;
+let consoleDraftTimer = null;
+let nodeLogTimer = null;
+let nodeLogGeneration = 0;
+let nodeLogCursor = 0;
+let nodeLogPath = null;
 let peerById = new Map();
 let peerRows = new Map();
 let lastZmqCursor = 0;
 let lastPeersRefreshMs = 0;
 let lastCelebratedHashblockCursor = 0;
 let celebrationAudioCtx = null;
+let unbroadcastSinceMs = 0;
+let lastUnbroadcastCheckMs = 0;
+let unbroadcastCheckInFlight = false;
+let lastViewSaveTimer = null;
+let pendingBlockLatency = null;
+let blockLatencySamples = [];
+let lastResultValue;
+let lastResultTreeState = null;
+let resultRenderGeneration = 0;
+let executeGeneration = 0;
+let executeTabId = null;
+let resultTabs = [];
+let activeResultTabId = null;
+let resultTabSeq = 0;
+let rpcHistory = [];
+let customCards = [];
+let customCardState = new Map();
+let customCardsInFlight = false;
+let customCardsQueued = null;
+let customCardsTip = null;
+let customCardEditing = null;
+let customCardsFallbackPending = null;
+let keyBindings = [];
+let keyBindingActions = new Map();
+let keyCaptureAction = null;
+let jsonViewMode = ["pretty", "compact"].includes(localStorage.getItem("ui-json-mode"))
+  ? localStorage.getItem("ui-json-mode")
+  : "tree";
+let connectionStatusText = "Disconnected";
+let connectionOk = false;
+let statusIndicators = null;
+let rpcQueueText = "";
+// Poll interval multiplier the backend raises while the node's RPC work
+// queue is full.
+let nodeBusyBackoff = 1;
+let fiatPriceSetAt = 0;
+let lastFeeEstimates = null;
+let peerView = null;
+let peerFilter = "";
+let changelog = null;
+let buildInfo = null;
+let statusPageAddress = null;
+let dashSections = null;
+let walletTxs = [];
+let walletsDisabled = false;
+let lastMempoolSnapshot = null;
+let trafficRecvSeries = null;
+let trafficSentSeries = null;
+let mempoolBytesSeries = null;
+let lastNetTotals = null;
+let lastTrafficRates = null;
+let graphWindows = null;
+let sparklines = {};
+let syncSamples = [];
+let nodeCompat = null;
+let relayPolicyKey = null;
+let peerFeeFloorKey = null;
+let dashboardHistory = [];
+let dashboardHistoryOffset = 0;
+let recentBlocks = [];
+let expandedBlockHash = null;
+const blockSummariesInFlight = new Set();
+let recentHeadersTip = null;
+let mempoolDeltas = [];
+let mempoolHistogramInFlight = false;
+let mempoolAncestorsInFlight = false;
+let lastMempoolHistogramMs = 0;
+let mempoolHistogramEstimated = false;
+let walletWatchOnly = false;
+let bumpState = null;
+let invalidatedBlocks = [];
+let peerDetail = null;
+let selectedPeerId = null;
+let usageMetricsEnabled = false;
+let usagePending = new Map();
+let usageView = null;
+let knownBlockHashes = [];
+let knownTxids = [];
+let chainDebugPending = null;
+let watchRequest = null;
+let composeState = null;
+let sectionStatusTimer = null;
+const DASHBOARD_SECTIONS = ["chain", "mempool", "fees", "network", "traffic", "mining", "peers"];
+const DASHBOARD_HISTORY_LIMIT = 60;
+// Network and Traffic share one fetch, so refreshing either updates both.
+const DASHBOARD_SECTION_GROUPS = {
+  chain: ["chain"],
+  mempool: ["mempool"],
+  fees: ["fees"],
+  network: ["network", "traffic"],
+  traffic: ["network", "traffic"],
+  mining: ["mining"],
+  peers: ["peers"],
+};
+const SECTION_STATUS_TICK_MS = 1000;
+const WALLET_TX_LIST_COUNT = 25;
+const MEMPOOL_DELTA_HISTORY = 5;
+// Sampling a large mempool still fetches every txid, so do it less often.
+const MEMPOOL_HISTOGRAM_SAMPLED_MIN_MS = 30_000;
+const WATCH_RANGE_END = 999;
+const RESULT_TAB_LIMIT = 8;
+const RPC_HISTORY_LIMIT = 50;
+const CONSOLE_DRAFT_DEBOUNCE_MS = 5000;
+const NODE_LOG_POLL_MS = 1000;
+const RPC_METHOD_NOT_FOUND = -32601;
+const WATCH_SCAN_POLL_MS = 2000;
 const ZMQ_FAST_POLL_MS = 250;
 const ZMQ_SLOW_POLL_MS = 2000;
 const DASHBOARD_ZMQ_FALLBACK_MS = 15_000;
 const DASHBOARD_PART_DEBOUNCE_MS = 250;
 const PEERS_REFRESH_MIN_MS = 10_000;
 const ZMQ_FEED_MAX_ROWS = 200;
+// Kept across topics so narrowing the filter still has rows to show.
+const ZMQ_RECENT_LIMIT = 2000;
+const ZMQ_FILTER_TOPICS = ["hashblock", "hashtx", "rawblock", "rawtx"];
 const ZMQ_LONG_POLL_WAIT_MS = 5_000;
 const ZMQ_RENDER_BATCH_MS = 200;
+const ZMQ_DETAIL_CACHE_LIMIT = 100;
+const UNBROADCAST_WARN_MS = 5 * 60_000;
+const UNBROADCAST_CHECK_MIN_MS = 30_000;
+const UNBROADCAST_MAX_WALLET_TXS = 25;
+const LAST_VIEW_SAVE_DEBOUNCE_MS = 500;
+const HEX_PATTERN = "^[0-9a-fA-F]*$";
+const KNOWN_IDS_LIMIT = 500;
+const PEER_BAN_SECS = 86400;
+const USAGE_FLUSH_MS = 10 * 60_000;
+const USAGE_METHODS_SHOWN = 20;
+const HEX_ID_SUGGEST_MIN = 4;
+const HEX_ID_SUGGEST_MAX = 6;
+const MAIN_VIEWS = ["dashboard", "peer-view", "method-view", "wallet-view", "mining-view", "ban-view", "log-view", "metrics-view", "exports-view"];
+const PEER_SORT_FIELDS = ["id", "addr", "subver", "direction", "perms", "ping", "bytessent", "bytesrecv", "minfeefilter"];
+const DEFAULT_PEER_VIEW = { sort: "id", desc: false, feeFilter: false };
+const PEER_PERMISSION_ABBREV = {
+  noban: "N",
+  bloomfilter: "B",
+  forcerelay: "F",
+  relay: "R",
+  mempool: "M",
+  download: "D",
+  addr: "A",
+};
+const PEER_RISKY_INBOUND_PERMISSIONS = ["forcerelay", "mempool"];
+const SERIES_RECENT_MS = 10 * 60_000;
+const SERIES_BUCKET_MS = 30_000;
+// 30 s buckets for 12 hours; a longer session drops its oldest points.
+const SERIES_MAX_OLD_POINTS = 12 * 120;
+const GRAPH_WINDOWS = { "5m": 5 * 60_000, "30m": 30 * 60_000, "2h": 2 * 3_600_000, session: Infinity };
+const GRAPH_DEFAULT_WINDOW = "30m";
+const SPARKLINE_SAMPLES = 240;
+// Change points only, so this covers hours outside IBD.
+const SYNC_SAMPLE_LIMIT = 200;
+const RECENT_BLOCKS_LIMIT = 20;
+const ORIGIN_MIN_CLEARNET_PEERS = 5;
+const ORIGIN_TOP_PREFIXES = 5;
+// Always listed, in bitcoin-cli -netinfo order, even when a node omits one.
+const REACHABILITY_NETWORKS = ["ipv4", "ipv6", "onion", "i2p", "cjdns"];
+const BLOCK_LATENCY_MAX_SAMPLES = 100;
+const JSON_HIGHLIGHT_MAX_CHARS = 2_000_000;
+const JSON_HIGHLIGHT_CHUNK_TOKENS = 4000;
+const JSON_TREE_OPEN_DEPTH = 2;
+const JSON_TREE_STRING_MAX = 120;
+const JSON_TREE_PAGE = 200;
+const CHANGELOG_AREAS = ["dashboard", "peers", "wallet", "rpc", "zmq", "cli"];
 
 function encodeHeaderJson(value) {
   return encodeURIComponent(JSON.stringify(value));
@@ -41,8 +207,19 @@ async function init() {
     const r = await fetch("/features");
     const j = await r.json();
     audioEnabled = j.audio !== false;
+    buildInfo = j.build || null;
+    statusPageAddress = j.status_page || null;
   } catch (_) {}
+  try {
+    const r = await fetch("/status-indicators");
+    const j = await r.json();
+    if (j.severities && Array.isArray(j.ping)) statusIndicators = j;
+  } catch (_) {}
+  renderBuildInfo();
+  renderStatusPageAddress();
   loadConfig();
+  applyHighVisibility();
+  initUsageMetrics();
   await pushConfig();
   const ok = await loadWallets();
   updateStatus(ok);
@@ -50,25 +227,116 @@ async function init() {
   document.getElementById("search").addEventListener("input", filterMethods);
   document.getElementById("cfg-toggle").addEventListener("click", toggleConfig);
   document.getElementById("cfg-connect").addEventListener("click", connectClicked);
+  document.getElementById("cfg-import-conf").addEventListener("click", importBitcoinConf);
+  document.getElementById("compat-dismiss").addEventListener("click", dismissCompatBanner);
   document.getElementById("cfg-wallet").addEventListener("change", walletChanged);
+  document.getElementById("cfg-wallet-text").addEventListener("change", walletTextChanged);
   document.getElementById("cfg-zmq-buffer-limit").addEventListener("change", zmqBufferLimitChanged);
+  document.getElementById("cfg-log-tail-lines").addEventListener("change", logTailLinesChanged);
+  document.getElementById("cfg-timeout").addEventListener("change", timeoutChanged);
   document.getElementById("cfg-hashblock-party").addEventListener("change", saveConfig);
+  document.getElementById("cfg-high-visibility").addEventListener("change", () => {
+    saveConfig();
+    applyHighVisibility();
+  });
+  document.getElementById("cfg-usage-metrics").addEventListener("change", usageMetricsToggled);
+  document.getElementById("cfg-usage-view").addEventListener("click", showUsageMetrics);
+  document.getElementById("cfg-exports-view").addEventListener("click", showExports);
+  document.getElementById("exports-open-folder").addEventListener("click", openExportsFolder);
+  document.querySelector("#exports-table tbody").addEventListener("click", exportsTableClicked);
+  document.getElementById("metrics-delete").addEventListener("click", () => setMetricsDeleteConfirm(true));
+  document.getElementById("metrics-delete-cancel").addEventListener("click", () => setMetricsDeleteConfirm(false));
+  document.getElementById("metrics-delete-confirm-btn").addEventListener("click", deleteUsageMetricsConfirmed);
+  document.getElementById("cfg-startup-view").addEventListener("change", saveConfig);
+  document.getElementById("cfg-auth-mode").addEventListener("change", (ev) => setAuthMode(ev.target.value));
+  initTlsMode();
+  document.getElementById("cfg-password-reveal").addEventListener("click", () => setPasswordVisible(!passwordVisible()));
+  document.getElementById("cfg-fiat-currency").addEventListener("change", fiatPriceChanged);
+  document.getElementById("cfg-fiat-price").addEventListener("change", fiatPriceChanged);
+  document.getElementById("cfg-fee-alert").addEventListener("change", () => {
+    saveConfig();
+    if (lastFeeEstimates) renderFeeEstimates(lastFeeEstimates);
+  });
+  document.getElementById("cfg-copy-logs").addEventListener("click", copyRecentLogs);
+  document.getElementById("cfg-manage-wallets").addEventListener("click", showWalletManager);
+  document.getElementById("nav-mining").addEventListener("click", showMining);
+  document.getElementById("mining-template-refresh").addEventListener("click", refreshBlockTemplate);
+  document.getElementById("chain-debug-form").addEventListener("submit", chainDebugChecked);
+  attachHexIdInput(document.getElementById("chain-debug-target"), { kind: "block", allowHeight: true });
+  document.getElementById("chain-debug-confirm-btn").addEventListener("click", chainDebugConfirmed);
+  document.getElementById("chain-debug-cancel").addEventListener("click", () => setChainDebugPending(null));
+  document.getElementById("chain-debug-list").addEventListener("click", chainDebugListClicked);
+  document.getElementById("nav-bans").addEventListener("click", showBans);
+  document.getElementById("nav-logs").addEventListener("click", showNodeLog);
+  document.getElementById("log-categories").addEventListener("click", logCategoryClicked);
+  document.getElementById("log-clear").addEventListener("click", clearNodeLogView);
+  document.getElementById("peer-disconnect").addEventListener("click", () => setPeerDisconnectConfirm(true));
+  document.getElementById("peer-disconnect-confirm-btn").addEventListener("click", disconnectPeerConfirmed);
+  document.getElementById("peer-disconnect-cancel").addEventListener("click", () => setPeerDisconnectConfirm(false));
+  document.getElementById("dash-zmq-reconnect").addEventListener("click", zmqReconnectClicked);
+  document.getElementById("execute-cancel").addEventListener("click", cancelExecute);
+  initDashboardWalletToggle();
+  document.getElementById("peer-ban").addEventListener("click", banPeerClicked);
+  document.getElementById("ban-table").addEventListener("click", banTableClicked);
+  document.getElementById("ban-clear").addEventListener("click", () => setBanClearConfirm(true));
+  document.getElementById("ban-clear-cancel").addEventListener("click", () => setBanClearConfirm(false));
+  document.getElementById("ban-clear-confirm-btn").addEventListener("click", clearBansConfirmed);
+  document.getElementById("ban-form").addEventListener("submit", banSubmitted);
+  document.getElementById("wallet-create-form").addEventListener("submit", createWalletSubmitted);
+  document.getElementById("wallet-bump-form").addEventListener("submit", bumpPreviewSubmitted);
+  document.getElementById("wallet-bump-cancel").addEventListener("click", closeBumpPanel);
+  document.getElementById("wallet-bump-confirm").addEventListener("click", bumpConfirmClicked);
+  document.getElementById("watch-form").addEventListener("submit", watchReviewSubmitted);
+  document.getElementById("watch-confirm").addEventListener("change", updateWatchImportEnabled);
+  document.getElementById("watch-import").addEventListener("click", watchImportClicked);
+  document.getElementById("compose-form").addEventListener("submit", composeSubmitted);
+  document.getElementById("compose-form").addEventListener("input", discardComposed);
+  document.getElementById("compose-broadcast").addEventListener("click", composeBroadcastClicked);
+  document.getElementById("compose-discard").addEventListener("click", discardComposed);
+  initWalletTableClick();
   document.getElementById("execute").addEventListener("click", execute);
+  document.getElementById("result-toolbar").addEventListener("click", resultModeClicked);
+  initResultTabs();
+  initRpcHistory();
   document.getElementById("header-title").addEventListener("click", showDashboard);
   document.getElementById("cfg-poll-interval").addEventListener("change", () => {
     saveConfig();
     startDashboardPolling();
   });
-  document.getElementById("cfg-url").addEventListener("input", clearUrlError);
+  peerView = loadPeerView();
+  initGraphs();
+  document.querySelector("#dash-blocks-table tbody").addEventListener("click", blockRowClicked);
   initPeerTableClick();
+  initSectionRefresh();
+  initDashboardHistory();
   initZmqFeedClick();
-  startDashboardPolling();
+  initZmqDetail();
+  initZmqTopicFilter();
+  initCustomCards();
+  const startup = resolveStartupView(getConfig().startup_view, loadLastView(), schema.methods);
+  if (startup.method) {
+    selectMethod(startup.method);
+    revealMethodLink(startup.method.name);
+  } else if (startup.view === "wallets") {
+    showWalletManager();
+  } else if (startup.view === "mining") {
+    showMining();
+  } else if (startup.view === "bans") {
+    showBans();
+  } else if (startup.view === "logs") {
+    showNodeLog();
+  } else {
+    startDashboardPolling();
+  }
   if (audioEnabled) {
     initMusic();
   } else {
     const bar = document.getElementById("music-bar");
     if (bar) bar.hidden = true;
   }
+  initChangelog();
+  initKeyBindings();
+  initConsoleDraft();
 }
 
 function loadConfig() {
@@ -77,32 +345,76 @@ function loadConfig() {
   try {
     const cfg = JSON.parse(saved);
     if (cfg.url) document.getElementById("cfg-url").value = cfg.url;
+    // Configs saved before the TLS selector existed follow the URL.
+    setTlsMode(cfg.tls_mode || tlsModeForUrl(cfg.url || ""));
     if (cfg.user) document.getElementById("cfg-user").value = cfg.user;
     if (cfg.password) {
       document.getElementById("cfg-password").value = cfg.password;
       document.getElementById("cfg-save-pw").checked = true;
     }
-    if (cfg.wallet) document.getElementById("cfg-wallet").value = cfg.wallet;
+    if (cfg.cookie_path) document.getElementById("cfg-cookie").value = cfg.cookie_path;
+    if (cfg.ca_cert_path) document.getElementById("cfg-ca-cert").value = cfg.ca_cert_path;
+    // Configs saved before the mode select existed only have the path.
+    setAuthMode(cfg.auth_mode || (cfg.cookie_path ? "cookie" : "password"));
+    if (cfg.wallet) selectWallet(cfg.wallet);
     if (cfg.pollInterval) document.getElementById("cfg-poll-interval").value = cfg.pollInterval;
     if (cfg.zmq_address) document.getElementById("cfg-zmq").value = cfg.zmq_address;
     if (cfg.zmq_buffer_limit) document.getElementById("cfg-zmq-buffer-limit").value = cfg.zmq_buffer_limit;
+    if (cfg.debug_log_path) document.getElementById("cfg-debug-log").value = cfg.debug_log_path;
+    if (cfg.log_tail_lines) document.getElementById("cfg-log-tail-lines").value = cfg.log_tail_lines;
+    if (cfg.exports_dir) document.getElementById("cfg-exports-dir").value = cfg.exports_dir;
+    // null is a saved "no deadline"; a missing key keeps the default.
+    if (cfg.timeout_secs === null) document.getElementById("cfg-timeout").value = "";
+    else if (cfg.timeout_secs) document.getElementById("cfg-timeout").value = cfg.timeout_secs;
     if (typeof cfg.hashblock_party === "boolean") {
       document.getElementById("cfg-hashblock-party").checked = cfg.hashblock_party;
     }
+    document.getElementById("cfg-usage-metrics").checked = cfg.usage_metrics === true;
+    document.getElementById("cfg-high-visibility").checked = cfg.high_visibility === true;
+    document.getElementById("cfg-save-history").checked = cfg.save_history === true;
+    if (cfg.startup_view) document.getElementById("cfg-startup-view").value = cfg.startup_view;
+    if (cfg.fiat_currency) document.getElementById("cfg-fiat-currency").value = cfg.fiat_currency;
+    if (cfg.fiat_price) document.getElementById("cfg-fiat-price").value = cfg.fiat_price;
+    if (cfg.fiat_price_set_at) fiatPriceSetAt = cfg.fiat_price_set_at;
+    if (cfg.fee_alert_sat_vb) document.getElementById("cfg-fee-alert").value = cfg.fee_alert_sat_vb;
   } catch (_) {}
 }
 
+function setAuthMode(mode) {
+  document.getElementById("cfg-auth-mode").value = mode;
+  document.querySelectorAll("#config [data-auth]").forEach((el) => {
+    el.hidden = el.dataset.auth !== mode;
+  });
+}
+
 function getConfig() {
   const zmqBufferLimit = Number(document.getElementById("cfg-zmq-buffer-limit").value);
+  const logTailLines = Number(document.getElementById("cfg-log-tail-lines").value);
   return {
     url: document.getElementById("cfg-url").value,
     user: document.getElementById("cfg-user").value,
     password: document.getElementById("cfg-password").value,
+    auth_mode: document.getElementById("cfg-auth-mode").value,
+    cookie_path: document.getElementById("cfg-cookie").value.trim(),
+    ca_cert_path: document.getElementById("cfg-ca-cert").value.trim(),
+    tls_mode: tlsMode(),
     wallet: document.getElementById("cfg-wallet").value,
     pollInterval: document.getElementById("cfg-poll-interval").value,
     zmq_address: document.getElementById("cfg-zmq").value,
     zmq_buffer_limit: Number.isFinite(zmqBufferLimit) ? zmqBufferLimit : 5000,
+    debug_log_path: document.getElementById("cfg-debug-log").value.trim(),
+    log_tail_lines: Number.isFinite(logTailLines) ? logTailLines : 1000,
+    exports_dir: document.getElementById("cfg-exports-dir").value.trim(),
+    timeout_secs: parseTimeoutInput(document.getElementById("cfg-timeout").value),
     hashblock_party: document.getElementById("cfg-hashblock-party").checked,
+    usage_metrics: document.getElementById("cfg-usage-metrics").checked,
+    high_visibility: document.getElementById("cfg-high-visibility").checked,
+    save_history: document.getElementById("cfg-save-history").checked,
+    startup_view: document.getElementById("cfg-startup-view").value,
+    fiat_currency: document.getElementById("cfg-fiat-currency").value,
+    fiat_price: document.getElementById("cfg-fiat-price").value,
+    fiat_price_set_at: fiatPriceSetAt,
+    fee_alert_sat_vb: feeAlertSatVb(),
   };
 }
 
@@ -119,6 +431,9 @@ function saveConfig() {
 
 async function pushConfig() {
   const cfg = getConfig();
+  // A stale wallet name would send every call to a /wallet/ path the node
+  // does not serve; the selection is kept locally for nodes that have one.
+  if (walletsDisabled) cfg.wallet = "";
   try {
     const resp = await fetch("/config", {
       method: "POST",
@@ -128,48 +443,224 @@ async function pushConfig() {
       },
       body: JSON.stringify(cfg),
     });
-    return await resp.json();
+    const result = await resp.json();
+    showConfigErrors(result.errors || []);
+    return result;
   } catch (_) {
     return { ok: false };
   }
 }
 
+function loadLastView() {
+  try {
+    return JSON.parse(localStorage.getItem("ui-last-view") || "null");
+  } catch (_) {
+    return null;
+  }
+}
+
+function rememberView(view) {
+  if (lastViewSaveTimer) clearTimeout(lastViewSaveTimer);
+  lastViewSaveTimer = setTimeout(() => {
+    lastViewSaveTimer = null;
+    localStorage.setItem("ui-last-view", JSON.stringify(view));
+  }, LAST_VIEW_SAVE_DEBOUNCE_MS);
+}
+
+// Persisted views are validated against the loaded schema so a renamed or
+// removed method falls back to the dashboard instead of a blank pane.
+function resolveStartupView(startupView, saved, methods) {
+  const fallback = { view: "dashboard", method: null };
+  if (startupView !== "last" || !saved || typeof saved !== "object") return fallback;
+  if (["wallets", "mining", "bans", "logs"].includes(saved.view)) return { view: saved.view, method: null };
+  if (saved.view !== "method" || typeof saved.method !== "string") return fallback;
+  const method = (methods || []).find((m) => m.name === saved.method);
+  return method ? { view: "method", method } : fallback;
+}
+
+function revealMethodLink(name) {
+  const link = document.querySelector(`#method-list .method[data-name="${name}"]`);
+  const details = link && link.closest("details");
+  if (details) details.open = true;
+  if (link) link.scrollIntoView({ block: "nearest" });
+}
+
+function fiatPriceChanged() {
+  const err = document.getElementById("cfg-fiat-error");
+  const raw = document.getElementById("cfg-fiat-price").value.trim();
+  const invalid = raw !== "" && parsePriceCents(raw) == null;
+  err.textContent = invalid ? "Price must be a positive number, e.g. 97000.50" : "";
+  err.hidden = !invalid;
+  if (invalid) return;
+  fiatPriceSetAt = raw === "" ? 0 : Date.now();
+  saveConfig();
+}
+
+// Empty means no deadline; anything else must be whole seconds, clamped to
+// the 1–300 s range the backend accepts.
+function parseTimeoutInput(raw) {
+  const s = String(raw).trim();
+  if (s === "") return null;
+  if (!/^\d+$/.test(s)) return undefined;
+  return Math.min(300, Math.max(1, Number(s)));
+}
+
+async function timeoutChanged() {
+  const input = document.getElementById("cfg-timeout");
+  const err = document.getElementById("cfg-timeout-error");
+  const secs = parseTimeoutInput(input.value);
+  err.textContent = secs === undefined ? "Timeout must be whole seconds between 1 and 300" : "";
+  err.hidden = secs !== undefined;
+  if (secs === undefined) return;
+  input.value = secs == null ? "" : String(secs);
+  saveConfig();
+  await pushConfig();
+}
+
 function toggleConfig() {
   document.getElementById("config").classList.toggle("collapsed");
+  setPasswordVisible(false);
 }
 
-function clearUrlError() {
-  const input = document.getElementById("cfg-url");
-  const err = document.getElementById("cfg-url-error");
-  input.classList.remove("cfg-error");
-  err.hidden = true;
-  err.textContent = "";
+function passwordVisible() {
+  return document.getElementById("cfg-password").type === "text";
+}
+
+// --- TLS mode ---
+
+function tlsMode() {
+  const pressed = document.querySelector('#cfg-tls-mode [aria-pressed="true"]');
+  return pressed ? pressed.dataset.tls : "none";
+}
+
+function setTlsMode(mode) {
+  document.querySelectorAll("#cfg-tls-mode button").forEach((btn) => {
+    btn.setAttribute("aria-pressed", String(btn.dataset.tls === mode));
+  });
+}
+
+function tlsModeForUrl(url) {
+  return /^https:\/\//i.test(url.trim()) ? "system" : "none";
 }
 
-function showUrlError(msg) {
+// The mode and the URL's scheme must agree, so picking a mode rewrites the
+// scheme and typing a URL with the other scheme picks the mode it implies.
+function tlsModeClicked(ev) {
+  const btn = ev.target.closest("button[data-tls]");
+  if (!btn || btn.disabled) return;
+  setTlsMode(btn.dataset.tls);
   const input = document.getElementById("cfg-url");
-  const err = document.getElementById("cfg-url-error");
-  input.classList.add("cfg-error");
-  err.textContent = msg;
-  err.hidden = false;
+  input.value = input.value.replace(/^\s*https?:\/\//i, btn.dataset.tls === "none" ? "http://" : "https://");
+}
+
+function urlSchemeChanged() {
+  const implied = tlsModeForUrl(document.getElementById("cfg-url").value);
+  if ((implied === "none") !== (tlsMode() === "none")) setTlsMode(implied);
+}
+
+async function initTlsMode() {
+  document.getElementById("cfg-tls-mode").addEventListener("click", tlsModeClicked);
+  document.getElementById("cfg-url").addEventListener("change", urlSchemeChanged);
+  let allowed = false;
+  try {
+    const r = await fetch("/allow-insecure-rpc");
+    allowed = (await r.json()).allowed === true;
+  } catch (_) {}
+  document.querySelector('#cfg-tls-mode [data-tls="insecure"]').disabled = !allowed;
+}
+
+// Shown only until the panel is closed or Connect is pressed.
+function setPasswordVisible(visible) {
+  const btn = document.getElementById("cfg-password-reveal");
+  document.getElementById("cfg-password").type = visible ? "text" : "password";
+  btn.textContent = visible ? "hide" : "show";
+  btn.setAttribute("aria-pressed", String(visible));
+  btn.title = visible ? "Hide password" : "Show password";
+}
+
+// The backend applies nothing from an update it rejects and lists every
+// problem at once.
+function showConfigErrors(errors) {
+  const list = document.getElementById("cfg-errors");
+  list.innerHTML = errors.map((e) => `<li>${esc(e)}</li>`).join("");
+  list.hidden = errors.length === 0;
 }
 
 async function connectClicked() {
+  setPasswordVisible(false);
+  nodeCompat = null;
+  relayPolicyKey = null;
+  dashboardHistory = [];
+  sparklines = {};
+  syncSamples = [];
+  resetTrafficRates();
+  setDashboardHistoryOffset(0);
   const cfgResp = await pushConfig();
-  if (cfgResp.insecure_blocked) {
-    showUrlError("Non-local RPC address blocked. Set DANGER_INSECURE_RPC=1 to override.");
-    return;
-  }
-  clearUrlError();
+  if (cfgResp.errors && cfgResp.errors.length) return;
   saveConfig();
   const ok = await loadWallets();
   updateStatus(ok);
   if (!document.getElementById("dashboard").hidden) startDashboardPolling();
 }
 
+// Fills in the settings bitcoin.conf determines and leaves the rest; the
+// user still reviews them and presses Connect.
+async function importBitcoinConf() {
+  const status = document.getElementById("cfg-import-status");
+  const path = document.getElementById("cfg-bitcoin-conf").value.trim();
+  let imported;
+  try {
+    const r = await fetch("/config/bitcoin-conf?path=" + encodeURIComponent(path));
+    imported = await r.json();
+  } catch (e) {
+    imported = { error: String(e) };
+  }
+  status.hidden = false;
+  if (imported.error) {
+    status.textContent = "Import failed: " + imported.error;
+    return;
+  }
+  document.getElementById("cfg-url").value = imported.url;
+  urlSchemeChanged();
+  if (imported.user != null) document.getElementById("cfg-user").value = imported.user;
+  if (imported.password != null) document.getElementById("cfg-password").value = imported.password;
+  if (imported.cookie_path != null) document.getElementById("cfg-cookie").value = imported.cookie_path;
+  setAuthMode(imported.password != null ? "password" : "cookie");
+  if (imported.zmq_address != null) document.getElementById("cfg-zmq").value = imported.zmq_address;
+  const auth = imported.password != null ? "user/password" : "cookie";
+  status.textContent = `Imported ${imported.chain} settings (${auth}). Press Connect to use them.`;
+}
+
 async function walletChanged() {
   saveConfig();
   await pushConfig();
+  renderMethodEndpoint();
+}
+
+// The select stays the source of truth; names it doesn't list yet (a saved
+// wallet before listwallets answers, or one typed by hand) get an option.
+function selectWallet(name) {
+  const select = document.getElementById("cfg-wallet");
+  if (name && ![...select.options].some((o) => o.value === name)) {
+    const opt = document.createElement("option");
+    opt.value = name;
+    opt.textContent = name;
+    select.appendChild(opt);
+  }
+  select.value = name;
+}
+
+async function walletTextChanged() {
+  selectWallet(document.getElementById("cfg-wallet-text").value.trim());
+  await walletChanged();
+}
+
+// Free text is only offered while listwallets can't be used to fill the list.
+function setWalletTextFallback(on) {
+  const text = document.getElementById("cfg-wallet-text");
+  document.getElementById("cfg-wallet").hidden = on;
+  text.hidden = !on;
+  if (on) text.value = document.getElementById("cfg-wallet").value;
 }
 
 async function zmqBufferLimitChanged() {
@@ -177,14 +668,45 @@ async function zmqBufferLimitChanged() {
   await pushConfig();
 }
 
+async function logTailLinesChanged() {
+  saveConfig();
+  await pushConfig();
+}
+
+// true/false once listwallets has answered, null if the node was unreachable.
+function walletSupportFromResponse(resp) {
+  if (resp && resp.error && resp.error.code === RPC_METHOD_NOT_FOUND) return false;
+  if (resp && Array.isArray(resp.result)) return true;
+  return null;
+}
+
+async function applyWalletSupport(supported) {
+  const disabled = supported === false;
+  const changed = disabled !== walletsDisabled;
+  walletsDisabled = disabled;
+  document.getElementById("cfg-wallet").disabled = disabled;
+  document.getElementById("cfg-wallet-disabled").hidden = !disabled;
+  if (changed) await pushConfig();
+}
+
+// Also detects wallet support, so it asks the node endpoint: the configured
+// wallet's path does not exist on a node without wallets.
 async function loadWallets() {
   const select = document.getElementById("cfg-wallet");
   const current = select.value;
   try {
-    const resp = await rpcCall("listwallets", []);
-    if (resp.error) return false;
+    const resp = await nodeRpcCall("listwallets", []);
+    const supported = walletSupportFromResponse(resp);
+    if (supported !== null) await applyWalletSupport(supported);
+    if (supported === false) {
+      setWalletTextFallback(false);
+      return true;
+    }
     const wallets = resp.result;
-    if (!Array.isArray(wallets)) return false;
+    if (resp.error || !Array.isArray(wallets)) {
+      setWalletTextFallback(true);
+      return false;
+    }
     select.innerHTML = '<option value="">(none)</option>';
     for (const w of wallets) {
       const opt = document.createElement("option");
@@ -193,16 +715,41 @@ async function loadWallets() {
       select.appendChild(opt);
     }
     select.value = current;
+    setWalletTextFallback(false);
     return true;
   } catch (_) {
+    setWalletTextFallback(true);
     return false;
   }
 }
 
 function updateStatus(connected) {
+  connectionOk = connected;
+  renderConnectionStatus();
+}
+
+// A busy node is reachable, so it gets the calm warn color rather than red.
+function renderConnectionStatus() {
   const dot = document.getElementById("connection-status");
-  dot.classList.toggle("connected", connected);
-  dot.title = connected ? "Connected" : "Disconnected";
+  const busy = connectionOk && nodeBusyBackoff > 1;
+  setStatus(dot, busy ? "warn" : connectionOk ? "ok" : "error", "");
+  connectionStatusText = busy
+    ? `Connected \u00b7 node busy, refreshing every ${formatDurationHuman(dashboardPollMs() / 1000)}`
+    : connectionOk ? "Connected" : "Disconnected";
+  dot.title = connectionStatusText + rpcQueueText;
+}
+
+async function refreshRpcStats() {
+  try {
+    const resp = await fetch("/rpc/stats");
+    const s = await resp.json();
+    const queued = s.queued_interactive + s.queued_background;
+    rpcQueueText = `\nNode RPC: ${s.outstanding}/${s.max_outstanding} in use, ${queued} queued`;
+    nodeBusyBackoff = s.busy_backoff || 1;
+  } catch (_) {
+    rpcQueueText = "";
+  }
+  renderConnectionStatus();
 }
 
 function renderSidebar() {
@@ -264,13 +811,12 @@ function selectMethod(m) {
   const link = document.querySelector(`#method-list .method[data-name="${m.name}"]`);
   if (link) link.classList.add("active");
 
-  document.getElementById("dashboard").hidden = true;
-  document.getElementById("peer-view").hidden = true;
+  showMainView("method-view");
   stopDashboardPolling();
-  document.getElementById("method-view").hidden = false;
   document.getElementById("execute").hidden = false;
   document.getElementById("method-name").textContent = m.name;
   document.getElementById("method-desc").textContent = m.description || "";
+  renderMethodEndpoint();
 
   const form = document.getElementById("param-form");
   form.innerHTML = "";
@@ -278,9 +824,18 @@ function selectMethod(m) {
     form.appendChild(buildField(p));
   }
 
-  const result = document.getElementById("result");
-  result.classList.remove("visible", "error");
-  result.textContent = "";
+  rememberView({ view: "method", method: m.name });
+}
+
+// Calls go to the wallet path whenever a wallet is selected; node methods
+// are answered there too.
+function renderMethodEndpoint() {
+  const el = document.getElementById("method-endpoint");
+  el.hidden = !currentMethod;
+  if (el.hidden) return;
+  const { url, wallet } = getConfig();
+  const target = !walletsDisabled && wallet ? `${url}/wallet/${wallet}` : url;
+  el.textContent = "Endpoint: " + target;
 }
 
 function buildField(param) {
@@ -323,10 +878,70 @@ function buildField(param) {
 
   input.dataset.paramName = param.name;
   input.dataset.schemaType = s.type || "string";
+  if (s.pattern === HEX_PATTERN) input.dataset.hex = "true";
   div.appendChild(input);
+  if (input.tagName === "TEXTAREA") attachJsonPasteCleanup(input);
+  if (input.tagName === "INPUT" && /^(blockhash|hash_or_height)$/.test(param.name)) {
+    attachHexIdInput(input, { kind: "block", allowHeight: param.name === "hash_or_height" });
+  } else if (input.tagName === "INPUT" && /^(txid|parent_txid)$/.test(param.name)) {
+    attachHexIdInput(input, { kind: "tx" });
+  }
   return div;
 }
 
+// --- Pasted JSON cleanup ---
+
+// Growth this large in one input event is treated as a paste.
+const PASTE_DELTA_CHARS = 12;
+
+async function cleanPastedJson(text) {
+  const payload = { text };
+  try {
+    const r = await fetch("/json/clean", {
+      method: "POST",
+      headers: {
+        "content-type": "application/json",
+        "x-app-json": encodeHeaderJson(payload),
+      },
+      body: JSON.stringify(payload),
+    });
+    return await r.json();
+  } catch (_) {
+    return null;
+  }
+}
+
+// Pasted JSON that only parses after the backend's lenient pass is replaced
+// with the cleaned text. Valid or hopeless text is left as it was.
+function attachJsonPasteCleanup(input) {
+  const note = document.createElement("span");
+  note.className = "json-paste-note";
+  note.hidden = true;
+  input.parentNode.insertBefore(note, input.nextSibling);
+  let lastLength = input.value.length;
+  input.addEventListener("input", async (ev) => {
+    const grew = input.value.length - lastLength;
+    lastLength = input.value.length;
+    note.hidden = true;
+    // Restoring a history entry fills inputs through synthetic events.
+    if (!ev.isTrusted) return;
+    if ((ev.inputType !== "insertFromPaste" && grew < PASTE_DELTA_CHARS) || !input.value.trim()) return;
+    const text = input.value;
+    const res = await cleanPastedJson(text);
+    // Typing since the paste wins over a late reply.
+    if (!res || input.value !== text) return;
+    if (res.status === "cleaned") {
+      input.value = res.json;
+      lastLength = input.value.length;
+      setStatus(note, "ok", `Cleaned up pasted JSON (${res.fixes.join(", ")})`);
+      note.hidden = false;
+    } else if (res.status === "invalid") {
+      setStatus(note, "error", `Not valid JSON: ${res.error}`);
+      note.hidden = false;
+    }
+  });
+}
+
 function extractValue(input) {
   const raw = input.value.trim();
   if (raw === "") return undefined;
@@ -348,12 +963,116 @@ function extractValue(input) {
   return raw;
 }
 
+function hexInputError(raw) {
+  for (let i = 0; i < raw.length; i++) {
+    if (!/[0-9a-fA-F]/.test(raw[i])) return `invalid hex character "${raw[i]}" at position ${i}`;
+  }
+  if (raw.length % 2 !== 0) return `hex string has odd length ${raw.length}`;
+  return null;
+}
+
+// --- Hex id inputs ---
+
+// Block hashes and txids are always 64 hex characters; with allowHeight a
+// plain number is accepted as a block height instead.
+function hexIdStatus(raw, allowHeight) {
+  const t = raw.trim();
+  if (t === "") return "empty";
+  if (allowHeight && /^\d{1,63}$/.test(t)) return "valid";
+  if (!/^[0-9a-fA-F]*$/.test(t) || t.length > 64) return "invalid";
+  return t.length === 64 ? "valid" : "partial";
+}
+
+function normalizeHexId(raw) {
+  return raw.trim().toLowerCase();
+}
+
+// ids are newest first; the result is deduplicated and bounded.
+function rememberIds(list, ids, limit) {
+  const fresh = ids.filter((id) => typeof id === "string" && /^[0-9a-fA-F]{64}$/.test(id)).map((id) => id.toLowerCase());
+  if (!fresh.length) return list;
+  return [...new Set([...fresh, ...list])].slice(0, limit);
+}
+
+function matchIdPrefix(list, prefix, max) {
+  const p = normalizeHexId(prefix);
+  if (p.length < HEX_ID_SUGGEST_MIN || p.length >= 64) return [];
+  const out = [];
+  for (const id of list) {
+    if (id.startsWith(p)) out.push(id);
+    if (out.length >= max) break;
+  }
+  return out;
+}
+
+function rememberBlockHashes(hashes) {
+  knownBlockHashes = rememberIds(knownBlockHashes, hashes, KNOWN_IDS_LIMIT);
+}
+
+function rememberTxids(txids) {
+  knownTxids = rememberIds(knownTxids, txids, KNOWN_IDS_LIMIT);
+}
+
+// Live validity border plus suggestions from ids this session has seen.
+// The input must already have a parent; it is wrapped so the list can drop
+// down over whatever follows.
+function attachHexIdInput(input, { kind, allowHeight = false }) {
+  input.dataset.hexId = allowHeight ? "height" : "hash";
+  input.spellcheck = false;
+  input.autocomplete = "off";
+  const wrap = document.createElement("span");
+  wrap.className = "hex-id-wrap";
+  input.parentNode.insertBefore(wrap, input);
+  wrap.appendChild(input);
+  const list = document.createElement("ul");
+  list.className = "hex-suggest";
+  list.hidden = true;
+  wrap.appendChild(list);
+
+  const update = () => {
+    const status = hexIdStatus(input.value, allowHeight);
+    input.classList.toggle("hex-valid", status === "valid");
+    input.classList.toggle("hex-invalid", status === "invalid");
+    const matches = status === "partial"
+      ? matchIdPrefix(kind === "block" ? knownBlockHashes : knownTxids, input.value, HEX_ID_SUGGEST_MAX)
+      : [];
+    list.innerHTML = matches.map((id) => `<li data-id="${id}">${id}</li>`).join("");
+    list.hidden = matches.length === 0;
+  };
+  input.addEventListener("input", update);
+  input.addEventListener("blur", () => { list.hidden = true; });
+  input.addEventListener("keydown", (ev) => {
+    if (ev.key === "Escape") list.hidden = true;
+  });
+  // mousedown so the pick lands before the input's blur hides the list.
+  list.addEventListener("mousedown", (ev) => {
+    const li = ev.target.closest("li[data-id]");
+    if (!li) return;
+    ev.preventDefault();
+    input.value = li.dataset.id;
+    update();
+  });
+}
+
 async function execute() {
   if (!currentMethod) return;
 
   const inputs = document.querySelectorAll("#param-form [data-param-name]");
   const params = [];
   for (const input of inputs) {
+    if (input.dataset.hexId) input.value = normalizeHexId(input.value);
+    const raw = input.value.trim();
+    let hexError = input.dataset.hex && raw !== "" ? hexInputError(raw) : null;
+    if (!hexError && input.dataset.hexId && hexIdStatus(raw, input.dataset.hexId === "height") === "invalid") {
+      hexError = input.dataset.hexId === "height"
+        ? "expected a 64-character hex hash or a height"
+        : "expected a 64-character hex hash";
+    }
+    if (hexError) {
+      const tabId = openResultTab(currentMethod.name);
+      setResultTabContent(tabId, { kind: "text", text: `${input.dataset.paramName}: ${hexError}`, isError: true });
+      return;
+    }
     params.push(extractValue(input));
   }
 
@@ -367,387 +1086,5074 @@ async function execute() {
   const btn = document.getElementById("execute");
   btn.disabled = true;
   btn.textContent = "Loading...";
+  document.getElementById("execute-cancel").hidden = false;
 
-  const result = document.getElementById("result");
-  result.classList.remove("visible", "error");
+  const gen = ++executeGeneration;
+  const method = currentMethod.name;
+  const inputValues = Array.from(inputs, (input) => input.value);
+  clearConsoleDraft();
+  const tabId = openResultTab(method);
+  executeTabId = tabId;
+  countUsage("rpc_calls", method);
 
+  const startedAt = Date.now();
+  const finished = (content) => {
+    setResultTabContent(tabId, content);
+    const entry = {
+      method,
+      params,
+      inputValues,
+      content,
+      atMs: startedAt,
+      durationMs: Date.now() - startedAt,
+      isError: !!content.isError,
+      pinned: false,
+    };
+    recordRpcHistory(entry);
+  };
   try {
-    const resp = await rpcCall(currentMethod.name, params);
-    result.classList.add("visible");
-    if (resp.error) {
-      result.classList.add("error");
-      result.textContent = JSON.stringify(resp.error, null, 2);
-    } else {
-      result.textContent = JSON.stringify(resp.result !== undefined ? resp.result : resp, null, 2);
-    }
+    const resp = await rpcCall(method, params, "interactive");
+    if (gen === executeGeneration) finished(responseContent(resp));
   } catch (e) {
-    result.classList.add("visible", "error");
-    result.textContent = String(e);
+    if (gen === executeGeneration) finished({ kind: "text", text: String(e), isError: true });
   } finally {
-    btn.disabled = false;
-    btn.textContent = "Execute";
+    if (gen === executeGeneration) endExecute();
   }
 }
 
-async function rpcCall(method, params) {
-  const payload = { method, params };
-  const resp = await fetch("/rpc", {
-    method: "POST",
-    headers: {
-      "content-type": "application/json",
-      "x-app-json": encodeHeaderJson(payload),
-    },
-    body: JSON.stringify(payload),
-  });
-  return resp.json();
+function endExecute() {
+  const btn = document.getElementById("execute");
+  btn.disabled = false;
+  btn.textContent = "Execute";
+  document.getElementById("execute-cancel").hidden = true;
+  executeTabId = null;
 }
 
-// --- Dashboard ---
+// The request can't be recalled from the node; bumping the generation just
+// makes its reply land nowhere and frees the Execute button.
+function cancelExecute() {
+  const tabId = executeTabId;
+  executeGeneration += 1;
+  endExecute();
+  if (tabId != null) {
+    setResultTabContent(tabId, {
+      kind: "text",
+      text: "Cancelled. The node may still finish the call; its reply will be ignored.",
+      isError: true,
+    });
+  }
+}
 
-function showDashboard() {
-  document.getElementById("method-view").hidden = true;
-  document.getElementById("peer-view").hidden = true;
-  document.getElementById("dashboard").hidden = false;
-  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
-  currentMethod = null;
-  startDashboardPolling();
+// --- Call history ---
+
+// The backend's canonical form of the call, so entries that differ only in
+// params whitespace or object key order are recognised as the same call.
+async function rpcCallKey(method, params) {
+  const query = `method=${encodeURIComponent(method)}&params=${encodeURIComponent(JSON.stringify(params))}`;
+  try {
+    const r = await fetch("/params/key?" + query);
+    const j = await r.json();
+    if (typeof j.key === "string") return j.key;
+  } catch (_) {}
+  return `${method} ${JSON.stringify(params)}`;
 }
 
-function startDashboardPolling() {
-  dashboardPollingGeneration += 1;
-  stopDashboardPolling();
-  const generation = dashboardPollingGeneration;
-  fetchDashboard();
-  scheduleDashboardPoll(generation);
-  startZmqPolling(generation);
+async function recordRpcHistory(entry) {
+  entry.key = await rpcCallKey(entry.method, entry.params);
+  rpcHistory = pushRpcHistory(rpcHistory, entry, RPC_HISTORY_LIMIT);
+  renderRpcHistory();
+  saveRpcHistory();
 }
 
-function stopDashboardPolling() {
-  dashboardPollingGeneration += 1;
-  if (dashTimer) {
-    clearTimeout(dashTimer);
-    dashTimer = null;
+// Newest first. Cancelled calls never get here, as their replies are dropped.
+// Running a call again moves its entry to the top, keeping its pin. Over the
+// limit the oldest unpinned entry goes; pinned ones only go once nothing else
+// is left.
+function pushRpcHistory(history, entry, limit) {
+  const previous = entry.key && history.find((e) => e.key === entry.key);
+  const next = previous
+    ? [{ ...entry, pinned: entry.pinned || previous.pinned }, ...history.filter((e) => e !== previous)]
+    : [entry, ...history];
+  while (next.length > limit) {
+    let i = next.length - 1;
+    while (i > 0 && next[i].pinned) i--;
+    next.splice(i > 0 ? i : next.length - 1, 1);
   }
-  stopZmqPolling();
+  return next;
 }
 
-function dashboardPollMs() {
-  const configured = Math.max(1, Number(document.getElementById("cfg-poll-interval").value) || 5) * 1000;
-  return zmqConnected ? Math.max(configured, DASHBOARD_ZMQ_FALLBACK_MS) : configured;
+function formatCallDuration(ms) {
+  return ms < 1000 ? `${ms} ms` : `${(ms / 1000).toFixed(1)} s`;
 }
 
-function scheduleDashboardPoll(generation) {
-  if (dashTimer) clearTimeout(dashTimer);
-  dashTimer = setTimeout(async () => {
-    if (generation !== dashboardPollingGeneration) return;
-    await fetchDashboard();
-    if (generation !== dashboardPollingGeneration) return;
-    scheduleDashboardPoll(generation);
-  }, dashboardPollMs());
+function renderRpcHistory() {
+  const panel = document.getElementById("rpc-history");
+  panel.hidden = rpcHistory.length === 0;
+  panel.querySelector("ol").innerHTML = rpcHistory.map((e, i) => {
+    const params = e.params.length ? JSON.stringify(e.params) : "";
+    const cls = e.isError ? ' class="error"' : "";
+    const duration = typeof e.durationMs === "number" ? formatCallDuration(e.durationMs) : "";
+    const pin = e.pinned ? "Unpin" : "Pin to keep it when older calls are dropped";
+    return `<li${cls} data-history-index="${i}" title="${esc(params)}">`
+      + `<button class="rpc-history-pin" type="button" aria-pressed="${e.pinned}" title="${pin}">${e.pinned ? "&#9733;" : "&#9734;"}</button>`
+      + `<span class="rpc-history-time">${esc(new Date(e.atMs).toLocaleTimeString())}</span>`
+      + `<span class="rpc-history-method">${esc(e.method)}</span>`
+      + `<span class="rpc-history-params">${esc(params)}</span>`
+      + `<span class="rpc-history-duration">${duration}</span></li>`;
+  }).join("");
 }
 
-function requestDashboardRefreshSoon() {
-  if (zmqRefreshTimer) return;
-  zmqRefreshTimer = setTimeout(async () => {
-    zmqRefreshTimer = null;
-    await flushDashboardPartRefreshes();
-  }, DASHBOARD_PART_DEBOUNCE_MS);
+function saveHistoryEnabled() {
+  return document.getElementById("cfg-save-history").checked;
 }
 
-function dashboardVisible() {
-  return !document.getElementById("dashboard").hidden;
+// Replies stay in memory only; the backend also drops calls whose params
+// or reply can hold secrets before anything reaches disk.
+async function saveRpcHistory() {
+  if (!saveHistoryEnabled()) return;
+  const payload = {
+    entries: rpcHistory.map((e) => ({
+      method: e.method,
+      params: e.params,
+      input_values: e.inputValues,
+      at_ms: e.atMs,
+      duration_ms: e.durationMs,
+      error: e.isError,
+      pinned: e.pinned,
+    })),
+  };
+  try {
+    await fetch("/rpc-history/save", {
+      method: "POST",
+      headers: {
+        "content-type": "application/json",
+        "x-app-json": encodeHeaderJson(payload),
+      },
+      body: JSON.stringify(payload),
+    });
+  } catch (_) {}
 }
 
-function queueDashboardPartRefresh(parts) {
-  if (!dashboardVisible()) return;
-  for (const part of parts) pendingDashboardParts.add(part);
-  requestDashboardRefreshSoon();
+async function loadRpcHistory() {
+  if (!saveHistoryEnabled()) return;
+  let entries = [];
+  try {
+    const r = await fetch("/rpc-history");
+    entries = (await r.json()).entries || [];
+  } catch (_) {}
+  const saved = entries.map((e) => ({
+    key: e.key,
+    method: e.method,
+    params: e.params,
+    inputValues: e.input_values,
+    content: null,
+    atMs: e.at_ms,
+    durationMs: e.duration_ms,
+    isError: e.error,
+    pinned: e.pinned,
+  }));
+  // Calls made while the file was loading go on top, oldest first.
+  rpcHistory = rpcHistory.reduceRight(
+    (history, entry) => pushRpcHistory(history, entry, RPC_HISTORY_LIMIT),
+    saved.slice(0, RPC_HISTORY_LIMIT),
+  );
+  renderRpcHistory();
 }
 
-function deriveDashboardParts(messages) {
-  const parts = new Set();
-  for (const msg of messages) {
-    if (msg.topic === "hashblock") {
-      parts.add("chain");
-      parts.add("mempool");
-    } else if (msg.topic === "hashtx") {
-      parts.add("mempool");
-    }
+function saveHistoryToggled() {
+  saveConfig();
+  if (saveHistoryEnabled()) {
+    saveRpcHistory();
+  } else {
+    fetch("/rpc-history/delete", { method: "POST" }).catch(() => {});
   }
-  return parts;
 }
 
-async function flushDashboardPartRefreshes() {
-  if (!dashboardVisible() || pendingDashboardParts.size === 0) return;
-  if (dashboardFetchInFlight) return;
-  const parts = new Set(pendingDashboardParts);
-  pendingDashboardParts.clear();
-  const tasks = [];
-  if (parts.has("chain")) {
-    tasks.push((async () => {
-      const [chain, uptime] = await Promise.all([
-        rpcCall("getblockchaininfo", []),
-        rpcCall("uptime", []),
-      ]);
-      if (chain.result) renderChain(chain.result, uptime.result);
-    })());
-  }
-  if (parts.has("mempool")) {
-    tasks.push((async () => {
-      const mempool = await rpcCall("getmempoolinfo", []);
-      if (mempool.result) renderMempool(mempool.result);
-    })());
+function rpcHistoryPinClicked(index) {
+  const entry = rpcHistory[index];
+  if (!entry) return;
+  entry.pinned = !entry.pinned;
+  renderRpcHistory();
+  saveRpcHistory();
+}
+
+// Restores the method and its inputs as typed, plus the reply when this
+// session still has it, without calling the node again.
+function rpcHistorySelected(index) {
+  const entry = rpcHistory[index];
+  const method = entry && (schema?.methods || []).find((m) => m.name === entry.method);
+  if (!method) return;
+  if (currentMethod !== method) selectMethod(method);
+  const inputs = document.querySelectorAll("#param-form [data-param-name]");
+  inputs.forEach((input, i) => {
+    input.value = entry.inputValues[i] ?? "";
+    input.dispatchEvent(new Event("input"));
+  });
+  if (!entry.content) return;
+  const tabId = openResultTab(entry.method);
+  setResultTabContent(tabId, entry.content);
+}
+
+function initRpcHistory() {
+  document.querySelector("#rpc-history ol").addEventListener("click", (ev) => {
+    const li = ev.target.closest("[data-history-index]");
+    if (!li) return;
+    const index = Number(li.dataset.historyIndex);
+    if (ev.target.closest(".rpc-history-pin")) rpcHistoryPinClicked(index);
+    else rpcHistorySelected(index);
+  });
+  document.getElementById("cfg-save-history").addEventListener("change", saveHistoryToggled);
+  loadRpcHistory();
+}
+
+// --- Console draft ---
+
+// The method form is saved a few seconds after the last edit, so a crash
+// loses little typing. Running the call or a clean exit drops the draft.
+function consoleDraftEdited() {
+  if (consoleDraftTimer) clearTimeout(consoleDraftTimer);
+  consoleDraftTimer = setTimeout(saveConsoleDraft, CONSOLE_DRAFT_DEBOUNCE_MS);
+}
+
+async function saveConsoleDraft() {
+  consoleDraftTimer = null;
+  if (!currentMethod) return;
+  const inputs = document.querySelectorAll("#param-form [data-param-name]");
+  const payload = { method: currentMethod.name, input_values: Array.from(inputs, (input) => input.value) };
+  try {
+    await fetch("/console-draft/save", {
+      method: "POST",
+      headers: { "content-type": "application/json", "x-app-json": encodeHeaderJson(payload) },
+      body: JSON.stringify(payload),
+    });
+  } catch (_) {}
+}
+
+function clearConsoleDraft() {
+  if (consoleDraftTimer) {
+    clearTimeout(consoleDraftTimer);
+    consoleDraftTimer = null;
   }
-  const now = Date.now();
+  fetch("/console-draft/delete", { method: "POST" }).catch(() => {});
+}
+
+// Offered once per launch, and only for a draft left behind by a crash.
+async function initConsoleDraft() {
+  document.getElementById("param-form").addEventListener("input", consoleDraftEdited);
+  let draft = null;
+  try {
+    draft = (await (await fetch("/console-draft")).json()).draft;
+  } catch (_) {}
+  const method = draft && (schema?.methods || []).find((m) => m.name === draft.method);
+  if (!method) return;
+  const banner = document.getElementById("console-draft");
+  document.getElementById("console-draft-text").textContent =
+    `Restore unsent console input? ${draft.method}, last edited ${new Date(draft.saved_ms).toLocaleString()}.`;
+  banner.hidden = false;
+  document.getElementById("console-draft-restore").addEventListener("click", () => {
+    banner.hidden = true;
+    selectMethod(method);
+    revealMethodLink(method.name);
+    const inputs = document.querySelectorAll("#param-form [data-param-name]");
+    inputs.forEach((input, i) => {
+      input.value = draft.input_values[i] ?? "";
+      input.dispatchEvent(new Event("input"));
+    });
+  });
+  document.getElementById("console-draft-discard").addEventListener("click", () => {
+    banner.hidden = true;
+    clearConsoleDraft();
+  });
+}
+
+// --- Result view ---
+
+function clearResult() {
+  resultRenderGeneration += 1;
+  lastResultValue = undefined;
+  lastResultTreeState = null;
+  const result = document.getElementById("result");
+  result.classList.remove("visible", "error");
+  result.textContent = "";
+  document.getElementById("result-toolbar").hidden = true;
+}
+
+function showResultText(text, isError) {
+  clearResult();
+  const result = document.getElementById("result");
+  result.classList.add("visible");
+  result.classList.toggle("error", isError);
+  result.textContent = text;
+}
+
+function responseContent(resp) {
+  if (resp && resp.error) return { kind: "json", value: resp.error, isError: true };
+  return { kind: "json", value: resp && resp.result !== undefined ? resp.result : resp, isError: false };
+}
+
+// `treeState` holds the tree view's expand/collapse overrides; it lives on
+// the tab's content so switching tabs keeps each result's tree as left.
+function showResultJson(value, isError, treeState) {
+  clearResult();
+  lastResultValue = value;
+  lastResultTreeState = treeState || new Map();
+  const result = document.getElementById("result");
+  result.classList.add("visible");
+  result.classList.toggle("error", isError);
+  document.getElementById("result-toolbar").hidden = false;
+  renderResultJson();
+}
+
+function renderResultJson() {
+  for (const btn of document.querySelectorAll("#result-toolbar button")) {
+    btn.classList.toggle("active", btn.dataset.mode === jsonViewMode);
+  }
+  const result = document.getElementById("result");
+  result.classList.toggle("tree", jsonViewMode === "tree");
+  if (jsonViewMode === "tree") {
+    resultRenderGeneration += 1;
+    result.textContent = "";
+    result.appendChild(jsonTreeNode(null, lastResultValue, "$", 0, lastResultTreeState));
+    return;
+  }
+  const text = jsonViewMode === "compact"
+    ? JSON.stringify(lastResultValue)
+    : JSON.stringify(lastResultValue, null, 2);
+  renderHighlightedJson(document.getElementById("result"), text ?? String(lastResultValue));
+}
+
+function resultModeClicked(ev) {
+  if (ev.target.closest("#result-copy")) {
+    if (lastResultValue === undefined) return;
+    const text = jsonViewMode === "compact"
+      ? JSON.stringify(lastResultValue)
+      : JSON.stringify(lastResultValue, null, 2);
+    copyWithFlash(ev.target.closest("#result-copy"), text ?? String(lastResultValue));
+    return;
+  }
+  if (ev.target.closest("#result-save")) {
+    saveResult(ev.target.closest("#result-save"));
+    return;
+  }
+  const btn = ev.target.closest("button[data-mode]");
+  if (!btn || btn.dataset.mode === jsonViewMode) return;
+  jsonViewMode = btn.dataset.mode;
+  localStorage.setItem("ui-json-mode", jsonViewMode);
+  if (lastResultValue !== undefined) renderResultJson();
+}
+
+// --- Mining ---
+
+const SI_PREFIXES = ["", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+function formatSi(value, unit) {
+  let v = Number(value);
+  if (!Number.isFinite(v)) return "?";
+  let i = 0;
+  while (Math.abs(v) >= 1000 && i < SI_PREFIXES.length - 1) {
+    v /= 1000;
+    i += 1;
+  }
+  return `${v.toFixed(i === 0 ? 0 : 2)} ${SI_PREFIXES[i]}${unit}`;
+}
+
+async function showMining() {
+  showMainView("mining-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  rememberView({ view: "mining" });
+  const [info, hashps, template] = await rpcBatch([
+    ["getmininginfo", []],
+    ["getnetworkhashps", []],
+    ["getblocktemplate", [{ rules: ["segwit"] }]],
+  ], "interactive");
+  renderMiningInfo(info, hashps);
+  renderBlockTemplate(template);
+  await renderChainDebug(info.result ? info.result.chain : null);
+}
+
+async function refreshBlockTemplate() {
+  const btn = document.getElementById("mining-template-refresh");
+  btn.disabled = true;
+  try {
+    const [template] = await rpcBatch([["getblocktemplate", [{ rules: ["segwit"] }]]], "interactive");
+    renderBlockTemplate(template);
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+function rpcErrorText(error) {
+  return typeof error === "string" ? error : (error && error.message) || JSON.stringify(error);
+}
+
+function renderMiningInfo(info, hashps) {
+  const err = document.getElementById("mining-error");
+  err.hidden = !info.error;
+  err.textContent = info.error ? rpcErrorText(info.error) : "";
+  const rows = miningEntries(info.result || {}, hashps.result);
+  document.getElementById("mining-info").innerHTML = rows.map(([k, v]) => dd(k, v)).join("");
+}
+
+// Shared by the Mining view and the dashboard card.
+function miningEntries(m, hashps) {
+  const rows = [];
+  if (m.chain) rows.push(["Chain", m.chain]);
+  if (m.blocks != null) rows.push(["Blocks", m.blocks.toLocaleString()]);
+  if (m.difficulty != null) rows.push(["Difficulty", formatSi(m.difficulty, "")]);
+  // getmininginfo's networkhashps is the same 120-block estimate.
+  const rate = hashps != null ? hashps : m.networkhashps;
+  if (rate != null) rows.push(["Network hashrate", formatSi(rate, "H/s")]);
+  if (m.pooledtx != null) rows.push(["Pooled transactions", m.pooledtx.toLocaleString()]);
+  if (m.warnings && m.warnings.length) rows.push(["Warnings", [].concat(m.warnings).join("; ")]);
+  return rows;
+}
+
+// --- Fee estimates ---
+
+const DEFAULT_FEE_ALERT_SAT_VB = 50;
+
+// Never throws: a failed round trip comes back as { error }.
+async function fetchFeeEstimates() {
+  try {
+    return await (await fetch("/fee-estimates")).json();
+  } catch (e) {
+    return { error: String(e) };
+  }
+}
+
+function feeAlertSatVb() {
+  const n = Number(document.getElementById("cfg-fee-alert").value);
+  return Number.isFinite(n) && n > 0 ? n : DEFAULT_FEE_ALERT_SAT_VB;
+}
+
+// Only the next-block estimate is flagged; it is the one that spikes.
+function feeEstimateRows(estimates, alertSatVb) {
+  return estimates.map((e) => ({
+    label: e.target === 1 ? "1 block" : `${e.target} blocks`,
+    text: e.text,
+    secondary: e.blocks != null && e.blocks !== e.target ? `for ${e.blocks}` : "",
+    note: e.note || "",
+    spike: e.target === 1 && e.sat_vb != null && e.sat_vb > alertSatVb,
+  }));
+}
+
+function renderFeeEstimates(fees) {
+  lastFeeEstimates = fees;
+  const dl = document.querySelector("#dash-fees dl");
+  dl.textContent = "";
+  for (const row of feeEstimateRows(fees.estimates, feeAlertSatVb())) {
+    const dt = document.createElement("dt");
+    dt.textContent = row.label;
+    const dd = document.createElement("dd");
+    if (row.spike) setStatus(dd, "warn", row.text, { suffix: true });
+    else dd.textContent = row.text;
+    if (row.secondary) {
+      const span = document.createElement("span");
+      span.className = "dd-secondary";
+      span.textContent = row.secondary;
+      dd.appendChild(span);
+    }
+    if (row.note) dd.title = row.note;
+    else if (row.spike) dd.title = `Above the ${feeAlertSatVb()} sat/vB fee alert`;
+    dl.append(dt, dd);
+  }
+}
+
+function renderDashboardMining(m, hashps) {
+  updateDl(document.querySelector("#dash-mining dl"), miningEntries(m, hashps));
+}
+
+// null when no wallet answered: none loaded, several loaded and none
+// selected, or a node built without wallet support.
+function walletSummary(balances, legacyBalance) {
+  const mine = balances.result && balances.result.mine;
+  if (mine) {
+    return { trusted: mine.trusted, untrustedPending: mine.untrusted_pending, immature: mine.immature };
+  }
+  if (legacyBalance && typeof legacyBalance.result === "number") {
+    return { trusted: legacyBalance.result, untrustedPending: null, immature: null };
+  }
+  return null;
+}
+
+function renderDashboardWallet(wallet) {
+  const card = document.getElementById("dash-wallet");
+  card.hidden = !wallet;
+  if (!wallet) return;
+  const entries = [["Trusted", btcAmount(wallet.trusted)]];
+  if (wallet.untrustedPending != null) entries.push(["Untrusted pending", btcAmount(wallet.untrustedPending)]);
+  if (wallet.immature != null) entries.push(["Immature", btcAmount(wallet.immature)]);
+  updateDl(card.querySelector("dl"), entries);
+}
+
+function setDashboardWalletCollapsed(collapsed) {
+  document.getElementById("dash-wallet").classList.toggle("collapsed", collapsed);
+  document.getElementById("dash-wallet-toggle").setAttribute("aria-expanded", String(!collapsed));
+}
+
+function initDashboardWalletToggle() {
+  setDashboardWalletCollapsed(localStorage.getItem("ui-dash-wallet-collapsed") === "1");
+  document.getElementById("dash-wallet-toggle").addEventListener("click", () => {
+    const collapsed = !document.getElementById("dash-wallet").classList.contains("collapsed");
+    localStorage.setItem("ui-dash-wallet-collapsed", collapsed ? "1" : "0");
+    setDashboardWalletCollapsed(collapsed);
+  });
+}
+
+// getblocktemplate fails while the node is in IBD or has no peers; Core's
+// message explains which, so it is shown as-is.
+function renderBlockTemplate(template) {
+  const summary = document.getElementById("mining-template-summary");
+  const pre = document.getElementById("mining-template");
+  pre.classList.toggle("error", !!template.error);
+  if (template.error) {
+    summary.innerHTML = "";
+    pre.textContent = rpcErrorText(template.error);
+    return;
+  }
+  const t = template.result || {};
+  const txs = Array.isArray(t.transactions) ? t.transactions : [];
+  const fees = txs.reduce((sum, tx) => sum + (tx.fee || 0), 0);
+  const weight = txs.reduce((sum, tx) => sum + (tx.weight || 0), 0);
+  summary.innerHTML = [
+    dd("Height", t.height != null ? t.height.toLocaleString() : "?"),
+    dd("Transactions", txs.length.toLocaleString()),
+    dd("Fees", (fees / 1e8).toFixed(8) + " BTC"),
+    dd("Weight", weight.toLocaleString() + " WU"),
+    dd("Bits", t.bits || "?"),
+    dd("Template time", t.curtime ? new Date(t.curtime * 1000).toLocaleTimeString() : "?"),
+  ].join("");
+  pre.textContent = JSON.stringify(t, null, 2);
+}
+
+// --- Chain debugging ---
+
+// invalidateblock survives restarts and rewinds the chain, so it is only
+// offered where a reorg costs nothing: regtest and signet.
+function chainDebugAllowed(chain) {
+  return chain === "regtest" || chain === "signet";
+}
+
+function parseBlockTarget(text) {
+  const t = text.trim();
+  if (/^[0-9a-fA-F]{64}$/.test(t)) return { ok: true, hash: t.toLowerCase() };
+  if (/^\d+$/.test(t)) return { ok: true, height: Number(t) };
+  return { ok: false, error: "Enter a 64-character block hash or a height" };
+}
+
+function chainDebugErrorMessage(error) {
+  const code = error && error.code;
+  // -5 is RPC_INVALID_ADDRESS_OR_KEY, which Core uses for unknown blocks.
+  if (code === -5) return "Unknown block: it isn't in this node's block index.";
+  if (code === -8 && /height out of range/i.test(rpcErrorText(error))) {
+    return "No block at that height on the active chain.";
+  }
+  return rpcErrorText(error);
+}
+
+// Core can't list invalidated blocks, so this session keeps its own list.
+// A block that is back on the active chain was reconsidered (perhaps from
+// the console) and one the node no longer knows about can't be; both drop.
+function reconcileInvalidatedBlocks(tracked, headers) {
+  return tracked.filter((_, i) => {
+    const h = headers[i];
+    if (h.error) return h.error.code !== -5;
+    return !(h.result && h.result.confirmations >= 0);
+  });
+}
+
+function trackInvalidatedBlock(tracked, entry) {
+  return tracked.filter((e) => e.hash !== entry.hash).concat([entry]);
+}
+
+async function renderChainDebug(chain) {
+  const panel = document.getElementById("chain-debug");
+  panel.hidden = !chainDebugAllowed(chain);
+  if (panel.hidden) {
+    setChainDebugPending(null);
+    return;
+  }
+  if (invalidatedBlocks.length > 0) {
+    const headers = await rpcBatch(invalidatedBlocks.map((e) => ["getblockheader", [e.hash, true]]), "interactive");
+    invalidatedBlocks = reconcileInvalidatedBlocks(invalidatedBlocks, headers);
+  }
+  renderInvalidatedBlocks();
+}
+
+function renderInvalidatedBlocks() {
+  const table = document.getElementById("chain-debug-list");
+  table.hidden = invalidatedBlocks.length === 0;
+  table.querySelector("tbody").innerHTML = invalidatedBlocks.map((e) => "<tr>"
+    + `<td>${e.height != null ? e.height.toLocaleString() : "?"}</td>`
+    + `<td title="${esc(e.hash)}">${esc(e.hash.slice(0, 16))}\u2026</td>`
+    + `<td>${esc(new Date(e.atMs).toLocaleTimeString())}</td>`
+    + `<td><button type="button" data-reconsider="${esc(e.hash)}">Reconsider</button></td>`
+    + "</tr>").join("");
+}
+
+function setChainDebugStatus(message, isError) {
+  const el = document.getElementById("chain-debug-status");
+  el.classList.toggle("error", isError);
+  el.textContent = message || "";
+  el.hidden = !message;
+}
+
+function setChainDebugPending(pending) {
+  chainDebugPending = pending;
+  document.getElementById("chain-debug-confirm").hidden = !pending;
+  if (!pending) return;
+  const depth = pending.confirmations > 1 ? ` and the ${(pending.confirmations - 1).toLocaleString()} after it` : "";
+  document.getElementById("chain-debug-confirm-text").textContent =
+    `Invalidate block ${pending.height.toLocaleString()} (${pending.hash.slice(0, 16)}\u2026)${depth}?`;
+}
+
+// The first step only resolves the block, so the confirmation can show
+// exactly what will be disconnected.
+async function chainDebugChecked(ev) {
+  ev.preventDefault();
+  setChainDebugPending(null);
+  const input = document.getElementById("chain-debug-target");
+  input.value = normalizeHexId(input.value);
+  const target = parseBlockTarget(input.value);
+  if (!target.ok) {
+    setChainDebugStatus(target.error, true);
+    return;
+  }
+  let hash = target.hash;
+  if (hash === undefined) {
+    const resp = await rpcCall("getblockhash", [target.height], "interactive");
+    if (resp.error) {
+      setChainDebugStatus(chainDebugErrorMessage(resp.error), true);
+      return;
+    }
+    hash = resp.result;
+  }
+  const header = await rpcCall("getblockheader", [hash, true], "interactive");
+  if (header.error) {
+    setChainDebugStatus(chainDebugErrorMessage(header.error), true);
+    return;
+  }
+  if (header.result.height === 0) {
+    setChainDebugStatus("The genesis block can't be invalidated.", true);
+    return;
+  }
+  setChainDebugStatus(null);
+  setChainDebugPending({ hash, height: header.result.height, confirmations: header.result.confirmations });
+}
+
+async function chainDebugConfirmed() {
+  const pending = chainDebugPending;
+  if (!pending) return;
+  const btn = document.getElementById("chain-debug-confirm-btn");
+  btn.disabled = true;
+  try {
+    const resp = await rpcCall("invalidateblock", [pending.hash], "interactive");
+    if (resp.error) {
+      setChainDebugStatus(chainDebugErrorMessage(resp.error), true);
+      return;
+    }
+    invalidatedBlocks = trackInvalidatedBlock(invalidatedBlocks, { hash: pending.hash, height: pending.height, atMs: Date.now() });
+    setChainDebugPending(null);
+    document.getElementById("chain-debug-target").value = "";
+    setChainDebugStatus(`Invalidated block ${pending.height.toLocaleString()}.`, false);
+    await afterChainDebugAction();
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+async function chainDebugListClicked(ev) {
+  const btn = ev.target.closest("button[data-reconsider]");
+  if (!btn) return;
+  const hash = btn.dataset.reconsider;
+  btn.disabled = true;
+  const resp = await rpcCall("reconsiderblock", [hash], "interactive");
+  if (resp.error) {
+    btn.disabled = false;
+    setChainDebugStatus(chainDebugErrorMessage(resp.error), true);
+    return;
+  }
+  invalidatedBlocks = invalidatedBlocks.filter((e) => e.hash !== hash);
+  setChainDebugStatus(`Reconsidered ${hash.slice(0, 16)}\u2026; the node reorgs back if that chain has more work.`, false);
+  await afterChainDebugAction();
+}
+
+// The chain tip moved, so everything height-dependent is stale.
+async function afterChainDebugAction() {
+  fetchDashboard();
+  const [info, hashps] = await rpcBatch([["getmininginfo", []], ["getnetworkhashps", []]], "interactive");
+  renderMiningInfo(info, hashps);
+  await renderChainDebug(info.result ? info.result.chain : null);
+}
+
+// --- Bans ---
+
+// listbanned reports absolute times; the remaining time is derived against
+// the local clock so the column keeps counting down between refreshes.
+function banRemaining(entry, nowSecs) {
+  const left = entry.banned_until - nowSecs;
+  return left > 0 ? formatDuration(left) : "expiring";
+}
+
+function renderBans(resp) {
+  const tbody = document.querySelector("#ban-table tbody");
+  const clear = document.getElementById("ban-clear");
+  if (resp.error) {
+    tbody.innerHTML = `<tr><td colspan="4" class="wallet-empty">${esc(rpcErrorText(resp.error))}</td></tr>`;
+    clear.disabled = true;
+    return;
+  }
+  const bans = resp.result || [];
+  clear.disabled = bans.length === 0;
+  if (!bans.length) {
+    tbody.innerHTML = '<tr><td colspan="4" class="wallet-empty">No banned addresses</td></tr>';
+    return;
+  }
+  const now = Date.now() / 1000;
+  tbody.innerHTML = bans.map((b) => `<tr>
+    <td>${esc(b.address)}</td>
+    <td>${esc(new Date(b.ban_created * 1000).toLocaleString())}</td>
+    <td>${esc(banRemaining(b, now))}</td>
+    <td><button type="button" data-unban="${esc(b.address)}">Unban</button></td>
+  </tr>`).join("");
+}
+
+async function refreshBans() {
+  renderBans(await rpcCall("listbanned", [], "interactive"));
+}
+
+async function showBans() {
+  showMainView("ban-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  rememberView({ view: "bans" });
+  setBanClearConfirm(false);
+  setBanFormError(null);
+  await refreshBans();
+}
+
+function setBanFormError(text) {
+  const el = document.getElementById("ban-form-error");
+  el.textContent = text || "";
+  el.hidden = !text;
+}
+
+function setBanClearConfirm(open) {
+  document.getElementById("ban-clear").hidden = open;
+  document.getElementById("ban-clear-confirm").hidden = !open;
+}
+
+async function banTableClicked(ev) {
+  const btn = ev.target.closest("button[data-unban]");
+  if (!btn) return;
+  btn.disabled = true;
+  const resp = await rpcCall("setban", [btn.dataset.unban, "remove"], "interactive");
+  if (resp.error) {
+    btn.disabled = false;
+    setBanFormError(rpcErrorText(resp.error));
+    return;
+  }
+  setBanFormError(null);
+  await refreshBans();
+}
+
+async function clearBansConfirmed() {
+  const btn = document.getElementById("ban-clear-confirm-btn");
+  btn.disabled = true;
+  try {
+    const resp = await rpcCall("clearbanned", [], "interactive");
+    if (resp.error) {
+      setBanFormError(rpcErrorText(resp.error));
+      return;
+    }
+    setBanClearConfirm(false);
+    setBanFormError(null);
+    await refreshBans();
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+async function banSubmitted(ev) {
+  ev.preventDefault();
+  const input = document.getElementById("ban-address");
+  const address = input.value.trim();
+  if (!address) {
+    setBanFormError("Enter an IP address or subnet.");
+    return;
+  }
+  const duration = Number(document.getElementById("ban-duration").value);
+  const btn = document.getElementById("ban-submit");
+  btn.disabled = true;
+  try {
+    const resp = await rpcCall("setban", [address, "add", duration], "interactive");
+    if (resp.error) {
+      setBanFormError(rpcErrorText(resp.error));
+      return;
+    }
+    input.value = "";
+    setBanFormError(null);
+    await refreshBans();
+  } finally {
+    btn.disabled = false;
+  }
+}
+
+// --- Node log ---
+
+async function showNodeLog() {
+  showMainView("log-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  rememberView({ view: "logs" });
+  startNodeLogPolling();
+  await refreshLogCategories();
+}
+
+function startNodeLogPolling() {
+  stopNodeLogPolling();
+  pollNodeLog(nodeLogGeneration);
+}
+
+function stopNodeLogPolling() {
+  nodeLogGeneration += 1;
+  clearTimeout(nodeLogTimer);
+  nodeLogTimer = null;
+}
+
+async function pollNodeLog(generation) {
+  let data;
+  try {
+    const r = await fetch(`/node-log?since=${nodeLogCursor}`);
+    data = await r.json();
+  } catch (e) {
+    data = { error: String(e) };
+  }
+  if (generation !== nodeLogGeneration) return;
+  renderNodeLog(data);
+  nodeLogTimer = setTimeout(() => pollNodeLog(generation), NODE_LOG_POLL_MS);
+}
+
+function nodeLogRow(className, text) {
+  const row = document.createElement("div");
+  row.className = `log-line ${className}`;
+  row.textContent = text;
+  return row;
+}
+
+// The backend keeps the last lines of the file; this view appends what is
+// new since the last poll and drops the oldest past the same limit.
+function renderNodeLog(data) {
+  const status = document.getElementById("log-tail-status");
+  if (data.path === undefined) {
+    status.textContent = `Error: ${data.error}`;
+    status.classList.add("log-tail-error");
+    return;
+  }
+  const list = document.getElementById("log-lines");
+  if (data.path !== nodeLogPath) {
+    nodeLogPath = data.path;
+    list.textContent = "";
+  }
+  nodeLogCursor = data.cursor;
+  status.classList.toggle("log-tail-error", Boolean(data.error));
+  if (!data.path) {
+    status.textContent = "Set Node debug.log in the settings and press Connect to follow the node's log here.";
+    return;
+  }
+  if (data.truncated) list.appendChild(nodeLogRow("log-gap", "\u2026 lines skipped"));
+  for (const line of data.lines) {
+    list.appendChild(nodeLogRow(`log-${line.severity}`, line.text));
+  }
+  while (list.childElementCount > data.limit) list.firstElementChild.remove();
+  const count = `${list.childElementCount.toLocaleString()} lines`;
+  status.textContent = data.error ? `Error: ${data.error} (${data.path})` : `${data.path} \u00b7 ${count}`;
+  if (data.lines.length && document.getElementById("log-follow").checked) {
+    list.scrollTop = list.scrollHeight;
+  }
+}
+
+function clearNodeLogView() {
+  document.getElementById("log-lines").textContent = "";
+}
+
+async function refreshLogCategories() {
+  renderLogCategories(await rpcCall("logging", [], "interactive"));
+}
+
+function renderLogCategories(resp) {
+  const error = document.getElementById("log-categories-error");
+  error.textContent = resp.error ? rpcErrorText(resp.error) : "";
+  error.hidden = !resp.error;
+  if (resp.error) return;
+  const categories = Object.entries(resp.result || {}).sort(([a], [b]) => a.localeCompare(b));
+  document.getElementById("log-categories").innerHTML = categories
+    .map(([name, on]) => `<button type="button" class="log-category" data-category="${esc(name)}" aria-pressed="${on === true}">${esc(name)}</button>`)
+    .join("");
+}
+
+// logging takes the categories to include and to exclude, and answers with
+// the state after the change.
+async function logCategoryClicked(ev) {
+  const btn = ev.target.closest("button[data-category]");
+  if (!btn) return;
+  const name = btn.dataset.category;
+  const on = btn.getAttribute("aria-pressed") === "true";
+  btn.disabled = true;
+  const resp = await rpcCall("logging", on ? [[], [name]] : [[name], []], "interactive");
+  if (resp.error) btn.disabled = false;
+  renderLogCategories(resp);
+}
+
+// --- Result tabs ---
+
+// Re-running a method updates its unpinned tab in place; otherwise a new tab
+// opens. Returns the new tab list and the tab that will hold the result.
+function placeResultTab(tabs, tab, nowMs) {
+  const existing = tabs.find((t) => t.method === tab.method && !t.pinned);
+  if (existing) {
+    const updated = { ...existing, label: tab.label, content: tab.content, usedAt: nowMs };
+    return { tabs: tabs.map((t) => (t.id === existing.id ? updated : t)), tab: updated };
+  }
+  const created = { ...tab, pinned: false, createdAt: nowMs, usedAt: nowMs };
+  return { tabs: [...tabs, created], tab: created };
+}
+
+// Drops least recently used unpinned tabs (never `keepId`) until the list fits.
+// Pinned tabs are never evicted, so the list can exceed the limit if every
+// other tab is pinned.
+function evictResultTabs(tabs, limit, keepId) {
+  const out = tabs.slice();
+  while (out.length > limit) {
+    let victim = -1;
+    for (let i = 0; i < out.length; i++) {
+      if (out[i].pinned || out[i].id === keepId) continue;
+      if (victim < 0 || out[i].usedAt < out[victim].usedAt) victim = i;
+    }
+    if (victim < 0) break;
+    out.splice(victim, 1);
+  }
+  return out;
+}
+
+// Closing the active tab activates its right neighbour, or the left one at the end.
+function closeResultTab(tabs, id, activeId) {
+  const index = tabs.findIndex((t) => t.id === id);
+  if (index < 0) return { tabs, activeId };
+  const rest = tabs.filter((t) => t.id !== id);
+  if (id !== activeId) return { tabs: rest, activeId };
+  const next = rest[Math.min(index, rest.length - 1)];
+  return { tabs: rest, activeId: next ? next.id : null };
+}
+
+function adjacentResultTab(tabs, activeId, step) {
+  if (tabs.length === 0) return null;
+  const index = tabs.findIndex((t) => t.id === activeId);
+  if (index < 0) return tabs[0].id;
+  return tabs[(index + step + tabs.length) % tabs.length].id;
+}
+
+function openResultTab(method) {
+  resultTabSeq += 1;
+  const placed = placeResultTab(
+    resultTabs,
+    { id: resultTabSeq, method, label: `${method} #${resultTabSeq}`, content: { kind: "loading" } },
+    Date.now(),
+  );
+  resultTabs = evictResultTabs(placed.tabs, RESULT_TAB_LIMIT, placed.tab.id);
+  activeResultTabId = placed.tab.id;
+  renderResultTabBar();
+  renderActiveResultTab();
+  return placed.tab.id;
+}
+
+// Results for a tab closed while the call was in flight are dropped.
+function setResultTabContent(id, content) {
+  const tab = resultTabs.find((t) => t.id === id);
+  if (!tab) return;
+  tab.content = content;
+  renderResultTabBar();
+  if (id === activeResultTabId) renderActiveResultTab();
+}
+
+function activateResultTab(id) {
+  const tab = resultTabs.find((t) => t.id === id);
+  if (!tab || id === activeResultTabId) return;
+  tab.usedAt = Date.now();
+  activeResultTabId = id;
+  renderResultTabBar();
+  renderActiveResultTab();
+}
+
+function renderResultTabBar() {
+  const bar = document.getElementById("result-tabs");
+  bar.hidden = resultTabs.length === 0;
+  let html = "";
+  for (const t of resultTabs) {
+    const classes = ["result-tab"];
+    if (t.id === activeResultTabId) classes.push("active");
+    if (t.pinned) classes.push("pinned");
+    if (t.content && t.content.isError) classes.push("error");
+    html += `<div class="${classes.join(" ")}" data-tab-id="${t.id}">`
+      + `<span class="result-tab-label">${esc(t.label)}</span>`
+      + `<button data-action="pin" title="${t.pinned ? "Unpin" : "Pin (keep this result)"}">&#128204;</button>`
+      + '<button data-action="close" title="Close">&times;</button>'
+      + "</div>";
+  }
+  bar.innerHTML = html;
+}
+
+function renderActiveResultTab() {
+  const tab = resultTabs.find((t) => t.id === activeResultTabId);
+  if (!tab) {
+    clearResult();
+    return;
+  }
+  const c = tab.content;
+  if (c.kind === "loading") showResultText("Loading...", false);
+  else if (c.kind === "text") showResultText(c.text, c.isError);
+  else {
+    if (!c.treeState) c.treeState = new Map();
+    showResultJson(c.value, c.isError, c.treeState);
+  }
+}
+
+function initResultTabs() {
+  document.getElementById("result-tabs").addEventListener("click", (ev) => {
+    const el = ev.target.closest("[data-tab-id]");
+    if (!el) return;
+    const id = Number(el.dataset.tabId);
+    const action = ev.target.closest("button[data-action]");
+    if (action && action.dataset.action === "close") {
+      const closed = closeResultTab(resultTabs, id, activeResultTabId);
+      const activeChanged = closed.activeId !== activeResultTabId;
+      resultTabs = closed.tabs;
+      activeResultTabId = closed.activeId;
+      renderResultTabBar();
+      if (activeChanged) renderActiveResultTab();
+    } else if (action && action.dataset.action === "pin") {
+      const tab = resultTabs.find((t) => t.id === id);
+      if (tab) tab.pinned = !tab.pinned;
+      renderResultTabBar();
+    } else {
+      activateResultTab(id);
+    }
+  });
+}
+
+// Bound to Ctrl+PageUp / Ctrl+PageDown by default, as in browsers.
+function cycleResultTab(step) {
+  if (document.getElementById("method-view").hidden || resultTabs.length < 2) return false;
+  activateResultTab(adjacentResultTab(resultTabs, activeResultTabId, step));
+  return true;
+}
+
+// --- JSON tree ---
+
+// Containers deeper than JSON_TREE_OPEN_DEPTH start collapsed, and collapsed
+// children are never built, so a getblock at verbosity 2 renders instantly.
+// `state` maps a node path to the user's explicit open/closed choice.
+function jsonTreeOpen(state, path, depth) {
+  const saved = state.get(path);
+  return saved !== undefined ? saved : depth <= JSON_TREE_OPEN_DEPTH;
+}
+
+function jsonTreeSpan(className, text) {
+  const span = document.createElement("span");
+  span.className = className;
+  span.textContent = text;
+  return span;
+}
+
+function jsonTreeNode(key, value, path, depth, state) {
+  const row = document.createElement("div");
+  row.className = "json-node";
+  if (typeof key === "number") row.appendChild(jsonTreeSpan("json-index", key + ": "));
+  else if (key !== null) {
+    row.appendChild(jsonTreeSpan("json-key", JSON.stringify(key)));
+    row.appendChild(document.createTextNode(": "));
+  }
+  const rerender = () => row.replaceWith(jsonTreeNode(key, value, path, depth, state));
+
+  if (value === null || typeof value !== "object") {
+    if (typeof value === "string") {
+      const full = state.get(path + "#full") === true;
+      const long = value.length > JSON_TREE_STRING_MAX;
+      const shown = long && !full ? value.slice(0, JSON_TREE_STRING_MAX) + "\u2026" : value;
+      row.appendChild(jsonTreeSpan("json-string", JSON.stringify(shown)));
+      if (long) {
+        const more = jsonTreeSpan("json-more", full ? "less" : `${value.length.toLocaleString()} chars`);
+        more.addEventListener("click", () => {
+          state.set(path + "#full", !full);
+          rerender();
+        });
+        row.appendChild(more);
+      }
+    } else {
+      row.appendChild(jsonTreeSpan(
+        typeof value === "number" ? "json-number" : "json-literal",
+        String(value),
+      ));
+    }
+    return row;
+  }
+
+  const isArray = Array.isArray(value);
+  const keys = isArray ? null : Object.keys(value);
+  const count = isArray ? value.length : keys.length;
+  const [open, close] = isArray ? ["[", "]"] : ["{", "}"];
+  if (count === 0) {
+    row.appendChild(document.createTextNode(open + close));
+    return row;
+  }
+  const expanded = jsonTreeOpen(state, path, depth);
+  const toggle = jsonTreeSpan("json-toggle", expanded ? "\u25BE" : "\u25B8");
+  toggle.addEventListener("click", () => {
+    state.set(path, !expanded);
+    rerender();
+  });
+  row.prepend(toggle);
+  if (!expanded) {
+    const noun = isArray ? (count === 1 ? "item" : "items") : (count === 1 ? "key" : "keys");
+    const summary = jsonTreeSpan("json-summary", `${open} ${count.toLocaleString()} ${noun} ${close}`);
+    summary.addEventListener("click", () => {
+      state.set(path, true);
+      rerender();
+    });
+    row.appendChild(summary);
+    return row;
+  }
+  row.appendChild(document.createTextNode(open));
+  const children = document.createElement("div");
+  children.className = "json-children";
+  row.appendChild(children);
+  row.appendChild(document.createTextNode(close));
+
+  // Very long arrays (listunspent on a big wallet) are built a page at a time.
+  const appendPage = (from) => {
+    const end = Math.min(count, from + JSON_TREE_PAGE);
+    for (let i = from; i < end; i++) {
+      const childKey = isArray ? i : keys[i];
+      const childPath = path + "/" + (isArray ? i : encodeURIComponent(childKey));
+      children.appendChild(jsonTreeNode(childKey, value[childKey], childPath, depth + 1, state));
+    }
+    if (end < count) {
+      const more = jsonTreeSpan("json-more", `show ${Math.min(JSON_TREE_PAGE, count - end)} more of ${(count - end).toLocaleString()}`);
+      more.addEventListener("click", () => {
+        more.remove();
+        appendPage(end);
+      });
+      children.appendChild(more);
+    }
+  };
+  appendPage(0);
+  return row;
+}
+
+// Tokenizes JSON.stringify output for highlighting. The input is already
+// valid JSON, so the scanner only needs to classify, not validate.
+function tokenizeJson(text) {
+  const re = /("(?:\\.|[^"\\])*")(\s*:)?|(-?\d+(?:\.\d+)?(?:[eE][+-]?\d+)?)|(true|false|null)|([{}\[\],:])|(\s+)/y;
+  const tokens = [];
+  let pos = 0;
+  while (pos < text.length) {
+    re.lastIndex = pos;
+    const m = re.exec(text);
+    if (!m) {
+      tokens.push({ type: "punct", text: text[pos] });
+      pos += 1;
+      continue;
+    }
+    if (m[1] !== undefined) {
+      tokens.push({ type: m[2] !== undefined ? "key" : "string", text: m[1] });
+      if (m[2] !== undefined) tokens.push({ type: "punct", text: m[2] });
+    } else if (m[3] !== undefined) {
+      tokens.push({ type: "number", text: m[3] });
+    } else if (m[4] !== undefined) {
+      tokens.push({ type: "literal", text: m[4] });
+    } else if (m[5] !== undefined) {
+      tokens.push({ type: "punct", text: m[5] });
+    } else {
+      tokens.push({ type: "ws", text: m[6] });
+    }
+    pos = re.lastIndex;
+  }
+  return tokens;
+}
+
+// Large responses are appended a chunk per frame so a multi-megabyte result
+// never blocks the UI thread in one go.
+function renderHighlightedJson(el, text) {
+  const generation = ++resultRenderGeneration;
+  el.textContent = "";
+  if (text.length > JSON_HIGHLIGHT_MAX_CHARS) {
+    el.textContent = text;
+    return;
+  }
+  const tokens = tokenizeJson(text);
+  let i = 0;
+  const step = () => {
+    if (generation !== resultRenderGeneration) return;
+    const frag = document.createDocumentFragment();
+    const end = Math.min(tokens.length, i + JSON_HIGHLIGHT_CHUNK_TOKENS);
+    for (; i < end; i++) {
+      const t = tokens[i];
+      if (t.type === "ws" || t.type === "punct") {
+        frag.appendChild(document.createTextNode(t.text));
+      } else {
+        const span = document.createElement("span");
+        span.className = "json-" + t.type;
+        span.textContent = t.text;
+        frag.appendChild(span);
+      }
+    }
+    el.appendChild(frag);
+    if (i < tokens.length) requestAnimationFrame(step);
+  };
+  step();
+}
+
+// The backend echoes each request's id (and any client_tag) on the reply.
+let nextRpcId = 1;
+
+// Console calls are "interactive" so the backend lets them ahead of queued
+// dashboard traffic when the node's RPC budget is exhausted.
+async function rpcCall(method, params, priority = "background") {
+  return rpcCallWithOptions(method, params, { priority });
+}
+
+// Sends several calls as one JSON-RPC batch. Resolves to one {result, error}
+// per call in request order; a rejected batch gives every call the same error.
+async function rpcBatch(calls, priority = "background") {
+  const payload = { batch: calls.map(([method, params]) => ({ method, params })), priority, id: nextRpcId++ };
+  const resp = await fetch("/rpc", {
+    method: "POST",
+    headers: {
+      "content-type": "application/json",
+      "x-app-json": encodeHeaderJson(payload),
+    },
+    body: JSON.stringify(payload),
+  });
+  const out = await resp.json();
+  if (Array.isArray(out)) return out;
+  return calls.map(() => ({
+    result: null,
+    error: out.error || "invalid batch response",
+    node_busy: out.node_busy === true,
+  }));
+}
+
+async function rpcCallWithOptions(method, params, options) {
+  const payload = { method, params, id: nextRpcId++, ...options };
+  const resp = await fetch("/rpc", {
+    method: "POST",
+    headers: {
+      "content-type": "application/json",
+      "x-app-json": encodeHeaderJson(payload),
+    },
+    body: JSON.stringify(payload),
+  });
+  return resp.json();
+}
+
+// --- Dashboard ---
+
+function showMainView(id) {
+  for (const view of MAIN_VIEWS) {
+    document.getElementById(view).hidden = view !== id;
+  }
+  if (id !== "log-view") stopNodeLogPolling();
+  noteUsageView(id);
+}
+
+function showDashboard() {
+  showMainView("dashboard");
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  setDashboardHistoryOffset(0);
+  startDashboardPolling();
+  rememberView({ view: "dashboard" });
+}
+
+function startDashboardPolling() {
+  dashboardPollingGeneration += 1;
+  stopDashboardPolling();
+  const generation = dashboardPollingGeneration;
+  fetchDashboard();
+  scheduleDashboardPoll(generation);
+  startZmqPolling(generation);
+  sectionStatusTimer = setInterval(renderSectionStatus, SECTION_STATUS_TICK_MS);
+}
+
+function stopDashboardPolling() {
+  dashboardPollingGeneration += 1;
+  pendingBlockLatency = null;
+  if (dashTimer) {
+    clearTimeout(dashTimer);
+    dashTimer = null;
+  }
+  if (sectionStatusTimer) {
+    clearInterval(sectionStatusTimer);
+    sectionStatusTimer = null;
+  }
+  stopZmqPolling();
+}
+
+// --- Per-section refresh state ---
+
+// Every fetch takes a generation from one counter. A section is in flight while
+// its newest request is unsettled, and a result is applied only if nothing
+// newer has been applied already, so a slow full refresh cannot overwrite a
+// fresher targeted one.
+function createSectionState(sections) {
+  const state = { nextGen: 1, sections: {} };
+  for (const name of sections) {
+    state.sections[name] = { requestedGen: 0, settledGen: 0, appliedGen: 0, updatedMs: 0 };
+  }
+  return state;
+}
+
+function beginSectionFetch(state, sections) {
+  const gen = state.nextGen++;
+  for (const name of sections) state.sections[name].requestedGen = gen;
+  return gen;
+}
+
+function settleSectionFetch(state, name, gen, ok, nowMs) {
+  const s = state.sections[name];
+  s.settledGen = Math.max(s.settledGen, gen);
+  if (!ok || gen <= s.appliedGen) return false;
+  s.appliedGen = gen;
+  s.updatedMs = nowMs;
+  return true;
+}
+
+function sectionInFlight(state, name) {
+  const s = state.sections[name];
+  return s.requestedGen > s.settledGen;
+}
+
+// Applies section renderers from one fetch, dropping stale or failed ones.
+function applySectionResults(gen, renders) {
+  const now = Date.now();
+  for (const [name, render] of Object.entries(renders)) {
+    if (settleSectionFetch(dashSections, name, gen, typeof render === "function", now)) render();
+  }
+  renderSectionStatus();
+}
+
+function failSectionFetch(gen, sections) {
+  applySectionResults(gen, Object.fromEntries(sections.map((name) => [name, null])));
+}
+
+function formatUpdatedAgo(updatedMs, nowMs) {
+  if (!updatedMs) return "";
+  const secs = Math.max(0, Math.floor((nowMs - updatedMs) / 1000));
+  if (secs < 2) return "just now";
+  if (secs < 60) return secs + "s ago";
+  if (secs < 3600) return Math.floor(secs / 60) + "m ago";
+  return Math.floor(secs / 3600) + "h ago";
+}
+
+function renderSectionStatus() {
+  const now = Date.now();
+  for (const el of document.querySelectorAll(".card-refresh[data-section]")) {
+    el.classList.toggle("refreshing", sectionInFlight(dashSections, el.dataset.section));
+  }
+  for (const el of document.querySelectorAll(".card-updated[data-section]")) {
+    const s = dashSections.sections[el.dataset.section];
+    const text = formatUpdatedAgo(s.updatedMs, now);
+    if (el.textContent !== text) el.textContent = text;
+    el.title = s.updatedMs ? new Date(s.updatedMs).toLocaleTimeString() : "";
+  }
+  for (const el of document.querySelectorAll(".card-updated[data-custom-card]")) {
+    const state = customCardState.get(el.dataset.customCard);
+    const updatedMs = state ? state.refreshedMs : 0;
+    const text = formatUpdatedAgo(updatedMs, now);
+    if (el.textContent !== text) el.textContent = text;
+    el.title = updatedMs ? new Date(updatedMs).toLocaleTimeString() : "";
+  }
+}
+
+function initSectionRefresh() {
+  dashSections = createSectionState(DASHBOARD_SECTIONS);
+  document.getElementById("dash-grid").addEventListener("click", (ev) => {
+    const btn = ev.target.closest(".card-refresh[data-section]");
+    if (btn) refreshDashboardSection(btn.dataset.section);
+  });
+}
+
+async function fetchChainUpdate() {
+  const [chain, uptime] = await Promise.all([
+    rpcCall("getblockchaininfo", [], "interactive"),
+    rpcCall("uptime", [], "interactive"),
+  ]);
+  return { chain: chain.result ? () => renderChain(chain.result, uptime.result) : null };
+}
+
+async function fetchMempoolUpdate() {
+  const [mempool, count] = await rpcBatch([["getmempoolinfo", []], ["getblockcount", []]], "interactive");
+  return { mempool: mempool.result ? () => renderMempool(mempool.result, count.result) : null };
+}
+
+async function fetchFeeEstimatesUpdate() {
+  const fees = await fetchFeeEstimates();
+  return { fees: fees.estimates ? () => renderFeeEstimates(fees) : null };
+}
+
+async function fetchNetworkAndTrafficUpdate() {
+  const [net, totals] = await Promise.all([
+    rpcCall("getnetworkinfo", [], "interactive"),
+    rpcCall("getnettotals", [], "interactive"),
+  ]);
+  return {
+    network: net.result ? () => renderNetwork(net.result) : null,
+    traffic: totals.result ? () => renderNetTotals(totals.result) : null,
+  };
+}
+
+async function fetchMiningUpdate() {
+  const [info, hashps] = await rpcBatch([["getmininginfo", []], ["getnetworkhashps", []]], "interactive");
+  return { mining: info.result ? () => renderDashboardMining(info.result, hashps.result) : null };
+}
+
+async function fetchPeersUpdate() {
+  const peers = await rpcCall("getpeerinfo", [], "interactive");
+  return {
+    peers: peers.result
+      ? () => {
+        renderPeers(peers.result);
+        lastPeersRefreshMs = Date.now();
+      }
+      : null,
+  };
+}
+
+const DASHBOARD_SECTION_FETCHERS = {
+  chain: fetchChainUpdate,
+  mempool: fetchMempoolUpdate,
+  fees: fetchFeeEstimatesUpdate,
+  network: fetchNetworkAndTrafficUpdate,
+  traffic: fetchNetworkAndTrafficUpdate,
+  mining: fetchMiningUpdate,
+  peers: fetchPeersUpdate,
+};
+
+async function refreshDashboardSection(section) {
+  const sections = DASHBOARD_SECTION_GROUPS[section];
+  if (!sections || dashboardHistoryOffset > 0) return;
+  const gen = beginSectionFetch(dashSections, sections);
+  renderSectionStatus();
+  try {
+    applySectionResults(gen, await DASHBOARD_SECTION_FETCHERS[section]());
+    updateStatus(true);
+  } catch (_) {
+    failSectionFetch(gen, sections);
+    updateStatus(false);
+  }
+}
+
+function dashboardPollMs() {
+  const configured = Math.max(1, Number(document.getElementById("cfg-poll-interval").value) || 5) * 1000;
+  const base = zmqConnected ? Math.max(configured, DASHBOARD_ZMQ_FALLBACK_MS) : configured;
+  return base * nodeBusyBackoff;
+}
+
+function scheduleDashboardPoll(generation) {
+  if (dashTimer) clearTimeout(dashTimer);
+  dashTimer = setTimeout(async () => {
+    if (generation !== dashboardPollingGeneration) return;
+    await fetchDashboard();
+    if (generation !== dashboardPollingGeneration) return;
+    scheduleDashboardPoll(generation);
+  }, dashboardPollMs());
+}
+
+function requestDashboardRefreshSoon() {
+  if (zmqRefreshTimer) return;
+  zmqRefreshTimer = setTimeout(async () => {
+    zmqRefreshTimer = null;
+    await flushDashboardPartRefreshes();
+  }, DASHBOARD_PART_DEBOUNCE_MS * nodeBusyBackoff);
+}
+
+function dashboardVisible() {
+  return !document.getElementById("dashboard").hidden;
+}
+
+function queueDashboardPartRefresh(parts) {
+  if (!dashboardVisible() || dashboardHistoryOffset > 0) return;
+  for (const part of parts) pendingDashboardParts.add(part);
+  requestDashboardRefreshSoon();
+}
+
+// Several hashblocks can collapse into one debounced refresh; the newest one
+// is the block the refreshed chain card reflects, so it owns the sample.
+function newestBlockLatencyTag(current, messages) {
+  let tag = current;
+  for (const msg of messages) {
+    if (msg.topic !== "hashblock" || typeof msg.timestamp_ms !== "number") continue;
+    if (!tag || msg.cursor > tag.cursor) tag = { cursor: msg.cursor, receivedMs: msg.timestamp_ms };
+  }
+  return tag;
+}
+
+function completeBlockLatency(tag, scheduledMs, appliedMs) {
+  if (!tag) return null;
+  return {
+    cursor: tag.cursor,
+    queueMs: Math.max(0, scheduledMs - tag.receivedMs),
+    totalMs: Math.max(0, appliedMs - tag.receivedMs),
+  };
+}
+
+function pushBlockLatencySample(samples, sample, max) {
+  const next = samples.concat([sample]);
+  return next.length > max ? next.slice(next.length - max) : next;
+}
+
+function percentile(sortedValues, p) {
+  if (sortedValues.length === 0) return null;
+  const idx = Math.min(sortedValues.length - 1, Math.ceil((p / 100) * sortedValues.length) - 1);
+  return sortedValues[Math.max(0, idx)];
+}
+
+function blockLatencyStats(samples) {
+  if (samples.length === 0) return null;
+  const totals = samples.map((s) => s.totalMs).sort((a, b) => a - b);
+  const queues = samples.map((s) => s.queueMs).sort((a, b) => a - b);
+  return {
+    count: samples.length,
+    median: percentile(totals, 50),
+    p95: percentile(totals, 95),
+    queueMedian: percentile(queues, 50),
+  };
+}
+
+function renderBlockLatency() {
+  const el = document.getElementById("dash-zmq-latency");
+  const stats = blockLatencyStats(blockLatencySamples);
+  if (!stats) {
+    el.hidden = true;
+    return;
+  }
+  const secs = (ms) => (ms / 1000).toFixed(1) + "s";
+  el.textContent = `block\u2192UI: median ${secs(stats.median)} \u00b7 p95 ${secs(stats.p95)} (${stats.count} blocks)`;
+  el.title = `Median time from ZMQ receipt to refresh scheduling: ${secs(stats.queueMedian)}`;
+  el.hidden = false;
+}
+
+function deriveDashboardParts(messages) {
+  const parts = new Set();
+  for (const msg of messages) {
+    if (msg.topic === "hashblock" || msg.topic === "rawblock") {
+      parts.add("chain");
+      parts.add("mempool");
+      parts.add("fees");
+      parts.add("mining");
+    } else if (msg.topic === "hashtx" || msg.topic === "rawtx") {
+      parts.add("mempool");
+    }
+  }
+  return parts;
+}
+
+function sectionTask(sections, fetcher) {
+  const gen = beginSectionFetch(dashSections, sections);
+  return fetcher().then(
+    (renders) => applySectionResults(gen, renders),
+    (err) => {
+      failSectionFetch(gen, sections);
+      throw err;
+    },
+  );
+}
+
+async function flushDashboardPartRefreshes() {
+  if (!dashboardVisible() || pendingDashboardParts.size === 0) return;
+  if (dashboardFetchInFlight) return;
+  const parts = new Set(pendingDashboardParts);
+  pendingDashboardParts.clear();
+  const tasks = [];
+  if (parts.has("chain")) {
+    const latencyTag = pendingBlockLatency;
+    const scheduledMs = Date.now();
+    pendingBlockLatency = null;
+    tasks.push(sectionTask(["chain"], async () => {
+      const [chain, uptime] = await Promise.all([
+        rpcCall("getblockchaininfo", []),
+        rpcCall("uptime", []),
+      ]);
+      if (!chain.result) return { chain: null };
+      return {
+        chain: () => {
+          renderChain(chain.result, uptime.result);
+          const sample = completeBlockLatency(latencyTag, scheduledMs, Date.now());
+          if (sample) {
+            blockLatencySamples = pushBlockLatencySample(blockLatencySamples, sample, BLOCK_LATENCY_MAX_SAMPLES);
+            renderBlockLatency();
+          }
+        },
+      };
+    }));
+  }
+  if (parts.has("mempool")) {
+    tasks.push(sectionTask(["mempool"], async () => {
+      // The height rides along so block deltas pair each snapshot with the
+      // chain tip it was taken at, whichever card finishes refreshing first.
+      const [mempool, count] = await rpcBatch([["getmempoolinfo", []], ["getblockcount", []]]);
+      return { mempool: mempool.result ? () => renderMempool(mempool.result, count.result) : null };
+    }));
+  }
+  if (parts.has("fees")) {
+    // Estimates only move when a block confirms transactions.
+    tasks.push(sectionTask(["fees"], fetchFeeEstimatesUpdate));
+  }
+  if (parts.has("mining")) {
+    // Difficulty and the hashrate estimate only change with a new block.
+    tasks.push(sectionTask(["mining"], async () => {
+      const [info, hashps] = await rpcBatch([["getmininginfo", []], ["getnetworkhashps", []]]);
+      return { mining: info.result ? () => renderDashboardMining(info.result, hashps.result) : null };
+    }));
+  }
+  const now = Date.now();
   if (parts.has("peers") && (now - lastPeersRefreshMs >= PEERS_REFRESH_MIN_MS)) {
-    tasks.push((async () => {
+    tasks.push(sectionTask(["peers"], async () => {
       const peers = await rpcCall("getpeerinfo", []);
-      if (peers.result) {
-        renderPeers(peers.result);
-        lastPeersRefreshMs = Date.now();
+      return {
+        peers: peers.result
+          ? () => {
+            renderPeers(peers.result);
+            lastPeersRefreshMs = Date.now();
+          }
+          : null,
+      };
+    }));
+  }
+  if (tasks.length === 0) return;
+  try {
+    await Promise.all(tasks);
+    updateStatus(true);
+  } catch (_) {
+    updateStatus(false);
+  }
+}
+
+async function fetchDashboard() {
+  if (dashboardFetchInFlight) {
+    dashboardFetchQueued = true;
+    return;
+  }
+  dashboardFetchInFlight = true;
+  const gen = beginSectionFetch(dashSections, DASHBOARD_SECTIONS);
+  renderSectionStatus();
+  try {
+    const [results, fees] = await Promise.all([rpcBatch([
+      ["getblockchaininfo", []],
+      ["getnetworkinfo", []],
+      ["getmempoolinfo", []],
+      ["getpeerinfo", []],
+      ["uptime", []],
+      ["getnettotals", []],
+      ["getmininginfo", []],
+      ["getnetworkhashps", []],
+      ["getbalances", []],
+    ]), fetchFeeEstimates()]);
+    const [chain, net, mempool, peers, up, totals, mining, hashps, balances] = results;
+    // A timed-out or unreachable node fails every call with the same error.
+    if (results.every((r) => r.error)) {
+      countUsage("dashboard_failures");
+      failSectionFetch(gen, DASHBOARD_SECTIONS);
+      if (results[0].node_busy) {
+        showDashboardBusy();
+        updateStatus(true);
+        return;
+      }
+      showDashboardError(results[0].error);
+      updateStatus(false);
+      return;
+    }
+    countUsage("dashboard_refreshes");
+    showDashboardError(null);
+    const compat = net.result && typeof net.result.version === "number"
+      ? await checkNodeVersion(net.result.version)
+      : nodeCompat;
+    if (compat && compat.level === "unusable") {
+      failSectionFetch(gen, DASHBOARD_SECTIONS);
+      stopDashboardPolling();
+      return;
+    }
+    // getbalances arrived in 0.19; older nodes only have the trusted total.
+    const legacyBalance = balances.error && balances.error.code === -32601
+      ? await rpcCall("getbalance", [])
+      : null;
+    const wallet = walletSummary(balances, legacyBalance);
+    const snapshot = { atMs: Date.now(), chain, net, mempool, peers, up, totals, mining, hashps, wallet, fees };
+    recordDashboardSnapshot(snapshot);
+    pushStatusPageSnapshot(snapshot);
+    if (dashboardHistoryOffset > 0) {
+      // Polling carries on so history stays current, but the cards keep
+      // showing the snapshot being browsed.
+      failSectionFetch(gen, DASHBOARD_SECTIONS);
+      updateStatus(true);
+      return;
+    }
+    requestAnimationFrame(() => {
+      try {
+        applySectionResults(gen, {
+          chain: chain.result ? () => renderChain(chain.result, up.result) : null,
+          mempool: mempool.result
+            ? () => renderMempool(mempool.result, chain.result ? chain.result.blocks : null)
+            : null,
+          fees: fees.estimates ? () => renderFeeEstimates(fees) : null,
+          network: net.result
+            ? () => {
+              renderNetwork(net.result);
+              if (mempool.result) refreshRelayPolicy(mempool.result, net.result);
+            }
+            : null,
+          traffic: totals.result ? () => renderNetTotals(totals.result) : null,
+          mining: mining.result ? () => renderDashboardMining(mining.result, hashps.result) : null,
+          peers: peers.result
+            ? () => {
+              renderPeers(peers.result);
+              lastPeersRefreshMs = Date.now();
+            }
+            : null,
+        });
+        renderDashboardWallet(wallet);
+        if (mempool.result) refreshMempoolAncestors();
+        refreshCustomCards("full");
+        pendingDashboardParts.clear();
+        pendingBlockLatency = null;
+        updateStatus(true);
+      } catch (_) {
+        failSectionFetch(gen, DASHBOARD_SECTIONS);
+        updateStatus(false);
+      }
+    });
+  } catch (e) {
+    countUsage("dashboard_failures");
+    showDashboardError(String(e));
+    failSectionFetch(gen, DASHBOARD_SECTIONS);
+    updateStatus(false);
+  } finally {
+    dashboardFetchInFlight = false;
+    // Awaited so the next poll is scheduled with the current busy backoff.
+    await refreshRpcStats();
+    if (dashboardFetchQueued) {
+      dashboardFetchQueued = false;
+      fetchDashboard();
+    }
+  }
+}
+
+// --- Custom cards ---
+
+// Definitions are kept by the backend in the app's config dir. It also
+// decides which cards a trigger makes due and fetches them in one batch, so
+// this side only tracks when each card last refreshed.
+async function loadCustomCards() {
+  try {
+    const resp = await fetch("/custom-cards");
+    const data = await resp.json();
+    customCards = Array.isArray(data.cards) ? data.cards : [];
+    if (data.read_only) renderCustomCardMethods(data.read_only);
+  } catch (_) {
+    customCards = [];
+  }
+  renderCustomCards();
+  renderCustomCardList();
+  if (dashboardVisible()) refreshCustomCards("full");
+}
+
+function customCardValueText(value) {
+  if (value === null || typeof value !== "object") return String(value);
+  return JSON.stringify(value);
+}
+
+function renderCustomCards() {
+  let html = "";
+  for (const card of customCards) {
+    const state = customCardState.get(card.id);
+    let body;
+    if (!state) {
+      body = '<div class="custom-card-value pending">\u2026</div>';
+    } else if (state.error) {
+      body = `<div class="custom-card-value error">${esc(state.error)}</div>`;
+    } else {
+      const scalar = state.value === null || typeof state.value !== "object";
+      body = `<div class="custom-card-value${scalar ? " scalar" : ""}">${esc(customCardValueText(state.value))}</div>`;
+    }
+    const id = esc(card.id);
+    html += `<section class="dash-card custom-card" data-card-id="${id}">`
+      + `<h3>${esc(card.title)} <span class="card-tools"><span class="card-updated" data-custom-card="${id}"></span>`
+      + `<button class="card-refresh" data-custom-card="${id}" title="Refresh ${esc(card.title)}">&#8635;</button></span></h3>`
+      + body
+      + `<div class="custom-card-source">${esc(card.field ? `${card.method} ${card.field}` : card.method)}</div>`
+      + "</section>";
+  }
+  document.getElementById("dash-custom-cards").innerHTML = html;
+  renderSectionStatus();
+}
+
+// `trigger` is "full" after a dashboard poll, "block" on a new tip and
+// "manual" from a card's refresh button, which also passes the card's id.
+async function refreshCustomCards(trigger, id) {
+  if (customCards.length === 0 || dashboardHistoryOffset > 0) return;
+  if (customCardsInFlight) {
+    if (!customCardsQueued || trigger === "manual") customCardsQueued = { trigger, id };
+    return;
+  }
+  customCardsInFlight = true;
+  const button = id ? document.querySelector(`.card-refresh[data-custom-card="${CSS.escape(id)}"]`) : null;
+  if (button) button.classList.add("refreshing");
+  const lastRefreshed = {};
+  for (const [cardId, state] of customCardState) {
+    if (state.refreshedMs) lastRefreshed[cardId] = state.refreshedMs;
+  }
+  try {
+    const resp = await fetch("/custom-cards/refresh", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: JSON.stringify({ trigger, id, last_refreshed: lastRefreshed }),
+    });
+    const data = await resp.json();
+    if (data.error) throw new Error(data.error);
+    for (const c of data.cards) {
+      customCardState.set(c.id, { value: c.value, error: c.error || null, refreshedMs: data.refreshed_ms });
+    }
+    if (data.cards.length) renderCustomCards();
+  } catch (e) {
+    // Cards without a refresh time are due again on the next trigger.
+    for (const card of customCards) {
+      if (id ? card.id === id : !customCardState.has(card.id)) {
+        customCardState.set(card.id, { value: null, error: String(e), refreshedMs: 0 });
+      }
+    }
+    renderCustomCards();
+  } finally {
+    customCardsInFlight = false;
+    if (button) button.classList.remove("refreshing");
+    if (customCardsQueued) {
+      const next = customCardsQueued;
+      customCardsQueued = null;
+      refreshCustomCards(next.trigger, next.id);
+    }
+  }
+}
+
+function noteCustomCardsTip(hash) {
+  const changed = customCardsTip !== null && hash !== customCardsTip;
+  customCardsTip = hash;
+  if (changed) refreshCustomCards("block");
+}
+
+const CUSTOM_CARD_CADENCES = { full: "every refresh", block: "each block", manual: "manual" };
+
+function renderCustomCardList() {
+  document.getElementById("custom-cards-count").textContent = customCards.length ? `(${customCards.length})` : "";
+  document.getElementById("custom-cards-list").innerHTML = customCards.map((card) => {
+    const source = card.field ? `${card.method} ${card.field}` : card.method;
+    return `<li data-card-id="${esc(card.id)}"><span class="custom-card-name">${esc(card.title)}</span>`
+      + `<span class="custom-card-meta">${esc(source)} \u00b7 ${esc(CUSTOM_CARD_CADENCES[card.cadence] || card.cadence)}</span>`
+      + '<button class="card-action" type="button" data-action="edit">Edit</button>'
+      + '<button class="card-action" type="button" data-action="delete">Delete</button></li>';
+  }).join("");
+}
+
+function setCustomCardError(message) {
+  const el = document.getElementById("custom-card-error");
+  el.textContent = message || "";
+  el.hidden = !message;
+}
+
+function resetCustomCardForm() {
+  customCardEditing = null;
+  document.getElementById("custom-card-form").reset();
+  document.getElementById("custom-card-save").textContent = "Add card";
+  document.getElementById("custom-card-cancel").hidden = true;
+  setCustomCardError(null);
+}
+
+function editCustomCard(card) {
+  customCardEditing = card.id;
+  document.getElementById("custom-card-title").value = card.title;
+  document.getElementById("custom-card-method").value = card.method;
+  document.getElementById("custom-card-params").value = JSON.stringify(card.params);
+  document.getElementById("custom-card-field").value = card.field;
+  document.getElementById("custom-card-cadence").value = card.cadence;
+  document.getElementById("custom-card-save").textContent = "Save card";
+  document.getElementById("custom-card-cancel").hidden = false;
+  setCustomCardError(null);
+}
+
+async function saveCustomCards(cards) {
+  const payload = { cards };
+  const resp = await fetch("/custom-cards/save", {
+    method: "POST",
+    headers: {
+      "content-type": "application/json",
+      "x-app-json": encodeHeaderJson(payload),
+    },
+    body: JSON.stringify(payload),
+  });
+  const data = await resp.json();
+  // A disk problem (as opposed to an invalid card) can be worked around by
+  // saving somewhere else for this session.
+  showCustomCardsFallback(data.problem ? { dir: data.fallback || "", cards } : null);
+  if (data.error) throw new Error(data.error);
+  customCards = data.cards;
+  renderCustomCards();
+  renderCustomCardList();
+}
+
+function showCustomCardsFallback(pending) {
+  customCardsFallbackPending = pending;
+  const row = document.getElementById("custom-cards-fallback");
+  row.hidden = !pending;
+  if (pending) document.getElementById("custom-cards-fallback-dir").value = pending.dir;
+}
+
+async function customCardsFallbackClicked() {
+  const pending = customCardsFallbackPending;
+  if (!pending) return;
+  const payload = { dir: document.getElementById("custom-cards-fallback-dir").value };
+  let data;
+  try {
+    const resp = await fetch("/config-store/fallback", {
+      method: "POST",
+      headers: {
+        "content-type": "application/json",
+        "x-app-json": encodeHeaderJson(payload),
+      },
+      body: JSON.stringify(payload),
+    });
+    data = await resp.json();
+  } catch (e) {
+    data = { error: String(e) };
+  }
+  if (data.error) {
+    setCustomCardError(data.error);
+    return;
+  }
+  try {
+    await saveCustomCards(pending.cards);
+  } catch (e) {
+    setCustomCardError(e.message);
+    return;
+  }
+  setCustomCardError(null);
+  const note = document.getElementById("custom-cards-fallback-note");
+  note.textContent = `Saved to ${data.dir} for this session.`;
+  note.hidden = false;
+}
+
+async function customCardSubmitted(ev) {
+  ev.preventDefault();
+  const paramsText = document.getElementById("custom-card-params").value.trim();
+  let params;
+  try {
+    params = paramsText ? JSON.parse(paramsText) : [];
+  } catch (e) {
+    setCustomCardError(`Params are not valid JSON: ${e.message}`);
+    return;
+  }
+  const id = customCardEditing || `card-${Date.now().toString(36)}`;
+  const card = {
+    id,
+    title: document.getElementById("custom-card-title").value.trim(),
+    method: document.getElementById("custom-card-method").value.trim(),
+    params,
+    field: document.getElementById("custom-card-field").value.trim(),
+    cadence: document.getElementById("custom-card-cadence").value,
+  };
+  const cards = customCardEditing
+    ? customCards.map((c) => (c.id === id ? card : c))
+    : [...customCards, card];
+  try {
+    await saveCustomCards(cards);
+  } catch (e) {
+    setCustomCardError(e.message);
+    return;
+  }
+  resetCustomCardForm();
+  customCardState.delete(id);
+  renderCustomCards();
+  refreshCustomCards("manual", id);
+}
+
+async function customCardListClicked(ev) {
+  const btn = ev.target.closest("button[data-action]");
+  if (!btn) return;
+  const id = btn.closest("li").dataset.cardId;
+  const card = customCards.find((c) => c.id === id);
+  if (!card) return;
+  if (btn.dataset.action === "edit") {
+    editCustomCard(card);
+    return;
+  }
+  try {
+    await saveCustomCards(customCards.filter((c) => c.id !== id));
+    customCardState.delete(id);
+    if (customCardEditing === id) resetCustomCardForm();
+  } catch (e) {
+    setCustomCardError(e.message);
+  }
+}
+
+// Cards refresh unattended, so the backend only accepts read-only methods;
+// only those are suggested.
+function customCardMethodAllowed(name, rule) {
+  if (rule.except.includes(name)) return false;
+  return rule.methods.includes(name) || rule.prefixes.some((p) => name.startsWith(p));
+}
+
+function renderCustomCardMethods(rule) {
+  document.getElementById("custom-card-methods").innerHTML = schema.methods
+    .filter((m) => customCardMethodAllowed(m.name, rule))
+    .map((m) => `<option value="${esc(m.name)}"></option>`)
+    .join("");
+}
+
+function initCustomCards() {
+  document.getElementById("custom-card-form").addEventListener("submit", customCardSubmitted);
+  attachJsonPasteCleanup(document.getElementById("custom-card-params"));
+  document.getElementById("custom-card-cancel").addEventListener("click", resetCustomCardForm);
+  document.getElementById("custom-cards-fallback-save").addEventListener("click", customCardsFallbackClicked);
+  document.getElementById("custom-cards-list").addEventListener("click", customCardListClicked);
+  document.getElementById("dash-custom-cards").addEventListener("click", (ev) => {
+    const btn = ev.target.closest(".card-refresh[data-custom-card]");
+    if (btn) refreshCustomCards("manual", btn.dataset.customCard);
+  });
+  loadCustomCards();
+}
+
+// --- Usage metrics ---
+
+// Counters only exist while recording is on; every hook returns at once
+// otherwise. Pending counts are bucketed by local date and merged into the
+// metrics file by the backend.
+function localDate(d) {
+  const pad = (n) => String(n).padStart(2, "0");
+  return `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}`;
+}
+
+function emptyUsageDay() {
+  return { launches: 0, view_secs: {}, rpc_calls: {}, dashboard_refreshes: 0, dashboard_failures: 0 };
+}
+
+function usageDay() {
+  const date = localDate(new Date());
+  if (!usagePending.has(date)) usagePending.set(date, emptyUsageDay());
+  return usagePending.get(date);
+}
+
+function countUsage(counter, key, amount = 1) {
+  if (!usageMetricsEnabled) return;
+  const day = usageDay();
+  if (key === undefined) {
+    day[counter] += amount;
+  } else {
+    day[counter][key] = (day[counter][key] || 0) + amount;
+  }
+}
+
+// Time is credited to the view that was showing when it ends, and only
+// while the window is visible.
+function closeUsageView() {
+  if (!usageView) return;
+  const secs = Math.round((Date.now() - usageView.since) / 1000);
+  if (secs > 0) countUsage("view_secs", usageView.id, secs);
+  usageView = null;
+}
+
+function noteUsageView(id) {
+  if (!usageMetricsEnabled) return;
+  closeUsageView();
+  if (!document.hidden) usageView = { id, since: Date.now() };
+}
+
+function currentMainView() {
+  return MAIN_VIEWS.find((id) => !document.getElementById(id).hidden) || "dashboard";
+}
+
+function flushUsageMetrics() {
+  if (usageView) {
+    const id = usageView.id;
+    closeUsageView();
+    if (!document.hidden) usageView = { id, since: Date.now() };
+  }
+  const pending = usagePending;
+  usagePending = new Map();
+  // keepalive lets the last flush finish while the window closes.
+  return Promise.all([...pending].map(([date, day]) => fetch("/metrics/record", {
+    method: "POST",
+    headers: { "content-type": "application/json" },
+    body: JSON.stringify({ date, ...day }),
+    keepalive: true,
+  }).catch(() => {})));
+}
+
+function setUsageMetricsEnabled(enabled) {
+  if (enabled === usageMetricsEnabled) return;
+  if (!enabled) flushUsageMetrics();
+  usageMetricsEnabled = enabled;
+  usageView = null;
+  if (enabled) noteUsageView(currentMainView());
+}
+
+function usageMetricsToggled() {
+  saveConfig();
+  setUsageMetricsEnabled(document.getElementById("cfg-usage-metrics").checked);
+}
+
+function initUsageMetrics() {
+  setUsageMetricsEnabled(document.getElementById("cfg-usage-metrics").checked);
+  countUsage("launches");
+  setInterval(() => {
+    if (usageMetricsEnabled) flushUsageMetrics();
+  }, USAGE_FLUSH_MS);
+  document.addEventListener("visibilitychange", () => {
+    if (!usageMetricsEnabled) return;
+    if (document.hidden) {
+      flushUsageMetrics();
+    } else {
+      noteUsageView(currentMainView());
+    }
+  });
+  window.addEventListener("pagehide", () => {
+    if (usageMetricsEnabled) flushUsageMetrics();
+  });
+}
+
+function summarizeUsageMetrics(days) {
+  const total = emptyUsageDay();
+  const dates = Object.keys(days || {}).sort();
+  for (const date of dates) {
+    const day = days[date];
+    total.launches += day.launches || 0;
+    total.dashboard_refreshes += day.dashboard_refreshes || 0;
+    total.dashboard_failures += day.dashboard_failures || 0;
+    for (const key of ["view_secs", "rpc_calls"]) {
+      for (const [name, n] of Object.entries(day[key] || {})) total[key][name] = (total[key][name] || 0) + n;
+    }
+  }
+  return { total, first: dates[0] || null, last: dates[dates.length - 1] || null, dayCount: dates.length };
+}
+
+function usageRows(counts, format, limit) {
+  const rows = Object.entries(counts).sort((a, b) => b[1] - a[1] || a[0].localeCompare(b[0])).slice(0, limit);
+  if (!rows.length) return '<tr><td class="wallet-empty">Nothing recorded</td></tr>';
+  return rows.map(([name, n]) => `<tr><td>${esc(name)}</td><td>${esc(format(n))}</td></tr>`).join("");
+}
+
+async function showUsageMetrics() {
+  showMainView("metrics-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  setMetricsDeleteConfirm(false);
+  if (usageMetricsEnabled) await flushUsageMetrics();
+  let metrics;
+  try {
+    metrics = await (await fetch("/metrics")).json();
+  } catch (e) {
+    metrics = { error: String(e) };
+  }
+  renderUsageMetrics(metrics);
+}
+
+function renderUsageMetrics(metrics) {
+  const note = document.getElementById("metrics-note");
+  const dl = document.getElementById("metrics-totals");
+  if (metrics.error) {
+    note.textContent = "Couldn't read metrics: " + metrics.error;
+    dl.innerHTML = "";
+    return;
+  }
+  const { total, first, last, dayCount } = summarizeUsageMetrics(metrics.days);
+  const state = usageMetricsEnabled ? "Recording is on" : "Recording is off";
+  note.textContent = `${state}. Stored locally in ${metrics.path}; nothing is sent anywhere. `
+    + (dayCount ? `Covers ${dayCount} day${dayCount === 1 ? "" : "s"}, ${first} to ${last}.` : "No metrics recorded yet.");
+  updateDl(dl, [
+    ["Launches", total.launches.toLocaleString()],
+    ["Dashboard refreshes", total.dashboard_refreshes.toLocaleString()],
+    ["Dashboard failures", total.dashboard_failures.toLocaleString()],
+  ]);
+  document.querySelector("#metrics-views tbody").innerHTML =
+    usageRows(total.view_secs, (secs) => formatDuration(secs), Infinity);
+  document.querySelector("#metrics-methods tbody").innerHTML =
+    usageRows(total.rpc_calls, (n) => n.toLocaleString(), USAGE_METHODS_SHOWN);
+}
+
+function setMetricsDeleteConfirm(open) {
+  document.getElementById("metrics-delete").hidden = open;
+  document.getElementById("metrics-delete-confirm").hidden = !open;
+}
+
+async function deleteUsageMetricsConfirmed() {
+  usagePending = new Map();
+  try {
+    await fetch("/metrics/delete", { method: "POST" });
+  } catch (_) {}
+  await showUsageMetrics();
+}
+
+// --- Exports ---
+
+async function saveResult(button) {
+  if (lastResultValue === undefined) return;
+  const tab = resultTabs.find((t) => t.id === activeResultTabId);
+  const payload = {
+    kind: "result",
+    label: tab ? tab.method : currentMethod?.name || "",
+    extension: "json",
+    text: JSON.stringify(lastResultValue, null, 2) ?? String(lastResultValue),
+  };
+  let error = "no reply";
+  try {
+    const r = await fetch("/exports/save", { method: "POST", body: JSON.stringify(payload) });
+    const j = await r.json();
+    error = j.error || null;
+  } catch (e) {
+    error = String(e);
+  }
+  flashSaved(button, error);
+}
+
+async function showExports() {
+  showMainView("exports-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  let exports;
+  try {
+    exports = await (await fetch("/exports")).json();
+  } catch (e) {
+    exports = { error: String(e) };
+  }
+  renderExports(exports);
+}
+
+function renderExports(exports) {
+  const note = document.getElementById("exports-note");
+  const tbody = document.querySelector("#exports-table tbody");
+  if (exports.error) {
+    note.textContent = "Couldn't list exports: " + exports.error;
+    tbody.innerHTML = "";
+    return;
+  }
+  note.textContent = `Saved to ${exports.dir}. Files are never overwritten; each save gets a new name.`;
+  if (!exports.entries.length) {
+    tbody.innerHTML = '<tr><td colspan="5" class="wallet-empty">Nothing exported yet</td></tr>';
+    return;
+  }
+  tbody.innerHTML = exports.entries.map((e) => {
+    const name = e.path.split(/[\\/]/).pop();
+    return `<tr><td title="${esc(e.path)}">${esc(name)}</td><td>${esc(e.kind)}</td>`
+      + `<td>${esc(formatBytes(e.size))}</td><td>${esc(new Date(e.created_ms).toLocaleString())}</td>`
+      + `<td class="wallet-actions"><button type="button" data-path="${esc(e.path)}">Delete</button></td></tr>`;
+  }).join("");
+}
+
+async function exportsTableClicked(ev) {
+  const button = ev.target.closest("button[data-path]");
+  if (!button) return;
+  button.disabled = true;
+  try {
+    const r = await fetch("/exports/delete", { method: "POST", body: JSON.stringify({ path: button.dataset.path }) });
+    const j = await r.json();
+    if (j.error) throw new Error(j.error);
+  } catch (e) {
+    button.disabled = false;
+    document.getElementById("exports-note").textContent = "Couldn't delete: " + (e.message || e);
+    return;
+  }
+  await showExports();
+}
+
+async function openExportsFolder() {
+  try {
+    const j = await (await fetch("/exports/open-folder", { method: "POST" })).json();
+    if (j.error) document.getElementById("exports-note").textContent = "Couldn't open the folder: " + j.error;
+  } catch (_) {}
+}
+
+// --- Dashboard history ---
+
+function pushDashboardHistory(history, snapshot, max) {
+  const next = history.concat([snapshot]);
+  return next.length > max ? next.slice(next.length - max) : next;
+}
+
+// While browsing, a new snapshot pushes the one on screen one step further
+// back; it stays put until it falls out of the ring.
+function historyOffsetAfterPush(offset, length) {
+  return offset === 0 ? 0 : Math.min(offset + 1, length - 1);
+}
+
+function recordDashboardSnapshot(snapshot) {
+  dashboardHistory = pushDashboardHistory(dashboardHistory, snapshot, DASHBOARD_HISTORY_LIMIT);
+  if (dashboardHistoryOffset === 0) return;
+  const offset = historyOffsetAfterPush(dashboardHistoryOffset, dashboardHistory.length);
+  const evicted = offset !== dashboardHistoryOffset + 1;
+  dashboardHistoryOffset = offset;
+  if (evicted) renderDashboardSnapshot(dashboardHistory[dashboardHistory.length - 1 - offset]);
+  renderDashboardHistoryBanner();
+}
+
+// Only a summary goes to the status page listener; the backend drops any
+// field it doesn't know.
+function pushStatusPageSnapshot(snap) {
+  if (!statusPageAddress) return;
+  const chain = snap.chain.result || {};
+  const net = snap.net.result || {};
+  const mempool = snap.mempool.result || {};
+  const payload = {
+    at_ms: snap.atMs,
+    chain: chain.chain,
+    blocks: chain.blocks,
+    headers: chain.headers,
+    verification_progress: chain.verificationprogress,
+    initial_block_download: chain.initialblockdownload,
+    best_block_hash: chain.bestblockhash,
+    mempool_transactions: mempool.size,
+    mempool_bytes: mempool.bytes,
+    connections_in: net.connections_in,
+    connections_out: net.connections_out,
+    subversion: net.subversion,
+    uptime_secs: snap.up.result,
+  };
+  fetch("/status-page/snapshot", {
+    method: "POST",
+    headers: { "content-type": "application/json", "x-app-json": encodeHeaderJson(payload) },
+    body: JSON.stringify(payload),
+  }).catch(() => {});
+}
+
+function renderDashboardSnapshot(snap) {
+  const { chain, net, mempool, peers, up, totals, mining, hashps, wallet, fees } = snap;
+  if (chain.result) renderChain(chain.result, up.result, false);
+  if (mempool.result) renderMempool(mempool.result, chain.result ? chain.result.blocks : null, false);
+  if (fees.estimates) renderFeeEstimates(fees);
+  if (net.result) renderNetwork(net.result, false);
+  if (totals.result) renderNetTotals(totals.result, false);
+  if (mining.result) renderDashboardMining(mining.result, hashps.result);
+  if (peers.result) renderPeers(peers.result);
+  renderDashboardWallet(wallet);
+}
+
+function renderDashboardHistoryBanner() {
+  const banner = document.getElementById("dash-history");
+  banner.hidden = dashboardHistoryOffset === 0;
+  if (banner.hidden) return;
+  const snap = dashboardHistory[dashboardHistory.length - 1 - dashboardHistoryOffset];
+  document.getElementById("dash-history-text").textContent =
+    `BROWSING HISTORY: T\u2212${dashboardHistoryOffset} \u00b7 ${new Date(snap.atMs).toLocaleTimeString()}`;
+}
+
+function setDashboardHistoryOffset(offset) {
+  const clamped = Math.max(0, Math.min(offset, dashboardHistory.length - 1));
+  if (clamped === dashboardHistoryOffset) return;
+  const wasBrowsing = dashboardHistoryOffset > 0;
+  dashboardHistoryOffset = clamped;
+  renderDashboardHistoryBanner();
+  if (clamped > 0) {
+    renderDashboardSnapshot(dashboardHistory[dashboardHistory.length - 1 - clamped]);
+  } else if (wasBrowsing) {
+    // Back at the live end: show the newest snapshot now, then catch up.
+    const newest = dashboardHistory[dashboardHistory.length - 1];
+    if (newest) renderDashboardSnapshot(newest);
+    if (dashboardVisible()) fetchDashboard();
+  }
+}
+
+function initDashboardHistory() {
+  document.getElementById("dash-history-live").addEventListener("click", () => setDashboardHistoryOffset(0));
+}
+
+// Bound to [ and ] by default.
+function stepDashboardHistory(step) {
+  if (!dashboardVisible()) return false;
+  setDashboardHistoryOffset(dashboardHistoryOffset + step);
+  return true;
+}
+
+// --- Relay policy ---
+
+// Policy only changes with a node restart, so the summary is fetched again
+// only when one of its inputs does.
+async function refreshRelayPolicy(mempool, net) {
+  const inputs = {
+    mempool: {
+      fullrbf: mempool.fullrbf,
+      minrelaytxfee: mempool.minrelaytxfee,
+      incrementalrelayfee: mempool.incrementalrelayfee,
+    },
+    network: { version: net.version, relayfee: net.relayfee, incrementalfee: net.incrementalfee },
+  };
+  const key = JSON.stringify(inputs);
+  if (key === relayPolicyKey) return;
+  try {
+    const r = await fetch("/relay-policy", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: key,
+    });
+    const policy = await r.json();
+    if (policy.error) return;
+    relayPolicyKey = key;
+    renderRelayPolicy(policy);
+  } catch (_) {}
+}
+
+function renderRelayPolicy(policy) {
+  const el = document.getElementById("dash-relay-policy");
+  el.hidden = false;
+  updateDl(el.querySelector("dl"), policy.settings.map((s) => [s.name, { text: s.value, secondary: s.about }]));
+  el.querySelector(".relay-policy-count").textContent = policy.hints.length
+    ? `(${policy.hints.length} non-default)`
+    : "";
+  el.querySelector(".relay-policy-hints").innerHTML = policy.hints.map((h) => `<li>${esc(h)}</li>`).join("");
+}
+
+// --- Node version compatibility ---
+
+async function checkNodeVersion(version) {
+  if (nodeCompat && nodeCompat.version === version) return nodeCompat;
+  try {
+    const r = await fetch(`/node/compat?version=${encodeURIComponent(String(version))}`);
+    const j = await r.json();
+    if (j.error) return null;
+    nodeCompat = j;
+  } catch (_) {
+    return null;
+  }
+  renderNodeCompat(nodeCompat);
+  return nodeCompat;
+}
+
+// Unusable nodes get no dashboard at all; the method list still works for
+// raw calls. Degraded ones get a banner that stays dismissed per version.
+function renderNodeCompat(c) {
+  const unusable = c.level === "unusable";
+  document.getElementById("dash-grid").hidden = unusable;
+  showDashboardError(unusable
+    ? `Bitcoin Core ${c.version_text} is too old for the dashboard (needs ${c.minimum_usable} or newer). `
+      + "Methods in the sidebar can still be called directly."
+    : null);
+  const banner = document.getElementById("compat-banner");
+  const dismissed = localStorage.getItem("ui-compat-dismissed") === String(c.version);
+  banner.hidden = c.level !== "degraded" || dismissed;
+  if (banner.hidden) return;
+  document.getElementById("compat-text").innerHTML =
+    `<p>Bitcoin Core ${esc(c.version_text)} is older than ${esc(c.minimum_fully_supported)}; `
+    + "these show placeholders or are unavailable:</p><ul>"
+    + c.degraded.map((d) => `<li>${esc(d.feature)} <span>(needs ${esc(d.since)})</span></li>`).join("")
+    + "</ul>";
+}
+
+function dismissCompatBanner() {
+  if (nodeCompat) localStorage.setItem("ui-compat-dismissed", String(nodeCompat.version));
+  document.getElementById("compat-banner").hidden = true;
+}
+
+function showDashboardError(error) {
+  const el = document.getElementById("dash-error");
+  const text = error == null ? "" : (typeof error === "string" ? error : error.message || JSON.stringify(error));
+  setStatus(el, "error", text, { word: true });
+  el.title = "";
+  el.hidden = !text;
+}
+
+function showDashboardBusy() {
+  const el = document.getElementById("dash-error");
+  setStatus(el, "warn", "Node is busy, reducing refresh rate", { word: true });
+  el.title = "The node's RPC work queue is full; polling slows down until it answers again";
+  el.hidden = false;
+}
+
+// --- Status indicators ---
+
+// Severity names map to a color, a glyph and a word from the backend's
+// table, so nothing relies on color alone. Without the table the text is
+// still shown, just uncolored.
+function statusIndicator(severity) {
+  const s = statusIndicators && statusIndicators.severities[severity];
+  return s || { color: "", glyph: "", word: "" };
+}
+
+function pingSeverity(ms) {
+  if (!statusIndicators) return null;
+  const tier = statusIndicators.ping.find((t) => ms < t.below_ms);
+  return tier ? tier.severity : statusIndicators.ping_otherwise;
+}
+
+// Sets an element's text with its severity glyph in front (or behind, for
+// table cells whose values should line up) and its color as --status-color.
+// `word` leads the text with "Warning:" or "Error:" for banners.
+function setStatus(el, severity, text, { word = false, suffix = false } = {}) {
+  const s = statusIndicator(severity);
+  el.classList.add("status");
+  el.style.setProperty("--status-color", s.color);
+  el.dataset.severity = severity;
+  el.textContent = "";
+  if (!text) {
+    el.append(s.glyph);
+    return;
+  }
+  const glyph = document.createElement("span");
+  glyph.className = "status-glyph";
+  glyph.setAttribute("aria-hidden", "true");
+  glyph.textContent = s.glyph;
+  const body = word && s.word ? `${s.word}: ${text}` : text;
+  if (suffix) el.append(body, glyph);
+  else el.append(glyph, body);
+}
+
+function clearStatus(el, text) {
+  el.classList.remove("status");
+  el.style.removeProperty("--status-color");
+  delete el.dataset.severity;
+  el.textContent = text;
+}
+
+function applyHighVisibility() {
+  document.body.classList.toggle("high-visibility", document.getElementById("cfg-high-visibility").checked);
+}
+
+function esc(s) {
+  return String(s).replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;").replace(/"/g, "&quot;");
+}
+
+function dd(label, value) {
+  return `<dt>${esc(label)}</dt><dd>${esc(String(value))}</dd>`;
+}
+
+// Values are plain strings or { text, secondary } where secondary is shown as
+// a dim annotation (e.g. a fiat estimate) after the main value.
+function setDdValue(dd, value) {
+  const text = typeof value === "object" && value !== null ? value.text : value;
+  const secondary = typeof value === "object" && value !== null ? value.secondary || "" : "";
+  const key = secondary ? text + "\u0000" + secondary : text;
+  if (dd.dataset.value === key) return;
+  dd.dataset.value = key;
+  dd.textContent = text;
+  if (secondary) {
+    const span = document.createElement("span");
+    span.className = "dd-secondary";
+    span.textContent = secondary;
+    dd.appendChild(span);
+  }
+}
+
+function updateDl(dl, entries) {
+  if (dl.children.length !== entries.length * 2) {
+    dl.textContent = "";
+    for (const [label, value] of entries) {
+      const dt = document.createElement("dt");
+      dt.textContent = label;
+      const dd = document.createElement("dd");
+      setDdValue(dd, value);
+      dl.appendChild(dt);
+      dl.appendChild(dd);
+    }
+    return;
+  }
+  for (let i = 0; i < entries.length; i++) {
+    const dt = dl.children[i * 2];
+    if (dt.textContent !== entries[i][0]) dt.textContent = entries[i][0];
+    setDdValue(dl.children[i * 2 + 1], entries[i][1]);
+  }
+}
+
+function formatDuration(secs) {
+  const d = Math.floor(secs / 86400);
+  const h = Math.floor((secs % 86400) / 3600);
+  const m = Math.floor((secs % 3600) / 60);
+  const parts = [];
+  if (d) parts.push(d + "d");
+  if (h) parts.push(h + "h");
+  parts.push(m + "m");
+  return parts.join(" ");
+}
+
+// A rough span for estimates: "45 seconds", "12 minutes", "3.5 hours", "9.2 days".
+function formatDurationHuman(secs) {
+  if (secs < 60) return Math.round(secs) + " seconds";
+  if (secs < 3600) return Math.round(secs / 60) + " minutes";
+  if (secs < 86400) return (secs / 3600).toFixed(1) + " hours";
+  return (secs / 86400).toFixed(1) + " days";
+}
+
+function formatBytes(bytes) {
+  if (bytes < 1e6) return (bytes / 1e3).toFixed(1) + " KB";
+  if (bytes < 1e9) return (bytes / 1e6).toFixed(1) + " MB";
+  return (bytes / 1e9).toFixed(2) + " GB";
+}
+
+const IEC_UNITS = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+// Binary units for traffic, matching -maxuploadtarget, which is in MiB.
+function formatBytesIec(bytes) {
+  let v = Number(bytes);
+  if (!Number.isFinite(v)) return "?";
+  let i = 0;
+  while (Math.abs(v) >= 1024 && i < IEC_UNITS.length - 1) {
+    v /= 1024;
+    i += 1;
+  }
+  return `${v.toFixed(i === 0 ? 0 : 1)} ${IEC_UNITS[i]}`;
+}
+
+// --- Amounts ---
+
+const SATS_PER_BTC = 100_000_000n;
+
+// Amounts arrive as JSON numbers with at most 8 decimals; rounding to whole
+// sats removes binary float noise before any further arithmetic.
+function btcToSats(btc) {
+  const n = Number(btc);
+  if (!Number.isFinite(n)) return null;
+  return BigInt(Math.round(n * 1e8));
+}
+
+function parsePriceCents(raw) {
+  const m = /^(\d+)(?:\.(\d{0,2}))?$/.exec(String(raw).trim().replace(/,/g, ""));
+  if (!m) return null;
+  const cents = BigInt(m[1]) * 100n + BigInt((m[2] || "").padEnd(2, "0") || "0");
+  return cents > 0n ? cents : null;
+}
+
+// Rounds half away from zero to the nearest cent.
+function satsToFiatCents(sats, priceCents) {
+  const product = sats * priceCents;
+  const half = SATS_PER_BTC / 2n;
+  return product >= 0n ? (product + half) / SATS_PER_BTC : -((-product + half) / SATS_PER_BTC);
+}
+
+function formatCents(cents) {
+  const neg = cents < 0n;
+  const abs = neg ? -cents : cents;
+  const whole = (abs / 100n).toLocaleString();
+  const frac = String(abs % 100n).padStart(2, "0");
+  return `${neg ? "-" : ""}${whole}.${frac}`;
+}
+
+function withCurrency(label, amount) {
+  if (!label) return amount;
+  return label.length === 1 ? `${label}${amount}` : `${amount} ${label}`;
+}
+
+function compactPrice(priceCents) {
+  const whole = Number(priceCents / 100n);
+  if (whole >= 1e6) return (whole / 1e6).toFixed(1).replace(/\.0$/, "") + "M";
+  if (whole >= 1e3) return (whole / 1e3).toFixed(1).replace(/\.0$/, "") + "k";
+  return String(whole);
+}
+
+function formatAge(ms) {
+  const secs = Math.max(0, Math.floor(ms / 1000));
+  if (secs < 3600) return Math.floor(secs / 60) + "m";
+  if (secs < 86400) return Math.floor(secs / 3600) + "h";
+  return Math.floor(secs / 86400) + "d";
+}
+
+// Returns "" when no price is configured so callers can skip the figure.
+function fiatSecondary(btc) {
+  const cfg = getConfig();
+  const priceCents = parsePriceCents(cfg.fiat_price);
+  const sats = btcToSats(btc);
+  if (priceCents == null || sats == null) return "";
+  const label = cfg.fiat_currency.trim();
+  const value = withCurrency(label, formatCents(satsToFiatCents(sats, priceCents)));
+  const age = fiatPriceSetAt ? `, set ${formatAge(Date.now() - fiatPriceSetAt)} ago` : "";
+  return `\u2248 ${value} @ ${withCurrency(label, compactPrice(priceCents))}${age}`;
+}
+
+function btcAmount(btc) {
+  return { text: Number(btc).toFixed(8) + " BTC", secondary: fiatSecondary(btc) };
+}
+
+// `live` is false when replaying a history snapshot, which must not feed
+// the trackers and series that assume each call is the newest state.
+const RETARGET_INTERVAL = 2016;
+const TARGET_BLOCK_SECS = 600;
+
+// getblockchaininfo has no retarget fields, but the schedule follows from the
+// height. Regtest never retargets.
+function difficultyRetarget(c) {
+  if (c.chain === "regtest" || typeof c.blocks !== "number") return null;
+  const blocks = RETARGET_INTERVAL - (c.blocks % RETARGET_INTERVAL);
+  return { blocks, expectedSecs: blocks * TARGET_BLOCK_SECS };
+}
+
+function renderChain(c, uptime, live = true) {
+  const dl = document.querySelector("#dash-chain dl");
+  const entries = [
+    ["Chain", c.chain],
+    ["Blocks", c.blocks.toLocaleString()],
+    ["Headers", c.headers.toLocaleString()],
+    ["Difficulty", Number(c.difficulty).toExponential(3)],
+  ];
+  const retarget = difficultyRetarget(c);
+  if (retarget) {
+    entries.push([
+      "Retarget",
+      `in ${retarget.blocks.toLocaleString()} blocks / ~${formatDurationHuman(retarget.expectedSecs)}`,
+    ]);
+  }
+  entries.push(
+    ["Progress", verificationProgressText(c)],
+    ["Pruning", pruningText(c)],
+    ["Disk size", formatBytes(c.size_on_disk)],
+  );
+  if (uptime != null) entries.push(["Uptime", formatDuration(uptime)]);
+  updateDl(dl, entries);
+  if (live && c.bestblockhash) {
+    loadRecentHeaders(c.blocks);
+    loadBlockSummary(c.bestblockhash);
+    noteCustomCardsTip(c.bestblockhash);
+  }
+  if (live) recordSyncSample(c);
+}
+
+function verificationProgressText(c) {
+  const progress = (c.verificationprogress * 100).toFixed(4) + "%";
+  return c.initialblockdownload === true ? `${progress} \u00b7 IBD` : progress;
+}
+
+// pruneheight is the lowest block still stored; automatic_pruning and
+// prune_target_size are absent on nodes older than v0.21.
+function pruningText(c) {
+  if (!c.pruned) return "not pruned";
+  const details = [];
+  if (typeof c.pruneheight === "number") details.push(`height: ${c.pruneheight.toLocaleString()}`);
+  if (c.automatic_pruning === true && typeof c.prune_target_size === "number") {
+    details.push(`target ${formatBytes(c.prune_target_size)}`);
+  } else if (c.automatic_pruning === false) {
+    details.push("manual");
+  }
+  return details.length ? `pruned \u2713 (${details.join(", ")})` : "pruned \u2713";
+}
+
+// --- Sync stall ---
+
+function maxPeerSyncedHeaders(peers) {
+  const heights = (peers || []).map((p) => p.synced_headers).filter((h) => typeof h === "number" && h >= 0);
+  return heights.length ? Math.max(...heights) : null;
+}
+
+// Only samples where a counter changed are kept; the current one is added
+// when asking the backend, which measures how long each value has held.
+async function recordSyncSample(c) {
+  const sample = { at_ms: Date.now(), blocks: c.blocks, headers: c.headers, peer_headers: maxPeerSyncedHeaders(lastPeers) };
+  const last = syncSamples[syncSamples.length - 1];
+  if (!last || last.blocks !== sample.blocks || last.headers !== sample.headers || last.peer_headers !== sample.peer_headers) {
+    syncSamples.push(sample);
+    if (syncSamples.length > SYNC_SAMPLE_LIMIT) syncSamples.shift();
+  }
+  const payload = {
+    samples: syncSamples[syncSamples.length - 1] === sample ? syncSamples : [...syncSamples, sample],
+    ibd: c.initialblockdownload === true,
+  };
+  let stall = null;
+  try {
+    const r = await fetch("/sync-stall", {
+      method: "POST",
+      headers: {
+        "content-type": "application/json",
+        "x-app-json": encodeHeaderJson(payload),
+      },
+      body: JSON.stringify(payload),
+    });
+    stall = (await r.json()).stall;
+  } catch (_) {}
+  renderSyncStall(stall, sample);
+}
+
+function renderSyncStall(stall, sample) {
+  const el = document.getElementById("dash-sync-stall");
+  el.hidden = !stall;
+  if (!stall) return;
+  const duration = formatDurationHuman(stall.duration_ms / 1000);
+  const text = stall.kind === "headers"
+    ? `No new headers or blocks for ${duration}, but peers have headers up to ${sample.peer_headers.toLocaleString()}`
+    : `Blocks stuck at ${sample.blocks.toLocaleString()} for ${duration} while headers reached ${sample.headers.toLocaleString()}`;
+  setStatus(el, "warn", text, { word: true });
+}
+
+function renderMempool(m, height, live = true) {
+  const dl = document.querySelector("#dash-mempool dl");
+  const entries = [
+    ["Transactions", m.size.toLocaleString()],
+    ["Size", formatBytes(m.bytes)],
+    ["Memory usage", formatBytes(m.usage)],
+    ["Min fee", m.mempoolminfee + " BTC/kvB"],
+  ];
+  if (m.total_fee != null) entries.push(["Total fees", btcAmount(m.total_fee)]);
+  // unbroadcast_count is absent on nodes older than v0.21.
+  const unbroadcast = typeof m.unbroadcast_count === "number" ? m.unbroadcast_count : null;
+  const now = Date.now();
+  if (unbroadcast != null) entries.push(["Unbroadcast", unbroadcast.toLocaleString()]);
+  updateDl(dl, entries);
+  if (!live) return;
+  unbroadcastSinceMs = trackUnbroadcastSince(unbroadcastSinceMs, unbroadcast, now);
+  if (unbroadcast != null) {
+    const value = dl.children[(entries.length - 1) * 2 + 1];
+    value.classList.toggle("dash-warn", isUnbroadcastStale(unbroadcastSinceMs, now));
+  }
+  maybeRefreshUnbroadcastWalletTxs(unbroadcast, now);
+  if (typeof height === "number") recordMempoolSnapshot(mempoolSnapshot(m, height));
+  maybeRefreshMempoolHistogram(now);
+  pushSeries(mempoolBytesSeries, now, m.bytes);
+  renderMempoolGraph();
+  pushSparkline("mempool-txs", m.size);
+  pushSparkline("mempool-bytes", m.bytes);
+  renderSparklines("dash-mempool-sparklines", [
+    ["Transactions", "mempool-txs", (v) => v.toLocaleString()],
+    ["Size", "mempool-bytes", formatBytes],
+  ]);
+}
+
+// --- Mempool change per block ---
+
+function mempoolSnapshot(m, height) {
+  return { height, size: m.size, bytes: m.bytes, minFee: m.mempoolminfee };
+}
+
+// Net change between the last snapshot before a block and the first one after
+// it. Transactions that arrived in between offset the cleared amounts, so this
+// underestimates what was mined. Returns null unless the height increased.
+function mempoolDelta(before, after) {
+  if (!before || !after || after.height <= before.height) return null;
+  return {
+    fromHeight: before.height + 1,
+    toHeight: after.height,
+    txDelta: after.size - before.size,
+    bytesDelta: after.bytes - before.bytes,
+    minFeeBefore: before.minFee,
+    minFeeAfter: after.minFee,
+  };
+}
+
+function recordMempoolSnapshot(snapshot) {
+  // A snapshot from an older tip arriving late must not become the baseline.
+  if (lastMempoolSnapshot && snapshot.height < lastMempoolSnapshot.height) return;
+  const delta = mempoolDelta(lastMempoolSnapshot, snapshot);
+  lastMempoolSnapshot = snapshot;
+  if (!delta) return;
+  mempoolDeltas = mempoolDeltas.concat([delta]).slice(-MEMPOOL_DELTA_HISTORY);
+  renderMempoolDeltas();
+}
+
+function formatMempoolDelta(d) {
+  const blocks = d.fromHeight === d.toHeight
+    ? "block " + d.toHeight.toLocaleString()
+    : `blocks ${d.fromHeight.toLocaleString()}\u2013${d.toHeight.toLocaleString()}`;
+  const cleared = -d.bytesDelta;
+  const abs = Math.abs(cleared);
+  const vb = abs >= 1e6 ? (abs / 1e6).toFixed(1) + " MvB" : Math.round(abs / 1e3) + " kvB";
+  const txs = Math.abs(d.txDelta).toLocaleString() + " txs";
+  const change = cleared >= 0 ? `cleared ~${vb} / ${txs}` : `grew ~${vb} / ${txs}`;
+  const rate = (btcPerKvb) => (btcPerKvb * 1e5).toFixed(2);
+  const floor = d.minFeeBefore === d.minFeeAfter
+    ? ""
+    : ` \u00b7 floor ${rate(d.minFeeBefore)}\u2192${rate(d.minFeeAfter)} sat/vB`;
+  return `${blocks} ${change}${floor}`;
+}
+
+function renderMempoolDeltas() {
+  const el = document.getElementById("dash-mempool-blocks");
+  el.hidden = mempoolDeltas.length === 0;
+  let html = '<div class="mempool-blocks-title">Per block (net estimate)</div>';
+  for (const d of mempoolDeltas.slice().reverse()) {
+    html += `<div class="mempool-block-line">${esc(formatMempoolDelta(d))}</div>`;
+  }
+  el.innerHTML = html;
+}
+
+// --- Time series ---
+
+// Samples newer than `recentMs` are kept as-is. Older ones are averaged into
+// one point per `bucketMs`, so memory stays bounded for a long session while
+// the recent window keeps full resolution.
+function createTieredSeries(recentMs = SERIES_RECENT_MS, bucketMs = SERIES_BUCKET_MS, maxOld = SERIES_MAX_OLD_POINTS) {
+  return { recentMs, bucketMs, maxOld, recent: [], old: [], pending: null };
+}
+
+function pushSeries(series, t, v) {
+  if (!Number.isFinite(v)) return;
+  const last = series.recent[series.recent.length - 1];
+  if (last && t <= last.t) return;
+  series.recent.push({ t, v });
+  while (series.recent.length && series.recent[0].t < t - series.recentMs) {
+    migrateSample(series, series.recent.shift());
+  }
+}
+
+function migrateSample(series, s) {
+  const start = Math.floor(s.t / series.bucketMs) * series.bucketMs;
+  const p = series.pending;
+  if (p && p.start === start) {
+    p.sumT += s.t;
+    p.sumV += s.v;
+    p.n += 1;
+    return;
+  }
+  if (p) {
+    series.old.push(bucketPoint(p));
+    if (series.old.length > series.maxOld) series.old.shift();
+  }
+  series.pending = { start, sumT: s.t, sumV: s.v, n: 1 };
+}
+
+function bucketPoint(p) {
+  return { t: p.sumT / p.n, v: p.sumV / p.n };
+}
+
+// Points with t in (nowMs - windowMs, nowMs], oldest first.
+function querySeries(series, windowMs, nowMs) {
+  const from = nowMs - windowMs;
+  const all = series.old.slice();
+  if (series.pending) all.push(bucketPoint(series.pending));
+  all.push(...series.recent);
+  return all.filter((pt) => pt.t > from && pt.t <= nowMs);
+}
+
+function loadGraphWindows() {
+  let saved = null;
+  try {
+    saved = JSON.parse(localStorage.getItem("ui-graph-windows") || "null");
+  } catch (_) {}
+  const out = {};
+  for (const graph of ["traffic", "mempool"]) {
+    const w = saved && saved[graph];
+    out[graph] = Object.hasOwn(GRAPH_WINDOWS, w) ? w : GRAPH_DEFAULT_WINDOW;
+  }
+  return out;
+}
+
+function initGraphs() {
+  trafficRecvSeries = createTieredSeries();
+  trafficSentSeries = createTieredSeries();
+  mempoolBytesSeries = createTieredSeries();
+  graphWindows = loadGraphWindows();
+  for (const row of document.querySelectorAll(".graph-windows[data-graph]")) {
+    row.innerHTML = Object.keys(GRAPH_WINDOWS)
+      .map((w) => `<button type="button" data-window="${w}">${w}</button>`)
+      .join("");
+    row.addEventListener("click", (ev) => {
+      const btn = ev.target.closest("button[data-window]");
+      if (!btn) return;
+      graphWindows[row.dataset.graph] = btn.dataset.window;
+      localStorage.setItem("ui-graph-windows", JSON.stringify(graphWindows));
+      if (row.dataset.graph === "traffic") renderTrafficGraph();
+      else renderMempoolGraph();
+    });
+  }
+}
+
+// Byte counters become per-second rates between consecutive polls. A counter
+// that goes backwards means the node restarted; that interval is skipped.
+function recordTrafficRates(t) {
+  const now = Date.now();
+  const sample = { ms: t.timemillis || now, recv: t.totalbytesrecv, sent: t.totalbytessent };
+  const prev = lastNetTotals;
+  lastNetTotals = sample;
+  if (!prev || sample.ms <= prev.ms || sample.recv < prev.recv || sample.sent < prev.sent) {
+    lastTrafficRates = null;
+    return;
+  }
+  const secs = (sample.ms - prev.ms) / 1000;
+  lastTrafficRates = { recv: (sample.recv - prev.recv) / secs, sent: (sample.sent - prev.sent) / secs };
+  pushSeries(trafficRecvSeries, now, lastTrafficRates.recv);
+  pushSeries(trafficSentSeries, now, lastTrafficRates.sent);
+}
+
+// Another node's counters must not be diffed against this one's.
+function resetTrafficRates() {
+  lastNetTotals = null;
+  lastTrafficRates = null;
+  trafficRecvSeries = createTieredSeries();
+  trafficSentSeries = createTieredSeries();
+}
+
+function renderTrafficGraph() {
+  const now = Date.now();
+  const windowMs = GRAPH_WINDOWS[graphWindows.traffic];
+  const recv = querySeries(trafficRecvSeries, windowMs, now);
+  const sent = querySeries(trafficSentSeries, windowMs, now);
+  const latest = (pts) => (pts.length ? formatBytesIec(pts[pts.length - 1].v) + "/s" : "–");
+  renderGraph("traffic", [
+    { points: recv, color: "#58a6ff" },
+    { points: sent, color: "#3fb950" },
+  ], `\u2193 ${latest(recv)} \u2191 ${latest(sent)}`);
+}
+
+function renderMempoolGraph() {
+  const points = querySeries(mempoolBytesSeries, GRAPH_WINDOWS[graphWindows.mempool], Date.now());
+  const latest = points.length ? formatBytes(points[points.length - 1].v) : "–";
+  renderGraph("mempool", [{ points, color: "#f0883e" }], latest);
+}
+
+// Polylines scaled to the shared time and value range of all lines.
+function renderGraph(graph, lines, caption) {
+  for (const btn of document.querySelectorAll(`.graph-windows[data-graph="${graph}"] button`)) {
+    btn.classList.toggle("active", btn.dataset.window === graphWindows[graph]);
+  }
+  const el = document.querySelector(`.series-graph[data-graph="${graph}"]`);
+  const all = lines.flatMap((l) => l.points);
+  if (all.length < 2) {
+    el.innerHTML = '<span class="series-graph-empty">Collecting samples\u2026</span>';
+    return;
+  }
+  const t0 = Math.min(...all.map((p) => p.t));
+  const t1 = Math.max(...all.map((p) => p.t));
+  const vMax = Math.max(...all.map((p) => p.v)) || 1;
+  const x = (t) => (t1 === t0 ? 0 : ((t - t0) / (t1 - t0)) * 300);
+  const y = (v) => 58 - (v / vMax) * 56;
+  const polylines = lines.map((l) => {
+    const pts = l.points.map((p) => `${x(p.t).toFixed(1)},${y(p.v).toFixed(1)}`).join(" ");
+    return `<polyline points="${pts}" fill="none" stroke="${l.color}" stroke-width="1.5" vector-effect="non-scaling-stroke"/>`;
+  }).join("");
+  el.innerHTML = `<svg viewBox="0 0 300 60" preserveAspectRatio="none">${polylines}</svg>`
+    + `<span class="series-graph-caption">${esc(caption)}</span>`;
+}
+
+// --- Sparklines ---
+
+// The last SPARKLINE_SAMPLES live values per metric, one per poll or card
+// refresh. Unlike the time series above these keep no timestamps: they
+// show which way a value is heading, not when it changed.
+function pushSparkline(name, v) {
+  if (!Number.isFinite(v)) return;
+  const values = sparklines[name] || (sparklines[name] = []);
+  values.push(v);
+  if (values.length > SPARKLINE_SAMPLES) values.shift();
+}
+
+// Scaled to the min/max of the samples held, so a small drift is still
+// visible; a flat line sits in the middle. The newest value gets a dot.
+function sparklineSvg(values) {
+  const lo = Math.min(...values);
+  const hi = Math.max(...values);
+  const x = (i) => (values.length < 2 ? 100 : (i / (values.length - 1)) * 100);
+  const y = (v) => (hi === lo ? 10 : 18 - ((v - lo) / (hi - lo)) * 16);
+  const pts = values.map((v, i) => `${x(i).toFixed(1)},${y(v).toFixed(1)}`).join(" ");
+  const last = values.length - 1;
+  const cx = x(last).toFixed(1);
+  const cy = y(values[last]).toFixed(1);
+  // A zero-length round-capped line stays a dot when the SVG is stretched.
+  const dot = `<line x1="${cx}" y1="${cy}" x2="${cx}" y2="${cy}" stroke="#58a6ff" stroke-width="4" stroke-linecap="round" vector-effect="non-scaling-stroke"/>`;
+  return '<svg viewBox="0 0 100 20" preserveAspectRatio="none">'
+    + `<polyline points="${pts}" fill="none" stroke="#8b949e" stroke-width="1" vector-effect="non-scaling-stroke"/>`
+    + dot + "</svg>";
+}
+
+// rows: [label, metric name, value formatter]
+function renderSparklines(id, rows) {
+  const el = document.getElementById(id);
+  const shown = rows.filter(([, name]) => (sparklines[name] || []).length > 1);
+  el.hidden = shown.length === 0;
+  el.innerHTML = shown.map(([label, name, format]) => {
+    const values = sparklines[name];
+    const lo = Math.min(...values);
+    const hi = Math.max(...values);
+    const range = `${values.length} samples, ${format(lo)} \u2013 ${format(hi)}`;
+    return `<div class="sparkline-row" title="${esc(range)}"><span class="sparkline-label">${esc(label)}</span>`
+      + sparklineSvg(values)
+      + `<span class="sparkline-value">${esc(format(values[values.length - 1]))}</span></div>`;
+  }).join("");
+}
+
+// --- Recent blocks ---
+
+// Newest first by height; a block already listed is replaced, so a reorg at
+// the same height keeps only the block that was summarised last.
+function insertRecentBlock(blocks, summary, limit) {
+  const rest = blocks.filter((b) => b.hash !== summary.hash && b.height !== summary.height);
+  return [summary, ...rest].sort((a, b) => b.height - a.height).slice(0, limit);
+}
+
+// Header-only rows for the blocks below the tip, so the card is filled on
+// startup and without ZMQ. Full summaries replace them as they load.
+function loadRecentHeaders(tip) {
+  if (tip == null || tip === recentHeadersTip) return;
+  recentHeadersTip = tip;
+  fetch(`/blocks/recent?tip=${tip}`)
+    .then((r) => r.json())
+    .then((res) => {
+      if (res.error) {
+        recentHeadersTip = null;
+        return;
       }
-    })());
+      for (const header of res.blocks) {
+        if (recentBlocks.some((b) => b.hash === header.hash)) continue;
+        recentBlocks = insertRecentBlock(recentBlocks, header, RECENT_BLOCKS_LIMIT);
+      }
+      rememberBlockHashes(res.blocks.map((b) => b.hash));
+      renderRecentBlocks();
+    })
+    .catch(() => { recentHeadersTip = null; });
+}
+
+function loadBlockSummary(hash) {
+  if (blockSummariesInFlight.has(hash) || recentBlocks.some((b) => b.hash === hash && !b.header_only)) return;
+  blockSummariesInFlight.add(hash);
+  fetch(`/block/summary?hash=${encodeURIComponent(hash)}`)
+    .then((r) => r.json())
+    .then((summary) => {
+      if (summary.error) return;
+      recentBlocks = insertRecentBlock(recentBlocks, summary, RECENT_BLOCKS_LIMIT);
+      rememberTxids(summary.txids);
+      rememberBlockHashes([summary.hash]);
+      renderRecentBlocks();
+    })
+    .catch(() => {})
+    .finally(() => blockSummariesInFlight.delete(hash));
+}
+
+function blockRowClicked(ev) {
+  const row = ev.target.closest("tr[data-hash]");
+  if (!row) return;
+  const copy = ev.target.closest(".copy-btn");
+  if (copy) {
+    copyWithFlash(copy, row.dataset.hash);
+    return;
+  }
+  expandedBlockHash = expandedBlockHash === row.dataset.hash ? null : row.dataset.hash;
+  if (expandedBlockHash) loadBlockSummary(expandedBlockHash);
+  renderRecentBlocks();
+}
+
+function renderRecentBlocks() {
+  document.getElementById("dash-blocks").hidden = recentBlocks.length === 0;
+  const now = Date.now();
+  document.querySelector("#dash-blocks-table tbody").innerHTML = recentBlocks.map((b) => {
+    const fees = b.total_fees_sat == null ? "\u2013" : (b.total_fees_sat / 1e8).toFixed(8);
+    const median = b.median_feerate == null ? "\u2013" : b.median_feerate.toFixed(1);
+    const weight = b.header_only ? "\u2013" : `${(b.weight / 1e6).toFixed(2)} MWU`;
+    const size = b.header_only ? "\u2013" : formatBytes(b.size);
+    const difficulty = !b.difficulty ? "\u2013"
+      : b.difficulty >= 1 ? formatSi(b.difficulty, "") : b.difficulty.toExponential(2);
+    const expanded = b.hash === expandedBlockHash;
+    const row = `<tr data-hash="${esc(b.hash)}" class="${expanded ? "expanded" : ""}">`
+      + `<td>${b.height.toLocaleString()}</td>`
+      + `<td title="${esc(b.hash)}">${esc(b.hash.slice(0, 8))}\u2026${esc(b.hash.slice(-8))}`
+      + '<button class="copy-btn" type="button" title="Copy hash">\u29C9</button></td>'
+      + `<td title="${esc(new Date(b.time * 1000).toLocaleString())}">${formatAge(now - b.time * 1000)}</td>`
+      + `<td>${b.tx_count.toLocaleString()}</td><td>${fees}</td><td>${median}</td>`
+      + `<td>${weight}</td><td>${size}</td><td>${difficulty}</td></tr>`;
+    return expanded ? row + blockDetailRow(b) : row;
+  }).join("");
+}
+
+function blockDetailRow(b) {
+  if (b.header_only) {
+    return `<tr class="block-detail"><td colspan="9">`
+      + `<div class="block-detail-hash">${esc(b.hash)}</div>`
+      + `<div class="block-detail-more">Loading block\u2026</div></td></tr>`;
+  }
+  const txids = b.txids.map((t, i) => `<div>${i === 0 ? "coinbase " : ""}${esc(t)}</div>`).join("");
+  const more = b.tx_count > b.txids.length
+    ? `<div class="block-detail-more">\u2026 ${(b.tx_count - b.txids.length).toLocaleString()} more</div>`
+    : "";
+  return `<tr class="block-detail"><td colspan="9">`
+    + `<div class="block-detail-hash">${esc(b.hash)}</div>`
+    + `<div class="block-detail-coinbase">${esc(b.coinbase_message || "(no text in coinbase)")}</div>`
+    + (b.parse_warnings && b.parse_warnings.length
+      ? `<div class="block-detail-warnings" title="Defaulted while parsing the node's reply">${b.parse_warnings.map(esc).join("<br>")}</div>`
+      : "")
+    + `<div class="block-detail-txids">${txids}${more}</div></td></tr>`;
+}
+
+// --- Mempool fee histogram ---
+
+function maybeRefreshMempoolHistogram(now) {
+  if (mempoolHistogramInFlight) return;
+  if (mempoolHistogramEstimated && now - lastMempoolHistogramMs < MEMPOOL_HISTOGRAM_SAMPLED_MIN_MS) return;
+  lastMempoolHistogramMs = now;
+  mempoolHistogramInFlight = true;
+  fetch("/mempool/histogram")
+    .then((r) => r.json())
+    .then((h) => {
+      if (h.error) return;
+      mempoolHistogramEstimated = h.estimated === true;
+      renderMempoolHistogram(h);
+    })
+    .catch(() => {})
+    .finally(() => { mempoolHistogramInFlight = false; });
+}
+
+// --- Mempool ancestor distribution ---
+
+// Reads the whole verbose mempool, so only full refreshes ask for it.
+function refreshMempoolAncestors() {
+  if (mempoolAncestorsInFlight) return;
+  mempoolAncestorsInFlight = true;
+  fetch("/mempool/verbose-summary")
+    .then((r) => r.json())
+    .then((s) => {
+      if (!s.error) renderMempoolAncestors(s);
+    })
+    .catch(() => {})
+    .finally(() => { mempoolAncestorsInFlight = false; });
+}
+
+function renderMempoolAncestors(s) {
+  const el = document.getElementById("dash-mempool-ancestors");
+  el.hidden = !s.examined;
+  if (el.hidden) return;
+  const note = s.estimated
+    ? ` \u00b7 estimated from ${s.examined.toLocaleString()} of ${s.total.toLocaleString()}`
+    : "";
+  el.querySelector(".mempool-blocks-title").textContent =
+    `Ancestors per transaction \u00b7 fees ${Number(s.total_fees_btc).toFixed(8)} BTC`
+    + ` \u00b7 avg ${s.avg_fee_rate_sat_vb} sat/vB${note}`;
+  el.querySelector("pre").textContent = s.chart;
+}
+
+function formatVsize(vsize) {
+  if (vsize >= 1e6) return (vsize / 1e6).toFixed(1) + " MvB";
+  if (vsize >= 1e3) return Math.round(vsize / 1e3) + " kvB";
+  return vsize + " vB";
+}
+
+// Rows run from the highest feerate down, bar widths proportional to vsize.
+function renderMempoolHistogram(h) {
+  const el = document.getElementById("dash-mempool-histogram");
+  const buckets = Array.isArray(h.buckets) ? h.buckets : [];
+  const totalVsize = buckets.reduce((sum, b) => sum + b.vsize, 0);
+  el.hidden = totalVsize === 0;
+  if (el.hidden) return;
+  const note = h.estimated
+    ? ` \u00b7 estimated from ${h.examined.toLocaleString()} of ${h.total.toLocaleString()}`
+    : "";
+  // The column chart reads left to right from the lowest feerate bucket.
+  const chart = typeof h.chart === "string" && h.chart.trim()
+    ? ` <span class="mempool-hist-chart" title="vsize per bucket, lowest fee rate first">${esc(h.chart)}</span>`
+    : "";
+  let html = `<div class="mempool-blocks-title">Fee rate (sat/vB)${chart}${esc(note)}</div>`;
+  for (const b of buckets.slice().reverse()) {
+    const pct = (b.vsize / totalVsize) * 100;
+    html += '<div class="mempool-hist-row">'
+      + `<span class="mempool-hist-label">${esc(b.label)}</span>`
+      + `<span class="mempool-hist-bar"><span style="width:${pct.toFixed(1)}%"></span></span>`
+      + `<span class="mempool-hist-value">${esc(b.count.toLocaleString())} tx \u00b7 ${esc(formatVsize(b.vsize))}</span>`
+      + "</div>";
+  }
+  el.innerHTML = html;
+}
+
+function trackUnbroadcastSince(sinceMs, count, nowMs) {
+  if (!count) return 0;
+  return sinceMs > 0 ? sinceMs : nowMs;
+}
+
+function isUnbroadcastStale(sinceMs, nowMs) {
+  return sinceMs > 0 && nowMs - sinceMs >= UNBROADCAST_WARN_MS;
+}
+
+function maybeRefreshUnbroadcastWalletTxs(count, now) {
+  const box = document.getElementById("dash-mempool-unbroadcast");
+  const wallet = walletsDisabled ? "" : document.getElementById("cfg-wallet").value;
+  if (!count || !wallet) {
+    box.hidden = true;
+    box.textContent = "";
+    return;
+  }
+  if (unbroadcastCheckInFlight || now - lastUnbroadcastCheckMs < UNBROADCAST_CHECK_MIN_MS) return;
+  lastUnbroadcastCheckMs = now;
+  unbroadcastCheckInFlight = true;
+  findUnbroadcastWalletTxs()
+    .then((txids) => renderUnbroadcastWalletTxs(txids))
+    .catch(() => {})
+    .finally(() => { unbroadcastCheckInFlight = false; });
+}
+
+async function findUnbroadcastWalletTxs() {
+  const resp = await rpcCall("listtransactions", ["*", 100]);
+  if (!Array.isArray(resp.result)) return [];
+  const pending = [];
+  for (const tx of resp.result) {
+    if (tx.confirmations === 0 && tx.txid && !pending.includes(tx.txid)) pending.push(tx.txid);
+  }
+  const affected = [];
+  // Query sequentially so a busy wallet cannot exhaust the RPC in-flight limit.
+  for (const txid of pending.slice(-UNBROADCAST_MAX_WALLET_TXS)) {
+    const entry = await rpcCall("getmempoolentry", [txid]);
+    if (entry.result && entry.result.unbroadcast) affected.push(txid);
+  }
+  return affected;
+}
+
+function renderUnbroadcastWalletTxs(txids) {
+  const box = document.getElementById("dash-mempool-unbroadcast");
+  if (txids.length === 0) {
+    box.hidden = true;
+    box.textContent = "";
+    return;
+  }
+  let html = '<div class="unbroadcast-title">Wallet transactions awaiting relay</div>';
+  for (const txid of txids) {
+    html += '<div class="unbroadcast-txid">' + esc(txid) + "</div>";
+  }
+  html += '<p class="unbroadcast-hint">Bitcoin Core re-announces unbroadcast transactions to peers every '
+    + "10\u201315 minutes until one requests them. Persistent entries usually mean few or no "
+    + "outbound peers are relaying; no manual resend is needed.</p>";
+  box.innerHTML = html;
+  box.hidden = false;
+}
+
+function renderNetwork(n, live = true) {
+  const dl = document.querySelector("#dash-network dl");
+  const entries = [
+    ["User agent", n.subversion],
+    ["Protocol", String(n.protocolversion)],
+  ];
+  // The in/out split is missing before v0.21; only the total is shown then.
+  if (typeof n.connections_in === "number" && typeof n.connections_out === "number") {
+    entries.push(["Inbound", n.connections_in.toLocaleString()]);
+    entries.push(["Outbound", n.connections_out.toLocaleString()]);
+  }
+  entries.push(["Connections", n.connections.toLocaleString()]);
+  if (n.localservicesnames) entries.push(["Services", n.localservicesnames.join(", ")]);
+  if (n.warnings) entries.push(["Warnings", n.warnings]);
+  updateDl(dl, entries);
+  renderReachability(n);
+  if (!live) return;
+  pushSparkline("connections", n.connections);
+  renderSparklines("dash-network-sparklines", [
+    ["Connections", "connections", (v) => v.toLocaleString()],
+  ]);
+}
+
+function localAddressText(a) {
+  // Onion and I2P addresses have no colons; only IPv6 needs brackets.
+  const host = a.address.includes(":") ? `[${a.address}]` : a.address;
+  return `${host}:${a.port}`;
+}
+
+// Which networks the node reaches and through which proxy, plus the
+// addresses it advertises, as in bitcoin-cli -netinfo. Older nodes don't
+// report every network; those rows say so.
+function renderReachability(n) {
+  const el = document.getElementById("dash-reachability");
+  const networks = Array.isArray(n.networks) ? n.networks : [];
+  el.hidden = networks.length === 0;
+  if (el.hidden) return;
+  const byName = new Map(networks.map((net) => [net.name, net]));
+  const extra = networks.map((net) => net.name).filter((name) => !REACHABILITY_NETWORKS.includes(name));
+  let html = '<table class="origin-table reachability-table"><thead><tr><th>Network</th><th>Reachable</th><th>Proxy</th></tr></thead><tbody>';
+  for (const name of [...REACHABILITY_NETWORKS, ...extra]) {
+    const net = byName.get(name);
+    if (!net) {
+      html += `<tr class="reachability-off"><td>${esc(name)}</td><td colspan="2">not reported</td></tr>`;
+      continue;
+    }
+    const cls = net.reachable ? "" : ' class="reachability-off"';
+    const reachable = net.reachable ? "yes" : net.limited ? "no (limited)" : "no";
+    html += `<tr${cls}><td>${esc(name)}</td><td>${reachable}</td><td>${net.proxy ? esc(net.proxy) : "&mdash;"}</td></tr>`;
+  }
+  html += "</tbody></table>";
+  const local = Array.isArray(n.localaddresses) ? n.localaddresses : [];
+  html += '<div class="origin-title">Local addresses</div>';
+  html += local.length
+    ? '<ul class="reachability-local">' + local
+      .map((a) => `<li>${esc(localAddressText(a))} <span class="reachability-score">score ${a.score}</span></li>`)
+      .join("") + "</ul>"
+    : '<p class="reachability-local">None advertised</p>';
+  el.innerHTML = html;
+}
+
+function renderNetTotals(t, live = true) {
+  const dl = document.querySelector("#dash-nettotals dl");
+  if (live) recordTrafficRates(t);
+  const entries = [
+    ["Received", formatBytesIec(t.totalbytesrecv)],
+    ["Sent", formatBytesIec(t.totalbytessent)],
+  ];
+  // Rates need two samples from this node, so a replayed snapshot has none.
+  if (live && lastTrafficRates) {
+    entries.push(
+      ["Download", `${formatBytesIec(lastTrafficRates.recv)}/s`],
+      ["Upload", `${formatBytesIec(lastTrafficRates.sent)}/s`],
+    );
+  }
+  const up = t.uploadtarget;
+  if (up && up.target > 0) {
+    entries.push(["Upload target", `${formatBytesIec(up.target)} per ${formatDurationHuman(up.timeframe)}`]);
+    entries.push([
+      "Left in cycle",
+      `${formatBytesIec(up.bytes_left_in_cycle)}, resets in ${formatDurationHuman(up.time_left_in_cycle)}`,
+    ]);
+    entries.push(["Serve historical", up.serve_historical_blocks ? "yes" : "no"]);
+  }
+  updateDl(dl, entries);
+  if (!live) return;
+  renderTrafficGraph();
+  pushSparkline("bytes-recv", t.totalbytesrecv);
+  pushSparkline("bytes-sent", t.totalbytessent);
+  renderSparklines("dash-nettotals-sparklines", [
+    ["Received", "bytes-recv", formatBytesIec],
+    ["Sent", "bytes-sent", formatBytesIec],
+  ]);
+}
+
+function renderPeers(peers) {
+  lastPeers = peers;
+  peerById = new Map(peers.map((p) => [p.id, p]));
+  const tbody = document.querySelector("#dash-peer-table tbody");
+  const seen = new Set();
+  const shown = peers.filter((p) => peerMatchesFilter(p, peerFilter));
+  for (const p of sortPeers(shown, peerView.sort, peerView.desc)) {
+    seen.add(p.id);
+    let row = peerRows.get(p.id);
+    if (!row) {
+      row = document.createElement("tr");
+      row.className = "peer-row";
+      row.dataset.peerId = String(p.id);
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      row.appendChild(document.createElement("td"));
+      const feeFilterCell = document.createElement("td");
+      feeFilterCell.className = "peer-fee-filter";
+      row.appendChild(feeFilterCell);
+      const copyCell = document.createElement("td");
+      copyCell.innerHTML = '<button class="copy-btn" type="button" title="Copy address">\u29C9</button>';
+      row.appendChild(copyCell);
+      peerRows.set(p.id, row);
+    }
+    const direction = p.inbound ? "in" : "out";
+    const perms = abbreviatePermissions(p.permissions);
+    const pingMs = p.pingtime != null ? p.pingtime * 1000 : null;
+    const ping = pingMs != null ? pingMs.toFixed(0) + " ms" : "–";
+    const pingTier = pingMs != null ? pingSeverity(pingMs) : null;
+    const sent = formatBytes(p.bytessent || 0);
+    const recv = formatBytes(p.bytesrecv || 0);
+    const feeFilter = typeof p.minfeefilter === "number" ? formatSatPerVb(p.minfeefilter) : "\u2013";
+    if (row.children[0].textContent !== p.addr) row.children[0].textContent = p.addr;
+    if (row.children[1].textContent !== p.subver) row.children[1].textContent = p.subver;
+    if (row.children[2].textContent !== direction) row.children[2].textContent = direction;
+    row.children[2].className = p.inbound ? "peer-in" : "peer-out";
+    if (row.children[3].textContent !== perms) row.children[3].textContent = perms;
+    row.children[3].title = (p.permissions || []).join(", ");
+    const pingCell = row.children[4];
+    if (pingCell.firstChild?.textContent !== ping || pingCell.dataset.severity !== (pingTier || undefined)) {
+      if (pingTier) setStatus(pingCell, pingTier, ping, { suffix: true });
+      else clearStatus(pingCell, ping);
+      pingCell.title = pingTier ? statusIndicator(pingTier).word : "";
+    }
+    if (row.children[5].textContent !== sent) row.children[5].textContent = sent;
+    if (row.children[6].textContent !== recv) row.children[6].textContent = recv;
+    if (row.children[7].textContent !== feeFilter) row.children[7].textContent = feeFilter;
+    row.classList.toggle("selected", p.id === selectedPeerId);
+    tbody.appendChild(row);
+  }
+  for (const [id, row] of peerRows) {
+    if (seen.has(id)) continue;
+    row.remove();
+    peerRows.delete(id);
+  }
+  renderPeerCounts(shown, peers.length);
+  renderPeerPermissionWarning(riskyPermissionPeers(peers));
+  renderNetworkOrigins(groupPeerPrefixes(peers));
+  refreshPeerFeeFloor(peers);
+}
+
+// BTC/kvB as sat/vB, as the backend prints relay fees.
+function formatSatPerVb(btcPerKvb) {
+  return `${Math.round(btcPerKvb * 1e8) / 1000} sat/vB`;
+}
+
+// The fee filters outbound peers announced, summarised on the Mempool card.
+async function refreshPeerFeeFloor(peers) {
+  const inputs = { peers: peers.map((p) => ({ inbound: p.inbound, minfeefilter: p.minfeefilter })) };
+  const key = JSON.stringify(inputs);
+  if (key === peerFeeFloorKey) return;
+  try {
+    const r = await fetch("/peer-fee-floor", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: key,
+    });
+    const j = await r.json();
+    if (j.error) return;
+    peerFeeFloorKey = key;
+    const el = document.getElementById("dash-peer-fee-floor");
+    el.hidden = !j.floor;
+    if (j.floor) el.textContent = `Network floor (peers): ${j.floor.text}`;
+  } catch (_) {}
+}
+
+function renderPeerFeeFilterColumn() {
+  document.getElementById("dash-peer-table").classList.toggle("show-fee-filter", peerView.feeFilter);
+  document.getElementById("peer-fee-filter-toggle").setAttribute("aria-pressed", String(peerView.feeFilter));
+}
+
+function peerFeeFilterToggled() {
+  peerView.feeFilter = !peerView.feeFilter;
+  savePeerView();
+  renderPeerFeeFilterColumn();
+}
+
+// --- Peer permissions ---
+
+function abbreviatePermissions(permissions) {
+  if (!Array.isArray(permissions) || permissions.length === 0) return "";
+  return permissions.map((name) => PEER_PERMISSION_ABBREV[name] || name).join("");
+}
+
+function peerHost(addr) {
+  const s = String(addr || "");
+  if (s.startsWith("[")) {
+    const end = s.indexOf("]");
+    return end > 0 ? s.slice(1, end) : s;
+  }
+  // A single colon separates the port; more than one means a bare IPv6 address.
+  const colon = s.lastIndexOf(":");
+  return colon > 0 && s.indexOf(":") === colon ? s.slice(0, colon) : s;
+}
+
+function isLoopbackHost(host) {
+  const h = host.toLowerCase();
+  return h === "localhost" || h === "::1" || /^(::ffff:)?127\./.test(h);
+}
+
+// forcerelay/mempool on a non-loopback inbound peer usually means a whitelist
+// range like 0.0.0.0/0 was meant for noban only.
+function riskyPermissionPeers(peers) {
+  return peers.filter((p) => p.inbound
+    && Array.isArray(p.permissions)
+    && p.permissions.some((name) => PEER_RISKY_INBOUND_PERMISSIONS.includes(name))
+    && !isLoopbackHost(peerHost(p.addr)));
+}
+
+// --- Network origins ---
+
+function parseIpv4(host) {
+  const m = /^(\d{1,3})\.(\d{1,3})\.(\d{1,3})\.(\d{1,3})$/.exec(host);
+  if (!m) return null;
+  const octets = m.slice(1).map(Number);
+  return octets.every((o) => o <= 255) ? octets : null;
+}
+
+// Expands an IPv6 address to eight 16-bit groups, or null if malformed.
+function parseIpv6(host) {
+  let h = host.toLowerCase();
+  const tail = /:(\d+\.\d+\.\d+\.\d+)$/.exec(h);
+  if (tail) {
+    const v4 = parseIpv4(tail[1]);
+    if (!v4) return null;
+    h = h.slice(0, -tail[1].length)
+      + ((v4[0] << 8) | v4[1]).toString(16) + ":" + ((v4[2] << 8) | v4[3]).toString(16);
+  }
+  const halves = h.split("::");
+  if (halves.length > 2) return null;
+  const split = (part) => (part === "" ? [] : part.split(":"));
+  const head = split(halves[0]);
+  const rest = halves.length === 2 ? split(halves[1]) : [];
+  const missing = 8 - head.length - rest.length;
+  if (halves.length === 2 ? missing < 1 : missing !== 0) return null;
+  const groups = [...head, ...Array(missing).fill("0"), ...rest];
+  if (!groups.every((g) => /^[0-9a-f]{1,4}$/.test(g))) return null;
+  return groups.map((g) => parseInt(g, 16));
+}
+
+function isPublicIpv4([a, b]) {
+  return !(a === 0 || a === 10 || a === 127 || (a === 100 && b >= 64 && b < 128)
+    || (a === 169 && b === 254) || (a === 172 && b >= 16 && b < 32) || (a === 192 && b === 168));
+}
+
+// Returns the /16 (IPv4) or /32 (IPv6) prefix for a clearnet peer address,
+// or null for onion, i2p, cjdns, local and unparseable addresses.
+function addressPrefix(addr) {
+  const host = peerHost(addr);
+  if (/\.(onion|i2p)$/i.test(host)) return null;
+  const v4 = parseIpv4(host);
+  if (v4) return isPublicIpv4(v4) ? `${v4[0]}.${v4[1]}.0.0/16` : null;
+  if (!host.includes(":")) return null;
+  const v6 = parseIpv6(host);
+  if (!v6) return null;
+  if (v6.slice(0, 5).every((g) => g === 0) && v6[5] === 0xffff) {
+    const mapped = [v6[6] >> 8, v6[6] & 0xff, v6[7] >> 8, v6[7] & 0xff];
+    return isPublicIpv4(mapped) ? `${mapped[0]}.${mapped[1]}.0.0/16` : null;
+  }
+  // Unspecified/loopback, cjdns and ULA (fc00::/7), and link-local (fe80::/10).
+  if (v6.slice(0, 7).every((g) => g === 0)) return null;
+  if ((v6[0] & 0xfe00) === 0xfc00 || (v6[0] & 0xffc0) === 0xfe80) return null;
+  return `${v6[0].toString(16)}:${v6[1].toString(16)}::/32`;
+}
+
+function groupPeerPrefixes(peers) {
+  const groups = new Map();
+  let clearnet = 0;
+  for (const p of peers) {
+    const prefix = addressPrefix(p.addr);
+    if (!prefix) continue;
+    clearnet += 1;
+    const g = groups.get(prefix) || { prefix, total: 0, outbound: 0 };
+    g.total += 1;
+    if (!p.inbound) g.outbound += 1;
+    groups.set(prefix, g);
+  }
+  const sorted = [...groups.values()].sort((a, b) =>
+    b.total - a.total || b.outbound - a.outbound || a.prefix.localeCompare(b.prefix));
+  return { total: peers.length, clearnet, groups: sorted };
+}
+
+function renderNetworkOrigins(summary) {
+  const el = document.getElementById("dash-network-origins");
+  el.hidden = summary.clearnet < ORIGIN_MIN_CLEARNET_PEERS;
+  if (el.hidden) return;
+  const shared = summary.groups.filter((g) => g.outbound > 1);
+  let html = `<div class="origin-title">Top prefixes (${summary.clearnet} clearnet of ${summary.total} peers)</div>`
+    + '<table class="origin-table"><thead><tr><th>Prefix</th><th>Peers</th><th>Out</th></tr></thead><tbody>';
+  for (const g of summary.groups.slice(0, ORIGIN_TOP_PREFIXES)) {
+    const cls = g.outbound > 1 ? ' class="origin-shared"' : "";
+    html += `<tr${cls}><td>${esc(g.prefix)}</td><td>${g.total}</td><td>${g.outbound}</td></tr>`;
+  }
+  html += "</tbody></table>";
+  if (shared.length) {
+    html += '<p class="origin-warning">Multiple outbound peers share '
+      + shared.map((g) => `${esc(g.prefix)} (${g.outbound})`).join(", ")
+      + ". Outbound diversity protects against eclipse attacks; consider -asmap.</p>";
+  }
+  el.innerHTML = html;
+}
+
+function renderPeerPermissionWarning(risky) {
+  const el = document.getElementById("dash-peer-warning");
+  el.hidden = risky.length === 0;
+  if (el.hidden) return;
+  const noun = risky.length === 1 ? "peer has" : "peers have";
+  el.textContent = risky.length + " inbound " + noun + " forcerelay or mempool permission from a "
+    + "non-loopback address (" + risky.map((p) => p.addr).join(", ") + "). "
+    + "Check that whitelist/whitebind ranges are not broader than intended.";
+}
+
+// --- Peer table view state ---
+
+function normalizePeerView(saved) {
+  const view = { ...DEFAULT_PEER_VIEW };
+  if (!saved || typeof saved !== "object") return view;
+  // A sort field from an older or newer version silently falls back.
+  if (PEER_SORT_FIELDS.includes(saved.sort)) {
+    view.sort = saved.sort;
+    view.desc = saved.desc === true;
+  }
+  view.feeFilter = saved.feeFilter === true;
+  return view;
+}
+
+function loadPeerView() {
+  try {
+    return normalizePeerView(JSON.parse(localStorage.getItem("ui-peer-view") || "null"));
+  } catch (_) {
+    return normalizePeerView(null);
+  }
+}
+
+function savePeerView() {
+  localStorage.setItem("ui-peer-view", JSON.stringify(peerView));
+}
+
+// Case-insensitive substring match; an empty filter matches every peer.
+function peerMatchesFilter(p, filter) {
+  const needle = filter.trim().toLowerCase();
+  if (!needle) return true;
+  return [p.addr, p.subver, p.network, p.connection_type]
+    .some((field) => typeof field === "string" && field.toLowerCase().includes(needle));
+}
+
+function renderPeerCounts(shown, total) {
+  const inbound = shown.filter((p) => p.inbound).length;
+  const counts = `${inbound} in, ${shown.length - inbound} out`;
+  document.getElementById("dash-peer-count").textContent = shown.length === total
+    ? `${total} peers (${counts})`
+    : `${shown.length} of ${total} peers match (${counts})`;
+}
+
+function peerFilterChanged(ev) {
+  peerFilter = ev.target.value;
+  renderPeers(lastPeers);
+}
+
+// --- Peer export ---
+
+// Redaction defaults on; it is only turned off deliberately, per format.
+function loadPeerExportRedact() {
+  let saved = null;
+  try {
+    saved = JSON.parse(localStorage.getItem("ui-peer-export-redact") || "null");
+  } catch (_) {}
+  return {
+    text: saved?.text !== false,
+    csv: saved?.csv !== false,
+  };
+}
+
+function initPeerExport() {
+  const redact = loadPeerExportRedact();
+  const row = document.getElementById("peer-export");
+  for (const box of row.querySelectorAll("input[data-format]")) {
+    box.checked = redact[box.dataset.format];
+    box.addEventListener("change", () => {
+      redact[box.dataset.format] = box.checked;
+      localStorage.setItem("ui-peer-export-redact", JSON.stringify(redact));
+    });
+  }
+  for (const button of row.querySelectorAll("button[data-format]")) {
+    button.addEventListener("click", () => copyPeerTable(button, redact[button.dataset.format]));
+  }
+  for (const button of row.querySelectorAll("button[data-save]")) {
+    button.addEventListener("click", () => savePeerTable(button, redact[button.dataset.save]));
+  }
+}
+
+async function copyPeerTable(button, redact) {
+  let ok = false;
+  try {
+    const r = await fetch(`/peers/export?format=${button.dataset.format}&redact=${redact ? 1 : 0}`);
+    const j = await r.json();
+    if (!j.error) ok = await copyText(j.text);
+  } catch (_) {}
+  flashCopied(button, ok);
+}
+
+async function savePeerTable(button, redact) {
+  let error = "no reply";
+  try {
+    const r = await fetch(`/peers/export?format=${button.dataset.save}&redact=${redact ? 1 : 0}&save=1`);
+    const j = await r.json();
+    error = j.error || null;
+  } catch (e) {
+    error = String(e);
+  }
+  flashSaved(button, error);
+}
+
+function peerSortKey(p, field) {
+  switch (field) {
+    case "addr": return p.addr || "";
+    case "subver": return p.subver || "";
+    case "direction": return p.inbound ? 1 : 0;
+    case "perms": return abbreviatePermissions(p.permissions);
+    case "ping": return p.pingtime != null ? p.pingtime : Infinity;
+    case "bytessent": return p.bytessent || 0;
+    case "bytesrecv": return p.bytesrecv || 0;
+    case "minfeefilter": return typeof p.minfeefilter === "number" ? p.minfeefilter : Infinity;
+    default: return p.id;
+  }
+}
+
+function sortPeers(peers, field, desc) {
+  const sorted = peers.slice().sort((a, b) => {
+    const ka = peerSortKey(a, field);
+    const kb = peerSortKey(b, field);
+    const cmp = typeof ka === "string" ? ka.localeCompare(kb) : ka - kb;
+    return cmp !== 0 ? cmp : a.id - b.id;
+  });
+  return desc ? sorted.reverse() : sorted;
+}
+
+function renderPeerSortHeaders() {
+  for (const th of document.querySelectorAll("#dash-peer-table th[data-sort]")) {
+    const active = th.dataset.sort === peerView.sort;
+    th.classList.toggle("sorted-asc", active && !peerView.desc);
+    th.classList.toggle("sorted-desc", active && peerView.desc);
+  }
+}
+
+function peerSortClicked(field) {
+  if (peerView.sort === field) {
+    peerView.desc = !peerView.desc;
+  } else {
+    peerView.sort = field;
+    peerView.desc = false;
+  }
+  savePeerView();
+  renderPeerSortHeaders();
+  renderPeers(lastPeers);
+}
+
+function resetPeerView() {
+  peerView = normalizePeerView(null);
+  savePeerView();
+  renderPeerSortHeaders();
+  renderPeerFeeFilterColumn();
+  renderPeers(lastPeers);
+}
+
+function initPeerTableClick() {
+  document.querySelector("#dash-peer-table thead").addEventListener("click", (ev) => {
+    const th = ev.target.closest("th[data-sort]");
+    if (th) peerSortClicked(th.dataset.sort);
+  });
+  document.getElementById("peer-view-reset").addEventListener("click", resetPeerView);
+  document.getElementById("peer-fee-filter-toggle").addEventListener("click", peerFeeFilterToggled);
+  document.getElementById("peer-filter").addEventListener("input", peerFilterChanged);
+  renderPeerSortHeaders();
+  renderPeerFeeFilterColumn();
+  initPeerExport();
+  const tbody = document.querySelector("#dash-peer-table tbody");
+  tbody.addEventListener("click", (ev) => {
+    const row = ev.target.closest(".peer-row");
+    if (!row) return;
+    const id = Number(row.dataset.peerId);
+    const peer = peerById.get(id) || lastPeers.find((p) => p.id === id);
+    const copy = ev.target.closest(".copy-btn");
+    if (copy) {
+      if (peer) copyWithFlash(copy, peer.addr);
+      return;
+    }
+    selectPeer(id);
+    if (peer) showPeerDetail(peer);
+  });
+  document.getElementById("dash-peer-table").addEventListener("keydown", peerTableKeyDown);
+}
+
+// --- Peer selection ---
+
+// Rows in the order shown, so the selection follows the sort and filter.
+function shownPeerIds() {
+  return Array.from(document.querySelectorAll("#dash-peer-table tbody .peer-row"), (row) => Number(row.dataset.peerId));
+}
+
+// Wraps at both ends; with nothing selected (or the selected peer gone)
+// the first step lands on the first or last row.
+function steppedPeerId(ids, currentId, step) {
+  if (ids.length === 0) return null;
+  const index = ids.indexOf(currentId);
+  if (index < 0) return step > 0 ? ids[0] : ids[ids.length - 1];
+  return ids[(index + step + ids.length) % ids.length];
+}
+
+function selectPeer(id) {
+  selectedPeerId = id;
+  for (const [peerId, row] of peerRows) row.classList.toggle("selected", peerId === id);
+  const row = peerRows.get(id);
+  if (row && dashboardVisible()) row.scrollIntoView({ block: "nearest" });
+}
+
+// Focuses the table so the arrow keys and Enter carry on from here.
+function stepPeerSelection(step) {
+  if (!dashboardVisible()) return false;
+  const id = steppedPeerId(shownPeerIds(), selectedPeerId, step);
+  if (id === null) return false;
+  selectPeer(id);
+  document.getElementById("dash-peer-table").focus({ preventScroll: true });
+  return true;
+}
+
+function togglePeerDetail() {
+  // Enter on a focused button or link keeps its usual meaning.
+  const focused = document.activeElement;
+  if (focused && focused.closest("button, a, summary")) return false;
+  if (!document.getElementById("peer-view").hidden) {
+    showDashboard();
+    selectPeer(selectedPeerId);
+    document.getElementById("dash-peer-table").focus({ preventScroll: true });
+    return true;
   }
-  if (tasks.length === 0) return;
+  const peer = dashboardVisible() && peerById.get(selectedPeerId);
+  if (!peer || !peerRows.has(selectedPeerId)) return false;
+  showPeerDetail(peer);
+  return true;
+}
+
+function peerTableKeyDown(ev) {
+  if (ev.ctrlKey || ev.altKey || ev.metaKey || ev.target.closest("button")) return;
+  const step = { ArrowDown: 1, ArrowUp: -1 }[ev.key];
+  if (step && stepPeerSelection(step)) ev.preventDefault();
+}
+
+function showPeerDetail(peer) {
+  showMainView("peer-view");
+  stopDashboardPolling();
+  peerDetail = peer;
+  document.getElementById("peer-view-title").textContent = peer.addr;
+  setPeerDisconnectConfirm(false);
+  document.getElementById("peer-disconnect-confirm-btn").disabled = false;
+  const ban = document.getElementById("peer-ban");
+  const target = peerBanTarget(peer);
+  ban.disabled = !target.ok;
+  ban.title = target.ok ? `setban ${target.host} add ${PEER_BAN_SECS}` : target.reason;
+  setPeerActionStatus(null);
+  const dl = document.getElementById("peer-view-dl");
+  let html = "";
+  for (const [key, val] of Object.entries(peer)) {
+    let display = typeof val === "object" ? JSON.stringify(val, null, 2) : String(val);
+    if (key === "permissions") display = val.length ? val.join(", ") : "none";
+    if ((key === "bytessent" || key === "bytesrecv") && typeof val === "number") {
+      display = `${formatBytes(val)} (${val.toLocaleString()} bytes)`;
+    }
+    html += dd(key, display);
+  }
+  dl.innerHTML = html;
+}
+
+// setban takes a host or subnet, never a port. Inbound Tor and I2P peers
+// show up as the local proxy's address, so banning that would shut out
+// every inbound peer on that network.
+function peerBanTarget(peer) {
+  const host = peerHost(peer.addr);
+  if (peer.inbound && (peer.network === "onion" || peer.network === "i2p" || isLoopbackHost(host))) {
+    return { ok: false, reason: "Inbound peer behind a local proxy; banning would ban the proxy address." };
+  }
+  return { ok: true, host };
+}
+
+function peerActionErrorMessage(error) {
+  // -29 is RPC_CLIENT_NODE_NOT_CONNECTED, -23 RPC_CLIENT_NODE_ALREADY_ADDED.
+  if (error && error.code === -29) return "The peer is no longer connected.";
+  if (error && error.code === -23) return "That address is already banned.";
+  return rpcErrorText(error);
+}
+
+function setPeerActionStatus(text, isError) {
+  const el = document.getElementById("peer-action-status");
+  el.textContent = text || "";
+  el.hidden = !text;
+  el.classList.toggle("error", !!isError);
+}
+
+// The button stays disabled after success so the action can't be repeated.
+async function runPeerAction(button, method, params) {
+  button.disabled = true;
+  const resp = await rpcCall(method, params, "interactive");
+  if (resp.error) {
+    button.disabled = false;
+    setPeerActionStatus(peerActionErrorMessage(resp.error), true);
+    return false;
+  }
+  return true;
+}
+
+function setPeerDisconnectConfirm(open) {
+  document.getElementById("peer-disconnect").hidden = open;
+  document.getElementById("peer-disconnect-confirm").hidden = !open;
+  if (open) setPeerActionStatus(null);
+}
+
+// A disconnected peer has nothing left to show, so its view is closed and
+// the dashboard refreshed.
+async function disconnectPeerConfirmed() {
+  if (!peerDetail) return;
+  const btn = document.getElementById("peer-disconnect-confirm-btn");
+  if (!(await runPeerAction(btn, "disconnectnode", ["", peerDetail.id]))) return;
+  peerDetail = null;
+  showDashboard();
+}
+
+async function banPeerClicked() {
+  if (!peerDetail) return;
+  const target = peerBanTarget(peerDetail);
+  if (!target.ok) return;
+  const btn = document.getElementById("peer-ban");
+  if (!(await runPeerAction(btn, "setban", [target.host, "add", PEER_BAN_SECS]))) return;
+  setPeerActionStatus(`Banned ${target.host} for 24 hours.`, false);
+  fetchDashboard();
+}
+
+async function showZmqRpcResult(title, description, run) {
+  showMainView("method-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+
+  document.getElementById("execute").hidden = true;
+  document.getElementById("method-endpoint").hidden = true;
+  document.getElementById("method-name").textContent = title;
+  document.getElementById("method-desc").textContent = description;
+  document.getElementById("param-form").innerHTML = "";
+  const tabId = openResultTab(title);
+
   try {
-    await Promise.all(tasks);
-    updateStatus(true);
+    setResultTabContent(tabId, responseContent(await run()));
+  } catch (e) {
+    setResultTabContent(tabId, { kind: "text", text: String(e), isError: true });
+  }
+}
+
+// --- Build info ---
+
+function buildInfoText(info) {
+  const dirty = info.git_dirty === true ? "-dirty" : "";
+  const features = info.features && info.features.length ? info.features.join(", ") : "none";
+  return `bitcoin-rpc-web ${info.version}\ncommit: ${info.git_commit}${dirty}\n`
+    + `built: ${info.build_date}\ntarget: ${info.target}\nfeatures: ${features}`;
+}
+
+function renderBuildInfo() {
+  if (!buildInfo) return;
+  document.getElementById("cfg-version").textContent = "v" + buildInfo.version;
+  const dirty = buildInfo.git_dirty === true ? "-dirty" : "";
+  const build = document.getElementById("cfg-build");
+  build.textContent = `${buildInfo.git_commit}${dirty} \u00b7 ${buildInfo.build_date}`;
+  build.title = buildInfoText(buildInfo);
+  const copy = document.getElementById("cfg-copy-build");
+  copy.hidden = false;
+  copy.addEventListener("click", () => copyWithFlash(copy, buildInfoText(buildInfo)));
+}
+
+function renderStatusPageAddress() {
+  const el = document.getElementById("cfg-status-page");
+  el.hidden = !statusPageAddress;
+  if (statusPageAddress) el.textContent = `status page: http://${statusPageAddress}/`;
+}
+
+// Recent warnings and errors (or everything captured, with debug ticked),
+// prefixed with build info so a pasted bug report is self-contained.
+async function copyRecentLogs() {
+  const button = document.getElementById("cfg-copy-logs");
+  const debug = document.getElementById("cfg-logs-debug").checked;
+  let ok = false;
+  try {
+    const r = await fetch("/logs?level=" + (debug ? "debug" : "warn"));
+    const j = await r.json();
+    const header = buildInfo ? buildInfoText(buildInfo) + "\n\n" : "";
+    ok = await copyText(header + (j.text || "(no recent log events)\n"));
+  } catch (_) {}
+  flashCopied(button, ok);
+}
+
+// The async clipboard API needs a secure context, which custom protocols are
+// not on every platform, so fall back to a hidden textarea.
+async function copyText(text) {
+  try {
+    await navigator.clipboard.writeText(text);
+    return true;
   } catch (_) {
-    updateStatus(false);
+    const area = document.createElement("textarea");
+    area.value = text;
+    area.style.position = "fixed";
+    area.style.opacity = "0";
+    document.body.appendChild(area);
+    area.select();
+    const ok = document.execCommand("copy");
+    area.remove();
+    return ok;
+  }
+}
+
+// Shows the outcome on the button for a moment. The original label is kept
+// aside so a second click during the flash doesn't lose it.
+const copyFlashes = new WeakMap();
+
+function flashCopied(button, ok) {
+  flashButton(button, ok ? "Copied" : "Copy failed");
+}
+
+// A failed save shows its reason in the tooltip until the next save.
+function flashSaved(button, error) {
+  flashButton(button, error ? "Save failed" : "Saved");
+  button.dataset.title ??= button.title;
+  button.title = error || button.dataset.title;
+}
+
+function flashButton(button, text) {
+  const flash = copyFlashes.get(button) || { label: button.textContent, timer: null };
+  clearTimeout(flash.timer);
+  button.textContent = text;
+  flash.timer = setTimeout(() => {
+    button.textContent = flash.label;
+    copyFlashes.delete(button);
+  }, 1500);
+  copyFlashes.set(button, flash);
+}
+
+async function copyWithFlash(button, text) {
+  flashCopied(button, await copyText(text));
+}
+
+// --- Changelog ---
+
+async function initChangelog() {
+  document.getElementById("cfg-whats-new").addEventListener("click", showChangelog);
+  document.getElementById("changelog-close").addEventListener("click", hideChangelog);
+  document.getElementById("changelog-overlay").addEventListener("click", (ev) => {
+    if (ev.target.id === "changelog-overlay") hideChangelog();
+  });
+  document.addEventListener("keydown", (ev) => {
+    if (ev.key === "Escape") hideChangelog();
+  });
+  try {
+    const seen = localStorage.getItem("ui-changelog-seen") || "";
+    const r = await fetch("/changelog?seen=" + encodeURIComponent(seen));
+    changelog = await r.json();
+  } catch (_) {
+    return;
+  }
+  document.getElementById("cfg-version").textContent = "v" + changelog.version;
+  // Only record versions the backend says are newer, so a downgrade keeps the
+  // newer marker and the overlay reappears once the user upgrades past it.
+  if (changelog.show) {
+    localStorage.setItem("ui-changelog-seen", changelog.version);
+    showChangelog();
+  }
+}
+
+function showChangelog() {
+  if (!changelog) return;
+  let html = "";
+  for (const entry of changelog.entries) {
+    const current = entry.version === changelog.version ? ' <span class="changelog-current">current</span>' : "";
+    html += `<section class="changelog-entry"><h3>v${esc(entry.version)}${current}`
+      + (entry.date ? ` <span class="changelog-date">${esc(entry.date)}</span>` : "")
+      + "</h3><ul>";
+    for (const change of entry.changes) {
+      const area = CHANGELOG_AREAS.includes(change.area) ? change.area : "other";
+      html += `<li><span class="changelog-area area-${area}">${esc(change.area)}</span>${esc(change.text)}</li>`;
+    }
+    html += "</ul></section>";
+  }
+  document.getElementById("changelog-entries").innerHTML = html;
+  document.getElementById("changelog-overlay").hidden = false;
+}
+
+function hideChangelog() {
+  document.getElementById("changelog-overlay").hidden = true;
+}
+
+// --- Keyboard shortcuts ---
+
+// Handlers return false when the action doesn't apply where focus is, so
+// the key keeps its usual meaning.
+const SHORTCUT_HANDLERS = {
+  execute: () => {
+    const btn = document.getElementById("execute");
+    if (document.getElementById("method-view").hidden || !currentMethod || btn.hidden || btn.disabled) return false;
+    execute();
+    return true;
+  },
+  "focus-search": () => {
+    const search = document.getElementById("search");
+    search.focus();
+    search.select();
+    return true;
+  },
+  "show-dashboard": () => {
+    showDashboard();
+    return true;
+  },
+  "history-older": () => stepDashboardHistory(1),
+  "history-newer": () => stepDashboardHistory(-1),
+  "peer-next": () => stepPeerSelection(1),
+  "peer-prev": () => stepPeerSelection(-1),
+  "peer-open": togglePeerDetail,
+  "tab-prev": () => cycleResultTab(-1),
+  "tab-next": () => cycleResultTab(1),
+  "toggle-password": () => {
+    if (document.getElementById("config").classList.contains("collapsed")) return false;
+    if (document.getElementById("cfg-auth-mode").value !== "password") return false;
+    setPasswordVisible(!passwordVisible());
+    return true;
+  },
+  shortcuts: () => {
+    showShortcuts();
+    return true;
+  },
+};
+
+// Builds the same descriptor the backend's KeyCombo::format does: modifiers
+// as Ctrl+Alt+Shift+Meta, upper-case letters, and Shift left out for
+// symbols because it is already part of the character.
+function keyDescriptor(ev) {
+  if (["Control", "Alt", "Shift", "Meta", "AltGraph", "CapsLock"].includes(ev.key)) return null;
+  let key = ev.key === " " ? "Space" : ev.key;
+  // Alt+letter types an accented character on macOS; fall back to the key.
+  if (ev.altKey && /^Key[A-Z]$/.test(ev.code || "")) key = ev.code.slice(3);
+  const single = [...key].length === 1;
+  if (single) key = key.toUpperCase();
+  const shift = ev.shiftKey && (!single || /^[A-Z]$/.test(key));
+  return [ev.ctrlKey && "Ctrl", ev.altKey && "Alt", shift && "Shift", ev.metaKey && "Meta", key]
+    .filter(Boolean)
+    .join("+");
+}
+
+function loadSavedKeyBindings() {
+  try {
+    const saved = JSON.parse(localStorage.getItem("ui-keybindings") || "{}");
+    return saved && typeof saved === "object" ? saved : {};
+  } catch (_) {
+    return {};
+  }
+}
+
+// The backend parses saved descriptors, fills in defaults for unmapped
+// actions and reports bindings that collide.
+async function resolveKeyBindings(saved) {
+  const r = await fetch("/keybindings?saved=" + encodeURIComponent(JSON.stringify(saved)));
+  return r.json();
+}
+
+function applyKeyBindings(table) {
+  keyBindings = table.bindings;
+  keyBindingActions = new Map();
+  for (const b of keyBindings) {
+    if (b.key && !keyBindingActions.has(b.key)) keyBindingActions.set(b.key, b.action);
+  }
+  renderShortcuts();
+}
+
+function shortcutKeyDown(ev) {
+  if (keyCaptureAction) {
+    captureKeyBinding(ev);
+    return;
+  }
+  const descriptor = keyDescriptor(ev);
+  const action = descriptor && keyBindingActions.get(descriptor);
+  if (!action) return;
+  // Plain keys belong to whatever is being typed into.
+  const typing = ev.target.closest && ev.target.closest("input, textarea, select, [contenteditable]");
+  if (typing && !ev.ctrlKey && !ev.altKey && !ev.metaKey) return;
+  if (SHORTCUT_HANDLERS[action]() !== false) ev.preventDefault();
+}
+
+function renderShortcuts() {
+  const tbody = document.querySelector("#shortcuts-table tbody");
+  tbody.innerHTML = keyBindings.map((b) => {
+    const capturing = b.action === keyCaptureAction;
+    const key = capturing ? "Press a key\u2026" : b.key || "(none)";
+    return `<tr data-action="${esc(b.action)}"><td>${esc(b.label)}</td>`
+      + `<td><button class="shortcut-key${capturing ? " capturing" : ""}" type="button" data-role="bind">${esc(key)}</button></td>`
+      + `<td>${b.custom ? `<button class="cfg-link" type="button" data-role="reset" title="Default: ${esc(b.default)}">Reset</button>` : ""}</td></tr>`;
+  }).join("");
+}
+
+function setShortcutsError(message) {
+  const el = document.getElementById("shortcuts-error");
+  el.textContent = message || "";
+  el.hidden = !message;
+}
+
+async function saveKeyBinding(action, descriptor) {
+  const saved = loadSavedKeyBindings();
+  if (descriptor === undefined) delete saved[action];
+  else saved[action] = descriptor;
+  let table;
+  try {
+    table = await resolveKeyBindings(saved);
+  } catch (e) {
+    setShortcutsError(String(e));
+    return;
+  }
+  const clash = table.conflicts.find((pair) => pair.includes(action));
+  if (clash) {
+    const other = clash[0] === action ? clash[1] : clash[0];
+    const label = (table.bindings.find((b) => b.action === other) || {}).label || other;
+    setShortcutsError(`${descriptor || "That key"} is already bound to \u201c${label}\u201d.`);
+    renderShortcuts();
+    return;
+  }
+  setShortcutsError(null);
+  localStorage.setItem("ui-keybindings", JSON.stringify(saved));
+  applyKeyBindings(table);
+}
+
+// Escape cancels, Backspace clears the binding, anything else is the new key.
+function captureKeyBinding(ev) {
+  const descriptor = keyDescriptor(ev);
+  if (!descriptor) return;
+  ev.preventDefault();
+  ev.stopPropagation();
+  const action = keyCaptureAction;
+  keyCaptureAction = null;
+  if (descriptor === "Escape") {
+    renderShortcuts();
+  } else if (descriptor === "Backspace") {
+    saveKeyBinding(action, "");
+  } else {
+    saveKeyBinding(action, descriptor);
+  }
+}
+
+function shortcutsTableClicked(ev) {
+  const btn = ev.target.closest("button[data-role]");
+  if (!btn) return;
+  const action = btn.closest("tr").dataset.action;
+  setShortcutsError(null);
+  if (btn.dataset.role === "reset") {
+    keyCaptureAction = null;
+    saveKeyBinding(action, undefined);
+    return;
+  }
+  keyCaptureAction = keyCaptureAction === action ? null : action;
+  renderShortcuts();
+}
+
+function showShortcuts() {
+  renderShortcuts();
+  document.getElementById("shortcuts-overlay").hidden = false;
+}
+
+function hideShortcuts() {
+  keyCaptureAction = null;
+  setShortcutsError(null);
+  document.getElementById("shortcuts-overlay").hidden = true;
+}
+
+async function initKeyBindings() {
+  document.getElementById("cfg-shortcuts").addEventListener("click", showShortcuts);
+  document.getElementById("shortcuts-close").addEventListener("click", hideShortcuts);
+  document.getElementById("shortcuts-overlay").addEventListener("click", (ev) => {
+    if (ev.target.id === "shortcuts-overlay") hideShortcuts();
+  });
+  document.getElementById("shortcuts-table").addEventListener("click", shortcutsTableClicked);
+  // Capture phase, so a key being recorded never reaches other handlers.
+  document.addEventListener("keydown", shortcutKeyDown, true);
+  document.addEventListener("keydown", (ev) => {
+    if (ev.key === "Escape" && !keyCaptureAction) hideShortcuts();
+  });
+  try {
+    applyKeyBindings(await resolveKeyBindings(loadSavedKeyBindings()));
+  } catch (_) {}
+}
+
+// --- Wallet management ---
+
+async function showWalletManager() {
+  showMainView("wallet-view");
+  stopDashboardPolling();
+  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
+  currentMethod = null;
+  rememberView({ view: "wallets" });
+  document.getElementById("wallet-disabled").hidden = !walletsDisabled;
+  for (const panel of document.querySelectorAll("#wallet-view section.view-panel")) panel.hidden = walletsDisabled;
+  if (walletsDisabled) return;
+  await refreshWalletManager();
+}
+
+// Wallet lifecycle calls always target the node endpoint; unloadwallet in
+// particular fails if the URL names a different wallet than its parameter.
+function nodeRpcCall(method, params) {
+  return rpcCallWithOptions(method, params, { priority: "interactive", wallet: "" });
+}
+
+async function refreshWalletManager() {
+  const [loaded, dir] = await Promise.all([
+    nodeRpcCall("listwallets", []),
+    nodeRpcCall("listwalletdir", []),
+  ]);
+  const loadedNames = Array.isArray(loaded.result) ? loaded.result : [];
+  const diskNames = dir.result && Array.isArray(dir.result.wallets)
+    ? dir.result.wallets.map((w) => w.name)
+    : [];
+  renderWalletTable("wallet-loaded-table", loadedNames, "unload", "Unload", "No wallets loaded");
+  renderWalletTable(
+    "wallet-disk-table",
+    diskNames.filter((name) => !loadedNames.includes(name)),
+    "load",
+    "Load",
+    "No other wallets found in the wallet directory",
+  );
+  if (loaded.error || dir.error) setWalletStatus(rpcErrorMessage(loaded.error || dir.error), true);
+  await refreshWalletTransactions();
+}
+
+function renderWalletTable(id, names, action, label, emptyText) {
+  const tbody = document.querySelector(`#${id} tbody`);
+  const active = document.getElementById("cfg-wallet").value;
+  if (names.length === 0) {
+    tbody.innerHTML = `<tr><td class="wallet-empty">${esc(emptyText)}</td></tr>`;
+    return;
+  }
+  let html = "";
+  for (const name of names) {
+    const display = name === "" ? "(default wallet)" : name;
+    const badge = name === active ? ' <span class="wallet-active">active</span>' : "";
+    html += `<tr><td>${esc(display)}${badge}</td>`
+      + `<td class="wallet-actions"><button data-wallet-action="${action}" data-wallet-name="${esc(name)}">${esc(label)}</button></td></tr>`;
+  }
+  tbody.innerHTML = html;
+}
+
+function initWalletTableClick() {
+  document.getElementById("wallet-view").addEventListener("click", async (ev) => {
+    const btn = ev.target.closest("button[data-wallet-action]");
+    if (!btn) return;
+    const name = btn.dataset.walletName;
+    const action = btn.dataset.walletAction;
+    if (action === "bump") {
+      openBumpPanel(btn.dataset.txid);
+      return;
+    }
+    if (action === "activate") {
+      await setActiveWallet(name);
+      setWalletStatus(`"${name}" is now the active wallet.`, false);
+      await refreshWalletManager();
+      return;
+    }
+    btn.disabled = true;
+    const resp = await nodeRpcCall(action === "load" ? "loadwallet" : "unloadwallet", [name]);
+    await finishWalletAction(action, name, resp);
+  });
+}
+
+function createWalletParams(opts) {
+  const params = [
+    opts.name,
+    opts.disablePrivateKeys,
+    opts.blank,
+    opts.passphrase,
+    null,
+    opts.descriptors,
+  ];
+  while (params.length > 1 && (params[params.length - 1] == null || params[params.length - 1] === "")) {
+    params.pop();
+  }
+  return params.map((p) => (p === "" ? null : p));
+}
+
+function validateCreateWallet(opts, confirm) {
+  if (!opts.name.trim()) return "Wallet name is required";
+  if (opts.passphrase !== confirm) return "Passphrases do not match";
+  if (opts.passphrase && opts.disablePrivateKeys) return "A watch-only wallet cannot be encrypted";
+  return null;
+}
+
+async function createWalletSubmitted(ev) {
+  ev.preventDefault();
+  const passphraseInput = document.getElementById("wallet-create-passphrase");
+  const confirmInput = document.getElementById("wallet-create-passphrase-confirm");
+  const opts = {
+    name: document.getElementById("wallet-create-name").value,
+    descriptors: document.getElementById("wallet-create-descriptors").checked,
+    disablePrivateKeys: document.getElementById("wallet-create-disable-keys").checked,
+    blank: document.getElementById("wallet-create-blank").checked,
+    passphrase: passphraseInput.value,
+  };
+  const invalid = validateCreateWallet(opts, confirmInput.value);
+  // Passphrases never outlive the request that uses them.
+  passphraseInput.value = "";
+  confirmInput.value = "";
+  if (invalid) {
+    setWalletStatus(invalid, true);
+    return;
+  }
+  const submit = document.getElementById("wallet-create-submit");
+  submit.disabled = true;
+  try {
+    const resp = await nodeRpcCall("createwallet", createWalletParams(opts));
+    if (!resp.error) document.getElementById("wallet-create-name").value = "";
+    await finishWalletAction("create", opts.name, resp);
+  } finally {
+    submit.disabled = false;
+  }
+}
+
+// Maps an action's RPC response and the post-action wallet list to an outcome.
+function reconcileWalletAction(action, name, resp, loadedAfter) {
+  const err = resp && resp.error;
+  const isLoaded = Array.isArray(loadedAfter) && loadedAfter.includes(name);
+  // Core reports "already loaded" as -4 on older releases and -35 since v22.
+  if (err && (err.code === -4 || err.code === -35) && action === "load" && isLoaded) {
+    return { ok: true, offerActivate: true, message: `"${name}" was already loaded.` };
+  }
+  if (err && err.code === -18) {
+    return { ok: false, message: `Wallet "${name}" was not found.` };
   }
+  if (err) return { ok: false, message: rpcErrorMessage(err) };
+  if (action === "unload") {
+    return isLoaded
+      ? { ok: false, message: `"${name}" is still loaded after unloadwallet.` }
+      : { ok: true, message: `Unloaded "${name}".` };
+  }
+  if (!isLoaded) {
+    return { ok: false, message: `"${name}" did not appear in listwallets after ${action}.` };
+  }
+  const verb = action === "create" ? "Created" : "Loaded";
+  return { ok: true, offerActivate: true, message: `${verb} "${name}".` };
+}
+
+async function finishWalletAction(action, name, resp) {
+  const after = await nodeRpcCall("listwallets", []);
+  const outcome = reconcileWalletAction(action, name, resp, after.result);
+  const active = document.getElementById("cfg-wallet").value;
+  if (action === "unload" && outcome.ok && active === name) await setActiveWallet("");
+  await loadWallets();
+  await refreshWalletManager();
+  setWalletStatus(outcome.message, !outcome.ok, outcome.offerActivate && name !== active ? name : null);
+}
+
+async function setActiveWallet(name) {
+  await loadWallets();
+  document.getElementById("cfg-wallet").value = name;
+  await walletChanged();
+}
+
+function setWalletStatus(message, isError, activateName = null) {
+  const el = document.getElementById("wallet-status");
+  el.classList.toggle("error", isError);
+  let html = esc(message);
+  if (activateName != null) {
+    html += ` <button data-wallet-action="activate" data-wallet-name="${esc(activateName)}">Use as active wallet</button>`;
+  }
+  el.innerHTML = html;
+  el.hidden = false;
+}
+
+// --- Wallet transactions and fee bumping ---
+
+// listtransactions reports one entry per output; fold them into one row per txid.
+function summarizeWalletTransactions(entries) {
+  const byTxid = new Map();
+  for (const e of entries || []) {
+    let tx = byTxid.get(e.txid);
+    if (!tx) {
+      tx = {
+        txid: e.txid,
+        time: e.time || 0,
+        confirmations: e.confirmations || 0,
+        amount: 0,
+        fee: null,
+        replaceable: e["bip125-replaceable"] || "unknown",
+        conflicts: e.walletconflicts || [],
+        abandoned: e.abandoned === true,
+        replacedBy: e.replaced_by_txid || null,
+        replaces: e.replaces_txid || null,
+      };
+      byTxid.set(e.txid, tx);
+    }
+    tx.amount += Number(e.amount) || 0;
+    if (e.category === "send" && typeof e.fee === "number") tx.fee = e.fee;
+  }
+  return [...byTxid.values()].sort((a, b) => b.time - a.time);
+}
+
+function bumpEligibility(tx) {
+  if (tx.confirmations > 0) return { ok: false, reason: "confirmed" };
+  if (tx.confirmations < 0 || tx.conflicts.length > 0) return { ok: false, reason: "conflicted" };
+  if (tx.abandoned) return { ok: false, reason: "abandoned" };
+  if (tx.replacedBy) return { ok: false, reason: "replaced" };
+  if (tx.fee == null) return { ok: false, reason: "not sent by this wallet" };
+  if (tx.replaceable !== "yes") return { ok: false, reason: "not replaceable" };
+  return { ok: true };
+}
+
+function parseFeeRateInput(raw) {
+  const s = String(raw).trim();
+  if (s === "") return { ok: true, value: null };
+  if (!/^\d+(\.\d{1,3})?$/.test(s) || Number(s) <= 0) {
+    return { ok: false, error: "Fee rate must be a positive number of sat/vB (up to 3 decimals)" };
+  }
+  return { ok: true, value: Number(s) };
+}
+
+function bumpParams(txid, feeRate) {
+  return feeRate == null ? [txid] : [txid, { fee_rate: feeRate }];
+}
+
+// The rate a blank-rate preview settled on, from the replacement's own
+// vsize. Rounded up to bumpfee's 3 decimals so confirming never pays less
+// than the preview showed.
+function previewedFeeRate(newFeeSats, replacementVsize) {
+  if (!(replacementVsize > 0) || !(newFeeSats > 0)) return null;
+  return Math.ceil((newFeeSats * 1000) / replacementVsize) / 1000;
+}
+
+// Rates use the original vsize; a replacement with a different change output
+// differs by a few vbytes, so the preview labels them as approximate.
+function bumpPreview(txDetail, bump) {
+  const vsize = txDetail && txDetail.decoded ? txDetail.decoded.vsize : null;
+  const origFeeSats = Number(btcToSats(bump.origfee));
+  const newFeeSats = Number(btcToSats(bump.fee));
+  const rate = (sats) => (vsize ? Math.round((sats / vsize) * 10) / 10 : null);
+  return {
+    vsize,
+    origFeeSats,
+    newFeeSats,
+    deltaSats: newFeeSats - origFeeSats,
+    origRate: rate(origFeeSats),
+    newRate: rate(newFeeSats),
+  };
+}
+
+function bumpErrorMessage(err) {
+  const msg = err && typeof err === "object" ? String(err.message || "") : String(err || "");
+  if (err && err.code === -13) return "The wallet is locked; unlock it with walletpassphrase first.";
+  if (/has been mined|conflicted with a mined/i.test(msg)) {
+    return "The transaction confirmed (or was conflicted) before the bump; refresh to see its state.";
+  }
+  if (/not BIP ?125 replaceable/i.test(msg)) return "This transaction does not signal replaceability (BIP 125).";
+  if (/already bumped/i.test(msg)) return "This transaction was already bumped; bump the replacement instead.";
+  if (/has descendants/i.test(msg)) return "Unconfirmed transactions spend this one; bump the descendant instead.";
+  if (/insufficient total fee|fee rate .*(too low|lower than)|lower than the minimum|min relay fee/i.test(msg)) {
+    return "Fee rate too low for a replacement: " + msg;
+  }
+  return rpcErrorMessage(err);
+}
+
+// Marks the original as replaced and lists the replacement just above it until
+// the next listtransactions refresh catches up.
+function reconcileBumpedTransactions(txs, oldTxid, result) {
+  const out = [];
+  for (const tx of txs) {
+    if (tx.txid !== oldTxid) {
+      out.push(tx);
+      continue;
+    }
+    out.push({ ...tx, txid: result.txid, fee: -Number(result.fee), replacedBy: null, replaces: oldTxid });
+    out.push({ ...tx, replacedBy: result.txid });
+  }
+  return out;
+}
+
+async function refreshWalletTransactions() {
+  const [list, info] = await Promise.all([
+    rpcCall("listtransactions", ["*", WALLET_TX_LIST_COUNT], "interactive"),
+    rpcCall("getwalletinfo", [], "interactive"),
+  ]);
+  walletWatchOnly = !!(info.result && info.result.private_keys_enabled === false);
+  if (list.error) {
+    walletTxs = [];
+    renderWalletTransactions(rpcErrorMessage(list.error));
+    return;
+  }
+  walletTxs = summarizeWalletTransactions(list.result);
+  rememberTxids(walletTxs.map((tx) => tx.txid));
+  renderWalletTransactions();
+}
+
+function walletTxStatus(tx) {
+  if (tx.replacedBy) return "replaced by " + tx.replacedBy.slice(0, 10) + "\u2026";
+  if (tx.confirmations > 0) return tx.confirmations + " conf";
+  if (tx.confirmations < 0 || tx.conflicts.length) return "conflicted";
+  if (tx.abandoned) return "abandoned";
+  return "unconfirmed";
+}
+
+function renderWalletTransactions(errorText) {
+  const tbody = document.querySelector("#wallet-tx-table tbody");
+  if (errorText || walletTxs.length === 0) {
+    const text = errorText || "No transactions in the active wallet";
+    tbody.innerHTML = `<tr><td class="wallet-empty">${esc(text)}</td></tr>`;
+    return;
+  }
+  let html = "";
+  for (const tx of walletTxs) {
+    const fee = tx.fee != null ? Math.abs(tx.fee).toFixed(8) : "";
+    const action = bumpEligibility(tx).ok
+      ? `<button data-wallet-action="bump" data-txid="${esc(tx.txid)}">Bump fee</button>`
+      : "";
+    const cls = tx.replacedBy ? ' class="wallet-tx-replaced"' : "";
+    html += `<tr${cls}><td title="${esc(tx.txid)}">${esc(tx.txid.slice(0, 16))}\u2026</td>`
+      + `<td class="wallet-tx-amount">${tx.amount.toFixed(8)}</td>`
+      + `<td class="wallet-tx-fee">${fee}</td>`
+      + `<td class="wallet-tx-status">${esc(walletTxStatus(tx))}</td>`
+      + `<td class="wallet-actions">${action}</td></tr>`;
+  }
+  tbody.innerHTML = html;
+}
+
+function openBumpPanel(txid) {
+  bumpState = { txid, feeRate: null, preview: null, psbt: null };
+  document.getElementById("wallet-bump-txid").textContent = txid;
+  document.getElementById("wallet-bump-feerate").value = "";
+  document.getElementById("wallet-bump-diff").hidden = true;
+  document.getElementById("wallet-bump-psbt").hidden = true;
+  const confirm = document.getElementById("wallet-bump-confirm");
+  confirm.hidden = true;
+  confirm.textContent = walletWatchOnly ? "Show PSBT" : "Broadcast bump";
+  document.getElementById("wallet-bump").hidden = false;
+}
+
+function closeBumpPanel() {
+  bumpState = null;
+  document.getElementById("wallet-bump").hidden = true;
+}
+
+async function bumpPreviewSubmitted(ev) {
+  ev.preventDefault();
+  if (!bumpState) return;
+  const parsed = parseFeeRateInput(document.getElementById("wallet-bump-feerate").value);
+  if (!parsed.ok) {
+    setWalletStatus(parsed.error, true);
+    return;
+  }
+  const { txid } = bumpState;
+  const previewBtn = document.getElementById("wallet-bump-preview");
+  previewBtn.disabled = true;
+  try {
+    // psbtbumpfee builds the replacement without signing or broadcasting, so
+    // it doubles as a dry run for wallets that hold keys.
+    const [bump, detail] = await Promise.all([
+      rpcCall("psbtbumpfee", bumpParams(txid, parsed.value), "interactive"),
+      rpcCall("gettransaction", [txid, true, true], "interactive"),
+    ]);
+    if (!bumpState || bumpState.txid !== txid) return;
+    if (bump.error) {
+      setWalletStatus(bumpErrorMessage(bump.error), true);
+      return;
+    }
+    const preview = bumpPreview(detail.result, bump.result);
+    let feeRate = parsed.value;
+    if (feeRate == null) {
+      // Left blank, the wallet estimated a rate. Confirm must use that rate,
+      // not estimate again, or the broadcast fee could differ from this one.
+      const decoded = await rpcCall("decodepsbt", [bump.result.psbt], "interactive");
+      if (!bumpState || bumpState.txid !== txid) return;
+      feeRate = previewedFeeRate(preview.newFeeSats, decoded.result && decoded.result.tx && decoded.result.tx.vsize);
+      if (feeRate == null) {
+        setWalletStatus("Could not work out the previewed fee rate; enter a rate to bump with.", true);
+        return;
+      }
+    }
+    bumpState.feeRate = feeRate;
+    bumpState.preview = preview;
+    bumpState.psbt = bump.result.psbt;
+    renderBumpPreview(bumpState.preview, feeRate);
+    document.getElementById("wallet-bump-confirm").hidden = false;
+  } finally {
+    previewBtn.disabled = false;
+  }
+}
+
+function renderBumpPreview(p, feeRate) {
+  const dl = document.getElementById("wallet-bump-diff");
+  const sats = (n) => n.toLocaleString() + " sat";
+  const rate = (r) => (r == null ? "?" : "\u2248" + r + " sat/vB");
+  let html = dd("Fee", `${sats(p.origFeeSats)} \u2192 ${sats(p.newFeeSats)} (+${sats(p.deltaSats)})`);
+  html += dd("Fee rate", `${rate(p.origRate)} \u2192 ${rate(p.newRate)}`);
+  if (p.vsize) html += dd("Size", p.vsize + " vB");
+  html += dd("Bump with", feeRate + " sat/vB");
+  dl.innerHTML = html;
+  dl.hidden = false;
 }
 
-async function fetchDashboard() {
-  if (dashboardFetchInFlight) {
-    dashboardFetchQueued = true;
+async function bumpConfirmClicked() {
+  if (!bumpState || !bumpState.preview) return;
+  if (walletWatchOnly) {
+    const area = document.getElementById("wallet-bump-psbt");
+    area.value = bumpState.psbt || "";
+    area.hidden = false;
+    setWalletStatus("Sign this PSBT externally, then finalizepsbt and sendrawtransaction to replace the original.", false);
     return;
   }
-  dashboardFetchInFlight = true;
+  const { txid, feeRate } = bumpState;
+  const btn = document.getElementById("wallet-bump-confirm");
+  btn.disabled = true;
   try {
-    const [chain, net, mempool, peers, up, totals] = await Promise.all([
-      rpcCall("getblockchaininfo", []),
-      rpcCall("getnetworkinfo", []),
-      rpcCall("getmempoolinfo", []),
-      rpcCall("getpeerinfo", []),
-      rpcCall("uptime", []),
-      rpcCall("getnettotals", []),
-    ]);
-    requestAnimationFrame(() => {
-      try {
-        if (chain.result) renderChain(chain.result, up.result);
-        if (mempool.result) renderMempool(mempool.result);
-        if (net.result) renderNetwork(net.result);
-        if (totals.result) renderNetTotals(totals.result);
-        if (peers.result) {
-          renderPeers(peers.result);
-          lastPeersRefreshMs = Date.now();
-        }
-        pendingDashboardParts.clear();
-        updateStatus(true);
-      } catch (_) {
-        updateStatus(false);
-      }
-    });
-  } catch (_) {
-    updateStatus(false);
-  } finally {
-    dashboardFetchInFlight = false;
-    if (dashboardFetchQueued) {
-      dashboardFetchQueued = false;
-      fetchDashboard();
+    const resp = await rpcCall("bumpfee", bumpParams(txid, feeRate), "interactive");
+    if (resp.error) {
+      setWalletStatus(bumpErrorMessage(resp.error), true);
+      return;
     }
+    walletTxs = reconcileBumpedTransactions(walletTxs, txid, resp.result);
+    renderWalletTransactions();
+    closeBumpPanel();
+    const fee = (btc) => Number(btcToSats(btc)).toLocaleString();
+    setWalletStatus(`Replaced ${txid.slice(0, 16)}\u2026 with ${resp.result.txid.slice(0, 16)}\u2026 `
+      + `(fee ${fee(resp.result.origfee)} \u2192 ${fee(resp.result.fee)} sat).`, false);
+  } finally {
+    btn.disabled = false;
   }
 }
 
-function esc(s) {
-  return String(s).replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;").replace(/"/g, "&quot;");
+// --- Watch-only import ---
+
+// Accepts a bare address or a descriptor (with or without checksum) and returns
+// the descriptor to pass to getdescriptorinfo.
+function normalizeWatchInput(raw) {
+  const s = String(raw).trim();
+  if (!s) return { ok: false, error: "Enter an address or descriptor" };
+  if (s.includes("(")) {
+    const hash = s.lastIndexOf("#");
+    return { ok: true, descriptor: hash > 0 ? s.slice(0, hash) : s, isAddress: false };
+  }
+  if (!/^[a-zA-Z0-9]{14,90}$/.test(s)) return { ok: false, error: "Not a valid address or descriptor" };
+  return { ok: true, descriptor: `addr(${s})`, isAddress: true };
 }
 
-function dd(label, value) {
-  return `<dt>${esc(label)}</dt><dd>${esc(String(value))}</dd>`;
+// "now" skips the rescan; a birth date rescans from midnight UTC of that day.
+function watchTimestamp(birthDate) {
+  if (!birthDate) return "now";
+  const ms = Date.parse(birthDate + "T00:00:00Z");
+  return Number.isFinite(ms) ? Math.floor(ms / 1000) : null;
 }
 
-function updateDl(dl, entries) {
-  if (dl.children.length !== entries.length * 2) {
-    dl.textContent = "";
-    for (const [label, value] of entries) {
-      const dt = document.createElement("dt");
-      dt.textContent = label;
-      const dd = document.createElement("dd");
-      dd.textContent = value;
-      dl.appendChild(dt);
-      dl.appendChild(dd);
-    }
+function buildImportRequest(info, label, timestamp) {
+  const req = { desc: info.descriptor, timestamp, active: false, internal: false };
+  // Core rejects labels on ranged descriptors.
+  if (info.isrange) {
+    req.range = [0, WATCH_RANGE_END];
+  } else if (label) {
+    req.label = label;
+  }
+  return [req];
+}
+
+function rescanWarning(timestamp, nowMs) {
+  if (timestamp === "now") return null;
+  const days = Math.max(0, Math.floor((nowMs / 1000 - timestamp) / 86400));
+  const since = new Date(timestamp * 1000).toISOString().slice(0, 10);
+  return `This rescans every block since ${since} (~${days.toLocaleString()} days of history). `
+    + "That can take from minutes to hours, and the wallet is busy until it finishes.";
+}
+
+function parseImportResponse(result) {
+  if (!Array.isArray(result)) return [{ ok: false, warnings: [], error: "unexpected importdescriptors response" }];
+  return result.map((r) => ({
+    ok: r.success === true,
+    warnings: Array.isArray(r.warnings) ? r.warnings : [],
+    error: r.error ? rpcErrorMessage(r.error) : null,
+  }));
+}
+
+async function watchReviewSubmitted(ev) {
+  ev.preventDefault();
+  document.getElementById("watch-review").hidden = true;
+  watchRequest = null;
+  const input = normalizeWatchInput(document.getElementById("watch-input").value);
+  if (!input.ok) {
+    setWalletStatus(input.error, true);
     return;
   }
-  for (let i = 0; i < entries.length; i++) {
-    const dd = dl.children[i * 2 + 1];
-    const value = entries[i][1];
-    if (dd.textContent !== value) dd.textContent = value;
+  const timestamp = watchTimestamp(document.getElementById("watch-birth").value);
+  if (timestamp == null) {
+    setWalletStatus("Invalid birth date", true);
+    return;
+  }
+  const [walletInfo, descInfo] = await Promise.all([
+    rpcCall("getwalletinfo", [], "interactive"),
+    rpcCall("getdescriptorinfo", [input.descriptor], "interactive"),
+  ]);
+  if (walletInfo.error) {
+    setWalletStatus(rpcErrorMessage(walletInfo.error), true);
+    return;
+  }
+  if (walletInfo.result.descriptors !== true) {
+    setWalletStatus(`"${walletInfo.result.walletname}" is a legacy wallet. Create a descriptor wallet `
+      + "(or convert this one with migratewallet) to watch descriptors.", true);
+    return;
+  }
+  if (descInfo.error) {
+    setWalletStatus(rpcErrorMessage(descInfo.error), true);
+    return;
+  }
+  if (descInfo.result.hasprivatekeys) {
+    setWalletStatus("That descriptor contains private keys; paste the public version to watch it.", true);
+    return;
   }
+  const label = document.getElementById("watch-label").value.trim();
+  watchRequest = buildImportRequest(descInfo.result, label, timestamp);
+  document.getElementById("watch-request").textContent = JSON.stringify(watchRequest, null, 2);
+  const warning = rescanWarning(timestamp, Date.now());
+  const warningEl = document.getElementById("watch-rescan-warning");
+  warningEl.textContent = warning || "";
+  warningEl.hidden = !warning;
+  document.getElementById("watch-confirm").checked = false;
+  document.getElementById("watch-confirm-label").hidden = !warning;
+  document.getElementById("watch-progress").hidden = true;
+  document.getElementById("watch-review").hidden = false;
+  document.getElementById("wallet-status").hidden = true;
+  updateWatchImportEnabled();
 }
 
-function formatDuration(secs) {
-  const d = Math.floor(secs / 86400);
-  const h = Math.floor((secs % 86400) / 3600);
-  const m = Math.floor((secs % 3600) / 60);
-  const parts = [];
-  if (d) parts.push(d + "d");
-  if (h) parts.push(h + "h");
-  parts.push(m + "m");
-  return parts.join(" ");
+function updateWatchImportEnabled() {
+  const needsConfirm = !document.getElementById("watch-confirm-label").hidden;
+  document.getElementById("watch-import").disabled = !watchRequest
+    || (needsConfirm && !document.getElementById("watch-confirm").checked);
 }
 
-function formatBytes(bytes) {
-  if (bytes < 1e6) return (bytes / 1e3).toFixed(1) + " KB";
-  if (bytes < 1e9) return (bytes / 1e6).toFixed(1) + " MB";
-  return (bytes / 1e9).toFixed(2) + " GB";
+async function watchImportClicked() {
+  if (!watchRequest) return;
+  const request = watchRequest;
+  const btn = document.getElementById("watch-import");
+  const progress = document.getElementById("watch-progress");
+  btn.disabled = true;
+  progress.textContent = "Importing\u2026";
+  progress.hidden = false;
+  // importdescriptors only returns once any rescan is done; report its progress meanwhile.
+  let done = false;
+  const poll = async () => {
+    while (!done) {
+      await new Promise((resolve) => setTimeout(resolve, WATCH_SCAN_POLL_MS));
+      if (done) break;
+      const info = await rpcCall("getwalletinfo", [], "interactive");
+      const scanning = info.result && info.result.scanning;
+      if (!done && scanning && typeof scanning.progress === "number") {
+        progress.textContent = `Rescanning\u2026 ${(scanning.progress * 100).toFixed(1)}%`;
+      }
+    }
+  };
+  poll();
+  try {
+    const resp = await rpcCall("importdescriptors", [request], "interactive");
+    done = true;
+    progress.hidden = true;
+    if (resp.error) {
+      setWalletStatus(rpcErrorMessage(resp.error), true);
+      return;
+    }
+    const outcomes = parseImportResponse(resp.result);
+    const failed = outcomes.find((o) => !o.ok);
+    const warnings = outcomes.flatMap((o) => o.warnings);
+    if (failed) {
+      setWalletStatus("Import failed: " + (failed.error || "unknown error"), true);
+      return;
+    }
+    watchRequest = null;
+    document.getElementById("watch-review").hidden = true;
+    document.getElementById("watch-input").value = "";
+    const note = warnings.length ? " Warnings: " + warnings.join("; ") : "";
+    setWalletStatus("Imported " + request[0].desc + "." + note, false);
+    await refreshWalletTransactions();
+  } finally {
+    done = true;
+    updateWatchImportEnabled();
+  }
 }
 
-function renderChain(c, uptime) {
-  const dl = document.querySelector("#dash-chain dl");
-  const entries = [
-    ["Chain", c.chain],
-    ["Blocks", c.blocks.toLocaleString()],
-    ["Headers", c.headers.toLocaleString()],
-    ["Difficulty", Number(c.difficulty).toExponential(3)],
-    ["Progress", (c.verificationprogress * 100).toFixed(4) + "%"],
-    ["Pruned", c.pruned ? "yes" : "no"],
-    ["Disk size", formatBytes(c.size_on_disk)],
-  ];
-  if (uptime != null) entries.push(["Uptime", formatDuration(uptime)]);
-  updateDl(dl, entries);
+// --- Transaction composer ---
+
+// Passed to bitcoind as a string so no float rounding creeps in.
+function parseBtcAmountInput(raw) {
+  const s = String(raw).trim();
+  if (!/^\d+(\.\d{1,8})?$/.test(s) || Number(s) <= 0) {
+    return { ok: false, error: "Amount must be a positive number of BTC (up to 8 decimals)" };
+  }
+  return { ok: true, value: s };
 }
 
-function renderMempool(m) {
-  const dl = document.querySelector("#dash-mempool dl");
-  updateDl(dl, [
-    ["Transactions", m.size.toLocaleString()],
-    ["Size", formatBytes(m.bytes)],
-    ["Memory usage", formatBytes(m.usage)],
-    ["Min fee", m.mempoolminfee + " BTC/kvB"],
-  ]);
+function composeFundingOptions(feeRate, subtractFee) {
+  const options = {};
+  if (feeRate != null) options.fee_rate = feeRate;
+  if (subtractFee) options.subtractFeeFromOutputs = [0];
+  return options;
 }
 
-function renderNetwork(n) {
-  const dl = document.querySelector("#dash-network dl");
-  const entries = [
-    ["User agent", n.subversion],
-    ["Protocol", String(n.protocolversion)],
-    ["Connections", n.connections + " (" + n.connections_in + " in / " + n.connections_out + " out)"],
-  ];
-  if (n.localservicesnames) entries.push(["Services", n.localservicesnames.join(", ")]);
-  if (n.warnings) entries.push(["Warnings", n.warnings]);
-  updateDl(dl, entries);
+// A failed step ends the chain with bitcoind's own message and code.
+async function composeStep(method, params) {
+  const resp = await rpcCall(method, params, "interactive");
+  if (resp.error) throw new Error(`${method}: ${rpcErrorMessage(resp.error)}`);
+  return resp.result;
 }
 
-function renderNetTotals(t) {
-  const dl = document.querySelector("#dash-nettotals dl");
-  const entries = [
-    ["Received", formatBytes(t.totalbytesrecv)],
-    ["Sent", formatBytes(t.totalbytessent)],
-  ];
-  const up = t.uploadtarget;
-  if (up && up.target > 0) {
-    entries.push(["Upload target", formatBytes(up.target)]);
-    entries.push(["Left in cycle", formatBytes(up.bytes_left_in_cycle)]);
-    entries.push(["Serve historical", up.serve_historical_blocks ? "yes" : "no"]);
+// Both builders resolve to { hex, fee }, or { psbt } when the wallet could
+// not sign every input (watch-only or external signer).
+async function composeWithPsbt(outputs, options) {
+  const funded = await composeStep("walletcreatefundedpsbt", [[], outputs, 0, options]);
+  const processed = await composeStep("walletprocesspsbt", [funded.psbt]);
+  if (!processed.complete) return { psbt: processed.psbt };
+  const final = await composeStep("finalizepsbt", [processed.psbt]);
+  if (!final.complete || !final.hex) return { psbt: processed.psbt };
+  return { hex: final.hex, fee: funded.fee };
+}
+
+async function composeWithRawTx(outputs, options) {
+  const raw = await composeStep("createrawtransaction", [[], outputs]);
+  const funded = await composeStep("fundrawtransaction", [raw, options]);
+  const signed = await composeStep("signrawtransactionwithwallet", [funded.hex]);
+  if (!signed.complete) {
+    const errors = (signed.errors || []).map((e) => e.error).join("; ");
+    throw new Error("signrawtransactionwithwallet could not sign every input" + (errors ? ": " + errors : ""));
   }
-  updateDl(dl, entries);
+  return { hex: signed.hex, fee: funded.fee };
 }
 
-function renderPeers(peers) {
-  lastPeers = peers;
-  peerById = new Map(peers.map((p) => [p.id, p]));
-  const tbody = document.querySelector("#dash-peer-table tbody");
-  const seen = new Set();
-  for (const p of peers) {
-    seen.add(p.id);
-    let row = peerRows.get(p.id);
-    if (!row) {
-      row = document.createElement("tr");
-      row.className = "peer-row";
-      row.dataset.peerId = String(p.id);
-      row.appendChild(document.createElement("td"));
-      row.appendChild(document.createElement("td"));
-      row.appendChild(document.createElement("td"));
-      row.appendChild(document.createElement("td"));
-      peerRows.set(p.id, row);
-    }
-    const direction = p.inbound ? "in" : "out";
-    const ping = p.pingtime != null ? (p.pingtime * 1000).toFixed(0) + " ms" : "–";
-    if (row.children[0].textContent !== p.addr) row.children[0].textContent = p.addr;
-    if (row.children[1].textContent !== p.subver) row.children[1].textContent = p.subver;
-    if (row.children[2].textContent !== direction) row.children[2].textContent = direction;
-    row.children[2].className = p.inbound ? "peer-in" : "peer-out";
-    if (row.children[3].textContent !== ping) row.children[3].textContent = ping;
-    tbody.appendChild(row);
+async function composeSubmitted(ev) {
+  ev.preventDefault();
+  discardComposed();
+  const address = document.getElementById("compose-address").value.trim();
+  const amount = parseBtcAmountInput(document.getElementById("compose-amount").value);
+  const feeRate = parseFeeRateInput(document.getElementById("compose-feerate").value);
+  const problem = !address ? "Enter a destination address" : !amount.ok ? amount.error : !feeRate.ok ? feeRate.error : null;
+  if (problem) {
+    setWalletStatus(problem, true);
+    return;
   }
-  for (const [id, row] of peerRows) {
-    if (seen.has(id)) continue;
-    row.remove();
-    peerRows.delete(id);
+  const outputs = [{ [address]: amount.value }];
+  const options = composeFundingOptions(feeRate.value, document.getElementById("compose-subtract-fee").checked);
+  const raw = document.getElementById("compose-mode").value === "raw";
+  const btn = document.getElementById("compose-build");
+  btn.disabled = true;
+  try {
+    const built = raw ? await composeWithRawTx(outputs, options) : await composeWithPsbt(outputs, options);
+    if (built.psbt) {
+      const area = document.getElementById("compose-psbt");
+      area.value = built.psbt;
+      area.hidden = false;
+      setWalletStatus("The wallet could not sign every input. Sign this PSBT externally, then finalizepsbt and sendrawtransaction.", false);
+      return;
+    }
+    const decoded = await composeStep("decoderawtransaction", [built.hex]);
+    composeState = { hex: built.hex, address };
+    renderComposeReview(decoded, built.fee, address);
+    document.getElementById("wallet-status").hidden = true;
+  } catch (e) {
+    setWalletStatus(e.message, true);
+  } finally {
+    btn.disabled = false;
   }
 }
 
-function initPeerTableClick() {
-  const tbody = document.querySelector("#dash-peer-table tbody");
-  tbody.addEventListener("click", (ev) => {
-    const row = ev.target.closest(".peer-row");
-    if (!row) return;
-    const id = Number(row.dataset.peerId);
-    const peer = peerById.get(id) || lastPeers.find((p) => p.id === id);
-    if (peer) showPeerDetail(peer);
-  });
+function outputAddress(vout) {
+  const spk = vout.scriptPubKey || {};
+  return spk.address || (spk.addresses || []).join(", ") || spk.type || "?";
 }
 
-function showPeerDetail(peer) {
-  document.getElementById("dashboard").hidden = true;
-  stopDashboardPolling();
-  document.getElementById("method-view").hidden = true;
-  document.getElementById("peer-view").hidden = false;
-  document.getElementById("peer-view-title").textContent = peer.addr;
-  const dl = document.getElementById("peer-view-dl");
-  let html = "";
-  for (const [key, val] of Object.entries(peer)) {
-    const display = typeof val === "object" ? JSON.stringify(val, null, 2) : String(val);
-    html += dd(key, display);
+function renderComposeReview(decoded, feeBtc, address) {
+  const feeSats = Number(btcToSats(feeBtc));
+  const rate = decoded.vsize ? ` (\u2248${(feeSats / decoded.vsize).toFixed(1)} sat/vB)` : "";
+  let html = dd("Txid", decoded.txid);
+  html += dd("Size", `${decoded.vsize} vB`);
+  html += dd("Fee", `${feeSats.toLocaleString()} sat${rate}`);
+  html += dd("Inputs", decoded.vin.length);
+  for (const vout of decoded.vout) {
+    const to = outputAddress(vout);
+    html += dd(to === address ? "Pays" : "Change", `${Number(vout.value).toFixed(8)} BTC to ${to}`);
   }
-  dl.innerHTML = html;
+  document.getElementById("compose-summary").innerHTML = html;
+  document.getElementById("compose-decoded").textContent = JSON.stringify(decoded, null, 2);
+  document.getElementById("compose-review").hidden = false;
 }
 
-async function showZmqRpcResult(title, description, run) {
-  document.getElementById("dashboard").hidden = true;
-  stopDashboardPolling();
-  document.getElementById("peer-view").hidden = true;
-  document.getElementById("method-view").hidden = false;
-  document.querySelectorAll("#method-list .method.active").forEach((el) => el.classList.remove("active"));
-  currentMethod = null;
-
-  document.getElementById("execute").hidden = true;
-  document.getElementById("method-name").textContent = title;
-  document.getElementById("method-desc").textContent = description;
-  document.getElementById("param-form").innerHTML = "";
-  const result = document.getElementById("result");
-  result.classList.remove("error");
-  result.classList.add("visible");
-  result.textContent = "Loading...";
+// Any edit to the form throws away the signed transaction, so what gets
+// broadcast is always what was reviewed.
+function discardComposed() {
+  composeState = null;
+  document.getElementById("compose-review").hidden = true;
+  document.getElementById("compose-psbt").hidden = true;
+}
 
+async function composeBroadcastClicked() {
+  if (!composeState) return;
+  const { hex } = composeState;
+  const btn = document.getElementById("compose-broadcast");
+  btn.disabled = true;
   try {
-    const resp = await run();
-    result.classList.remove("error");
-    if (resp && resp.error) {
-      result.classList.add("error");
-      result.textContent = JSON.stringify(resp.error, null, 2);
-    } else {
-      result.textContent = JSON.stringify(resp && resp.result !== undefined ? resp.result : resp, null, 2);
+    const resp = await rpcCall("sendrawtransaction", [hex], "interactive");
+    if (resp.error) {
+      setWalletStatus(`sendrawtransaction: ${rpcErrorMessage(resp.error)}`, true);
+      return;
     }
-  } catch (e) {
-    result.classList.add("error");
-    result.textContent = String(e);
+    discardComposed();
+    document.getElementById("compose-form").reset();
+    setWalletStatus(`Broadcast ${resp.result}.`, false);
+    await refreshWalletTransactions();
+  } finally {
+    btn.disabled = false;
   }
 }
 
+function rpcErrorMessage(err) {
+  if (!err) return "";
+  if (typeof err === "string") return err;
+  return err.code != null ? `${err.message} (code ${err.code})` : String(err.message || JSON.stringify(err));
+}
+
 // --- ZMQ feed ---
 
 let zmqTimer = null;
@@ -790,18 +6196,46 @@ async function pollZmqLoop(generation) {
 async function fetchZmq() {
   try {
     const waitMs = zmqConnected ? ZMQ_LONG_POLL_WAIT_MS : 0;
-    const resp = await fetch(`/zmq/messages?since=${encodeURIComponent(String(lastZmqCursor))}&wait_ms=${waitMs}`);
+    // Only the newest ZMQ_RECENT_LIMIT messages would be kept anyway.
+    const resp = await fetch(
+      `/zmq/messages?since=${encodeURIComponent(String(lastZmqCursor))}&wait_ms=${waitMs}&limit=${ZMQ_RECENT_LIMIT}`,
+    );
     const data = await resp.json();
     if (typeof data.cursor === "number" && Number.isFinite(data.cursor)) {
       lastZmqCursor = data.cursor;
     }
-    if (data.truncated) {
+    // Either way the feed would have a gap in it.
+    if (data.truncated || data.has_more) {
       clearZmqFeed();
       clearPendingZmqRender();
     }
+    // A new ZMQ address starts with every topic shown again.
+    if (typeof data.address === "string" && data.address !== zmqFilterAddress) {
+      zmqFilterAddress = data.address;
+      if (zmqTopicFilter.size > 0) {
+        zmqTopicFilter = new Set();
+        rebuildZmqFeed();
+      }
+    }
+    if (data.topics) {
+      zmqTopicTotals = data.topics;
+      renderZmqBandwidth(data.topics);
+    }
+    renderZmqTopicFilter();
+    renderZmqStatus(data);
     if (Array.isArray(data.messages) && data.messages.length > 0) {
       maybeCelebrateHashblock(data.messages);
-      queueZmqRender(data.messages);
+      for (const msg of data.messages) {
+        if ((msg.topic === "hashblock" || msg.topic === "rawblock") && msg.event_hash) {
+          rememberBlockHashes([msg.event_hash]);
+          loadBlockSummary(msg.event_hash);
+        }
+        if ((msg.topic === "hashtx" || msg.topic === "rawtx") && msg.event_hash) rememberTxids([msg.event_hash]);
+      }
+      zmqRecentMessages = zmqRecentMessages.concat(data.messages).slice(-ZMQ_RECENT_LIMIT);
+      const shown = data.messages.filter((msg) => zmqTopicShown(msg.topic));
+      if (shown.length > 0) queueZmqRender(shown);
+      if (dashboardVisible()) pendingBlockLatency = newestBlockLatencyTag(pendingBlockLatency, data.messages);
       queueDashboardPartRefresh(deriveDashboardParts(data.messages));
     }
     if (!data.connected) {
@@ -816,6 +6250,76 @@ async function fetchZmq() {
   }
 }
 
+// Per-minute rates read better than per-second for hash topics, which see
+// a few hundred bytes at a time.
+function renderZmqBandwidth(topics) {
+  const el = document.getElementById("dash-zmq-bandwidth");
+  const entries = Object.entries(topics);
+  el.hidden = entries.length === 0;
+  el.textContent = entries
+    .map(([topic, t]) => `${topic}: ${formatBytes(t.bytes_per_sec * 60)}/min`)
+    .join(" \u00b7 ");
+  el.title = entries
+    .map(([topic, t]) => `${topic}: ${t.messages.toLocaleString()} messages, ${formatBytes(t.bytes)} since connecting`)
+    .join("\n");
+}
+
+// --- ZMQ topic filter ---
+
+// An empty filter shows every topic.
+function zmqTopicShown(topic) {
+  return zmqTopicFilter.size === 0 || zmqTopicFilter.has(topic);
+}
+
+function initZmqTopicFilter() {
+  const row = document.getElementById("dash-zmq-topics");
+  for (const topic of ZMQ_FILTER_TOPICS) {
+    const button = document.createElement("button");
+    button.type = "button";
+    button.className = "zmq-topic-toggle " + zmqTopicClass(topic);
+    button.dataset.topic = topic;
+    button.textContent = topic;
+    button.addEventListener("click", () => zmqTopicFilterToggled(topic));
+    row.insertBefore(button, row.lastElementChild);
+  }
+  renderZmqTopicFilter();
+}
+
+// Counts come from the subscriber's per-topic totals, so they cover the
+// whole connection rather than the rows still on screen.
+function renderZmqTopicFilter() {
+  for (const button of document.querySelectorAll("#dash-zmq-topics .zmq-topic-toggle")) {
+    button.setAttribute("aria-pressed", String(zmqTopicFilter.has(button.dataset.topic)));
+  }
+  const seen = Object.entries(zmqTopicTotals)
+    .filter(([topic]) => zmqTopicShown(topic))
+    .reduce((sum, [, t]) => sum + (t.messages || 0), 0);
+  const filtered = zmqTopicFilter.size > 0 ? " matching" : "";
+  document.getElementById("dash-zmq-topic-count").textContent =
+    `${seen.toLocaleString()} event${seen === 1 ? "" : "s"}${filtered}`;
+}
+
+function zmqTopicFilterToggled(topic) {
+  if (zmqTopicFilter.has(topic)) zmqTopicFilter.delete(topic);
+  else zmqTopicFilter.add(topic);
+  renderZmqTopicFilter();
+  clearPendingZmqRender();
+  rebuildZmqFeed();
+}
+
+// Redraws the feed from the recent messages after the filter changed.
+function rebuildZmqFeed() {
+  const feed = document.getElementById("dash-zmq-feed");
+  feed.textContent = "";
+  zmqMessageLookup = new Map();
+  const frag = document.createDocumentFragment();
+  for (const msg of zmqRecentMessages.filter((m) => zmqTopicShown(m.topic)).slice(-ZMQ_FEED_MAX_ROWS)) {
+    frag.appendChild(buildZmqRow(msg));
+  }
+  feed.appendChild(frag);
+  feed.scrollTop = feed.scrollHeight;
+}
+
 function queueZmqRender(messages) {
   for (const msg of messages) pendingZmqMessages.push(msg);
   if (zmqRenderTimer) return;
@@ -928,41 +6432,125 @@ function formatUnixTime(secs) {
 }
 
 function zmqTopicClass(topic) {
-  if (topic === "hashblock") return "zmq-topic-block";
-  if (topic === "hashtx") return "zmq-topic-tx";
+  if (topic === "hashblock" || topic === "rawblock") return "zmq-topic-block";
+  if (topic === "hashtx" || topic === "rawtx") return "zmq-topic-tx";
   return "zmq-topic-meta";
 }
 
+// `cacheKey` names what the action fetches, so the detail card can reuse a
+// reply when another event points at the same block or transaction.
 function zmqRowAction(msg) {
   const hash = msg.event_hash;
   if (msg.topic === "hashblock" && hash) {
     return {
       title: `ZMQ hashblock ${hash}`,
       description: "Triggered by ZMQ hashblock. RPC: getblockheader <hash> true",
-      run: () => rpcCall("getblockheader", [hash, true]),
+      cacheKey: `block:${hash}`,
+      run: () => rpcCall("getblockheader", [hash, true], "interactive"),
     };
   }
   if (msg.topic === "hashtx" && hash) {
     return {
       title: `ZMQ hashtx ${hash}`,
       description: "Triggered by ZMQ hashtx. RPC: getrawtransaction <hash> 1",
-      run: () => rpcCall("getrawtransaction", [hash, 1]),
+      cacheKey: `tx:${hash}`,
+      run: () => rpcCall("getrawtransaction", [hash, 1], "interactive"),
+    };
+  }
+  if (msg.topic === "rawblock" && hash) {
+    return {
+      title: `ZMQ rawblock ${hash}`,
+      description: "Triggered by ZMQ rawblock. RPC: getblockheader <hash> true",
+      cacheKey: `block:${hash}`,
+      run: () => rpcCall("getblockheader", [hash, true], "interactive"),
+    };
+  }
+  if (msg.topic === "rawtx" && !msg.oversized && !msg.backlogged && msg.cursor != null) {
+    return {
+      title: `ZMQ rawtx ${hash || ""}`.trim(),
+      description: "Triggered by ZMQ rawtx. Decoded with decoderawtransaction, or locally when the node is unreachable.",
+      cacheKey: `rawtx:${msg.cursor}`,
+      run: () => fetch(`/zmq/decode-rawtx?cursor=${encodeURIComponent(String(msg.cursor))}`).then((r) => r.json()),
     };
   }
   return null;
 }
 
-function handleZmqRowClick(id) {
+// Only successful replies are cached: a transaction missing now can reach
+// the mempool again or be found by txindex once mined.
+async function fetchZmqDetail(action) {
+  const cached = zmqDetailCache.get(action.cacheKey);
+  if (cached) return cached;
+  let resp;
+  try {
+    resp = await action.run();
+  } catch (e) {
+    return { result: null, error: { message: String(e) } };
+  }
+  if (resp && !resp.error) {
+    zmqDetailCache.set(action.cacheKey, resp);
+    if (zmqDetailCache.size > ZMQ_DETAIL_CACHE_LIMIT) {
+      zmqDetailCache.delete(zmqDetailCache.keys().next().value);
+    }
+  }
+  return resp;
+}
+
+function zmqDetailErrorText(msg, error) {
+  // -5 is RPC_INVALID_ADDRESS_OR_KEY: "No such mempool or blockchain transaction".
+  if (error.code === -5 && (msg.topic === "hashtx" || msg.topic === "rawtx")) {
+    return "Transaction is no longer in the mempool: it was mined, replaced or evicted. "
+      + "Confirmed transactions can only be looked up by hash with -txindex.";
+  }
+  if (error.code === -5) return "Unknown block: it isn't in this node's block index.";
+  return error.message || JSON.stringify(error);
+}
+
+function showZmqDetail(title, text, isError) {
+  document.getElementById("dash-zmq-detail").hidden = false;
+  document.getElementById("dash-zmq-detail-title").textContent = title;
+  const body = document.getElementById("dash-zmq-detail-body");
+  body.textContent = text;
+  body.classList.toggle("error", isError);
+}
+
+async function handleZmqRowClick(id) {
   const msg = zmqMessageLookup.get(id);
   if (!msg) return;
   const action = zmqRowAction(msg);
   if (!action) return;
-  showZmqRpcResult(action.title, action.description, action.run);
+  zmqDetailAction = action;
+  showZmqDetail(action.title, "Loading\u2026", false);
+  const resp = await fetchZmqDetail(action);
+  if (zmqDetailAction !== action) return;
+  if (resp && resp.error) {
+    showZmqDetail(action.title, zmqDetailErrorText(msg, resp.error), true);
+  } else {
+    const value = resp && resp.result !== undefined ? resp.result : resp;
+    showZmqDetail(action.title, JSON.stringify(value, null, 2), false);
+  }
+}
+
+function initZmqDetail() {
+  document.getElementById("dash-zmq-detail-open").addEventListener("click", () => {
+    const action = zmqDetailAction;
+    if (action) showZmqRpcResult(action.title, action.description, () => fetchZmqDetail(action));
+  });
+  document.getElementById("dash-zmq-detail-close").addEventListener("click", () => {
+    zmqDetailAction = null;
+    document.getElementById("dash-zmq-detail").hidden = true;
+  });
 }
 
 function initZmqFeedClick() {
   const feed = document.getElementById("dash-zmq-feed");
   feed.addEventListener("click", (ev) => {
+    const copy = ev.target.closest(".copy-btn");
+    if (copy) {
+      const msg = zmqMessageLookup.get(copy.closest(".zmq-row").dataset.zmqId);
+      if (msg && msg.event_hash) copyWithFlash(copy, msg.event_hash);
+      return;
+    }
     const row = ev.target.closest(".zmq-row.zmq-clickable");
     if (!row) return;
     handleZmqRowClick(row.dataset.zmqId);
@@ -978,7 +6566,11 @@ function buildZmqRow(msg) {
   zmqMessageLookup.set(rowId, msg);
 
   let dataHtml;
-  if (msg.event_hash) {
+  if (msg.oversized) {
+    dataHtml = '<span class="zmq-oversized">oversized, ' + esc(formatBytes(msg.body_size)) + " body dropped</span>";
+  } else if (msg.backlogged) {
+    dataHtml = '<span class="zmq-oversized">arrived faster than decoded, ' + esc(formatBytes(msg.body_size)) + " body dropped</span>";
+  } else if (msg.event_hash) {
     dataHtml = esc(msg.event_hash);
   } else {
     dataHtml = esc(msg.body_hex);
@@ -990,7 +6582,8 @@ function buildZmqRow(msg) {
   row.innerHTML =
     '<span class="zmq-time">' + esc(time) + '</span>'
     + '<span class="zmq-topic ' + topicCls + '">' + esc(topic) + '</span>'
-    + '<span class="zmq-data">' + dataHtml + "</span>";
+    + '<span class="zmq-data">' + dataHtml + "</span>"
+    + (msg.event_hash ? '<button class="copy-btn" type="button" title="Copy hash">\u29C9</button>' : "");
   return row;
 }
 
@@ -999,13 +6592,34 @@ function isZmqFeedNearBottom(feed) {
   return gap <= 24;
 }
 
+// Shown while the subscriber is backing off after a failed connection.
+function renderZmqStatus(data) {
+  const el = document.getElementById("dash-zmq-status");
+  const attempts = data.reconnect_attempts || 0;
+  el.hidden = data.connected || attempts === 0;
+  if (el.hidden) return;
+  const error = data.last_error ? `: ${data.last_error}` : "";
+  setStatus(document.getElementById("dash-zmq-status-text"), "warn",
+    `Reconnecting (attempt ${attempts})\u2026${error}`, { word: true });
+}
+
+async function zmqReconnectClicked() {
+  const btn = document.getElementById("dash-zmq-reconnect");
+  btn.disabled = true;
+  try {
+    await fetch("/zmq/reconnect", { method: "POST" });
+  } catch (_) {}
+  btn.disabled = false;
+}
+
 function renderZmq(data) {
   const section = document.getElementById("dash-zmq");
   const feed = document.getElementById("dash-zmq-feed");
   if (!data.connected) {
-    section.hidden = true;
+    section.hidden = !(data.reconnect_attempts > 0);
     feed.textContent = "";
     zmqMessageLookup = new Map();
+    zmqRecentMessages = [];
     return;
   }
   if (!Array.isArray(data.messages) || data.messages.length === 0) {
@@ -1049,6 +6663,7 @@ function clearZmqFeed() {
   section.hidden = true;
   feed.textContent = "";
   zmqMessageLookup = new Map();
+  zmqRecentMessages = [];
 }
 
 // --- Music player ---
@@ -1061,6 +6676,19 @@ function initMusic() {
   document.getElementById("music-volume").addEventListener("input", (e) => {
     fetch("/music/volume?" + (e.target.value / 100));
   });
+  document.getElementById("music-pan-width").addEventListener("input", (e) => {
+    fetch("/music/panwidth?" + (e.target.value / 100));
+  });
+  const order = document.getElementById("music-order");
+  const savedOrder = localStorage.getItem("ui-music-order");
+  if (savedOrder && savedOrder !== "shuffle") {
+    order.value = savedOrder;
+    fetch("/music/order?" + encodeURIComponent(savedOrder));
+  }
+  order.addEventListener("change", () => {
+    localStorage.setItem("ui-music-order", order.value);
+    fetch("/music/order?" + encodeURIComponent(order.value));
+  });
   pollMusic();
   setInterval(pollMusic, 2000);
 }
@@ -1078,6 +6706,10 @@ async function pollMusic() {
     document.getElementById("music-play").textContent = s.playing ? "\u23F8" : "\u25B6";
     document.getElementById("music-mute").textContent = s.muted ? "\uD83D\uDD07" : "\uD83D\uDD0A";
     document.getElementById("music-volume").value = Math.round(s.volume * 100);
+    if (typeof s.pan_width === "number") {
+      document.getElementById("music-pan-width").value = Math.round(s.pan_width * 100);
+    }
+    if (s.order) document.getElementById("music-order").value = s.order;
   } catch (_) {}
 }
 
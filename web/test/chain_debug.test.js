@@ -0,0 +1,55 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+const HASH_A = "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206";
+const HASH_B = "3bfce6e4c9d8a1f0b2e7d6c5a4b3928170f6e5d4c3b2a19080706f5e4d3c2b1a";
+const HASH_C = "7d3e1c5b9a8f7e6d5c4b3a291807f6e5d4c3b2a1908f7e6d5c4b3a2918070605";
+
+test("invalidateblock is only offered on regtest and signet", () => {
+  assert.equal(app.chainDebugAllowed("regtest"), true);
+  assert.equal(app.chainDebugAllowed("signet"), true);
+  assert.equal(app.chainDebugAllowed("main"), false);
+  assert.equal(app.chainDebugAllowed("test"), false);
+  assert.equal(app.chainDebugAllowed("testnet4"), false);
+  assert.equal(app.chainDebugAllowed(undefined), false);
+});
+
+test("a block target is a hash or a height", () => {
+  assert.deepEqual(app.parseBlockTarget(` ${HASH_A.toUpperCase()} `), { ok: true, hash: HASH_A });
+  assert.deepEqual(app.parseBlockTarget("120"), { ok: true, height: 120 });
+  assert.equal(app.parseBlockTarget("-1").ok, false);
+  assert.equal(app.parseBlockTarget(HASH_A.slice(1)).ok, false);
+});
+
+test("reconsidered and forgotten blocks drop off the tracked list", () => {
+  const tracked = [
+    { hash: HASH_A, height: 101 },
+    { hash: HASH_B, height: 102 },
+    { hash: HASH_C, height: 103 },
+  ];
+  // One getblockheader reply per tracked block, in order.
+  const headers = [
+    { result: { hash: HASH_A, height: 101, confirmations: -1 } },
+    { result: { hash: HASH_B, height: 102, confirmations: 3 } },
+    { error: { code: -5, message: "Block not found" } },
+  ];
+  assert.deepEqual(app.reconcileInvalidatedBlocks(tracked, headers), [tracked[0]]);
+});
+
+test("a failed lookup other than unknown block keeps the entry", () => {
+  const tracked = [{ hash: HASH_A, height: 101 }];
+  const headers = [{ error: { code: -28, message: "Loading block index…" } }];
+  assert.deepEqual(app.reconcileInvalidatedBlocks(tracked, headers), tracked);
+});
+
+test("invalidating a block again moves it to the end without duplicating it", () => {
+  const tracked = [{ hash: HASH_A, height: 101 }, { hash: HASH_B, height: 102 }];
+  assert.deepEqual(app.trackInvalidatedBlock(tracked, { hash: HASH_A, height: 101 }), [
+    { hash: HASH_B, height: 102 },
+    { hash: HASH_A, height: 101 },
+  ]);
+  assert.equal(app.trackInvalidatedBlock([], { hash: HASH_C, height: 103 }).length, 1);
+});
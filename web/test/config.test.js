@@ -0,0 +1,26 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+function loadSaved(cfg) {
+  app.document.getElementById("cfg-timeout").value = "30";
+  app.localStorage.setItem("rpc-config", JSON.stringify(cfg));
+  app.loadConfig();
+  return app.document.getElementById("cfg-timeout").value;
+}
+
+test("a saved timeout is restored", () => {
+  assert.equal(loadSaved({ url: "http://127.0.0.1:8332", timeout_secs: 90 }), 90);
+});
+
+test("a saved null timeout clears the field instead of showing the default", () => {
+  assert.equal(loadSaved({ url: "http://127.0.0.1:8332", timeout_secs: null }), "");
+  // The next save keeps it cleared.
+  assert.equal(app.parseTimeoutInput(app.document.getElementById("cfg-timeout").value), null);
+});
+
+test("configs saved before the timeout existed keep the default", () => {
+  assert.equal(loadSaved({ url: "http://127.0.0.1:8332" }), "30");
+});
@@ -0,0 +1,49 @@
+// Loads web/app.js into this process with just enough of a browser for its
+// top-level declarations, without calling init(). The pure helpers then
+// become globals the tests can call directly, in the same realm as the
+// test's own objects so deepStrictEqual works on what they return.
+const fs = require("node:fs");
+const path = require("node:path");
+const vm = require("node:vm");
+
+function element() {
+  return new Proxy({}, {
+    get(target, prop) {
+      if (prop in target) return target[prop];
+      if (prop === "classList" || prop === "style" || prop === "dataset") return (target[prop] = {});
+      return () => element();
+    },
+    set(target, prop, value) {
+      target[prop] = value;
+      return true;
+    },
+  });
+}
+
+function loadApp() {
+  const store = new Map();
+  globalThis.localStorage = {
+    getItem: (k) => (store.has(k) ? store.get(k) : null),
+    setItem: (k, v) => store.set(k, String(v)),
+    removeItem: (k) => store.delete(k),
+  };
+  const elements = new Map();
+  globalThis.document = element();
+  // One element per id, so a test can read back what the app set on it.
+  globalThis.document.getElementById = (id) => {
+    if (!elements.has(id)) elements.set(id, element());
+    return elements.get(id);
+  };
+  globalThis.window = globalThis;
+  const source = fs.readFileSync(path.join(__dirname, "..", "app.js"), "utf8");
+  // The last line starts the app; everything above it only declares.
+  vm.runInThisContext(source.replace(/^init\(\);\s*$/m, ""), { filename: "app.js" });
+  return globalThis;
+}
+
+// Top-level let/const bindings are not properties of globalThis.
+function state(expr) {
+  return vm.runInThisContext(expr);
+}
+
+module.exports = { loadApp, state };
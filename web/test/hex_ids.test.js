@@ -0,0 +1,48 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp, state } = require("./harness");
+
+const app = loadApp();
+
+const GENESIS = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+const TXID = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+test("hex ids are partial until 64 characters", () => {
+  assert.equal(app.hexIdStatus("  ", false), "empty");
+  assert.equal(app.hexIdStatus("00000000", false), "partial");
+  assert.equal(app.hexIdStatus(` ${GENESIS.toUpperCase()} `, false), "valid");
+  assert.equal(app.hexIdStatus(GENESIS + "0", false), "invalid");
+  assert.equal(app.hexIdStatus("0000zz", false), "invalid");
+});
+
+test("a plain number is a height only where heights are allowed", () => {
+  assert.equal(app.hexIdStatus("840000", true), "valid");
+  assert.equal(app.hexIdStatus("840000", false), "partial");
+  assert.equal(app.hexIdStatus("-1", true), "invalid");
+  // 64 digits is a hash, not a height.
+  assert.equal(app.hexIdStatus("1".repeat(64), true), "valid");
+});
+
+test("ids are normalized to trimmed lower case", () => {
+  assert.equal(app.normalizeHexId(` ${TXID.toUpperCase()}\n`), TXID);
+});
+
+test("remembered ids are newest first, deduplicated and bounded", () => {
+  const a = "a".repeat(64);
+  const b = "b".repeat(64);
+  const c = "c".repeat(64);
+  assert.deepEqual(app.rememberIds([a, b], [c, b.toUpperCase()], 10), [c, b, a]);
+  assert.deepEqual(app.rememberIds([a, b], [c], 2), [c, a]);
+  const list = [a];
+  assert.equal(app.rememberIds(list, ["not-a-txid", 42, null], 10), list);
+});
+
+test("suggestions need a few characters and stop at the limit", () => {
+  const min = state("HEX_ID_SUGGEST_MIN");
+  const ids = [GENESIS, "000000000019d6" + "f".repeat(50), "0000000000000000" + "e".repeat(48), TXID];
+  assert.deepEqual(app.matchIdPrefix(ids, "0".repeat(min - 1), 6), []);
+  assert.deepEqual(app.matchIdPrefix(ids, "000000000019D6", 6), ids.slice(0, 2));
+  assert.deepEqual(app.matchIdPrefix(ids, "0000", 2), ids.slice(0, 2));
+  // A complete id needs no suggestion.
+  assert.deepEqual(app.matchIdPrefix(ids, GENESIS, 6), []);
+});
@@ -0,0 +1,45 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+// Trimmed getmempoolinfo replies. Formatted heights and counts stay below
+// 1000 so the output does not depend on the locale's digit grouping.
+const info = (size, bytes, mempoolminfee) => ({ loaded: true, size, bytes, usage: bytes * 4, mempoolminfee });
+
+test("a snapshot keeps what the per-block estimate needs", () => {
+  assert.deepEqual(app.mempoolSnapshot(info(3_000, 1_200_000, 0.00001), 200), {
+    height: 200,
+    size: 3_000,
+    bytes: 1_200_000,
+    minFee: 0.00001,
+  });
+});
+
+test("a delta needs the height to have increased", () => {
+  const before = app.mempoolSnapshot(info(3_000, 1_200_000, 0.00001), 200);
+  assert.equal(app.mempoolDelta(null, before), null);
+  assert.equal(app.mempoolDelta(before, app.mempoolSnapshot(info(3_100, 1_250_000, 0.00001), 200)), null);
+  // A reorg to a lower tip is not a block being mined.
+  assert.equal(app.mempoolDelta(before, app.mempoolSnapshot(info(3_100, 1_250_000, 0.00001), 199)), null);
+  assert.deepEqual(app.mempoolDelta(before, app.mempoolSnapshot(info(900, 250_000, 0.000012), 202)), {
+    fromHeight: 201,
+    toHeight: 202,
+    txDelta: -2_100,
+    bytesDelta: -950_000,
+    minFeeBefore: 0.00001,
+    minFeeAfter: 0.000012,
+  });
+});
+
+test("deltas read as cleared or grown, with the floor only when it moved", () => {
+  assert.equal(app.formatMempoolDelta({
+    fromHeight: 201, toHeight: 201, txDelta: -850, bytesDelta: -1_460_000,
+    minFeeBefore: 0.00001, minFeeAfter: 0.00001,
+  }), "block 201 cleared ~1.5 MvB / 850 txs");
+  assert.equal(app.formatMempoolDelta({
+    fromHeight: 201, toHeight: 203, txDelta: 40, bytesDelta: 12_400,
+    minFeeBefore: 0.00001, minFeeAfter: 0.000012,
+  }), "blocks 201–203 grew ~12 kvB / 40 txs · floor 1.00→1.20 sat/vB");
+});
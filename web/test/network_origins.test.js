@@ -0,0 +1,69 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+test("IPv4 parsing rejects out-of-range octets", () => {
+  assert.deepEqual(app.parseIpv4("203.0.113.9"), [203, 0, 113, 9]);
+  assert.equal(app.parseIpv4("203.0.113.256"), null);
+  assert.equal(app.parseIpv4("203.0.113"), null);
+});
+
+test("IPv6 parsing expands :: and embedded IPv4", () => {
+  assert.deepEqual(app.parseIpv6("2001:DB8::1"), [0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]);
+  assert.deepEqual(app.parseIpv6("::ffff:203.0.113.9"), [0, 0, 0, 0, 0, 0xffff, 0xcb00, 0x7109]);
+  assert.deepEqual(app.parseIpv6("1:2:3:4:5:6:7:8"), [1, 2, 3, 4, 5, 6, 7, 8]);
+  assert.equal(app.parseIpv6("1::2::3"), null);
+  assert.equal(app.parseIpv6("1:2:3:4:5:6:7:8::"), null);
+  assert.equal(app.parseIpv6("1:2:3"), null);
+  assert.equal(app.parseIpv6("2001:db8::g"), null);
+});
+
+test("private and shared IPv4 ranges are not public", () => {
+  for (const host of ["0.1.2.3", "10.0.0.1", "127.0.0.1", "100.64.0.1", "169.254.1.1", "172.16.0.1", "172.31.255.255", "192.168.1.1"]) {
+    assert.equal(app.isPublicIpv4(app.parseIpv4(host)), false, host);
+  }
+  for (const host of ["100.128.0.1", "172.32.0.1", "203.0.113.9"]) {
+    assert.equal(app.isPublicIpv4(app.parseIpv4(host)), true, host);
+  }
+});
+
+test("clearnet peers map to a /16 or /32 prefix", () => {
+  assert.equal(app.addressPrefix("203.0.113.9:8333"), "203.0.0.0/16");
+  assert.equal(app.addressPrefix("[2001:db8:aa::1]:8333"), "2001:db8::/32");
+  assert.equal(app.addressPrefix("[::ffff:198.51.100.7]:8333"), "198.51.0.0/16");
+});
+
+test("overlay, local and malformed addresses have no prefix", () => {
+  for (const addr of [
+    "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion:8333",
+    "ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p:0",
+    "[fc32:17ea:e415:c3bf:9808:149d:b5a2:c9aa]:8333",
+    "[fe80::1]:8333",
+    "[::1]:8333",
+    "[::ffff:192.168.1.1]:8333",
+    "192.168.1.20:8333",
+    "localhost:8333",
+  ]) {
+    assert.equal(app.addressPrefix(addr), null, addr);
+  }
+});
+
+test("peers group by prefix, largest and most outbound first", () => {
+  const summary = app.groupPeerPrefixes([
+    { addr: "203.0.113.9:8333", inbound: false },
+    { addr: "203.0.7.1:8333", inbound: false },
+    { addr: "198.51.100.7:8333", inbound: true },
+    { addr: "198.51.3.3:8333", inbound: false },
+    { addr: "[2001:db8::1]:8333", inbound: true },
+    { addr: "abcdefghijklmnop.onion:8333", inbound: false },
+  ]);
+  assert.equal(summary.total, 6);
+  assert.equal(summary.clearnet, 5);
+  assert.deepEqual(summary.groups, [
+    { prefix: "203.0.0.0/16", total: 2, outbound: 2 },
+    { prefix: "198.51.0.0/16", total: 2, outbound: 1 },
+    { prefix: "2001:db8::/32", total: 1, outbound: 0 },
+  ]);
+});
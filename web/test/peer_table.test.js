@@ -0,0 +1,51 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp, state } = require("./harness");
+
+const app = loadApp();
+
+// Trimmed getpeerinfo entries.
+const PEERS = [
+  { id: 4, addr: "203.0.113.9:8333", subver: "/Satoshi:27.0.0/", inbound: true, permissions: ["noban"], pingtime: 0.21, bytessent: 900, bytesrecv: 50, minfeefilter: 0.00001 },
+  { id: 1, addr: "198.51.100.7:8333", subver: "/Satoshi:26.1.0/", inbound: false, permissions: [], pingtime: 0.05, bytessent: 100, bytesrecv: 700, minfeefilter: 0.000001 },
+  { id: 7, addr: "[2001:db8::1]:8333", subver: "/btcd:0.24.0/", inbound: false, permissions: ["mempool", "noban"], bytessent: 400, bytesrecv: 300 },
+];
+const ids = (peers) => peers.map((p) => p.id);
+
+test("a saved peer view keeps known sort fields and drops unknown ones", () => {
+  const defaults = state("DEFAULT_PEER_VIEW");
+  assert.deepEqual(app.normalizePeerView(null), defaults);
+  assert.deepEqual(app.normalizePeerView("ping"), defaults);
+  assert.deepEqual(app.normalizePeerView({ sort: "ping", desc: true, feeFilter: true }), {
+    sort: "ping",
+    desc: true,
+    feeFilter: true,
+  });
+  assert.deepEqual(app.normalizePeerView({ sort: "country", desc: true, feeFilter: "yes" }), defaults);
+});
+
+test("peers sort by each column, with missing values last", () => {
+  assert.deepEqual(ids(app.sortPeers(PEERS, "id", false)), [1, 4, 7]);
+  assert.deepEqual(ids(app.sortPeers(PEERS, "addr", false)), [7, 1, 4]);
+  assert.deepEqual(ids(app.sortPeers(PEERS, "bytessent", true)), [4, 7, 1]);
+  assert.deepEqual(ids(app.sortPeers(PEERS, "ping", false)), [1, 4, 7]);
+  assert.deepEqual(ids(app.sortPeers(PEERS, "minfeefilter", false)), [1, 4, 7]);
+});
+
+test("equal keys fall back to peer id so the order is stable", () => {
+  assert.deepEqual(ids(app.sortPeers(PEERS, "direction", false)), [1, 7, 4]);
+  assert.deepEqual(ids(app.sortPeers(PEERS, "direction", true)), [4, 7, 1]);
+});
+
+test("sorting leaves the caller's list untouched", () => {
+  const before = ids(PEERS);
+  app.sortPeers(PEERS, "bytesrecv", true);
+  assert.deepEqual(ids(PEERS), before);
+});
+
+test("sort keys read the abbreviated permissions and direction", () => {
+  assert.equal(app.peerSortKey(PEERS[0], "direction"), 1);
+  assert.equal(app.peerSortKey(PEERS[2], "perms"), app.abbreviatePermissions(["mempool", "noban"]));
+  assert.equal(app.peerSortKey(PEERS[2], "ping"), Infinity);
+  assert.equal(app.peerSortKey(PEERS[1], "unknown"), 1);
+});
@@ -0,0 +1,41 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+// Keys as /params/key returns them.
+function call(method, params, atMs, pinned = false) {
+  return { key: `${method} ${JSON.stringify(params)}`, method, params, atMs, pinned };
+}
+const stamps = (history) => history.map((e) => e.atMs);
+
+test("new calls go on top", () => {
+  const history = [call("getblockcount", [], 2), call("getpeerinfo", [], 1)];
+  assert.deepEqual(stamps(app.pushRpcHistory(history, call("uptime", [], 3), 50)), [3, 2, 1]);
+});
+
+test("running a call again moves its entry to the top and keeps its pin", () => {
+  const history = [
+    call("getblockcount", [], 3),
+    call("getblockhash", [7], 2, true),
+    call("getpeerinfo", [], 1),
+  ];
+  const next = app.pushRpcHistory(history, call("getblockhash", [7], 4), 50);
+  assert.deepEqual(stamps(next), [4, 3, 1]);
+  assert.equal(next[0].pinned, true);
+  // Different params are a different call.
+  assert.equal(app.pushRpcHistory(history, call("getblockhash", [8], 4), 50).length, 4);
+});
+
+test("the oldest unpinned entry is evicted first", () => {
+  const history = [call("a", [], 3), call("b", [], 2), call("c", [], 1, true)];
+  assert.deepEqual(stamps(app.pushRpcHistory(history, call("d", [], 4), 3)), [4, 3, 1]);
+  const allPinned = history.map((e) => ({ ...e, pinned: true }));
+  assert.deepEqual(stamps(app.pushRpcHistory(allPinned, call("d", [], 4), 3)), [4, 3, 2]);
+});
+
+test("a repeat does not count against the limit", () => {
+  const history = [call("a", [], 2), call("b", [], 1)];
+  assert.deepEqual(stamps(app.pushRpcHistory(history, call("b", [], 3), 2)), [3, 2]);
+});
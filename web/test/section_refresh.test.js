@@ -0,0 +1,51 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+test("a section is in flight until its newest fetch settles", () => {
+  const s = app.createSectionState(["chain", "mempool"]);
+  const full = app.beginSectionFetch(s, ["chain", "mempool"]);
+  const targeted = app.beginSectionFetch(s, ["mempool"]);
+  assert.equal(app.sectionInFlight(s, "chain"), true);
+  assert.equal(app.sectionInFlight(s, "mempool"), true);
+
+  assert.equal(app.settleSectionFetch(s, "mempool", full, true, 1_000), true);
+  // The older full refresh settling does not end the targeted one.
+  assert.equal(app.sectionInFlight(s, "mempool"), true);
+  assert.equal(app.settleSectionFetch(s, "mempool", targeted, true, 2_000), true);
+  assert.equal(app.sectionInFlight(s, "mempool"), false);
+
+  assert.equal(app.settleSectionFetch(s, "chain", full, true, 1_000), true);
+  assert.equal(app.sectionInFlight(s, "chain"), false);
+});
+
+test("a slow full refresh cannot overwrite a fresher targeted one", () => {
+  const s = app.createSectionState(["mempool"]);
+  const full = app.beginSectionFetch(s, ["mempool"]);
+  const targeted = app.beginSectionFetch(s, ["mempool"]);
+  assert.equal(app.settleSectionFetch(s, "mempool", targeted, true, 2_000), true);
+  assert.equal(app.settleSectionFetch(s, "mempool", full, true, 3_000), false);
+  assert.equal(s.sections.mempool.updatedMs, 2_000);
+  assert.equal(app.sectionInFlight(s, "mempool"), false);
+});
+
+test("a failed fetch settles without marking the section updated", () => {
+  const s = app.createSectionState(["peers"]);
+  const gen = app.beginSectionFetch(s, ["peers"]);
+  assert.equal(app.settleSectionFetch(s, "peers", gen, false, 1_000), false);
+  assert.equal(app.sectionInFlight(s, "peers"), false);
+  assert.equal(s.sections.peers.updatedMs, 0);
+});
+
+test("update ages read in the largest whole unit", () => {
+  const now = 10_000_000;
+  assert.equal(app.formatUpdatedAgo(0, now), "");
+  assert.equal(app.formatUpdatedAgo(now - 1_500, now), "just now");
+  // Clock skew must not show a negative age.
+  assert.equal(app.formatUpdatedAgo(now + 5_000, now), "just now");
+  assert.equal(app.formatUpdatedAgo(now - 42_000, now), "42s ago");
+  assert.equal(app.formatUpdatedAgo(now - 5 * 60_000, now), "5m ago");
+  assert.equal(app.formatUpdatedAgo(now - 2 * 3_600_000 - 1, now), "2h ago");
+});
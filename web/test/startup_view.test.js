@@ -0,0 +1,36 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+const METHODS = [
+  { name: "getblockchaininfo", category: "blockchain" },
+  { name: "getpeerinfo", category: "network" },
+];
+const DASHBOARD = { view: "dashboard", method: null };
+
+test("the dashboard opens unless the last view is restored", () => {
+  assert.deepEqual(app.resolveStartupView("dashboard", { view: "wallets" }, METHODS), DASHBOARD);
+  assert.deepEqual(app.resolveStartupView("last", null, METHODS), DASHBOARD);
+  assert.deepEqual(app.resolveStartupView("last", "wallets", METHODS), DASHBOARD);
+});
+
+test("saved panes are restored by name", () => {
+  for (const view of ["wallets", "mining", "bans", "logs"]) {
+    assert.deepEqual(app.resolveStartupView("last", { view }, METHODS), { view, method: null });
+  }
+  assert.deepEqual(app.resolveStartupView("last", { view: "settings" }, METHODS), DASHBOARD);
+});
+
+test("a saved method is looked up in the loaded schema", () => {
+  assert.deepEqual(
+    app.resolveStartupView("last", { view: "method", method: "getpeerinfo" }, METHODS),
+    { view: "method", method: METHODS[1] },
+  );
+  // Renamed or removed in this node's version.
+  assert.deepEqual(app.resolveStartupView("last", { view: "method", method: "getinfo" }, METHODS), DASHBOARD);
+  assert.deepEqual(app.resolveStartupView("last", { view: "method", method: 3 }, METHODS), DASHBOARD);
+  // The schema has not loaded.
+  assert.deepEqual(app.resolveStartupView("last", { view: "method", method: "getpeerinfo" }, null), DASHBOARD);
+});
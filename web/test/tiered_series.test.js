@@ -0,0 +1,43 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+// Tiny tiers: 10 ms at full resolution, then 5 ms buckets, at most two kept.
+function filled(until) {
+  const series = app.createTieredSeries(10, 5, 2);
+  for (let t = 0; t <= until; t++) app.pushSeries(series, t, t * 2);
+  return series;
+}
+
+test("recent samples keep full resolution", () => {
+  const series = filled(8);
+  assert.deepEqual(app.querySeries(series, Infinity, 8), Array.from({ length: 9 }, (_, t) => ({ t, v: t * 2 })));
+});
+
+test("older samples are averaged per bucket and the oldest buckets dropped", () => {
+  const series = filled(30);
+  assert.deepEqual(series.old, [{ t: 7, v: 14 }, { t: 12, v: 24 }]);
+  const points = app.querySeries(series, Infinity, 30);
+  // The bucket still filling is shown as its running average.
+  assert.deepEqual(points.slice(0, 4), [{ t: 7, v: 14 }, { t: 12, v: 24 }, { t: 17, v: 34 }, { t: 20, v: 40 }]);
+  assert.equal(points.length, 3 + 11);
+});
+
+test("a window returns only points inside it", () => {
+  const series = filled(30);
+  assert.deepEqual(app.querySeries(series, 3, 30).map((p) => p.t), [28, 29, 30]);
+  assert.deepEqual(app.querySeries(series, 15, 30).map((p) => p.t), [17, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30]);
+  assert.deepEqual(app.querySeries(series, 5, 25).map((p) => p.t), [21, 22, 23, 24, 25]);
+});
+
+test("out-of-order and non-finite samples are ignored", () => {
+  const series = app.createTieredSeries(10, 5, 2);
+  app.pushSeries(series, 5, 1);
+  app.pushSeries(series, 5, 2);
+  app.pushSeries(series, 4, 3);
+  app.pushSeries(series, 6, NaN);
+  app.pushSeries(series, 7, Infinity);
+  assert.deepEqual(app.querySeries(series, Infinity, 10), [{ t: 5, v: 1 }]);
+});
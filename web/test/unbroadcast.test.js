@@ -0,0 +1,76 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp, state } = require("./harness");
+
+const app = loadApp();
+
+const txid = (n) => n.toString(16).padStart(64, "0");
+
+// Answers rpcCall from canned replies and records what was asked.
+function stubRpc(replies) {
+  const calls = [];
+  app.rpcCall = async (method, params) => {
+    calls.push([method, params]);
+    return replies(method, params);
+  };
+  return calls;
+}
+
+test("the unbroadcast clock starts once and clears when the count drops to zero", () => {
+  assert.equal(app.trackUnbroadcastSince(0, 2, 1_000), 1_000);
+  assert.equal(app.trackUnbroadcastSince(1_000, 3, 9_000), 1_000);
+  assert.equal(app.trackUnbroadcastSince(1_000, 0, 9_000), 0);
+});
+
+test("unbroadcast transactions only warn after the grace period", () => {
+  const warnMs = state("UNBROADCAST_WARN_MS");
+  assert.equal(app.isUnbroadcastStale(0, 10 * warnMs), false);
+  assert.equal(app.isUnbroadcastStale(1_000, 1_000 + warnMs - 1), false);
+  assert.equal(app.isUnbroadcastStale(1_000, 1_000 + warnMs), true);
+});
+
+test("only unconfirmed wallet transactions the mempool marks unbroadcast are listed", async () => {
+  const calls = stubRpc((method, params) => {
+    if (method === "listtransactions") {
+      return {
+        result: [
+          { txid: txid(1), confirmations: 3 },
+          { txid: txid(2), confirmations: 0 },
+          // A send to self lists the same txid twice.
+          { txid: txid(2), confirmations: 0 },
+          { txid: txid(3), confirmations: 0 },
+          { txid: txid(4), confirmations: -2 },
+        ],
+      };
+    }
+    if (params[0] === txid(2)) return { result: { vsize: 141, unbroadcast: true } };
+    return { result: { vsize: 141, unbroadcast: false } };
+  });
+  assert.deepEqual(await app.findUnbroadcastWalletTxs(), [txid(2)]);
+  assert.deepEqual(calls, [
+    ["listtransactions", ["*", 100]],
+    ["getmempoolentry", [txid(2)]],
+    ["getmempoolentry", [txid(3)]],
+  ]);
+});
+
+test("the mempool lookups are capped at the newest wallet transactions", async () => {
+  const max = state("UNBROADCAST_MAX_WALLET_TXS");
+  const listed = Array.from({ length: max + 5 }, (_, i) => ({ txid: txid(i + 1), confirmations: 0 }));
+  const calls = stubRpc((method) => (method === "listtransactions"
+    ? { result: listed }
+    : { result: { unbroadcast: true } }));
+  const found = await app.findUnbroadcastWalletTxs();
+  assert.equal(found.length, max);
+  assert.equal(found[0], txid(6));
+  assert.equal(calls.length, max + 1);
+});
+
+test("a wallet error or evicted transaction lists nothing", async () => {
+  stubRpc(() => ({ error: { code: -18, message: "Requested wallet does not exist or is not loaded" } }));
+  assert.deepEqual(await app.findUnbroadcastWalletTxs(), []);
+  stubRpc((method) => (method === "listtransactions"
+    ? { result: [{ txid: txid(7), confirmations: 0 }] }
+    : { error: { code: -5, message: "Transaction not in mempool" } }));
+  assert.deepEqual(await app.findUnbroadcastWalletTxs(), []);
+});
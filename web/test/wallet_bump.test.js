@@ -0,0 +1,135 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp } = require("./harness");
+
+const app = loadApp();
+
+const SENT = "a".repeat(64);
+const RECEIVED = "b".repeat(64);
+
+// listtransactions: one entry per output of our send, plus a confirmed receive.
+const LIST = [
+  {
+    address: "bcrt1qrecipient",
+    category: "send",
+    amount: -0.001,
+    fee: -0.0000141,
+    confirmations: 0,
+    "bip125-replaceable": "yes",
+    walletconflicts: [],
+    txid: SENT,
+    time: 1_700_000_100,
+    abandoned: false,
+  },
+  {
+    address: "bcrt1qsecond",
+    category: "send",
+    amount: -0.0005,
+    fee: -0.0000141,
+    confirmations: 0,
+    "bip125-replaceable": "yes",
+    walletconflicts: [],
+    txid: SENT,
+    time: 1_700_000_100,
+    abandoned: false,
+  },
+  {
+    address: "bcrt1qours",
+    category: "receive",
+    amount: 0.25,
+    confirmations: 3,
+    "bip125-replaceable": "no",
+    walletconflicts: [],
+    txid: RECEIVED,
+    time: 1_700_000_000,
+  },
+];
+
+// gettransaction <txid> true true, trimmed to what the preview reads.
+const DETAIL = { txid: SENT, fee: -0.0000141, decoded: { txid: SENT, vsize: 141 } };
+const PSBT_BUMP = { psbt: "cHNidP8BAH0CAAAA", origfee: 0.0000141, fee: 0.0000282, errors: [] };
+const BUMPED = { txid: "c".repeat(64), origfee: 0.0000141, fee: 0.0000282, errors: [] };
+
+test("listtransactions entries fold into one row per txid, newest first", () => {
+  const txs = app.summarizeWalletTransactions(LIST);
+  assert.deepEqual(txs.map((t) => t.txid), [SENT, RECEIVED]);
+  assert.ok(Math.abs(txs[0].amount - -0.0015) < 1e-12);
+  assert.equal(txs[0].fee, -0.0000141);
+  assert.equal(txs[0].replaceable, "yes");
+  assert.equal(txs[1].fee, null);
+  assert.deepEqual(app.summarizeWalletTransactions(null), []);
+});
+
+test("only unconfirmed, replaceable sends of this wallet can be bumped", () => {
+  const [sent, received] = app.summarizeWalletTransactions(LIST);
+  assert.deepEqual(app.bumpEligibility(sent), { ok: true });
+  assert.equal(app.bumpEligibility(received).reason, "confirmed");
+  const cases = [
+    [{ confirmations: -1 }, "conflicted"],
+    [{ conflicts: ["d".repeat(64)] }, "conflicted"],
+    [{ abandoned: true }, "abandoned"],
+    [{ replacedBy: BUMPED.txid }, "replaced"],
+    [{ fee: null }, "not sent by this wallet"],
+    [{ replaceable: "no" }, "not replaceable"],
+    [{ replaceable: "unknown" }, "not replaceable"],
+  ];
+  for (const [change, reason] of cases) {
+    assert.deepEqual(app.bumpEligibility({ ...sent, ...change }), { ok: false, reason });
+  }
+});
+
+test("fee rate input is blank or a positive sat/vB with up to 3 decimals", () => {
+  assert.deepEqual(app.parseFeeRateInput("  "), { ok: true, value: null });
+  assert.deepEqual(app.parseFeeRateInput("12.5"), { ok: true, value: 12.5 });
+  assert.deepEqual(app.parseFeeRateInput("1.125"), { ok: true, value: 1.125 });
+  for (const bad of ["0", "-1", "1.1234", "abc", "1e3"]) {
+    assert.equal(app.parseFeeRateInput(bad).ok, false, bad);
+  }
+  assert.deepEqual(app.bumpParams(SENT, null), [SENT]);
+  assert.deepEqual(app.bumpParams(SENT, 20), [SENT, { fee_rate: 20 }]);
+});
+
+test("preview compares old and new fee over the original vsize", () => {
+  assert.deepEqual(app.bumpPreview(DETAIL, PSBT_BUMP), {
+    vsize: 141,
+    origFeeSats: 1410,
+    newFeeSats: 2820,
+    deltaSats: 1410,
+    origRate: 10,
+    newRate: 20,
+  });
+  const unsized = app.bumpPreview(null, PSBT_BUMP);
+  assert.equal(unsized.newRate, null);
+  assert.equal(unsized.deltaSats, 1410);
+});
+
+test("a blank-rate preview pins the rate confirm sends", () => {
+  // The replacement dropped a change output, so it is smaller than the original.
+  assert.equal(app.previewedFeeRate(2820, 141), 20);
+  assert.equal(app.previewedFeeRate(2820, 110), 25.637);
+  assert.ok(app.previewedFeeRate(2820, 110) * 110 >= 2820);
+  assert.equal(app.previewedFeeRate(2820, undefined), null);
+  assert.equal(app.previewedFeeRate(0, 141), null);
+});
+
+test("bumpfee errors read as what to do next", () => {
+  assert.match(app.bumpErrorMessage({ code: -13, message: "Please enter the wallet passphrase" }), /locked/);
+  assert.match(app.bumpErrorMessage({ code: -8, message: "Transaction has been mined, or is conflicted with a mined transaction" }), /confirmed/);
+  assert.match(app.bumpErrorMessage({ code: -4, message: "Transaction is not BIP 125 replaceable" }), /BIP 125/);
+  assert.match(app.bumpErrorMessage({ code: -4, message: "Cannot bump transaction abc which was already bumped by transaction def" }), /already bumped/);
+  assert.match(app.bumpErrorMessage({ code: -8, message: "Insufficient total fee 0.00001, must be at least 0.00002" }), /too low/);
+  assert.equal(app.bumpErrorMessage({ code: -1, message: "boom" }), "boom (code -1)");
+});
+
+test("a bump lists the replacement above the original until the next refresh", () => {
+  const txs = app.summarizeWalletTransactions(LIST);
+  const out = app.reconcileBumpedTransactions(txs, SENT, BUMPED);
+  assert.deepEqual(out.map((t) => t.txid), [BUMPED.txid, SENT, RECEIVED]);
+  assert.equal(out[0].replaces, SENT);
+  assert.equal(out[0].fee, -0.0000282);
+  assert.equal(out[0].replacedBy, null);
+  assert.equal(out[1].replacedBy, BUMPED.txid);
+  assert.equal(app.bumpEligibility(out[1]).reason, "replaced");
+  assert.deepEqual(app.bumpEligibility(out[0]), { ok: true });
+  assert.deepEqual(app.reconcileBumpedTransactions(txs, "e".repeat(64), BUMPED), txs);
+});
@@ -0,0 +1,97 @@
+const test = require("node:test");
+const assert = require("node:assert/strict");
+const { loadApp, state } = require("./harness");
+
+const app = loadApp();
+
+const ADDRESS = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+const XPUB_DESC = "wpkh([d34db33f/84h/1h/0h]tpubDC8msFGeGuwnKG9Upg7DM2b4DaRqg3CUZa5g8v2SRQ6K4NSkxUgd7HsL2XVWbVm39yBA4LAxysQAm397zwQSQoQgewGiYZqrA9DsP4zbQ1M/0/*)";
+
+// getdescriptorinfo replies.
+const ADDRESS_INFO = {
+  descriptor: `addr(${ADDRESS})#8ljj5fpe`,
+  checksum: "8ljj5fpe",
+  isrange: false,
+  issolvable: false,
+  hasprivatekeys: false,
+};
+const RANGED_INFO = {
+  descriptor: `${XPUB_DESC}#0wqnl9q6`,
+  checksum: "0wqnl9q6",
+  isrange: true,
+  issolvable: true,
+  hasprivatekeys: false,
+};
+
+test("addresses are wrapped in addr() and descriptor checksums dropped", () => {
+  assert.deepEqual(app.normalizeWatchInput(`  ${ADDRESS} `), {
+    ok: true,
+    descriptor: `addr(${ADDRESS})`,
+    isAddress: true,
+  });
+  assert.deepEqual(app.normalizeWatchInput(`${XPUB_DESC}#0wqnl9q6`), {
+    ok: true,
+    descriptor: XPUB_DESC,
+    isAddress: false,
+  });
+  assert.equal(app.normalizeWatchInput("").ok, false);
+  assert.equal(app.normalizeWatchInput("not an address!").ok, false);
+});
+
+test("a birth date becomes midnight UTC; none means no rescan", () => {
+  assert.equal(app.watchTimestamp(""), "now");
+  assert.equal(app.watchTimestamp("2024-01-02"), 1_704_153_600);
+  assert.equal(app.watchTimestamp("2024-13-40"), null);
+});
+
+test("the import request labels single descriptors and ranges ranged ones", () => {
+  assert.deepEqual(app.buildImportRequest(ADDRESS_INFO, "savings", "now"), [{
+    desc: ADDRESS_INFO.descriptor,
+    timestamp: "now",
+    active: false,
+    internal: false,
+    label: "savings",
+  }]);
+  assert.deepEqual(app.buildImportRequest(ADDRESS_INFO, "", 1_704_153_600), [{
+    desc: ADDRESS_INFO.descriptor,
+    timestamp: 1_704_153_600,
+    active: false,
+    internal: false,
+  }]);
+  // Core rejects a label on a ranged descriptor, so it is left off.
+  assert.deepEqual(app.buildImportRequest(RANGED_INFO, "savings", "now"), [{
+    desc: RANGED_INFO.descriptor,
+    timestamp: "now",
+    active: false,
+    internal: false,
+    range: [0, state("WATCH_RANGE_END")],
+  }]);
+});
+
+test("only an import that rescans needs the extra confirmation", () => {
+  const now = Date.parse("2024-01-12T12:00:00Z");
+  assert.equal(app.rescanWarning("now", now), null);
+  const warning = app.rescanWarning(app.watchTimestamp("2024-01-02"), now);
+  assert.match(warning, /since 2024-01-02 \(~10 days of history\)/);
+  // A birth date in the future still warns, with no negative day count.
+  assert.match(app.rescanWarning(app.watchTimestamp("2024-02-01"), now), /~0 days/);
+});
+
+test("importdescriptors replies become per-request outcomes", () => {
+  assert.deepEqual(app.parseImportResponse([{ success: true }]), [
+    { ok: true, warnings: [], error: null },
+  ]);
+  assert.deepEqual(app.parseImportResponse([{
+    success: true,
+    warnings: ["Range not given, using default keypool range"],
+  }]), [{ ok: true, warnings: ["Range not given, using default keypool range"], error: null }]);
+  assert.deepEqual(app.parseImportResponse([{
+    success: false,
+    error: { code: -5, message: "Provided checksum 'abcd' does not match computed checksum" },
+  }]), [{
+    ok: false,
+    warnings: [],
+    error: "Provided checksum 'abcd' does not match computed checksum (code -5)",
+  }]);
+  assert.equal(app.parseImportResponse({ success: true })[0].ok, false);
+});